@@ -0,0 +1,98 @@
+//! Makes sure the dummy stays fast with thousands of views.
+//!
+//! Run with `cargo bench`. Each operation must take well under a
+//! millisecond with 10,000 views on an output, or this exits with an error.
+
+extern crate rustwlc;
+
+use std::time::{Duration, Instant};
+
+use rustwlc::{callback, dummy, simulate, Geometry, Point, Size, WlcView, EDGE_NONE};
+use rustwlc::dummy::{Layer, MotionRate, ViewFilter};
+use rustwlc::input::pointer;
+
+const VIEWS: u32 = 10_000;
+const BUDGET_MICROS: u128 = 1_000;
+
+/// Runs `f` `times` times and reports the average duration.
+fn measure<F>(name: &str, times: u32, mut f: F) -> bool where F: FnMut(u32) {
+    let start = Instant::now();
+    for index in 0..times {
+        f(index);
+    }
+    let average = start.elapsed() / times;
+    let ok = average < Duration::from_micros(BUDGET_MICROS as u64);
+    println!("{:<26} {:>10.3?} {}", name, average, if ok { "" } else { "(too slow)" });
+    ok
+}
+
+fn main() {
+    let output = dummy::add_output("WLC-1", Size { w: 1920, h: 1080 });
+    let mut views: Vec<WlcView> = Vec::new();
+    let mut ok = measure("add_view", VIEWS, |index| {
+        let view = dummy::add_view(output);
+        view.set_geometry(EDGE_NONE, Geometry {
+            origin: Point { x: (index % 1900) as i32, y: (index % 1000) as i32 },
+            size: Size { w: 20, h: 20 }
+        });
+        views.push(view);
+    });
+    for view in views.iter().step_by(100) {
+        dummy::set_layer(*view, Layer::Above);
+    }
+    ok &= measure("bring_to_front", 1_000, |index| {
+        views[(index * 7) as usize].bring_to_front();
+    });
+    ok &= measure("send_below", 1_000, |index| {
+        views[(index * 7) as usize].send_below(views[(index * 3) as usize]);
+    });
+    ok &= measure("view_at (miss)", 1_000, |_| {
+        dummy::view_at(output, Point { x: 1919, y: 1079 });
+    });
+    ok &= measure("view_at (hit)", 1_000, |index| {
+        dummy::view_at(output, Point { x: (index % 1900) as i32, y: 10 });
+    });
+    callback::rust::pointer_motion(|_, _, point| {
+        pointer::set_position(*point);
+        false
+    });
+    ok &= measure("pointer_motion", 1_000, |index| {
+        simulate::pointer_motion(Point { x: (index % 1900) as i32, y: 10 });
+    });
+    // Motion coalesced per frame, or flooded, as compositors see it
+    dummy::set_motion_rate(MotionRate::Coalesce);
+    ok &= measure("pointer_motion (coalesce)", 1_000, |index| {
+        simulate::pointer_motion(Point { x: (index % 1900) as i32, y: 10 });
+    });
+    // Every tenth call floods ten motions, so the average is per motion
+    dummy::set_motion_rate(MotionRate::Flood(10));
+    ok &= measure("pointer_motion (flood)", 1_000, |index| {
+        if index % 10 == 0 {
+            simulate::pointer_motion(Point { x: (index % 1900) as i32, y: 1079 });
+        }
+    });
+    dummy::set_motion_rate(MotionRate::Immediate);
+    ok &= measure("get_views", 1_000, |_| {
+        output.get_views();
+    });
+    ok &= measure("get_views_by_type", 1_000, |_| {
+        output.get_views_by_type(ViewFilter { mask: Some(1), .. ViewFilter::default() });
+    });
+    ok &= measure("visible_views", 1_000, |_| {
+        dummy::visible_views(output);
+    });
+    let mut snapshots = Vec::new();
+    ok &= measure("snapshot", 1_000, |_| {
+        snapshots.push(dummy::snapshot());
+    });
+    ok &= measure("set_mask + snapshot", 1_000, |index| {
+        views[(index * 7) as usize].set_mask(index);
+        snapshots.push(dummy::snapshot());
+    });
+    ok &= measure("remove_view", VIEWS / 2, |index| {
+        dummy::remove_view(views[(index * 2) as usize]);
+    });
+    if !ok {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,29 @@
+//! With the `wlc-headers` feature, generates bindings to wlc's callback
+//! registration functions with bindgen, so the tests can check rustwlc's
+//! signatures against the real headers. Set `WLC_INCLUDE_DIR` if wlc isn't
+//! installed where clang looks for headers.
+
+#[cfg(feature = "wlc-headers")]
+extern crate bindgen;
+
+#[cfg(feature = "wlc-headers")]
+fn main() {
+    use std::env;
+    use std::path::PathBuf;
+
+    println!("cargo:rerun-if-env-changed=WLC_INCLUDE_DIR");
+    let mut builder = bindgen::Builder::default()
+        .header_contents("wlc-headers.h", "#include <wlc/wlc.h>\n")
+        .allowlist_function("wlc_set_.*_cb")
+        .allowlist_type("wlc_(point|size|geometry|modifiers)")
+        .layout_tests(false);
+    if let Ok(dir) = env::var("WLC_INCLUDE_DIR") {
+        builder = builder.clang_arg(format!("-I{}", dir));
+    }
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap()).join("wlc.rs");
+    builder.generate().expect("Couldn't generate bindings from wlc/wlc.h")
+        .write_to_file(out).expect("Couldn't write the wlc bindings");
+}
+
+#[cfg(not(feature = "wlc-headers"))]
+fn main() {}
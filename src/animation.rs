@@ -0,0 +1,168 @@
+//! Steps a compositor's animations one frame at a time.
+//!
+//! Compositors animate by moving views a little on every frame, usually
+//! from `output_render_pre` or a timer, based on the current time. This
+//! module drives the mock clock and the frame loop together, and records
+//! each frame's geometry of a view into a `Timeline` which tests can check
+//! for smoothness.
+//!
+//! # Example
+//! ```rust
+//! use rustwlc::{animation, callback, dummy};
+//! use rustwlc::{Geometry, Point, Size, EDGE_NONE};
+//!
+//! let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+//! let view = dummy::add_view(output);
+//! let size = Size { w: 800, h: 600 };
+//! view.set_geometry(EDGE_NONE, Geometry { origin: Point { x: -800, y: 0 }, size });
+//! // Slide the view in from the left over 200ms
+//! callback::rust::output_render_pre(move |_| {
+//!     let x = (dummy::time().min(200) as i32 - 200) * 4;
+//!     view.set_geometry(EDGE_NONE, Geometry { origin: Point { x, y: 0 }, size });
+//! });
+//! let timeline = animation::record(view, 15, 16);
+//! assert!(timeline.is_monotonic());
+//! assert!(timeline.max_step() <= 64);
+//! assert_eq!(timeline.last().unwrap().geometry.origin, Point { x: 0, y: 0 });
+//! ```
+
+use super::dummy;
+use super::handle::WlcView;
+use super::simulate;
+use super::types::Geometry;
+
+/// A view's geometry at one frame of a `Timeline`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame {
+    /// Time the frame was captured, from `dummy::time()`
+    pub time: u32,
+    /// Geometry of the view after the frame was rendered
+    pub geometry: Geometry
+}
+
+/// Geometry of a view over a series of frames.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timeline {
+    view: WlcView,
+    frames: Vec<Frame>
+}
+
+impl Timeline {
+    /// Starts an empty timeline of a view.
+    pub fn new(view: WlcView) -> Timeline {
+        Timeline { view, frames: Vec::new() }
+    }
+
+    /// Gets the view the timeline is about.
+    pub fn view(&self) -> WlcView {
+        self.view
+    }
+
+    /// Adds the view's current geometry to the timeline.
+    ///
+    /// Nothing is added once the view has been removed.
+    pub fn capture(&mut self) {
+        if let Some(info) = dummy::view_info(self.view) {
+            self.frames.push(Frame { time: dummy::time(), geometry: info.geometry });
+        }
+    }
+
+    /// Gets the captured frames, oldest first.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// Gets the last captured frame.
+    pub fn last(&self) -> Option<Frame> {
+        self.frames.last().cloned()
+    }
+
+    /// Whether the view never turned back: its x, y, width, and height
+    /// each only grew or only shrank over the timeline.
+    pub fn is_monotonic(&self) -> bool {
+        (0..4).all(|component| {
+            let steps = self.steps(component);
+            steps.iter().all(|&step| step >= 0) || steps.iter().all(|&step| step <= 0)
+        })
+    }
+
+    /// Gets the largest change of x, y, width, or height between two
+    /// consecutive frames. Smooth animations make many small steps.
+    pub fn max_step(&self) -> u32 {
+        (0..4).flat_map(|component| self.steps(component))
+            .map(|step| step.unsigned_abs() as u32)
+            .max().unwrap_or(0)
+    }
+
+    /// Changes of one component of the geometry between frames.
+    fn steps(&self, component: usize) -> Vec<i64> {
+        let values: Vec<i64> = self.frames.iter().map(|frame| {
+            let geometry = frame.geometry;
+            match component {
+                0 => geometry.origin.x as i64,
+                1 => geometry.origin.y as i64,
+                2 => geometry.size.w as i64,
+                _ => geometry.size.h as i64
+            }
+        }).collect();
+        values.windows(2).map(|pair| pair[1] - pair[0]).collect()
+    }
+}
+
+/// Runs one frame: advances the time by `interval` milliseconds, then
+/// renders every output with `simulate::render_frame`.
+pub fn step_frame(interval: u32) {
+    dummy::advance_time(interval);
+    for output in dummy::outputs() {
+        simulate::render_frame(output);
+    }
+}
+
+/// Records a view over `frames` frames, `interval` milliseconds apart.
+///
+/// The timeline starts with the view's current geometry, then captures it
+/// after each frame.
+pub fn record(view: WlcView, frames: u32, interval: u32) -> Timeline {
+    let mut timeline = Timeline::new(view);
+    timeline.capture();
+    for _ in 0..frames {
+        step_frame(interval);
+        timeline.capture();
+    }
+    timeline
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn timeline() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let view = dummy::add_view(output);
+        // Overshoots, then settles back
+        callback::rust::output_render_pre(move |_| {
+            let w = match dummy::time() / 16 {
+                0..=2 => 100 * (dummy::time() / 16),
+                3 => 350,
+                _ => 300
+            };
+            view.set_geometry(EDGE_NONE, Geometry {
+                origin: Point { x: 0, y: 0 }, size: Size { w, h: 100 }
+            });
+        });
+        let timeline = animation::record(view, 5, 16);
+        let widths: Vec<u32> = timeline.frames().iter()
+            .map(|frame| frame.geometry.size.w).collect();
+        assert_eq!(widths, vec![0, 100, 200, 350, 300, 300]);
+        assert_eq!(timeline.frames()[5].time, 80);
+        assert!(!timeline.is_monotonic());
+        assert_eq!(timeline.max_step(), 150);
+
+        let mut timeline = animation::Timeline::new(view);
+        dummy::remove_view(view);
+        animation::step_frame(16);
+        timeline.capture();
+        assert!(timeline.frames().is_empty());
+    }
+}
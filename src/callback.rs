@@ -2,6 +2,29 @@
 //!
 //! See individual methods for callback details.
 //!
+//! dummy-rustwlc keeps the registered callbacks, and invokes them when
+//! events are injected with the `simulate` module.
+//!
+//! # Rust callbacks
+//! rustwlc requires callbacks to be `extern "C" fn`s, since wlc calls them
+//! from C. The dummy has no C caller, so the `callback::rust` module offers
+//! the same functions for plain `fn`s and closures. Compositors which build
+//! against both crates should keep using the functions here.
+//!
+//! The functions here aren't made generic instead, as they would then take
+//! closures which the real rustwlc doesn't: they keep rustwlc's signatures
+//! so code which builds against the dummy builds against rustwlc too. Each
+//! of them names its counterpart in `callback::rust`, for tests.
+//!
+//! # Arguments by reference
+//! As in wlc, callbacks are given sizes, geometries, points and modifiers
+//! by reference. The dummy copies them out of the event before invoking
+//! the callback, so the references point to storage which stays the same
+//! for the whole call, even if the callback simulates more events. They
+//! can't outlive the call: a callback which wants to keep them copies the
+//! values, or registers with the `callback::owned` module, whose functions
+//! take callbacks given the values themselves.
+//!
 //! # wlc Example
 //! ```no_run
 //! use rustwlc;
@@ -29,66 +52,302 @@
 //! run_wlc();
 //! ```
 
+use std::fmt;
+use std::ops::Deref;
+use std::rc::Rc;
+use std::time::Instant;
+
 use super::types::*;
 use super::handle::{WlcOutput, WlcView};
+use super::event::WlcEvent;
+use super::dummy::{self, Lifecycle, Threading};
+
+/// A registered callback.
+///
+/// Callbacks are reference counted so they can be invoked outside of the
+/// dummy state, where they may register other callbacks.
+pub(crate) struct Callback<F: ?Sized>(pub Rc<F>);
+
+impl<F: ?Sized> Clone for Callback<F> {
+    fn clone(&self) -> Self {
+        Callback(self.0.clone())
+    }
+}
+
+impl<F: ?Sized> Deref for Callback<F> {
+    type Target = F;
+
+    fn deref(&self) -> &F {
+        &self.0
+    }
+}
+
+impl<F: ?Sized> fmt::Debug for Callback<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Callback({:p})", self.0)
+    }
+}
+
+/// The callbacks a compositor has registered.
+// Each field spells out its callback's signature, as in rustwlc.
+#[allow(clippy::type_complexity)]
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Callbacks {
+    pub output_created: Option<Callback<dyn Fn(WlcOutput) -> bool>>,
+    pub output_destroyed: Option<Callback<dyn Fn(WlcOutput)>>,
+    pub output_focus: Option<Callback<dyn Fn(WlcOutput, bool)>>,
+    pub output_resolution: Option<Callback<dyn Fn(WlcOutput, &Size, &Size)>>,
+    pub output_context_destroyed: Option<Callback<dyn Fn(WlcOutput)>>,
+    pub output_context_created: Option<Callback<dyn Fn(WlcOutput)>>,
+    pub output_render_pre: Option<Callback<dyn Fn(WlcOutput)>>,
+    pub output_render_post: Option<Callback<dyn Fn(WlcOutput)>>,
+    pub view_created: Option<Callback<dyn Fn(WlcView) -> bool>>,
+    pub view_destroyed: Option<Callback<dyn Fn(WlcView)>>,
+    pub view_focus: Option<Callback<dyn Fn(WlcView, bool)>>,
+    pub view_move_to_output: Option<Callback<dyn Fn(WlcView, WlcOutput, WlcOutput)>>,
+    pub view_request_geometry: Option<Callback<dyn Fn(WlcView, &Geometry)>>,
+    pub view_request_state: Option<Callback<dyn Fn(WlcView, ViewState, bool)>>,
+    pub view_request_move: Option<Callback<dyn Fn(WlcView, &Point)>>,
+    pub view_request_resize: Option<Callback<dyn Fn(WlcView, ResizeEdge, &Point)>>,
+    pub view_render_pre: Option<Callback<dyn Fn(WlcView)>>,
+    pub view_render_post: Option<Callback<dyn Fn(WlcView)>>,
+    pub keyboard_key: Option<Callback<dyn Fn(WlcView, u32, &KeyboardModifiers,
+                                             u32, KeyState) -> bool>>,
+    pub pointer_button: Option<Callback<dyn Fn(WlcView, u32, &KeyboardModifiers,
+                                               u32, ButtonState, &Point) -> bool>>,
+    pub pointer_scroll: Option<Callback<dyn Fn(WlcView, u32, &KeyboardModifiers,
+                                               ScrollAxis, [f64; 2]) -> bool>>,
+    pub pointer_motion: Option<Callback<dyn Fn(WlcView, u32, &Point) -> bool>>,
+    pub touch: Option<Callback<dyn Fn(WlcView, u32, &KeyboardModifiers,
+                                      TouchType, i32, &Point) -> bool>>,
+    pub compositor_ready: Option<Callback<dyn Fn()>>,
+    pub compositor_terminate: Option<Callback<dyn Fn()>>,
+    pub all_events: Option<Callback<dyn Fn(&WlcEvent)>>
+}
+
+/// Gets the callbacks registered so far.
+pub(crate) fn registered() -> Callbacks {
+    dummy::state::with(|state| state.callbacks.clone())
+}
+
+/// A handler added with `add_handler`.
+#[derive(Debug, Clone)]
+pub(crate) struct Handler {
+    pub id: HandlerId,
+    pub priority: i32,
+    pub handler: Callback<dyn Fn(&WlcEvent) -> bool>
+}
+
+/// Invokes the handlers and callback registered for an event.
+///
+/// Handlers with a positive priority run first, then the callback, then the
+/// other handlers. Vetoable events stop at the first handler which returns
+/// `true`, or at a callback which blocks or rejects them.
+///
+/// Returns the result of the event: whether input was blocked, or whether
+/// a created handle was allowed. If nothing decided, returns what wlc would
+/// assume: `true` (allowed) for `OutputCreated` and `ViewCreated`, `false`
+/// (not blocked) otherwise.
+///
+/// The dispatch is timed if `dummy::record_callback_times` is enabled.
+pub(crate) fn dispatch(event: &WlcEvent) -> bool {
+    let start = Instant::now();
+    let result = match dummy::state::with(|state| state.threading) {
+        Threading::LoopThread => dispatch_here(event),
+        #[cfg(feature = "off-thread-callbacks")]
+        Threading::OffThread => dummy::state::off_thread(|| dispatch_here(event))
+    };
+    dummy::time_callback(event, start.elapsed());
+    result
+}
+
+/// Marks an event as being dispatched until dropped, see
+/// `dummy::current_event`.
+struct Dispatching;
+
+impl Dispatching {
+    fn start(event: &WlcEvent) -> Dispatching {
+        dummy::watch(event);
+        dummy::state::with(|state| {
+            state.dispatching.push(*event);
+            state.note_event(*event);
+        });
+        Dispatching
+    }
+}
+
+impl Drop for Dispatching {
+    fn drop(&mut self) {
+        dummy::state::with(|state| state.dispatching.pop());
+    }
+}
+
+/// Invokes the handlers and callback of an event on the current thread.
+fn dispatch_here(event: &WlcEvent) -> bool {
+    let _dispatching = Dispatching::start(event);
+    let (all_events, handlers) = dummy::state::with(|state| {
+        (state.callbacks.all_events.clone(), state.handlers.clone())
+    });
+    if let Some(cb) = all_events {
+        cb(event);
+    }
+    let vetoable = event.is_vetoable();
+    let allowed_by_default = matches!(*event,
+        WlcEvent::OutputCreated(..) | WlcEvent::ViewCreated(..));
+    let (before, after): (Vec<&Handler>, Vec<&Handler>) = handlers.iter()
+        .partition(|handler| handler.priority > 0);
+    for handler in before {
+        if (handler.handler)(event) && vetoable {
+            return !allowed_by_default
+        }
+    }
+    let result = invoke_callback(event);
+    if vetoable && result != allowed_by_default {
+        return result
+    }
+    for handler in after {
+        if (handler.handler)(event) && vetoable {
+            return !allowed_by_default
+        }
+    }
+    allowed_by_default
+}
+
+/// Invokes the callback registered for an event.
+///
+/// Returns the callback's result. If the callback returns nothing or isn't
+/// registered, returns what wlc would assume: `true` (allowed) for
+/// `OutputCreated` and `ViewCreated`, `false` (not blocked) otherwise.
+fn invoke_callback(event: &WlcEvent) -> bool {
+    let cbs = registered();
+    match *event {
+        WlcEvent::OutputCreated(output) =>
+            return cbs.output_created.is_none_or(|cb| cb(output)),
+        WlcEvent::ViewCreated(view) =>
+            return cbs.view_created.is_none_or(|cb| cb(view)),
+        WlcEvent::KeyboardKey { view, time, mods, key, state } =>
+            return cbs.keyboard_key
+                .is_some_and(|cb| cb(view, time, &mods, key, state)),
+        WlcEvent::PointerButton { view, time, mods, button, state, point } =>
+            return cbs.pointer_button
+                .is_some_and(|cb| cb(view, time, &mods, button, state, &point)),
+        WlcEvent::PointerScroll { view, time, mods, axis, amount, .. } =>
+            return cbs.pointer_scroll
+                .is_some_and(|cb| cb(view, time, &mods, axis, amount)),
+        WlcEvent::PointerMotion { view, time, point } =>
+            return cbs.pointer_motion.is_some_and(|cb| cb(view, time, &point)),
+        WlcEvent::Touch { view, time, mods, touch, slot, point } =>
+            return cbs.touch
+                .is_some_and(|cb| cb(view, time, &mods, touch, slot, &point)),
+        WlcEvent::OutputDestroyed(output) =>
+            if let Some(cb) = cbs.output_destroyed { cb(output) },
+        WlcEvent::OutputFocus(output, focused) =>
+            if let Some(cb) = cbs.output_focus { cb(output, focused) },
+        WlcEvent::OutputResolution { output, old_size, new_size } =>
+            if let Some(cb) = cbs.output_resolution {
+                cb(output, &old_size, &new_size)
+            },
+        WlcEvent::OutputContextCreated(output) =>
+            if let Some(cb) = cbs.output_context_created { cb(output) },
+        WlcEvent::OutputContextDestroyed(output) =>
+            if let Some(cb) = cbs.output_context_destroyed { cb(output) },
+        WlcEvent::OutputRenderPre(output) =>
+            if let Some(cb) = cbs.output_render_pre { cb(output) },
+        WlcEvent::OutputRenderPost(output) =>
+            if let Some(cb) = cbs.output_render_post { cb(output) },
+        WlcEvent::ViewDestroyed(view) =>
+            if let Some(cb) = cbs.view_destroyed { cb(view) },
+        WlcEvent::ViewFocus(view, focused) =>
+            if let Some(cb) = cbs.view_focus { cb(view, focused) },
+        WlcEvent::ViewMoveToOutput { view, old_output, new_output } =>
+            if let Some(cb) = cbs.view_move_to_output {
+                cb(view, old_output, new_output)
+            },
+        WlcEvent::ViewRequestGeometry { view, geometry } =>
+            if let Some(cb) = cbs.view_request_geometry { cb(view, &geometry) },
+        WlcEvent::ViewRequestState { view, state, handled } =>
+            if let Some(cb) = cbs.view_request_state { cb(view, state, handled) },
+        WlcEvent::ViewRequestMove { view, destination } =>
+            if let Some(cb) = cbs.view_request_move { cb(view, &destination) },
+        WlcEvent::ViewRequestResize { view, edge, location } =>
+            if let Some(cb) = cbs.view_request_resize { cb(view, edge, &location) },
+        WlcEvent::ViewRenderPre(view) =>
+            if let Some(cb) = cbs.view_render_pre { cb(view) },
+        WlcEvent::ViewRenderPost(view) =>
+            if let Some(cb) = cbs.view_render_post { cb(view) },
+        WlcEvent::CompositorReady =>
+            if let Some(cb) = cbs.compositor_ready { cb() },
+        WlcEvent::CompositorTerminate =>
+            if let Some(cb) = cbs.compositor_terminate { cb() }
+    }
+    false
+}
 
 /// Callback invoked when an output is created.
 /// Return `true` to allow the output to exist.
 ///
+/// Tests can register a closure instead, with `rust::output_created`.
+///
 /// # Example
 /// ```rust
 /// use rustwlc::WlcOutput;
 ///
-/// extern fn on_output_created(output: WlcOutput) -> bool {
+/// extern "C" fn on_output_created(output: WlcOutput) -> bool {
 ///     println!("Output {} ({:?}) was created", output.get_name(), output);
 ///     return true;
 /// }
 /// # fn main() { }
 /// ```
 pub fn output_created(callback: extern "C" fn(output: WlcOutput) -> bool) {
-    
+    rust::output_created(move |output| callback(output));
 }
 
 /// Callback invoked when an output is destroyed.
 ///
+/// Tests can register a closure instead, with `rust::output_destroyed`.
+///
 /// # Example
 /// ```rust
 /// use rustwlc::WlcOutput;
 ///
-/// extern fn output_destroyed(output: WlcOutput) {
+/// extern "C" fn output_destroyed(output: WlcOutput) {
 ///     println!("Goodbye, {:?}", output);
 /// }
 /// # fn main() { }
 /// ```
 pub fn output_destroyed(callback: extern "C" fn(output: WlcOutput)) {
-    
+    rust::output_destroyed(move |output| callback(output));
 }
 
 /// Callback invoked when an output gains focus.
 ///
+/// Tests can register a closure instead, with `rust::output_focus`.
+///
 /// # Example
 /// ```rust
 /// use rustwlc::WlcOutput;
 ///
-/// extern fn output_focus(output: WlcOutput, focused: bool) {
+/// extern "C" fn output_focus(output: WlcOutput, focused: bool) {
 ///     println!("Output {} {} focus", output.get_name(),
 ///              if focused { "gained" } else { "lost" });
 /// }
 /// # fn main() { }
 /// ```
 pub fn output_focus(callback: extern "C" fn(output: WlcOutput, focused: bool)) {
-    
+    rust::output_focus(move |output, focused| callback(output, focused));
 }
 
 /// Callback invoked when an output's resolution changes.
 ///
+/// Tests can register a closure instead, with `rust::output_resolution`.
+///
 /// # Example
 /// ```rust
 /// use rustwlc::WlcOutput;
 /// use rustwlc::Size;
 ///
-/// extern fn output_resolution(output: WlcOutput,
-///                             old_size: &Size, new_size: &Size) {
+/// extern "C" fn output_resolution(output: WlcOutput,
+///                                 old_size: &Size, new_size: &Size) {
 ///     println!("Output {} went from {} to {}",
 ///              output.get_name(), old_size, new_size);
 /// }
@@ -97,27 +356,37 @@ pub fn output_focus(callback: extern "C" fn(output: WlcOutput, focused: bool)) {
 pub fn output_resolution(callback: extern "C" fn(output: WlcOutput,
                                                  old_size: &Size,
                                                  new_size: &Size)) {
-    
+    rust::output_resolution(move |output, old_size, new_size| {
+        callback(output, old_size, new_size)
+    });
 }
 
 /// Output context created. This generally happens on a tty switch.
+///
+/// Tests can register a closure instead, with `rust::output_context_destroyed`.
 pub fn output_context_destroyed(cb: extern "C" fn(output: WlcOutput)) {
-    
+    rust::output_context_destroyed(move |output| cb(output));
 }
 
 /// Output context destroyed
+///
+/// Tests can register a closure instead, with `rust::output_context_created`.
 pub fn output_context_created(cb: extern "C" fn(output: WlcOutput)) {
-    
+    rust::output_context_created(move |output| cb(output));
 }
 
 /// Callback invoked pre-render for an output.
+///
+/// Tests can register a closure instead, with `rust::output_render_pre`.
 pub fn output_render_pre(callback: extern "C" fn(output: WlcOutput)) {
-    
+    rust::output_render_pre(move |output| callback(output));
 }
 
 /// Callback invoked post-render for an output.
+///
+/// Tests can register a closure instead, with `rust::output_render_post`.
 pub fn output_render_post(callback: extern "C" fn(output: WlcOutput)) {
-    
+    rust::output_render_post(move |output| callback(output));
 }
 
 /// Callback invoked when a view is created.
@@ -128,11 +397,13 @@ pub fn output_render_post(callback: extern "C" fn(output: WlcOutput)) {
 /// * Focus the view
 /// * Bring the view to the front
 ///
+/// Tests can register a closure instead, with `rust::view_created`.
+///
 /// # Example
 /// ```rust
 /// use rustwlc::WlcView;
 ///
-/// extern fn view_created(view: WlcView) -> bool {
+/// extern "C" fn view_created(view: WlcView) -> bool {
 ///     println!("View \"{}\" was created ({:?})", view.get_class(), view);
 ///     view.set_mask(view.get_output().get_mask());
 ///     view.bring_to_front();
@@ -142,7 +413,7 @@ pub fn output_render_post(callback: extern "C" fn(output: WlcOutput)) {
 /// # fn main() { }
 /// ```
 pub fn view_created(callback: extern "C" fn(view: WlcView) -> bool) {
-    
+    rust::view_created(move |view| callback(view));
 }
 
 /// Callback invoked when a view is destroyed.
@@ -150,37 +421,41 @@ pub fn view_created(callback: extern "C" fn(view: WlcView) -> bool) {
 /// When a view is destroyed, it's a good idea to shift focus to
 /// some other view, i.e. the last one used.
 ///
+/// Tests can register a closure instead, with `rust::view_destroyed`.
+///
 /// # Example
 /// ```rust
 /// use rustwlc::WlcView;
 ///
-/// extern fn view_destroyed(view: WlcView) {
+/// extern "C" fn view_destroyed(view: WlcView) {
 ///     println!("Goodbye, {:?}", view);
 /// }
 /// # fn main() { }
 /// ```
 pub fn view_destroyed(callback: extern "C" fn(view: WlcView)) {
-    
+    rust::view_destroyed(move |view| callback(view));
 }
 
 /// Callback invoked when a view is focused.
 ///
 /// The view's `ViewState::VIEW_ACTIVATED` bit should be set to true here.
 ///
+/// Tests can register a closure instead, with `rust::view_focus`.
+///
 /// # Example
 /// ```rust
 /// use rustwlc::WlcView;
 /// // The bitflags constants need to be imported manually.
 /// use rustwlc::VIEW_ACTIVATED;
 ///
-/// extern fn view_focus(view: WlcView, focused: bool) {
+/// extern "C" fn view_focus(view: WlcView, focused: bool) {
 ///     println!("View {:?} is {} focus, updating...",
 ///               view, if focused { "in" } else { "out of" });
 ///     view.set_state(VIEW_ACTIVATED, focused);
 /// }
 /// ```
 pub fn view_focus(callback: extern "C" fn(handle: WlcView, focused: bool)) {
-    
+    rust::view_focus(move |handle, focused| callback(handle, focused));
 }
 
 /// Callback invoked when a view switches outputs.
@@ -188,46 +463,68 @@ pub fn view_focus(callback: extern "C" fn(handle: WlcView, focused: bool)) {
 /// Moving views between outputs is unsupported in wlc at the time of writing.
 /// Wayland mandates each output have its own memory buffer so it may take wlc
 /// some time before this is implemented.
+///
+/// Tests can register a closure instead, with `rust::view_move_to_output`.
 pub fn view_move_to_output(callback: extern "C" fn(view: WlcView,
                                                    old_output: WlcOutput,
                                                    new_output: WlcOutput)) {
-    
+    rust::view_move_to_output(move |view, old_output, new_output| {
+        callback(view, old_output, new_output)
+    });
 }
 
 /// Callback invoked when a view requests geometry.
+///
+/// Tests can register a closure instead, with `rust::view_request_geometry`.
 pub fn view_request_geometry(callback: extern "C" fn(handle: WlcView,
                                                      geometry: &Geometry)) {
-    
+    rust::view_request_geometry(move |handle, geometry| callback(handle, geometry));
 }
 
 /// Callback invoked when a view requests a `ViewState`.
+///
+/// Tests can register a closure instead, with `rust::view_request_state`.
 pub fn view_request_state(callback: extern "C" fn(current: WlcView,
                                                   state: ViewState,
                                                   handled: bool)) {
-    
+    rust::view_request_state(move |current, state, handled| {
+        callback(current, state, handled)
+    });
 }
 
 /// Callback invoked when a view requests a move.
+///
+/// Tests can register a closure instead, with `rust::view_request_move`.
 pub fn view_request_move(callback: extern "C" fn(handle: WlcView,
                                                  destination: &Point)) {
-    
+    rust::view_request_move(move |handle, destination| {
+        callback(handle, destination)
+    });
 }
 
 /// Callback invoked when a view requests a resize.
+///
+/// Tests can register a closure instead, with `rust::view_request_resize`.
 pub fn view_request_resize(callback: extern "C" fn(handle: WlcView,
                                                    edge: ResizeEdge,
                                                    location: &Point)) {
-    
+    rust::view_request_resize(move |handle, edge, location| {
+        callback(handle, edge, location)
+    });
 }
 
 /// Callback invoked pre-view-render.
+///
+/// Tests can register a closure instead, with `rust::view_render_pre`.
 pub fn view_render_pre(callback: extern "C" fn(view: WlcView)) {
-    
+    rust::view_render_pre(move |view| callback(view));
 }
 
 /// Callback invoked post-view-render.
+///
+/// Tests can register a closure instead, with `rust::view_render_post`.
 pub fn view_render_post(callback: extern "C" fn(view: WlcView)) {
-    
+    rust::view_render_post(move |view| callback(view));
 }
 
 /// Callback invoked on keypresses.
@@ -237,17 +534,19 @@ pub fn view_render_post(callback: extern "C" fn(view: WlcView)) {
 /// The first `u32` is a timestamp, the second is the key code. The view may be
 /// the root window.
 ///
-/// Proper values for `key` can be found in `input.h` or a similar library/crate
-/// - see wlc documentation on the subject, it may not support your keyboard
+/// Proper values for `key` can be found in `input.h` or a similar library/crate;
+/// see wlc documentation on the subject, it may not support your keyboard
 /// layout at the moment.
 ///
+/// Tests can register a closure instead, with `rust::keyboard_key`.
+///
 /// # Example
 /// ```rust
 /// use rustwlc::WlcView;
 /// use rustwlc::{KeyboardModifiers, KeyState};
 ///
-/// extern fn keyboard_key(view: WlcView, time: u32, mods: &KeyboardModifiers,
-///                        key: u32, state: KeyState) -> bool {
+/// extern "C" fn keyboard_key(view: WlcView, time: u32, mods: &KeyboardModifiers,
+///                            key: u32, state: KeyState) -> bool {
 ///     println!("Key {} {:?} on {:?} at {} with modifiers {:?}",
 ///              key, view, state, time, mods);
 ///     return false;
@@ -257,7 +556,9 @@ pub fn view_render_post(callback: extern "C" fn(view: WlcView)) {
 pub fn keyboard_key(callback: extern "C" fn(view: WlcView, time: u32,
                                             mods: &KeyboardModifiers, key: u32,
                                             state: KeyState) -> bool) {
-    
+    rust::keyboard_key(move |view, time, mods, key, state| {
+        callback(view, time, mods, key, state)
+    });
 }
 
 /// Callback invoked on mouse clicks.
@@ -268,14 +569,16 @@ pub fn keyboard_key(callback: extern "C" fn(view: WlcView, time: u32,
 /// The view may be the root window. Proper values for `button`
 /// can be found in `input.h` or a similar library/crate.
 ///
+/// Tests can register a closure instead, with `rust::pointer_button`.
+///
 /// # Example
 /// ```rust
 /// use rustwlc::WlcView;
 /// use rustwlc::{KeyboardModifiers, ButtonState, Point};
 ///
-/// extern fn pointer_button(view: WlcView, time: u32,
-///                          mods: &KeyboardModifiers, button: u32,
-///                          state: ButtonState, point: &Point) -> bool {
+/// extern "C" fn pointer_button(view: WlcView, time: u32,
+///                              mods: &KeyboardModifiers, button: u32,
+///                              state: ButtonState, point: &Point) -> bool {
 ///     println!("Button {} {:?} at {} at {} in {:?}, keyboard mods: {:?}",
 ///              button, state, time, point, view, mods);
 ///     return false;
@@ -286,7 +589,9 @@ pub fn pointer_button(callback: extern "C" fn(view: WlcView, time: u32,
                                               mods: &KeyboardModifiers,
                                               button: u32, state: ButtonState,
                                               point: &Point) -> bool) {
-    
+    rust::pointer_button(move |view, time, mods, button, state, point| {
+        callback(view, time, mods, button, state, point)
+    });
 }
 
 /// Callback invoked on mouse scroll.
@@ -298,14 +603,18 @@ pub fn pointer_button(callback: extern "C" fn(view: WlcView, time: u32,
 /// * mods: Current pressed keyboard modifiers
 /// * axis: Which direction the scroll was in
 /// * amount: The first argument seems to be either 10 or -10 depending on
-/// up/down (or right/left if `axis == ScrollAxis::Horizontal`).
-/// The second one, when tested on a standard laptop trackpad, seems to be
-/// a double slightly above zero.
+///   up/down (or right/left if `axis == ScrollAxis::Horizontal`).
+///   The second one, when tested on a standard laptop trackpad, seems to be
+///   a double slightly above zero.
+///
+/// Tests can register a closure instead, with `rust::pointer_scroll`.
 pub fn pointer_scroll(callback: extern "C" fn(view: WlcView, time: u32,
                                               mods: &KeyboardModifiers,
                                               axis: ScrollAxis,
                                               amount: [f64; 2]) -> bool) {
-    
+    rust::pointer_scroll(move |view, time, mods, axis, amount| {
+        callback(view, time, mods, axis, amount)
+    });
 }
 
 /// Callback invoked on pointer motion.
@@ -314,23 +623,25 @@ pub fn pointer_scroll(callback: extern "C" fn(view: WlcView, time: u32,
 /// `rustwlc::input::pointer::set_position`
 /// must be invoked to actually move the cursor!
 ///
+/// Tests can register a closure instead, with `rust::pointer_motion`.
+///
 /// # Example
 /// ```rust
 /// use rustwlc::WlcView;
 /// use rustwlc::Point;
 /// use rustwlc::input::pointer;
 ///
-/// extern fn pointer_motion(view: WlcView, time: u32, point: &Point) -> bool {
+/// extern "C" fn pointer_motion(view: WlcView, time: u32, point: &Point) -> bool {
 ///     println!("Pointer was moved to {} in {:?} at {}", point, view, time);
 ///     // This is very important.
-///     pointer::set_position(point);
+///     pointer::set_position(*point);
 ///     return false;
 /// }
 /// # fn main() { }
 /// ```
 pub fn pointer_motion(callback: extern "C" fn(view: WlcView, time: u32,
                                               point: &Point) -> bool) {
-    
+    rust::pointer_motion(move |view, time, point| callback(view, time, point));
 }
 
 /// Callback invoked on touchscreen touch.
@@ -339,23 +650,675 @@ pub fn pointer_motion(callback: extern "C" fn(view: WlcView, time: u32,
 /// # Arguments
 /// * `mods`: Which keyboard modifiers are being pressed during the event
 /// * `touch`: What kind of event it is (a touch down, a frame being made,
-/// a touch release). In the case of `TouchType::Frame`, `slot` and `point`
-/// will both be zero.
+///   a touch release). In the case of `TouchType::Frame`, `slot` and `point`
+///   will both be zero.
 /// * `slot`: Which finger - in cases of multiple touches down - is causing
-/// the event
+///   the event
 /// * `point`: Where the touch event happened
+///
+/// Tests can register a closure instead, with `rust::touch`.
 pub fn touch(callback: extern "C" fn(handle: WlcView, time: u32,
                                      mods: &KeyboardModifiers, touch: TouchType,
                                      slot: i32, point: &Point) -> bool) {
-    
+    rust::touch(move |handle, time, mods, touch, slot, point| {
+        callback(handle, time, mods, touch, slot, point)
+    });
 }
 
 /// Callback invoked by wlc after `rustwlc::init` is called.
+///
+/// Tests can register a closure instead, with `rust::compositor_ready`.
 pub fn compositor_ready(callback: extern "C" fn()) {
-    
+    rust::compositor_ready(move || callback());
 }
 
 /// Callback invoked by wlc when a compositor is terminating
+///
+/// Tests can register a closure instead, with `rust::compositor_terminate`.
 pub fn compositor_terminate(callback: extern "C" fn()) {
-    
+    rust::compositor_terminate(move || callback());
+}
+
+/// Callback invoked for every event, before the event's own callback.
+///
+/// wlc has no such callback: it is offered so compositors which route
+/// events through their own message bus can register a single function.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, simulate};
+/// use rustwlc::{WlcEvent, WlcView};
+///
+/// fn all_events(event: &WlcEvent) {
+///     println!("{} {:?}", event.name(), event);
+/// }
+///
+/// callback::all_events(all_events);
+/// simulate::dispatch(WlcEvent::ViewFocus(WlcView::dummy(1), true));
+/// ```
+pub fn all_events<F>(callback: F) where F: Fn(&WlcEvent) + 'static {
+    registering("all_events");
+    dummy::state::with(|state| {
+        state.callbacks.all_events = Some(Callback(Rc::new(callback)))
+    });
+}
+
+/// Reports registering a callback while wlc runs, which is too late for
+/// wlc's callbacks to be set up.
+fn registering(name: &str) {
+    if dummy::lifecycle() == Lifecycle::Running {
+        dummy::violation(format!("callback::{} was called while wlc was running", name));
+    }
+}
+
+/// Identifies a handler added with `add_handler`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HandlerId(u32);
+
+/// Adds a handler which is invoked for every event, alongside callbacks.
+///
+/// Unlike callbacks, any number of handlers can be added, so a compositor's
+/// plugins can each handle events without multiplexing one callback.
+/// Handlers match on the events they care about and ignore the rest.
+///
+/// # Order
+/// Handlers run by priority, highest first. The callback registered with
+/// the other functions in this module acts as a handler of priority `0`,
+/// running before handlers of the same or lower priority. Handlers of the
+/// same priority run in the order they were added.
+///
+/// # Vetoing
+/// A handler returns `true` to stop vetoable events (see
+/// `WlcEvent::is_vetoable`) from reaching later handlers: input events are
+/// then blocked from the view, and `OutputCreated`/`ViewCreated` events are
+/// rejected. A callback stops them the same way, by blocking input or
+/// returning `false` when a handle is created. The return value of handlers
+/// is ignored for other events.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, simulate};
+/// use rustwlc::{WlcEvent, WlcView, KeyboardModifiers, KeyState};
+/// use rustwlc::input::keys;
+///
+/// fn screenshot_key(event: &WlcEvent) -> bool {
+///     match *event {
+///         WlcEvent::KeyboardKey { key, .. } => key == keys::KEY_SYSRQ.get_code(),
+///         _ => false
+///     }
+/// }
+///
+/// fn keyboard_key(view: WlcView, time: u32, mods: &KeyboardModifiers,
+///                 key: u32, state: KeyState) -> bool {
+///     panic!("The screenshot plugin takes print screen first");
+/// }
+///
+/// callback::rust::keyboard_key(keyboard_key);
+/// callback::add_handler(10, screenshot_key);
+/// assert!(simulate::keyboard_key(keys::KEY_SYSRQ, KeyState::Pressed));
+/// ```
+pub fn add_handler<F>(priority: i32, handler: F) -> HandlerId
+    where F: Fn(&WlcEvent) -> bool + 'static {
+    registering("add_handler");
+    dummy::state::with(|state| {
+        let id = HandlerId(state.next_handler);
+        state.next_handler += 1;
+        let index = state.handlers.iter()
+            .position(|other| other.priority < priority)
+            .unwrap_or(state.handlers.len());
+        state.handlers.insert(index, Handler {
+            id, priority, handler: Callback(Rc::new(handler))
+        });
+        state.allocations.handlers.created += 1;
+        id
+    })
+}
+
+/// Removes a handler added with `add_handler`.
+///
+/// Returns whether the handler was still registered.
+pub fn remove_handler(id: HandlerId) -> bool {
+    dummy::state::with(|state| {
+        let len = state.handlers.len();
+        state.handlers.retain(|handler| handler.id != id);
+        let removed = state.handlers.len() != len;
+        if removed {
+            state.allocations.handlers.destroyed += 1;
+        }
+        removed
+    })
+}
+
+/// Registration functions which take Rust callbacks.
+///
+/// These take the same arguments as the functions of the same name in
+/// `callback`, but accept any `Fn`: plain `fn`s and closures don't need to
+/// be `extern`. Either function registers the same callback, replacing the
+/// one registered with the other.
+///
+/// # Example
+/// ```rust
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+/// use rustwlc::{callback, simulate};
+/// use rustwlc::{WlcEvent, WlcView};
+///
+/// let focused = Rc::new(Cell::new(None));
+/// let last = focused.clone();
+/// callback::rust::view_focus(move |view, focus| {
+///     if focus { last.set(Some(view)) }
+/// });
+/// simulate::dispatch(WlcEvent::ViewFocus(WlcView::dummy(1), true));
+/// assert_eq!(focused.get(), Some(WlcView::dummy(1)));
+/// ```
+pub mod rust {
+    use std::rc::Rc;
+
+    use super::super::types::*;
+    use super::super::handle::{WlcOutput, WlcView};
+    use super::super::dummy;
+    use super::{registering, Callback};
+
+    /// Registers a Rust callback for `callback::output_created`.
+    pub fn output_created<F>(callback: F) where F: Fn(WlcOutput) -> bool + 'static {
+        registering("output_created");
+        dummy::state::with(|state| {
+            state.callbacks.output_created = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::output_destroyed`.
+    pub fn output_destroyed<F>(callback: F) where F: Fn(WlcOutput) + 'static {
+        registering("output_destroyed");
+        dummy::state::with(|state| {
+            state.callbacks.output_destroyed = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::output_focus`.
+    pub fn output_focus<F>(callback: F) where F: Fn(WlcOutput, bool) + 'static {
+        registering("output_focus");
+        dummy::state::with(|state| {
+            state.callbacks.output_focus = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::output_resolution`.
+    pub fn output_resolution<F>(callback: F)
+        where F: Fn(WlcOutput, &Size, &Size) + 'static {
+        registering("output_resolution");
+        dummy::state::with(|state| {
+            state.callbacks.output_resolution = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::output_context_destroyed`.
+    pub fn output_context_destroyed<F>(callback: F) where F: Fn(WlcOutput) + 'static {
+        registering("output_context_destroyed");
+        dummy::state::with(|state| {
+            state.callbacks.output_context_destroyed = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::output_context_created`.
+    pub fn output_context_created<F>(callback: F) where F: Fn(WlcOutput) + 'static {
+        registering("output_context_created");
+        dummy::state::with(|state| {
+            state.callbacks.output_context_created = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::output_render_pre`.
+    pub fn output_render_pre<F>(callback: F) where F: Fn(WlcOutput) + 'static {
+        registering("output_render_pre");
+        dummy::state::with(|state| {
+            state.callbacks.output_render_pre = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::output_render_post`.
+    pub fn output_render_post<F>(callback: F) where F: Fn(WlcOutput) + 'static {
+        registering("output_render_post");
+        dummy::state::with(|state| {
+            state.callbacks.output_render_post = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::view_created`.
+    pub fn view_created<F>(callback: F) where F: Fn(WlcView) -> bool + 'static {
+        registering("view_created");
+        dummy::state::with(|state| {
+            state.callbacks.view_created = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::view_destroyed`.
+    pub fn view_destroyed<F>(callback: F) where F: Fn(WlcView) + 'static {
+        registering("view_destroyed");
+        dummy::state::with(|state| {
+            state.callbacks.view_destroyed = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::view_focus`.
+    pub fn view_focus<F>(callback: F) where F: Fn(WlcView, bool) + 'static {
+        registering("view_focus");
+        dummy::state::with(|state| {
+            state.callbacks.view_focus = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::view_move_to_output`.
+    pub fn view_move_to_output<F>(callback: F)
+        where F: Fn(WlcView, WlcOutput, WlcOutput) + 'static {
+        registering("view_move_to_output");
+        dummy::state::with(|state| {
+            state.callbacks.view_move_to_output = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::view_request_geometry`.
+    pub fn view_request_geometry<F>(callback: F)
+        where F: Fn(WlcView, &Geometry) + 'static {
+        registering("view_request_geometry");
+        dummy::state::with(|state| {
+            state.callbacks.view_request_geometry = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::view_request_state`.
+    pub fn view_request_state<F>(callback: F)
+        where F: Fn(WlcView, ViewState, bool) + 'static {
+        registering("view_request_state");
+        dummy::state::with(|state| {
+            state.callbacks.view_request_state = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::view_request_move`.
+    pub fn view_request_move<F>(callback: F) where F: Fn(WlcView, &Point) + 'static {
+        registering("view_request_move");
+        dummy::state::with(|state| {
+            state.callbacks.view_request_move = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::view_request_resize`.
+    pub fn view_request_resize<F>(callback: F)
+        where F: Fn(WlcView, ResizeEdge, &Point) + 'static {
+        registering("view_request_resize");
+        dummy::state::with(|state| {
+            state.callbacks.view_request_resize = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::view_render_pre`.
+    pub fn view_render_pre<F>(callback: F) where F: Fn(WlcView) + 'static {
+        registering("view_render_pre");
+        dummy::state::with(|state| {
+            state.callbacks.view_render_pre = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::view_render_post`.
+    pub fn view_render_post<F>(callback: F) where F: Fn(WlcView) + 'static {
+        registering("view_render_post");
+        dummy::state::with(|state| {
+            state.callbacks.view_render_post = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::keyboard_key`.
+    pub fn keyboard_key<F>(callback: F)
+        where F: Fn(WlcView, u32, &KeyboardModifiers, u32, KeyState) -> bool
+               + 'static {
+        registering("keyboard_key");
+        dummy::state::with(|state| {
+            state.callbacks.keyboard_key = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::pointer_button`.
+    pub fn pointer_button<F>(callback: F)
+        where F: Fn(WlcView, u32, &KeyboardModifiers, u32, ButtonState, &Point) -> bool
+               + 'static {
+        registering("pointer_button");
+        dummy::state::with(|state| {
+            state.callbacks.pointer_button = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::pointer_scroll`.
+    pub fn pointer_scroll<F>(callback: F)
+        where F: Fn(WlcView, u32, &KeyboardModifiers, ScrollAxis, [f64; 2]) -> bool
+               + 'static {
+        registering("pointer_scroll");
+        dummy::state::with(|state| {
+            state.callbacks.pointer_scroll = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::pointer_motion`.
+    pub fn pointer_motion<F>(callback: F)
+        where F: Fn(WlcView, u32, &Point) -> bool + 'static {
+        registering("pointer_motion");
+        dummy::state::with(|state| {
+            state.callbacks.pointer_motion = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::touch`.
+    pub fn touch<F>(callback: F)
+        where F: Fn(WlcView, u32, &KeyboardModifiers, TouchType, i32, &Point) -> bool
+               + 'static {
+        registering("touch");
+        dummy::state::with(|state| state.callbacks.touch = Some(Callback(Rc::new(callback))));
+    }
+
+    /// Registers a Rust callback for `callback::compositor_ready`.
+    pub fn compositor_ready<F>(callback: F) where F: Fn() + 'static {
+        registering("compositor_ready");
+        dummy::state::with(|state| {
+            state.callbacks.compositor_ready = Some(Callback(Rc::new(callback)))
+        });
+    }
+
+    /// Registers a Rust callback for `callback::compositor_terminate`.
+    pub fn compositor_terminate<F>(callback: F) where F: Fn() + 'static {
+        registering("compositor_terminate");
+        dummy::state::with(|state| {
+            state.callbacks.compositor_terminate = Some(Callback(Rc::new(callback)))
+        });
+    }
+}
+
+/// Registers Rust callbacks which are given values instead of references.
+///
+/// These are for the callbacks whose arguments wlc passes by reference, so
+/// closures can keep the values without copying them out first. Each
+/// function registers the callback of the same name in `callback::rust`,
+/// replacing any registered there.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, simulate, Geometry, Point, Size, WlcView};
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+///
+/// let requests = Rc::new(RefCell::new(Vec::new()));
+/// let log = requests.clone();
+/// callback::owned::view_request_geometry(move |_, geometry| log.borrow_mut().push(geometry));
+/// let geometry = Geometry { origin: Point { x: 0, y: 0 }, size: Size { w: 640, h: 480 } };
+/// simulate::request_geometry(WlcView::dummy(1), geometry);
+/// assert_eq!(*requests.borrow(), vec![geometry]);
+/// ```
+pub mod owned {
+    use super::super::types::*;
+    use super::super::handle::{WlcOutput, WlcView};
+    use super::rust;
+
+    /// Registers a callback for `callback::output_resolution`, given the old
+    /// and new sizes.
+    pub fn output_resolution<F>(callback: F) where F: Fn(WlcOutput, Size, Size) + 'static {
+        rust::output_resolution(move |output, old_size, new_size| {
+            callback(output, *old_size, *new_size)
+        });
+    }
+
+    /// Registers a callback for `callback::view_request_geometry`, given the
+    /// requested geometry.
+    pub fn view_request_geometry<F>(callback: F) where F: Fn(WlcView, Geometry) + 'static {
+        rust::view_request_geometry(move |view, geometry| callback(view, *geometry));
+    }
+
+    /// Registers a callback for `callback::view_request_move`, given the
+    /// destination.
+    pub fn view_request_move<F>(callback: F) where F: Fn(WlcView, Point) + 'static {
+        rust::view_request_move(move |view, destination| callback(view, *destination));
+    }
+
+    /// Registers a callback for `callback::view_request_resize`, given the
+    /// location.
+    pub fn view_request_resize<F>(callback: F)
+        where F: Fn(WlcView, ResizeEdge, Point) + 'static {
+        rust::view_request_resize(move |view, edge, location| callback(view, edge, *location));
+    }
+
+    /// Registers a callback for `callback::keyboard_key`, given the
+    /// modifiers.
+    pub fn keyboard_key<F>(callback: F)
+        where F: Fn(WlcView, u32, KeyboardModifiers, u32, KeyState) -> bool + 'static {
+        rust::keyboard_key(move |view, time, mods, key, state| {
+            callback(view, time, *mods, key, state)
+        });
+    }
+
+    /// Registers a callback for `callback::pointer_button`, given the
+    /// modifiers and the point.
+    pub fn pointer_button<F>(callback: F)
+        where F: Fn(WlcView, u32, KeyboardModifiers, u32, ButtonState, Point) -> bool
+               + 'static {
+        rust::pointer_button(move |view, time, mods, button, state, point| {
+            callback(view, time, *mods, button, state, *point)
+        });
+    }
+
+    /// Registers a callback for `callback::pointer_scroll`, given the
+    /// modifiers.
+    pub fn pointer_scroll<F>(callback: F)
+        where F: Fn(WlcView, u32, KeyboardModifiers, ScrollAxis, [f64; 2]) -> bool
+               + 'static {
+        rust::pointer_scroll(move |view, time, mods, axis, amount| {
+            callback(view, time, *mods, axis, amount)
+        });
+    }
+
+    /// Registers a callback for `callback::pointer_motion`, given the point.
+    pub fn pointer_motion<F>(callback: F) where F: Fn(WlcView, u32, Point) -> bool + 'static {
+        rust::pointer_motion(move |view, time, point| callback(view, time, *point));
+    }
+
+    /// Registers a callback for `callback::touch`, given the modifiers and
+    /// the point.
+    pub fn touch<F>(callback: F)
+        where F: Fn(WlcView, u32, KeyboardModifiers, TouchType, i32, Point) -> bool
+               + 'static {
+        rust::touch(move |view, time, mods, touch, slot, point| {
+            callback(view, time, *mods, touch, slot, *point)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn arguments_outlive_nested_events() {
+        let view = WlcView::dummy(1);
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let log = seen.clone();
+        callback::rust::view_request_move(move |view, destination| {
+            let before = *destination;
+            if before.x == 0 {
+                simulate::dispatch(WlcEvent::ViewRequestMove {
+                    view, destination: Point { x: 1, y: 1 }
+                });
+            }
+            // Still the point of this call, not the nested one
+            log.borrow_mut().push((before, *destination));
+        });
+        simulate::dispatch(WlcEvent::ViewRequestMove { view, destination: Point { x: 0, y: 0 } });
+        let (outer, inner) = (Point { x: 0, y: 0 }, Point { x: 1, y: 1 });
+        assert_eq!(*seen.borrow(), vec![(inner, inner), (outer, outer)]);
+
+        let kept = Rc::new(RefCell::new(Vec::new()));
+        let log = kept.clone();
+        callback::owned::pointer_button(move |_, _, mods, _, _, point| {
+            log.borrow_mut().push((mods, point));
+            true
+        });
+        dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        input::pointer::set_position(Point { x: 5, y: 6 });
+        assert!(simulate::pointer_button(input::keys::BTN_LEFT, ButtonState::Pressed));
+        assert_eq!(kept.borrow()[0].1, Point { x: 5, y: 6 });
+        assert_eq!(kept.borrow()[0].0.mods, MOD_NONE);
+    }
+
+    /// Each registration function takes the callback type its
+    /// `wlc_set_*_cb` function declares in `wlc.h`, with `wlc_handle` as a
+    /// `WlcView` or `WlcOutput` and `const struct wlc_*` pointers as
+    /// references. These only compile if the signatures match.
+    ///
+    /// `pointer_scroll` is left out: wlc passes `uint8_t axis_bits` and a
+    /// `double *`, while rustwlc declares a `ScrollAxis` and a `[f64; 2]`.
+    ///
+    /// The types here are copied from `wlc.h` by hand; the `wlc-headers`
+    /// feature checks them against the headers themselves.
+    #[test]
+    fn signatures_match_wlc() {
+        let _: fn(extern "C" fn(WlcOutput) -> bool) = callback::output_created;
+        let _: fn(extern "C" fn(WlcOutput)) = callback::output_destroyed;
+        let _: fn(extern "C" fn(WlcOutput, bool)) = callback::output_focus;
+        let _: fn(extern "C" fn(WlcOutput, &Size, &Size)) = callback::output_resolution;
+        let _: fn(extern "C" fn(WlcOutput)) = callback::output_context_created;
+        let _: fn(extern "C" fn(WlcOutput)) = callback::output_context_destroyed;
+        let _: fn(extern "C" fn(WlcOutput)) = callback::output_render_pre;
+        let _: fn(extern "C" fn(WlcOutput)) = callback::output_render_post;
+        let _: fn(extern "C" fn(WlcView) -> bool) = callback::view_created;
+        let _: fn(extern "C" fn(WlcView)) = callback::view_destroyed;
+        let _: fn(extern "C" fn(WlcView, bool)) = callback::view_focus;
+        let _: fn(extern "C" fn(WlcView, WlcOutput, WlcOutput)) =
+            callback::view_move_to_output;
+        let _: fn(extern "C" fn(WlcView, &Geometry)) = callback::view_request_geometry;
+        let _: fn(extern "C" fn(WlcView, ViewState, bool)) = callback::view_request_state;
+        let _: fn(extern "C" fn(WlcView, &Point)) = callback::view_request_move;
+        let _: fn(extern "C" fn(WlcView, ResizeEdge, &Point)) =
+            callback::view_request_resize;
+        let _: fn(extern "C" fn(WlcView)) = callback::view_render_pre;
+        let _: fn(extern "C" fn(WlcView)) = callback::view_render_post;
+        let _: fn(extern "C" fn(WlcView, u32, &KeyboardModifiers, u32,
+                                KeyState) -> bool) = callback::keyboard_key;
+        let _: fn(extern "C" fn(WlcView, u32, &KeyboardModifiers, u32,
+                                ButtonState, &Point) -> bool) = callback::pointer_button;
+        let _: fn(extern "C" fn(WlcView, u32, &Point) -> bool) = callback::pointer_motion;
+        let _: fn(extern "C" fn(WlcView, u32, &KeyboardModifiers, TouchType,
+                                i32, &Point) -> bool) = callback::touch;
+        let _: fn(extern "C" fn()) = callback::compositor_ready;
+        let _: fn(extern "C" fn()) = callback::compositor_terminate;
+    }
+}
+
+/// Checks the callback signatures against bindings bindgen generates from
+/// `wlc/wlc.h`, see `build.rs`. Everything is checked while compiling, so
+/// nothing here runs or links against wlc.
+#[cfg(all(test, feature = "wlc-headers"))]
+#[allow(non_camel_case_types, non_upper_case_globals, non_snake_case, dead_code)]
+mod wlc_headers {
+    use std::mem::{offset_of, size_of};
+
+    use super::super::types::*;
+    use super::super::handle::{WlcOutput, WlcView};
+    use super::super::callback;
+
+    include!(concat!(env!("OUT_DIR"), "/wlc.rs"));
+
+    /// The type wlc declares for each type in rustwlc's callbacks.
+    trait Wlc {
+        type C;
+    }
+
+    impl Wlc for WlcView { type C = wlc_handle; }
+    impl Wlc for WlcOutput { type C = wlc_handle; }
+    impl Wlc for bool { type C = bool; }
+    impl Wlc for u32 { type C = u32; }
+    impl Wlc for i32 { type C = i32; }
+    impl Wlc for () { type C = (); }
+    impl Wlc for ViewState { type C = wlc_view_state_bit; }
+    impl Wlc for ResizeEdge { type C = u32; }
+    impl Wlc for KeyState { type C = wlc_key_state; }
+    impl Wlc for ButtonState { type C = wlc_button_state; }
+    impl Wlc for TouchType { type C = wlc_touch_type; }
+    impl Wlc for &Point { type C = *const wlc_point; }
+    impl Wlc for &Size { type C = *const wlc_size; }
+    impl Wlc for &Geometry { type C = *const wlc_geometry; }
+    impl Wlc for &KeyboardModifiers { type C = *const wlc_modifiers; }
+
+    // Types passed by value are the same size as wlc's
+    const _: () = {
+        assert!(size_of::<WlcView>() == size_of::<wlc_handle>());
+        assert!(size_of::<WlcOutput>() == size_of::<wlc_handle>());
+        assert!(size_of::<ViewState>() == size_of::<wlc_view_state_bit>());
+        assert!(size_of::<KeyState>() == size_of::<wlc_key_state>());
+        assert!(size_of::<ButtonState>() == size_of::<wlc_button_state>());
+        assert!(size_of::<TouchType>() == size_of::<wlc_touch_type>());
+    };
+
+    // Types passed by reference are laid out as wlc's
+    const _: () = {
+        assert!(size_of::<Point>() == size_of::<wlc_point>());
+        assert!(offset_of!(Point, x) == offset_of!(wlc_point, x));
+        assert!(offset_of!(Point, y) == offset_of!(wlc_point, y));
+        assert!(size_of::<Size>() == size_of::<wlc_size>());
+        assert!(offset_of!(Size, w) == offset_of!(wlc_size, w));
+        assert!(offset_of!(Size, h) == offset_of!(wlc_size, h));
+        assert!(size_of::<Geometry>() == size_of::<wlc_geometry>());
+        assert!(offset_of!(Geometry, origin) == offset_of!(wlc_geometry, origin));
+        assert!(offset_of!(Geometry, size) == offset_of!(wlc_geometry, size));
+        assert!(size_of::<KeyboardModifiers>() == size_of::<wlc_modifiers>());
+        assert!(offset_of!(KeyboardModifiers, leds) == offset_of!(wlc_modifiers, leds));
+        assert!(offset_of!(KeyboardModifiers, mods) == offset_of!(wlc_modifiers, mods));
+    };
+
+    /// Checks that a registration function takes the callback its
+    /// `wlc_set_*_cb` function does, argument for argument.
+    macro_rules! same_callback {
+        ($rust:ident, $wlc:ident, ($($arg:ty),*) -> $ret:ty) => {{
+            let _: fn(extern "C" fn($($arg),*) -> $ret) = callback::$rust;
+            let _: unsafe extern "C" fn(Option<unsafe extern "C" fn($(<$arg as Wlc>::C),*)
+                                                -> <$ret as Wlc>::C>) = $wlc;
+        }};
+    }
+
+    /// Never called: it only has to compile.
+    fn signatures() {
+        same_callback!(output_created, wlc_set_output_created_cb, (WlcOutput) -> bool);
+        same_callback!(output_destroyed, wlc_set_output_destroyed_cb, (WlcOutput) -> ());
+        same_callback!(output_focus, wlc_set_output_focus_cb, (WlcOutput, bool) -> ());
+        same_callback!(output_resolution, wlc_set_output_resolution_cb,
+                       (WlcOutput, &Size, &Size) -> ());
+        same_callback!(output_context_created, wlc_set_output_context_created_cb,
+                       (WlcOutput) -> ());
+        same_callback!(output_context_destroyed, wlc_set_output_context_destroyed_cb,
+                       (WlcOutput) -> ());
+        same_callback!(output_render_pre, wlc_set_output_render_pre_cb, (WlcOutput) -> ());
+        same_callback!(output_render_post, wlc_set_output_render_post_cb, (WlcOutput) -> ());
+        same_callback!(view_created, wlc_set_view_created_cb, (WlcView) -> bool);
+        same_callback!(view_destroyed, wlc_set_view_destroyed_cb, (WlcView) -> ());
+        same_callback!(view_focus, wlc_set_view_focus_cb, (WlcView, bool) -> ());
+        same_callback!(view_move_to_output, wlc_set_view_move_to_output_cb,
+                       (WlcView, WlcOutput, WlcOutput) -> ());
+        same_callback!(view_request_geometry, wlc_set_view_request_geometry_cb,
+                       (WlcView, &Geometry) -> ());
+        same_callback!(view_request_state, wlc_set_view_request_state_cb,
+                       (WlcView, ViewState, bool) -> ());
+        same_callback!(view_request_move, wlc_set_view_request_move_cb,
+                       (WlcView, &Point) -> ());
+        same_callback!(view_request_resize, wlc_set_view_request_resize_cb,
+                       (WlcView, ResizeEdge, &Point) -> ());
+        same_callback!(view_render_pre, wlc_set_view_render_pre_cb, (WlcView) -> ());
+        same_callback!(view_render_post, wlc_set_view_render_post_cb, (WlcView) -> ());
+        same_callback!(keyboard_key, wlc_set_keyboard_key_cb,
+                       (WlcView, u32, &KeyboardModifiers, u32, KeyState) -> bool);
+        same_callback!(pointer_button, wlc_set_pointer_button_cb,
+                       (WlcView, u32, &KeyboardModifiers, u32, ButtonState, &Point) -> bool);
+        same_callback!(pointer_motion, wlc_set_pointer_motion_cb,
+                       (WlcView, u32, &Point) -> bool);
+        same_callback!(touch, wlc_set_touch_cb,
+                       (WlcView, u32, &KeyboardModifiers, TouchType, i32, &Point) -> bool);
+        same_callback!(compositor_ready, wlc_set_compositor_ready_cb, () -> ());
+        same_callback!(compositor_terminate, wlc_set_compositor_terminate_cb, () -> ());
+    }
 }
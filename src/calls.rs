@@ -0,0 +1,333 @@
+//! Records the calls a compositor makes on handles.
+//!
+//! While recording, each handle method which changes the compositor, such
+//! as `WlcView::set_geometry` or `WlcOutput::set_mask`, is saved as a
+//! `Call` with the time, the handle, its arguments, and the event whose
+//! callback made it. Tests can assert on `calls()` directly; long
+//! integration runs can write the log out as JSON lines with `write_jsonl`
+//! and leave the analysis to external tools, i.e. as a CI artifact.
+//!
+//! `churn` sums the log up per view and per output, to catch compositors
+//! which do far more than they need to, such as setting the geometry of
+//! every view on every key press.
+//!
+//! # Example
+//! ```rust
+//! use rustwlc::{calls, callback, dummy, simulate};
+//! use rustwlc::{Size, WlcEvent, WlcView};
+//!
+//! fn view_created(view: WlcView) -> bool {
+//!     view.set_mask(1);
+//!     true
+//! }
+//!
+//! let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+//! let view = dummy::add_view(output);
+//! callback::rust::view_created(view_created);
+//! calls::set_recording(true);
+//! simulate::dispatch(WlcEvent::ViewCreated(view));
+//!
+//! let mut log = Vec::new();
+//! calls::write_jsonl(&mut log).unwrap();
+//! assert_eq!(String::from_utf8(log).unwrap(),
+//!            "{\"schema\":1,\"time\":0,\"method\":\"WlcView::set_mask\",\"handle\":2,\
+//!             \"args\":[\"1\"],\"event\":\"view_created\"}\n");
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug, Write as FmtWrite};
+use std::io::{self, Write};
+
+use super::contract;
+use super::dummy::{self, state};
+use super::event::WlcEvent;
+use super::handle::{WlcOutput, WlcView};
+
+/// Version of the JSON written by `Call::to_json`.
+///
+/// Each line carries it as `"schema"`. It only changes when fields are
+/// renamed, removed, or change meaning; new fields may be added without
+/// changing it.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A call the compositor made on a handle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    /// Time of the call, from `dummy::time()`
+    pub time: u32,
+    /// The method called, such as `"WlcView::focus"`
+    pub method: &'static str,
+    /// Handle the method was called on, or `0` for static methods
+    pub handle: usize,
+    /// The arguments, as printed by `Debug`
+    pub args: Vec<String>,
+    /// Event whose callback made the call, or `None` outside of callbacks
+    pub origin: Option<WlcEvent>
+}
+
+impl Call {
+    /// Formats the call as one line of JSON, without the newline.
+    ///
+    /// The object has the fields `schema` (see `SCHEMA_VERSION`), `time`,
+    /// `method`, `handle`, `args` (an array of strings), and `event`, the
+    /// name of the origin event or `null`.
+    pub fn to_json(&self) -> String {
+        let args: Vec<String> = self.args.iter().map(|arg| json_string(arg)).collect();
+        let event = self.origin.map_or("null".to_string(), |event| json_string(event.name()));
+        format!("{{\"schema\":{},\"time\":{},\"method\":{},\"handle\":{},\"args\":[{}],\"event\":{}}}",
+                SCHEMA_VERSION, self.time, json_string(self.method), self.handle,
+                args.join(","), event)
+    }
+}
+
+/// Quotes and escapes a string for JSON.
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => { let _ = write!(json, "\\u{:04x}", c as u32); },
+            c => json.push(c)
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Starts or stops recording calls.
+///
+/// Stopping keeps the calls recorded so far.
+pub fn set_recording(enabled: bool) {
+    state::with(|state| state.recording_calls = enabled);
+}
+
+/// Gets the calls recorded so far, oldest first.
+pub fn calls() -> Vec<Call> {
+    state::with(|state| state.calls.to_vec())
+}
+
+/// Forgets the calls recorded so far, and the frames counted by `churn`.
+pub fn clear() {
+    state::with(|state| {
+        state.calls.clear();
+        state.frames_rendered.clear();
+        state.views_rendered.clear();
+    });
+}
+
+/// Writes the calls recorded so far as JSON lines, oldest first.
+///
+/// Each call is one line, see `Call::to_json`.
+pub fn write_jsonl<W: Write>(writer: &mut W) -> io::Result<()> {
+    for call in calls() {
+        writeln!(writer, "{}", call.to_json())?;
+    }
+    Ok(())
+}
+
+/// How much the compositor changed a view, see `churn`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ViewChurn {
+    /// Calls to `WlcView::set_geometry`
+    pub geometry_changes: u32,
+    /// Calls to `WlcView::focus` which moved focus to the view from
+    /// another
+    pub focus_gains: u32,
+    /// Calls which moved the view in its output's stack
+    pub restacks: u32,
+    /// Frames the view was rendered in
+    pub renders: u32
+}
+
+/// How much the compositor changed an output and its views, see `churn`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OutputChurn {
+    /// Calls to `WlcOutput::schedule_render`
+    pub scheduled_renders: u32,
+    /// Frames rendered
+    pub frames: u32,
+    /// Other calls on the output, such as `WlcOutput::set_mask`
+    pub changes: u32,
+    /// Geometry changes of the views it shows now
+    pub view_geometry_changes: u32
+}
+
+/// The recorded calls summed up per view and per output.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Churn {
+    /// Views the compositor called or which were rendered
+    pub views: BTreeMap<WlcView, ViewChurn>,
+    /// Outputs the compositor called or which were rendered
+    pub outputs: BTreeMap<WlcOutput, OutputChurn>
+}
+
+impl Churn {
+    /// Gets the churn of a view, which is zero if it isn't in the report.
+    pub fn view(&self, view: WlcView) -> ViewChurn {
+        self.views.get(&view).cloned().unwrap_or_default()
+    }
+
+    /// Gets the churn of an output, which is zero if it isn't in the
+    /// report.
+    pub fn output(&self, output: WlcOutput) -> OutputChurn {
+        self.outputs.get(&output).cloned().unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Churn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (output, churn) in &self.outputs {
+            writeln!(f, "{:?}: {} scheduled renders, {} frames, {} changes, \
+                         {} view geometry changes", output, churn.scheduled_renders,
+                     churn.frames, churn.changes, churn.view_geometry_changes)?;
+        }
+        for (view, churn) in &self.views {
+            writeln!(f, "{:?}: {} geometry changes, {} focus gains, {} restacks, {} renders",
+                     view, churn.geometry_changes, churn.focus_gains, churn.restacks,
+                     churn.renders)?;
+        }
+        Ok(())
+    }
+}
+
+/// Sums up the calls recorded so far per view and per output.
+///
+/// Frames rendered with `simulate::render_frame` while recording are
+/// counted too. A view's geometry changes count towards the output which
+/// shows it when the report is made, so those of removed views only count
+/// for the views.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{calls, callback, dummy, simulate};
+/// use rustwlc::{Geometry, KeyState, Point, Size, WlcOutput, EDGE_NONE};
+/// use rustwlc::input::keys;
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let views: Vec<_> = (0..50).map(|_| dummy::add_view(output)).collect();
+/// // Lays every view out again on every key
+/// callback::rust::keyboard_key(|_, _, _, _, _| {
+///     for view in WlcOutput::get_primary().get_views() {
+///         view.set_geometry(EDGE_NONE, Geometry {
+///             origin: Point { x: 0, y: 0 }, size: Size { w: 800, h: 600 }
+///         });
+///     }
+///     false
+/// });
+/// calls::set_recording(true);
+/// simulate::keyboard_key(keys::KEY_A, KeyState::Pressed);
+/// simulate::keyboard_key(keys::KEY_A, KeyState::Released);
+/// let churn = calls::churn();
+/// assert_eq!(churn.view(views[0]).geometry_changes, 2);
+/// assert_eq!(churn.output(output).view_geometry_changes, 100);
+/// ```
+pub fn churn() -> Churn {
+    let (calls, frames, renders) = state::with(|state| {
+        (state.calls.to_vec(), state.frames_rendered.clone(), state.views_rendered.clone())
+    });
+    let mut churn = Churn::default();
+    let mut focused = None;
+    for call in &calls {
+        let (kind, method) = call.method.split_at(call.method.find("::").unwrap_or(0));
+        if kind == "WlcOutput" && call.handle != 0 {
+            let output = churn.outputs.entry(WlcOutput::dummy(call.handle as u32)).or_default();
+            match method {
+                "::schedule_render" => output.scheduled_renders += 1,
+                _ => output.changes += 1
+            }
+        } else if kind == "WlcView" {
+            let handle = WlcView::dummy(call.handle as u32);
+            let view = churn.views.entry(handle).or_default();
+            match method {
+                "::set_geometry" => view.geometry_changes += 1,
+                "::focus" if focused.replace(handle) != Some(handle) => view.focus_gains += 1,
+                "::send_to_back" | "::send_below" | "::bring_above" | "::bring_to_front" => {
+                    view.restacks += 1
+                },
+                _ => {}
+            }
+        }
+    }
+    for (output, frames) in frames {
+        churn.outputs.entry(output).or_default().frames = frames;
+    }
+    for (view, renders) in renders {
+        churn.views.entry(view).or_default().renders = renders;
+    }
+    for (&view, view_churn) in &churn.views {
+        if let Some(info) = dummy::view_info(view) {
+            churn.outputs.entry(info.output).or_default().view_geometry_changes +=
+                view_churn.geometry_changes;
+        }
+    }
+    churn
+}
+
+/// Notes a frame of an output was rendered with some views, counting it
+/// for `churn` if calls are being recorded.
+pub(crate) fn rendered(output: WlcOutput, views: &[WlcView]) {
+    state::with(|state| if state.recording_calls {
+        *state.frames_rendered.entry(output).or_default() += 1;
+        for &view in views {
+            *state.views_rendered.entry(view).or_default() += 1;
+        }
+    });
+}
+
+/// Notes a handle method was called, recording it if calls are being
+/// recorded.
+pub(crate) fn called(method: &'static str, handle: usize, args: &[&dyn Debug]) {
+    contract::handle_call(method);
+    dummy::check_kind(method, handle);
+    state::with(|state| if state.recording_calls {
+        let origin = state.dispatching.last().cloned();
+        let args = args.iter().map(|arg| format!("{:?}", arg)).collect();
+        state.calls.push(Call { time: state.time, method, handle, args, origin });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn churn() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let first = dummy::add_view(output);
+        let second = dummy::add_view(output);
+        calls::set_recording(true);
+        first.focus();
+        first.focus();
+        second.focus();
+        first.focus();
+        second.bring_to_front();
+        first.send_below(second);
+        output.schedule_render();
+        output.set_mask(2);
+        simulate::render_frame(output);
+        simulate::render_frame(output);
+        let churn = calls::churn();
+        assert_eq!(churn.view(first), calls::ViewChurn {
+            geometry_changes: 0, focus_gains: 2, restacks: 1, renders: 0
+        });
+        assert_eq!(churn.view(second).focus_gains, 1);
+        assert_eq!(churn.output(output), calls::OutputChurn {
+            scheduled_renders: 1, frames: 2, changes: 1, view_geometry_changes: 0
+        });
+        assert_eq!(churn.to_string(),
+                   "WlcOutput(1): 1 scheduled renders, 2 frames, 1 changes, \
+                    0 view geometry changes\n\
+                    WlcView(2): 0 geometry changes, 2 focus gains, 1 restacks, 0 renders\n\
+                    WlcView(3): 0 geometry changes, 1 focus gains, 1 restacks, 0 renders\n");
+        output.set_mask(1);
+        simulate::render_frame(output);
+        assert_eq!(calls::churn().view(first).renders, 1);
+        calls::clear();
+        assert_eq!(calls::churn(), calls::Churn::default());
+    }
+}
@@ -0,0 +1,383 @@
+//! Captures live sessions against the real wlc, to replay them against the
+//! dummy.
+//!
+//! dummy-rustwlc never links wlc, so the capture is done by the compositor:
+//! a build against the real rustwlc also depends on dummy-rustwlc, renamed
+//! and with the `real-wlc` feature, and its callbacks tell a `Recorder`
+//! what wlc gave them, along with the handle calls they make. The recorder
+//! writes the events out as a `script` and the calls as JSON lines, as
+//! `calls::write_jsonl` does, so a bug seen on real hardware can be
+//! replayed deterministically with `script::run` in CI, and the calls of
+//! the replay compared with those of the live session.
+//!
+//! Handles are given by their raw values. Scripts refer to outputs by name
+//! and to views by title, which the recorder remembers from when they were
+//! created. Events are written as these steps:
+//!
+//! | Callback | Step |
+//! |----------|------|
+//! | `output_created` | `output NAME WxH` |
+//! | `output_destroyed` | `disconnect NAME` |
+//! | `output_resolution` | `resolution NAME WxH` |
+//! | `view_created` | `view TITLE` |
+//! | `view_destroyed` | `close TITLE` |
+//! | `pointer_motion` | `motion X Y` |
+//! | `pointer_button` | `click TITLE`, for presses over a view |
+//! | `keyboard_key` | `type TEXT`, for presses of keys which type a character |
+//!
+//! The time between input events is written as `wait` steps, and the
+//! script's clock starts at the first one. Events the format has no step
+//! for, such as keys which don't type a character, are written as
+//! comments, so readers of the script can see where the replay differs.
+//! Views which share a title may be mixed up by the replay.
+//!
+//! # Example
+//! ```rust
+//! use rustwlc::{calls, callback, script, Point, Size};
+//! use rustwlc::capture::Recorder;
+//!
+//! // In the live session, the compositor's callbacks report to a recorder
+//! let mut recorder = Recorder::new();
+//! recorder.output_created(1, "DP-1", Size { w: 1920, h: 1080 });
+//! recorder.view_created(2, "xterm");
+//! recorder.call("WlcView::set_mask", 2, &[&1u32]);
+//! recorder.pointer_motion(2, 86400, Point { x: 10, y: 20 });
+//! recorder.pointer_motion(2, 86650, Point { x: 30, y: 20 });
+//! assert_eq!(recorder.script(), "version 2\n\
+//!                                output DP-1 1920x1080\n\
+//!                                view xterm\n\
+//!                                motion 10 20\n\
+//!                                wait 250ms\n\
+//!                                motion 30 20\n");
+//!
+//! // In CI, the same compositor replays it against the dummy
+//! callback::rust::view_created(|view| { view.set_mask(1); true });
+//! calls::set_recording(true);
+//! script::run(&recorder.script()).unwrap();
+//! assert_eq!(calls::calls(), recorder.calls());
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::io::{self, Write};
+
+use super::calls::Call;
+use super::event::WlcEvent;
+use super::handle::{WlcOutput, WlcView};
+use super::input::keys::{Key, KEY_LEFTSHIFT, KEY_RIGHTSHIFT};
+use super::script::VERSION;
+use super::types::{ButtonState, KeyboardModifiers, KeyState, Point, Size};
+use super::types::{MOD_ALT, MOD_CTRL, MOD_MOD4, MOD_SHIFT};
+use super::user;
+
+/// Records the callbacks and handle calls of a live session.
+///
+/// Each method named after a callback takes what wlc gave that callback,
+/// with handles as their raw values, and should be called first thing in
+/// it. Calls are attributed to the event last given to the recorder, as
+/// compositors make them from callbacks.
+#[derive(Debug, Clone, Default)]
+pub struct Recorder {
+    /// Steps of the script so far, after its `version` step
+    steps: Vec<String>,
+    /// The calls recorded so far
+    calls: Vec<Call>,
+    /// Names of the outputs, by raw handle
+    outputs: HashMap<usize, String>,
+    /// Titles of the views, by raw handle
+    views: HashMap<usize, String>,
+    /// wlc's timestamp of the last input event
+    last_input: Option<u32>,
+    /// The script's clock, in milliseconds
+    time: u32,
+    /// The event calls are made from, timed by the script's clock
+    origin: Option<WlcEvent>
+}
+
+impl Recorder {
+    /// Creates a recorder with nothing recorded.
+    pub fn new() -> Recorder {
+        Recorder::default()
+    }
+
+    /// Records `output_created`.
+    pub fn output_created(&mut self, output: usize, name: &str, resolution: Size) {
+        self.origin = Some(WlcEvent::OutputCreated(WlcOutput::from_raw(output)));
+        self.outputs.insert(output, name.to_string());
+        self.steps.push(format!("output {} {}x{}", name, resolution.w, resolution.h));
+    }
+
+    /// Records `output_destroyed`.
+    pub fn output_destroyed(&mut self, output: usize) {
+        self.origin = Some(WlcEvent::OutputDestroyed(WlcOutput::from_raw(output)));
+        if let Some(name) = self.outputs.remove(&output) {
+            self.steps.push(format!("disconnect {}", name));
+        }
+    }
+
+    /// Records `output_resolution`.
+    ///
+    /// Changes the compositor made itself are replayed by its call to
+    /// `WlcOutput::set_resolution`, so their step does nothing.
+    pub fn output_resolution(&mut self, output: usize, old_size: Size, new_size: Size) {
+        self.origin = Some(WlcEvent::OutputResolution {
+            output: WlcOutput::from_raw(output), old_size, new_size
+        });
+        if let Some(name) = self.outputs.get(&output) {
+            self.steps.push(format!("resolution {} {}x{}", name, new_size.w, new_size.h));
+        }
+    }
+
+    /// Records `view_created`, with the title of the view.
+    pub fn view_created(&mut self, view: usize, title: &str) {
+        self.origin = Some(WlcEvent::ViewCreated(WlcView::from_raw(view)));
+        self.views.insert(view, title.to_string());
+        self.steps.push(format!("view {}", title).trim_end().to_string());
+    }
+
+    /// Records `view_destroyed`.
+    ///
+    /// The view's client closes it in the replay, even if the compositor
+    /// asked it to with `WlcView::close`, which the dummy only records.
+    pub fn view_destroyed(&mut self, view: usize) {
+        self.origin = Some(WlcEvent::ViewDestroyed(WlcView::from_raw(view)));
+        if let Some(title) = self.views.remove(&view) {
+            self.steps.push(format!("close {}", title).trim_end().to_string());
+        }
+    }
+
+    /// Records `keyboard_key`.
+    ///
+    /// Presses of keys which type a character on a US keyboard, with shift
+    /// or without other modifiers, are gathered into `type` steps; shift
+    /// itself is pressed again by the replay. Spaces at either end of a
+    /// `type` step are lost, as steps are trimmed.
+    pub fn keyboard_key(&mut self, view: usize, time: u32, mods: KeyboardModifiers, key: u32,
+                        state: KeyState) {
+        self.wait(time);
+        self.origin = Some(WlcEvent::KeyboardKey {
+            view: WlcView::from_raw(view), time: self.time, mods, key, state
+        });
+        let key = Key::from(key);
+        if state != KeyState::Pressed || key == KEY_LEFTSHIFT || key == KEY_RIGHTSHIFT {
+            return
+        }
+        let typed = if mods.mods.intersects(MOD_CTRL | MOD_ALT | MOD_MOD4) {
+            None
+        } else {
+            user::char_for(key, mods.mods.contains(MOD_SHIFT)).filter(|c| (' '..='~').contains(c))
+        };
+        match (typed, self.steps.last_mut()) {
+            (Some(c), Some(step)) if step.starts_with("type ") => step.push(c),
+            (Some(c), _) => self.steps.push(format!("type {}", c)),
+            (None, _) => self.steps.push(format!("# key {} pressed", key.get_code()))
+        }
+    }
+
+    /// Records `pointer_button`.
+    ///
+    /// Presses over a view become `click` steps, which the replay makes at
+    /// the center of the view, releasing the button at once.
+    pub fn pointer_button(&mut self, view: usize, time: u32, mods: KeyboardModifiers,
+                          button: u32, state: ButtonState, point: Point) {
+        self.wait(time);
+        self.origin = Some(WlcEvent::PointerButton {
+            view: WlcView::from_raw(view), time: self.time, mods, button, state, point
+        });
+        if state != ButtonState::Pressed {
+            return
+        }
+        match self.views.get(&view) {
+            Some(title) => self.steps.push(format!("click {}", title).trim_end().to_string()),
+            None => {
+                self.steps.push(format!("# button {} pressed at {} {}", button, point.x, point.y));
+            }
+        }
+    }
+
+    /// Records `pointer_motion`.
+    pub fn pointer_motion(&mut self, view: usize, time: u32, point: Point) {
+        self.wait(time);
+        self.origin = Some(WlcEvent::PointerMotion {
+            view: WlcView::from_raw(view), time: self.time, point
+        });
+        self.steps.push(format!("motion {} {}", point.x, point.y));
+    }
+
+    /// Records a call the compositor made on a handle, such as
+    /// `"WlcView::set_mask"`, with its arguments, as `calls` would.
+    pub fn call(&mut self, method: &'static str, handle: usize, args: &[&dyn Debug]) {
+        let args = args.iter().map(|arg| format!("{:?}", arg)).collect();
+        self.calls.push(Call { time: self.time, method, handle, args, origin: self.origin });
+    }
+
+    /// Gets the script of the events recorded so far.
+    pub fn script(&self) -> String {
+        let mut script = format!("version {}\n", VERSION);
+        for step in &self.steps {
+            script.push_str(step);
+            script.push('\n');
+        }
+        script
+    }
+
+    /// Writes the script of the events recorded so far, see `script`.
+    pub fn write_script<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(self.script().as_bytes())
+    }
+
+    /// Gets the calls recorded so far, oldest first.
+    pub fn calls(&self) -> Vec<Call> {
+        self.calls.clone()
+    }
+
+    /// Writes the calls recorded so far as JSON lines, as
+    /// `calls::write_jsonl` does.
+    pub fn write_calls_jsonl<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for call in &self.calls {
+            writeln!(writer, "{}", call.to_json())?;
+        }
+        Ok(())
+    }
+
+    /// Writes the time since the last input event as a `wait` step.
+    fn wait(&mut self, time: u32) {
+        if let Some(last) = self.last_input {
+            if time > last {
+                self.steps.push(format!("wait {}ms", time - last));
+                self.time = self.time.wrapping_add(time - last);
+            }
+        }
+        self.last_input = Some(time);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::super::capture::Recorder;
+    use super::super::clients::{self, Action};
+    use super::super::input::keys::{BTN_LEFT, KEY_ESC, KEY_Q};
+    use super::super::input::pointer;
+    use std::cell::RefCell;
+
+    thread_local! {
+        static RECORDER: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+    }
+
+    /// Tells the recorder about an event or call, if there is one, as the
+    /// compositor would in a live session.
+    fn record<F>(f: F) where F: FnOnce(&mut Recorder) {
+        RECORDER.with(|recorder| if let Some(ref mut recorder) = *recorder.borrow_mut() {
+            f(recorder)
+        });
+    }
+
+    /// A compositor which tiles views side by side and closes the focused
+    /// one on `q`.
+    fn compositor() {
+        callback::rust::output_created(|output| {
+            record(|recorder| recorder.output_created(output.into_raw(), &output.get_name(),
+                                                      output.get_resolution().unwrap()));
+            true
+        });
+        callback::rust::output_resolution(|output, &old_size, &new_size| {
+            record(|recorder| recorder.output_resolution(output.into_raw(), old_size, new_size));
+        });
+        callback::rust::view_created(|view| {
+            record(|recorder| recorder.view_created(view.into_raw(), &view.get_title()));
+            let output = view.get_output();
+            let x = 640 * (output.get_views().len() as i32 - 1);
+            let geometry = Geometry { origin: Point { x, y: 0 }, size: Size { w: 640, h: 720 } };
+            view.set_mask(output.get_mask());
+            view.set_geometry(EDGE_NONE, geometry);
+            record(|recorder| {
+                recorder.call("WlcView::set_mask", view.into_raw(), &[&output.get_mask()]);
+                recorder.call("WlcView::set_geometry", view.into_raw(), &[&EDGE_NONE, &geometry]);
+            });
+            true
+        });
+        callback::rust::view_destroyed(|view| {
+            record(|recorder| recorder.view_destroyed(view.into_raw()));
+            if let Some(&next) = view.get_output().get_views().iter().rev()
+                .find(|&&other| other != view) {
+                    next.focus();
+                    record(|recorder| recorder.call("WlcView::focus", next.into_raw(), &[]));
+                }
+        });
+        callback::rust::keyboard_key(|view, time, &mods, key, state| {
+            record(|recorder| recorder.keyboard_key(view.into_raw(), time, mods, key, state));
+            if key == KEY_Q.get_code() && state == KeyState::Pressed && !view.is_root() {
+                view.close();
+                record(|recorder| recorder.call("WlcView::close", view.into_raw(), &[]));
+            }
+            false
+        });
+        callback::rust::pointer_button(|view, time, &mods, button, state, &point| {
+            record(|recorder| {
+                recorder.pointer_button(view.into_raw(), time, mods, button, state, point)
+            });
+            if state == ButtonState::Pressed && !view.is_root() {
+                view.focus();
+                record(|recorder| recorder.call("WlcView::focus", view.into_raw(), &[]));
+            }
+            false
+        });
+        callback::rust::pointer_motion(|view, time, &point| {
+            record(|recorder| recorder.pointer_motion(view.into_raw(), time, point));
+            pointer::set_position(point);
+            false
+        });
+    }
+
+    #[test]
+    fn round_trip() {
+        // The live session, as wlc would drive it
+        RECORDER.with(|recorder| *recorder.borrow_mut() = Some(Recorder::new()));
+        compositor();
+        calls::set_recording(true);
+        let output = simulate::connect_output("DP-1", Size { w: 1280, h: 720 });
+        let xterm = simulate::open_view("xterm");
+        simulate::open_view("firefox");
+        simulate::pointer_motion(Point { x: 900, y: 300 });
+        dummy::advance_time(400);
+        simulate::pointer_motion(Point { x: 320, y: 360 });
+        simulate::pointer_button(BTN_LEFT, ButtonState::Pressed);
+        simulate::pointer_button(BTN_LEFT, ButtonState::Released);
+        dummy::advance_time(1000);
+        user::type_str("Hi q").unwrap();
+        // wlc destroys the view once its client closes
+        clients::perform(xterm, &Action::Close);
+        simulate::output_resolution(output, Size { w: 1920, h: 1080 });
+        simulate::keyboard_key(KEY_ESC, KeyState::Pressed);
+        simulate::keyboard_key(KEY_ESC, KeyState::Released);
+        let live = calls::calls();
+        let recorder = RECORDER.with(|recorder| recorder.borrow_mut().take()).unwrap();
+        assert_eq!(recorder.calls(), live);
+        let script = recorder.script();
+        assert_eq!(script, "version 2\n\
+                            output DP-1 1280x720\n\
+                            view xterm\n\
+                            view firefox\n\
+                            motion 900 300\n\
+                            wait 400ms\n\
+                            motion 320 360\n\
+                            click xterm\n\
+                            wait 1000ms\n\
+                            type Hi q\n\
+                            close xterm\n\
+                            resolution DP-1 1920x1080\n\
+                            # key 1 pressed\n");
+
+        // The replay against the dummy
+        dummy::new_session();
+        compositor();
+        calls::set_recording(true);
+        script::run(&script).unwrap();
+        assert_eq!(calls::calls(), recorder.calls());
+        let (mut replayed, mut captured) = (Vec::new(), Vec::new());
+        calls::write_jsonl(&mut replayed).unwrap();
+        recorder.write_calls_jsonl(&mut captured).unwrap();
+        assert_eq!(replayed, captured);
+    }
+}
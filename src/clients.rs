@@ -0,0 +1,206 @@
+//! Scripted behavior of fake clients.
+//!
+//! Views from `dummy::add_view` or the `fixtures` module sit still unless a
+//! test moves them. Real clients don't: they change their titles, ask to go
+//! fullscreen, and close at awkward times. A behavior attached to a view
+//! acts as its client, producing that churn while a test runs.
+//!
+//! Behaviors run after each event injected through the `simulate` module,
+//! in the order they were attached, and are told how many events have been
+//! injected since they were attached. Events which the behaviors cause
+//! themselves aren't counted and don't run them again. A behavior is
+//! detached when its view is removed.
+//!
+//! # Example
+//! ```rust
+//! use rustwlc::{clients, dummy, fixtures, simulate};
+//! use rustwlc::{Size, WlcEvent, VIEW_FULLSCREEN};
+//! use rustwlc::clients::Action;
+//!
+//! let output = dummy::add_output("WLC-1", Size { w: 1920, h: 1080 });
+//! let player = fixtures::mpv(output);
+//! clients::script(player, vec![
+//!     (5, Action::RequestState(VIEW_FULLSCREEN, true)),
+//!     (10, Action::SetTitle("credits.mkv - mpv".to_string()))
+//! ]);
+//! for _ in 0..10 {
+//!     simulate::dispatch(WlcEvent::CompositorReady);
+//! }
+//! assert_eq!(dummy::pending_requests(player).len(), 1);
+//! assert_eq!(player.get_title(), "credits.mkv - mpv");
+//! ```
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::callback::Callback;
+use super::dummy::{self, state};
+use super::dummy::state::Behavior;
+use super::event::WlcEvent;
+use super::handle::WlcView;
+use super::selection::{self, Selection};
+use super::simulate;
+use super::types::{Geometry, ViewState};
+
+/// Something a client does with its view.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// Requests a geometry, see `simulate::request_geometry`
+    RequestGeometry(Geometry),
+    /// Requests a state be set or unset, see `simulate::request_state`
+    RequestState(ViewState, bool),
+    /// Changes the view's title
+    SetTitle(String),
+    /// Changes the view's class
+    SetClass(String),
+    /// Changes the view's app id
+    SetAppId(String),
+    /// Offers data as a selection under MIME types, see
+    /// `selection::offer`
+    SetSelection(Selection, Vec<(String, Vec<u8>)>),
+    /// Unmaps the view, see `simulate::unmap_view`
+    Unmap,
+    /// Maps the view again, see `simulate::map_view`
+    Map,
+    /// Closes the view, invoking `view_destroyed` and removing it
+    Close,
+    /// Crashes, destroying all of the client's views, see
+    /// `simulate::crash_client`
+    Crash
+}
+
+/// Does something as a view's client, right away.
+pub fn perform(view: WlcView, action: &Action) {
+    match *action {
+        Action::RequestGeometry(geometry) => {
+            simulate::request_geometry(view, geometry);
+        },
+        Action::RequestState(view_state, toggle) => {
+            simulate::request_state(view, view_state, toggle);
+        },
+        Action::SetTitle(ref title) => dummy::set_title(view, title),
+        Action::SetClass(ref class) => dummy::set_class(view, class),
+        Action::SetAppId(ref app_id) => dummy::set_app_id(view, app_id),
+        Action::SetSelection(selection, ref offers) => {
+            selection::offer(selection, view, offers.clone());
+        },
+        Action::Unmap => {
+            simulate::unmap_view(view);
+        },
+        Action::Map => {
+            simulate::map_view(view);
+        },
+        Action::Close => {
+            // An unmapped view was already destroyed for the compositor
+            if dummy::is_mapped(view) {
+                simulate::dispatch(WlcEvent::ViewDestroyed(view));
+            }
+            dummy::remove_view(view);
+        },
+        Action::Crash => {
+            simulate::crash_client(view);
+        }
+    }
+}
+
+/// Attaches a behavior to a view's client.
+///
+/// After each event, `behavior` is given the view and the number of events
+/// injected since it was attached, starting from `1`.
+pub fn attach<F>(view: WlcView, behavior: F) where F: FnMut(WlcView, u32) + 'static {
+    state::with(|state| state.behaviors.push(Behavior {
+        view, events: 0, run: Callback(Rc::new(RefCell::new(behavior)))
+    }));
+}
+
+/// Attaches a script to a view's client: each action is performed once
+/// the given number of events have been injected.
+pub fn script(view: WlcView, steps: Vec<(u32, Action)>) {
+    attach(view, move |view, events| {
+        for &(after, ref action) in &steps {
+            if after == events && dummy::view_info(view).is_some() {
+                perform(view, action);
+            }
+        }
+    });
+}
+
+/// Detaches the behaviors of a view's client.
+///
+/// Returns whether it had any.
+pub fn detach(view: WlcView) -> bool {
+    state::with(|state| {
+        let len = state.behaviors.len();
+        state.behaviors.retain(|behavior| behavior.view != view);
+        state.behaviors.len() != len
+    })
+}
+
+/// Runs the behaviors after an event, unless they caused it.
+pub(crate) fn run() {
+    let behaviors = state::with(|state| {
+        if state.running_behaviors || state.behaviors.is_empty() {
+            return Vec::new()
+        }
+        state.running_behaviors = true;
+        for behavior in &mut state.behaviors {
+            behavior.events += 1;
+        }
+        state.behaviors.clone()
+    });
+    // Either none are attached, or these are already running further up
+    if behaviors.is_empty() {
+        return
+    }
+    for behavior in behaviors {
+        // Earlier behaviors may have closed the view
+        if dummy::view_info(behavior.view).is_some() {
+            (behavior.run.borrow_mut())(behavior.view, behavior.events);
+        }
+    }
+    state::with(|state| state.running_behaviors = false);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::super::clients::Action;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn script() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let view = dummy::add_view(output);
+        let destroyed = Rc::new(RefCell::new(Vec::new()));
+        let log = destroyed.clone();
+        callback::rust::view_destroyed(move |view| log.borrow_mut().push(view));
+        clients::script(view, vec![
+            (1, Action::SetTitle("Loading".to_string())),
+            (2, Action::RequestState(VIEW_MAXIMIZED, true)),
+            (3, Action::Close),
+            (4, Action::SetTitle("Gone".to_string()))
+        ]);
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let seen = events.clone();
+        clients::attach(view, move |_, events| seen.borrow_mut().push(events));
+
+        simulate::dispatch(WlcEvent::CompositorReady);
+        assert_eq!(view.get_title(), "Loading");
+        // The request is one more event, which isn't counted
+        simulate::dispatch(WlcEvent::CompositorReady);
+        assert_eq!(dummy::pending_requests(view).len(), 1);
+        assert_eq!(*events.borrow(), vec![1, 2]);
+        simulate::dispatch(WlcEvent::CompositorReady);
+        assert_eq!(*destroyed.borrow(), vec![view]);
+        assert!(dummy::view_info(view).is_none());
+        assert!(!clients::detach(view));
+        simulate::dispatch(WlcEvent::CompositorReady);
+        assert_eq!(*events.borrow(), vec![1, 2]);
+        // Views removed with their output lose their behaviors too
+        let other = dummy::add_view(output);
+        clients::attach(other, |_, _| {});
+        dummy::remove_output(output);
+        assert!(!clients::detach(other));
+    }
+}
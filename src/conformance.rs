@@ -0,0 +1,178 @@
+//! Conformance tests for the basics every compositor should get right.
+//!
+//! With the `conformance` feature, compositors can run these checks in
+//! their own test suites to catch regressions in fundamentals. Each check
+//! takes a function which registers the compositor's callbacks, as before
+//! calling `rustwlc::init()`, runs it in a fresh dummy compositor, and
+//! returns an error describing what went wrong, if anything. The current
+//! dummy state is restored afterwards.
+//!
+//! * `focus_moves_on_destroy`: destroying the focused view focuses another
+//! * `new_views_get_output_mask`: `view_created` gives new views a mask
+//!   which their output shows
+//! * `no_input_before_init`: input isn't handled before `rustwlc::init()`
+//!
+//! # Example
+//! ```rust
+//! use rustwlc::{callback, conformance, WlcView};
+//!
+//! fn register() {
+//!     callback::rust::view_created(|view| {
+//!         view.set_mask(view.get_output().get_mask());
+//!         view.focus();
+//!         true
+//!     });
+//!     callback::rust::view_destroyed(|view| {
+//!         let output = view.get_output();
+//!         if let Some(&next) = output.get_views().iter().rev().find(|&&other| other != view) {
+//!             next.focus();
+//!         }
+//!     });
+//! }
+//!
+//! assert_eq!(conformance::check_all(register), Ok(()));
+//! assert_eq!(conformance::focus_moves_on_destroy(|| {}),
+//!            Err(format!("{:?} was focused after WlcView(3) was destroyed, instead of WlcView(2)",
+//!                        WlcView::root())));
+//! ```
+
+use super::clients::{self, Action};
+use super::contract::{self, Warning};
+use super::calls;
+use super::dummy::{self, state};
+use super::event::WlcEvent;
+use super::handle::WlcView;
+use super::input::keys::{self, BTN_LEFT};
+use super::simulate;
+use super::types::{ButtonState, KeyState, Point, Size};
+
+/// Runs every check, stopping at the first which fails.
+///
+/// Errors start with the name of the check.
+pub fn check_all<R: Fn()>(register: R) -> Result<(), String> {
+    let named = |name| move |error| format!("{}: {}", name, error);
+    focus_moves_on_destroy(&register).map_err(named("focus_moves_on_destroy"))?;
+    new_views_get_output_mask(&register).map_err(named("new_views_get_output_mask"))?;
+    no_input_before_init(&register).map_err(named("no_input_before_init"))
+}
+
+/// Checks that destroying the focused view focuses another view.
+///
+/// Two views are opened and the second is focused, then its client closes
+/// it. The first must be focused afterwards.
+pub fn focus_moves_on_destroy<R: FnOnce()>(register: R) -> Result<(), String> {
+    session(register, true, || {
+        simulate::connect_output("WLC-1", Size { w: 1280, h: 720 });
+        simulate::dispatch(WlcEvent::CompositorReady);
+        let first = opened("first")?;
+        let second = opened("second")?;
+        second.focus();
+        clients::perform(second, &Action::Close);
+        match dummy::focused_view() {
+            view if view == first => Ok(()),
+            view => Err(format!("{:?} was focused after {:?} was destroyed, instead of {:?}",
+                                view, second, first))
+        }
+    })
+}
+
+/// Checks that `view_created` sets the mask of new views to one which their
+/// output shows.
+pub fn new_views_get_output_mask<R: FnOnce()>(register: R) -> Result<(), String> {
+    session(register, true, || {
+        let output = simulate::connect_output("WLC-1", Size { w: 1280, h: 720 });
+        simulate::dispatch(WlcEvent::CompositorReady);
+        contract::set_enabled(true);
+        let view = opened("view")?;
+        if contract::warnings().contains(&Warning::MaskNotSet(view)) {
+            Err(format!("view_created accepted {:?} without setting its mask", view))
+        } else if view.get_mask() & output.get_mask() == 0 {
+            Err(format!("view_created gave {:?} mask {}, which {:?} with mask {} doesn't show",
+                        view, view.get_mask(), output, output.get_mask()))
+        } else {
+            Ok(())
+        }
+    })
+}
+
+/// Checks that input before `rustwlc::init()` isn't handled.
+///
+/// A key, pointer motion and a button are simulated after the callbacks
+/// are registered but before `rustwlc::init()`. None of them may be
+/// handled, and no handle methods may be called for them.
+pub fn no_input_before_init<R: FnOnce()>(register: R) -> Result<(), String> {
+    session(register, false, || {
+        dummy::add_output("WLC-1", Size { w: 1280, h: 720 });
+        calls::set_recording(true);
+        let handled = [
+            ("key", simulate::keyboard_key(keys::KEY_A, KeyState::Pressed)),
+            ("pointer motion", simulate::pointer_motion(Point { x: 10, y: 10 })),
+            ("button", simulate::pointer_button(BTN_LEFT, ButtonState::Pressed))
+        ];
+        if let Some(&(input, _)) = handled.iter().find(|&&(_, handled)| handled) {
+            return Err(format!("a {} was handled before rustwlc::init()", input))
+        }
+        match calls::calls().first() {
+            Some(call) => Err(format!("{} was called for input before rustwlc::init()",
+                                      call.method)),
+            None => Ok(())
+        }
+    })
+}
+
+/// Runs a check in a fresh dummy compositor with the callbacks registered
+/// by `register`, restoring the current state afterwards.
+fn session<R, F>(register: R, init: bool, check: F) -> Result<(), String>
+    where R: FnOnce(), F: FnOnce() -> Result<(), String>
+{
+    let start = dummy::snapshot();
+    state::with(|state| *state = state::State::default());
+    register();
+    if init {
+        super::init();
+    }
+    let result = check();
+    dummy::rewind_to(&start);
+    result
+}
+
+/// Opens a view, failing if `view_created` rejects it.
+fn opened(title: &str) -> Result<WlcView, String> {
+    let view = simulate::open_view(title);
+    if view.is_root() {
+        return Err(format!("view_created rejected a view titled \"{}\"", title))
+    }
+    Ok(view)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn failures() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        assert_eq!(conformance::new_views_get_output_mask(|| {}),
+                   Err("view_created accepted WlcView(2) without setting its mask".to_string()));
+        assert_eq!(conformance::new_views_get_output_mask(|| {
+            callback::rust::view_created(|view| { view.set_mask(0); true });
+        }), Err("view_created gave WlcView(2) mask 0, which WlcOutput(1) with mask 1 doesn't show"
+                .to_string()));
+        assert_eq!(conformance::no_input_before_init(|| {
+            callback::rust::keyboard_key(|_, _, _, _, _| {
+                WlcOutput::get_primary().schedule_render();
+                false
+            });
+        }), Err("WlcOutput::schedule_render was called for input before rustwlc::init()"
+                .to_string()));
+        assert_eq!(conformance::no_input_before_init(|| {
+            callback::rust::pointer_button(|_, _, _, _, _, _| true);
+        }), Err("a button was handled before rustwlc::init()".to_string()));
+        assert_eq!(conformance::check_all(|| {
+            callback::rust::view_created(|_| false);
+        }).unwrap_err(), "focus_moves_on_destroy: view_created rejected a view titled \"first\"");
+        // The state from before is back
+        assert_eq!(WlcOutput::list(), vec![output]);
+        assert!(simulate::dispatch(WlcEvent::ViewCreated(dummy::add_view(output))));
+    }
+}
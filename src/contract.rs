@@ -0,0 +1,163 @@
+//! Checks a compositor against wlc's unwritten rules.
+//!
+//! wlc doesn't complain when a compositor breaks its contract: the cursor
+//! freezes, clients draw as unfocused, or views never show up. With checks
+//! enabled, the dummy watches for these known mistakes while events are
+//! simulated, and records a `Warning` for each, which tests can assert on:
+//!
+//! * a `pointer_motion` callback which doesn't warp the pointer with
+//!   `input::pointer::set_position`
+//! * a `view_focus` callback which doesn't give the focused view
+//!   `VIEW_ACTIVATED`
+//! * a `view_created` callback which accepts the view without setting its
+//!   mask
+//! * changing handles before `rustwlc::init()`
+//!
+//! Checks are off by default, since most tests drive the dummy without a
+//! full compositor.
+//!
+//! # Example
+//! ```rust
+//! use rustwlc::{contract, dummy, simulate, Point, Size};
+//! use rustwlc::contract::Warning;
+//!
+//! rustwlc::init();
+//! contract::set_enabled(true);
+//! dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+//! // No pointer_motion callback, so nothing moves the pointer
+//! simulate::pointer_motion(Point { x: 10, y: 10 });
+//! assert_eq!(contract::warnings(), vec![Warning::MotionNotWarped(Point { x: 10, y: 10 })]);
+//! ```
+
+use super::dummy::{self, state, Lifecycle};
+use super::event::WlcEvent;
+use super::handle::WlcView;
+use super::types::{Point, VIEW_ACTIVATED};
+
+/// A broken rule of wlc's contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Warning {
+    /// The pointer moved to a point, but wasn't warped there
+    MotionNotWarped(Point),
+    /// A view was focused, but not given `VIEW_ACTIVATED`
+    FocusNotActivated(WlcView),
+    /// A view was accepted, but its mask wasn't set
+    MaskNotSet(WlcView),
+    /// A handle method was called before `rustwlc::init()`
+    BeforeInit(&'static str)
+}
+
+/// Enables or disables the checks.
+pub fn set_enabled(enabled: bool) {
+    state::with(|state| state.contract_checks = enabled);
+}
+
+/// Gets the warnings recorded so far, oldest first.
+pub fn warnings() -> Vec<Warning> {
+    state::with(|state| state.contract_warnings.to_vec())
+}
+
+/// Forgets the warnings recorded so far.
+pub fn clear_warnings() {
+    state::with(|state| state.contract_warnings.clear());
+}
+
+fn enabled() -> bool {
+    state::with(|state| state.contract_checks)
+}
+
+fn warn(warning: Warning) {
+    state::with(|state| state.contract_warnings.push(warning));
+}
+
+/// Dispatches an event with `dispatch`, checking its callbacks kept to the
+/// contract.
+pub(crate) fn checked<F>(event: &WlcEvent, dispatch: F) -> bool where F: FnOnce() -> bool {
+    if !enabled() {
+        return dispatch()
+    }
+    let warps = || state::with(|state| state.warps.len());
+    let before = warps();
+    let result = dispatch();
+    let warning = match *event {
+        WlcEvent::PointerMotion { point, .. } if warps() == before =>
+            Some(Warning::MotionNotWarped(point)),
+        WlcEvent::ViewFocus(view, true) => {
+            let activated = dummy::view_info(view)
+                .is_some_and(|info| info.state.contains(VIEW_ACTIVATED));
+            if dummy::focused_view() == view && !activated {
+                Some(Warning::FocusNotActivated(view))
+            } else {
+                None
+            }
+        },
+        WlcEvent::ViewCreated(view) => {
+            let masked = state::with(|state| state.masks_set.remove(&view));
+            if result && dummy::view_info(view).is_some() && !masked {
+                Some(Warning::MaskNotSet(view))
+            } else {
+                None
+            }
+        },
+        _ => None
+    };
+    if let Some(warning) = warning {
+        warn(warning);
+    }
+    result
+}
+
+/// Notes a view's mask was set.
+pub(crate) fn mask_set(view: WlcView) {
+    state::with(|state| {
+        if state.contract_checks {
+            state.masks_set.insert(view);
+        }
+    });
+}
+
+/// Notes a handle method was called, which needs `rustwlc::init()` first.
+pub(crate) fn handle_call(method: &'static str) {
+    if enabled() && dummy::lifecycle() == Lifecycle::Uninitialized {
+        warn(Warning::BeforeInit(method));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::super::contract::Warning;
+
+    fn view_created(view: WlcView) -> bool {
+        view.set_mask(view.get_output().get_mask());
+        view.focus();
+        true
+    }
+
+    #[test]
+    fn checks() {
+        contract::set_enabled(true);
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let view = dummy::add_view(output);
+        view.set_geometry(EDGE_NONE, Geometry::default());
+        init();
+        // Focused without VIEW_ACTIVATED
+        callback::rust::view_created(view_created);
+        assert!(simulate::dispatch(WlcEvent::ViewCreated(view)));
+        callback::rust::view_focus(|view, focused| view.set_state(VIEW_ACTIVATED, focused));
+        let other = dummy::add_view(output);
+        // Accepted without a mask
+        callback::rust::view_created(|_| true);
+        simulate::dispatch(WlcEvent::ViewCreated(other));
+        other.focus();
+        assert_eq!(contract::warnings(), vec![
+            Warning::BeforeInit("WlcView::set_geometry"),
+            Warning::FocusNotActivated(view),
+            Warning::MaskNotSet(other)
+        ]);
+        contract::clear_warnings();
+        contract::set_enabled(false);
+        simulate::pointer_motion(Point { x: 1, y: 1 });
+        assert!(contract::warnings().is_empty());
+    }
+}
@@ -0,0 +1,145 @@
+//! Runs two versions of a compositor against the same events.
+//!
+//! Refactoring a window manager's core is hard to check with assertions
+//! written for the old code. Instead, `compare` registers the callbacks of
+//! each version in turn, runs the same scenario against both from the same
+//! starting state, and reports where the calls they made (see the `calls`
+//! module) or the states they left the compositor in differ.
+//!
+//! # Example
+//! ```rust
+//! use rustwlc::{callback, differential, dummy, simulate};
+//! use rustwlc::{Size, WlcEvent, WlcView};
+//!
+//! fn old_view_created(view: WlcView) -> bool {
+//!     view.set_mask(1);
+//!     view.focus();
+//!     true
+//! }
+//!
+//! fn new_view_created(view: WlcView) -> bool {
+//!     view.set_mask(view.get_output().get_mask());
+//!     view.focus();
+//!     true
+//! }
+//!
+//! let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+//! let view = dummy::add_view(output);
+//! let comparison = differential::compare(
+//!     || callback::rust::view_created(old_view_created),
+//!     || callback::rust::view_created(new_view_created),
+//!     || { simulate::dispatch(WlcEvent::ViewCreated(view)); });
+//! assert!(comparison.is_same(), "{}", comparison);
+//! ```
+
+use std::fmt;
+
+use super::calls::{self, Call};
+use super::dummy::{self, Snapshot};
+
+/// What two versions of a compositor did in the same scenario.
+///
+/// The `Display` output describes the first difference, if any.
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    /// Calls made by the left and right versions
+    pub calls: [Vec<Call>; 2],
+    /// State the left and right versions ended in
+    pub snapshots: [Snapshot; 2]
+}
+
+impl Comparison {
+    /// Gets the index of the first call which differs, if any.
+    ///
+    /// If one version made all the calls of the other and then some, this
+    /// is the index of its first extra call.
+    pub fn first_difference(&self) -> Option<usize> {
+        let [ref left, ref right] = self.calls;
+        left.iter().zip(right).position(|(left, right)| left != right)
+            .or_else(|| if left.len() != right.len() {
+                Some(left.len().min(right.len()))
+            } else {
+                None
+            })
+    }
+
+    /// Whether both versions made the same calls and ended in the same
+    /// state.
+    pub fn is_same(&self) -> bool {
+        self.first_difference().is_none() && self.snapshots[0] == self.snapshots[1]
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(index) = self.first_difference() {
+            let [ref left, ref right] = self.calls;
+            write!(f, "call {} differs:\n    left: {:?}\n    right: {:?}",
+                   index, left.get(index), right.get(index))
+        } else if self.snapshots[0] != self.snapshots[1] {
+            write!(f, "same calls, but the final states differ")
+        } else {
+            write!(f, "no differences")
+        }
+    }
+}
+
+/// Runs a scenario against two versions of a compositor, comparing them.
+///
+/// `left` and `right` register the callbacks of each version. Each version
+/// starts from the current state, with the callbacks registered so far,
+/// and runs `scenario` while its calls are recorded. The current state is
+/// restored afterwards, so the callbacks of neither version stay
+/// registered.
+pub fn compare<L, R, F>(left: L, right: R, mut scenario: F) -> Comparison
+    where L: FnOnce(), R: FnOnce(), F: FnMut()
+{
+    let start = dummy::snapshot();
+    let (left_calls, left_end) = run(&start, left, &mut scenario);
+    let (right_calls, right_end) = run(&start, right, &mut scenario);
+    dummy::rewind_to(&start);
+    Comparison {
+        calls: [left_calls, right_calls],
+        snapshots: [left_end, right_end]
+    }
+}
+
+/// Runs a scenario from a starting state, with the callbacks registered by
+/// `register`.
+fn run<R, F>(start: &Snapshot, register: R, scenario: &mut F) -> (Vec<Call>, Snapshot)
+    where R: FnOnce(), F: FnMut()
+{
+    dummy::rewind_to(start);
+    register();
+    calls::clear();
+    calls::set_recording(true);
+    scenario();
+    (calls::calls(), dummy::snapshot())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn differences() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let view = dummy::add_view(output);
+        let comparison = differential::compare(
+            || callback::rust::view_created(|view| { view.focus(); true }),
+            || callback::rust::view_created(|view| { view.bring_to_front(); view.focus(); true }),
+            || { simulate::dispatch(WlcEvent::ViewCreated(view)); });
+        assert_eq!(comparison.first_difference(), Some(0));
+        assert_eq!(comparison.calls[1][0].method, "WlcView::bring_to_front");
+        // Same calls, but the right one's view can't take focus
+        let comparison = differential::compare(
+            || {},
+            || dummy::set_mapped(view, false),
+            || view.focus());
+        assert_eq!(comparison.first_difference(), None);
+        assert!(!comparison.is_same());
+        // Neither version is left registered
+        assert!(simulate::dispatch(WlcEvent::ViewCreated(view)));
+        assert!(dummy::focused_view().is_root());
+    }
+}
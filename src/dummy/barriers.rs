@@ -0,0 +1,166 @@
+//! Pointer barriers at the edges of outputs.
+
+use super::super::handle::WlcOutput;
+use super::super::types::{Gravity, Point};
+use super::state;
+
+/// A barrier which holds the pointer at an edge of an output.
+///
+/// Compositors put barriers on the edges between monitors, so the pointer
+/// sticks briefly instead of slipping onto the next monitor when it's
+/// thrown against the edge of a maximized window's scrollbar or panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Barrier {
+    /// The output the barrier is on
+    pub output: WlcOutput,
+    /// The edge: `Left`, `Right`, `Top` or `Bottom`, or a corner for both
+    /// of its edges. `Center` has no edges.
+    pub edge: Gravity,
+    /// How far the pointer must push past the edge, summed over motions,
+    /// before the barrier lets it through; `None` never does
+    pub resistance: Option<u32>
+}
+
+/// Adds a barrier, see `Barrier`.
+///
+/// `simulate::pointer_motion` stops motion which crosses the edge at the
+/// edge, and the compositor's `pointer_motion` callback is given the point
+/// on the edge. Each motion which pushes past the edge adds how far past
+/// to the barrier's pressure, and one which doesn't resets it. Once the
+/// pressure is over the resistance, the motion is let through and the
+/// pressure resets; if the pointer wasn't on the edge yet, the motion is
+/// split in two, with the callback invoked at the edge first.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, simulate, Gravity, Point, Size};
+/// use rustwlc::dummy::Barrier;
+/// use rustwlc::input::pointer;
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let seen = Rc::new(RefCell::new(Vec::new()));
+/// let log = seen.clone();
+/// callback::rust::pointer_motion(move |_, _, point| {
+///     log.borrow_mut().push(*point);
+///     pointer::set_position(*point);
+///     false
+/// });
+/// dummy::add_barrier(Barrier { output, edge: Gravity::Right, resistance: Some(50) });
+/// pointer::set_position(Point { x: 780, y: 300 });
+/// simulate::pointer_motion(Point { x: 830, y: 300 });
+/// simulate::pointer_motion(Point { x: 829, y: 300 });
+/// // Held at the edge until pushed 50 pixels past it
+/// assert_eq!(*seen.borrow(), vec![Point { x: 799, y: 300 }, Point { x: 829, y: 300 }]);
+/// ```
+pub fn add_barrier(barrier: Barrier) {
+    state::with(|state| state.barriers.push((barrier, 0)));
+}
+
+/// Removes all barriers.
+pub fn clear_barriers() {
+    state::with(|state| state.barriers.clear());
+}
+
+/// Applies the barriers of an output to a motion from `from` to `to`,
+/// giving the points the motion callback should see, in order.
+pub(crate) fn apply_barriers(output: WlcOutput, from: Point, to: Point) -> Vec<Point> {
+    state::with(|state| {
+        let size = match state.output(output) {
+            Some(info) => info.resolution,
+            None => return vec![to]
+        };
+        let max = Point { x: size.w as i32 - 1, y: size.h as i32 - 1 };
+        // Held by every barrier pushed past, or let through by those
+        // whose resistance is overcome
+        let (mut edge, mut held) = (to, to);
+        for &mut (barrier, ref mut pressure) in &mut state.barriers {
+            if barrier.output != output {
+                continue
+            }
+            let (horizontal, vertical) = edges(barrier.edge);
+            let clamp = |point: &mut Point| {
+                if horizontal == Some(false) && point.x < 0 { point.x = 0; }
+                if horizontal == Some(true) && point.x > max.x { point.x = max.x; }
+                if vertical == Some(false) && point.y < 0 { point.y = 0; }
+                if vertical == Some(true) && point.y > max.y { point.y = max.y; }
+            };
+            let mut clamped = to;
+            clamp(&mut clamped);
+            let past = (to.x as i64 - clamped.x as i64).unsigned_abs() +
+                (to.y as i64 - clamped.y as i64).unsigned_abs();
+            if past == 0 {
+                *pressure = 0;
+                continue
+            }
+            clamp(&mut edge);
+            *pressure = pressure.saturating_add(past.min(u32::MAX as u64) as u32);
+            match barrier.resistance {
+                Some(resistance) if *pressure > resistance => *pressure = 0,
+                _ => clamp(&mut held)
+            }
+        }
+        if held == edge || from == edge {
+            vec![held]
+        } else {
+            vec![edge, held]
+        }
+    })
+}
+
+/// The edges of a gravity, as whether each axis is at its far end, if at
+/// either.
+pub(super) fn edges(gravity: Gravity) -> (Option<bool>, Option<bool>) {
+    match gravity {
+        Gravity::TopLeft => (Some(false), Some(false)),
+        Gravity::Top => (None, Some(false)),
+        Gravity::TopRight => (Some(true), Some(false)),
+        Gravity::Left => (Some(false), None),
+        Gravity::Center => (None, None),
+        Gravity::Right => (Some(true), None),
+        Gravity::BottomLeft => (Some(false), Some(true)),
+        Gravity::Bottom => (None, Some(true)),
+        Gravity::BottomRight => (Some(true), Some(true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::*;
+
+    #[test]
+    fn barriers() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let log = seen.clone();
+        callback::rust::pointer_motion(move |_, _, point| {
+            log.borrow_mut().push(*point);
+            input::pointer::set_position(*point);
+            false
+        });
+        dummy::add_barrier(dummy::Barrier { output, edge: Gravity::TopLeft, resistance: None });
+        dummy::add_barrier(dummy::Barrier { output, edge: Gravity::Bottom, resistance: Some(10) });
+        input::pointer::set_position(Point { x: 100, y: 100 });
+        simulate::pointer_motion(Point { x: -50, y: -20 });
+        simulate::pointer_motion(Point { x: -500, y: 50 });
+        assert_eq!(*seen.borrow(), vec![Point { x: 0, y: 0 }, Point { x: 0, y: 50 }]);
+        seen.borrow_mut().clear();
+        // Overcome in one push, split at the edge
+        simulate::pointer_motion(Point { x: 10, y: 620 });
+        assert_eq!(*seen.borrow(), vec![Point { x: 10, y: 599 }, Point { x: 10, y: 620 }]);
+        seen.borrow_mut().clear();
+        // Pressure resets when the pointer moves away
+        simulate::pointer_motion(Point { x: 10, y: 595 });
+        simulate::pointer_motion(Point { x: 10, y: 605 });
+        simulate::pointer_motion(Point { x: 10, y: 590 });
+        simulate::pointer_motion(Point { x: 10, y: 605 });
+        assert_eq!(*seen.borrow(), vec![Point { x: 10, y: 595 }, Point { x: 10, y: 599 },
+                                        Point { x: 10, y: 590 }, Point { x: 10, y: 599 }]);
+        dummy::clear_barriers();
+        simulate::pointer_motion(Point { x: -5, y: 605 });
+        assert_eq!(seen.borrow().last(), Some(&Point { x: -5, y: 605 }));
+    }
+}
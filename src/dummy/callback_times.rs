@@ -0,0 +1,153 @@
+//! Timing of the compositor's callbacks.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use super::super::event::WlcEvent;
+use super::state;
+
+/// Wall-clock times taken by the callbacks of one kind of event.
+///
+/// Each time counts everything dispatching the event ran: the handlers
+/// from `callback::add_handler`, the callback, and the callbacks of events
+/// dispatched meanwhile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallbackTimes {
+    /// The times, shortest first
+    samples: Vec<Duration>
+}
+
+impl CallbackTimes {
+    /// Number of times the event was dispatched.
+    pub fn count(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Gets the time which `percent` percent of the dispatches took at
+    /// most, by nearest rank.
+    pub fn percentile(&self, percent: f64) -> Duration {
+        let rank = (percent.clamp(0.0, 100.0) / 100.0 * self.samples.len() as f64).ceil();
+        self.samples[(rank as usize).max(1) - 1]
+    }
+
+    /// The median time.
+    pub fn p50(&self) -> Duration {
+        self.percentile(50.0)
+    }
+
+    /// The time 95% of dispatches took at most.
+    pub fn p95(&self) -> Duration {
+        self.percentile(95.0)
+    }
+
+    /// The longest time.
+    pub fn max(&self) -> Duration {
+        self.samples[self.samples.len() - 1]
+    }
+}
+
+/// Starts or stops timing callbacks.
+///
+/// While enabled, each dispatch of an event is timed by the wall clock,
+/// and the times of each kind of event can be read with `callback_times`,
+/// by the name of its callback. Stopping forgets the times.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, simulate, Size, WlcEvent};
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// callback::rust::view_created(|_| { thread::sleep(Duration::from_millis(2)); true });
+/// dummy::record_callback_times(true);
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// simulate::dispatch(WlcEvent::ViewCreated(dummy::add_view(output)));
+/// let times = dummy::callback_times("view_created").unwrap();
+/// assert_eq!(times.count(), 1);
+/// assert!(times.p50() >= Duration::from_millis(2));
+/// dummy::assert_callback_budget("view_created", 95.0, Duration::from_secs(1));
+/// ```
+pub fn record_callback_times(enabled: bool) {
+    state::with(|state| {
+        state.callback_times = if enabled {
+            state.callback_times.take().or_else(|| Some(HashMap::new()))
+        } else {
+            None
+        }
+    });
+}
+
+/// Gets the times of the callbacks of an event, by the name of its
+/// callback (see `WlcEvent::name`), if any were timed.
+pub fn callback_times(name: &str) -> Option<CallbackTimes> {
+    state::with(|state| {
+        let mut samples = state.callback_times.as_ref()?.get(name)?.to_vec();
+        samples.sort();
+        Some(CallbackTimes { samples })
+    })
+}
+
+/// Forgets the times recorded so far, without stopping the timing.
+pub fn clear_callback_times() {
+    state::with(|state| {
+        if let Some(ref mut times) = state.callback_times {
+            times.clear();
+        }
+    });
+}
+
+/// Checks that `percent` percent of the timed dispatches of an event took
+/// at most `budget`, so a test fails when its callbacks get slower.
+///
+/// Events which were never timed pass.
+///
+/// # Panics
+/// If the event went over its budget.
+pub fn assert_callback_budget(name: &str, percent: f64, budget: Duration) {
+    if let Some(times) = callback_times(name) {
+        let time = times.percentile(percent);
+        assert!(time <= budget,
+                "dummy-rustwlc callback budget: {} took {:?} at the {}th percentile of {} \
+                 dispatches, over its budget of {:?}", name, time, percent, times.count(), budget);
+    }
+}
+
+/// Records the time an event's dispatch took, if callbacks are timed.
+pub(crate) fn time_callback(event: &WlcEvent, elapsed: Duration) {
+    state::with(|state| {
+        if let Some(ref mut times) = state.callback_times {
+            times.entry(event.name()).or_default().push(elapsed);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::*;
+
+    #[test]
+    fn callback_times() {
+        use std::thread;
+        use std::time::Duration;
+        callback::rust::compositor_ready(|| thread::sleep(Duration::from_millis(1)));
+        simulate::dispatch(WlcEvent::CompositorReady);
+        assert_eq!(dummy::callback_times("compositor_ready"), None);
+        dummy::record_callback_times(true);
+        for _ in 0..4 {
+            simulate::dispatch(WlcEvent::CompositorReady);
+        }
+        simulate::dispatch(WlcEvent::CompositorTerminate);
+        let times = dummy::callback_times("compositor_ready").unwrap();
+        assert_eq!(times.count(), 4);
+        assert!(times.percentile(0.0) >= Duration::from_millis(1));
+        assert!(times.p95() <= times.max());
+        assert_eq!(dummy::callback_times("compositor_terminate").unwrap().count(), 1);
+        let slow = ::std::panic::catch_unwind(|| {
+            dummy::assert_callback_budget("compositor_ready", 50.0, Duration::from_millis(1));
+        });
+        assert!(slow.is_err());
+        dummy::clear_callback_times();
+        assert_eq!(dummy::callback_times("compositor_ready"), None);
+        dummy::assert_callback_budget("compositor_ready", 50.0, Duration::from_millis(1));
+    }
+}
@@ -0,0 +1,191 @@
+//! Events queued for dispatch later, and the watchdog over dispatching.
+
+use super::super::event::WlcEvent;
+use super::state;
+
+/// Queues an event for `simulate::dispatch_pending`.
+///
+/// Functions of the `simulate` module deliver their events at once. A test
+/// can instead queue events, such as those of a recorded session, then look
+/// at and drop some before delivering the rest.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, simulate, WlcEvent, WlcOutput};
+///
+/// let output = WlcOutput::dummy(1);
+/// dummy::queue_event(WlcEvent::OutputRenderPre(output));
+/// dummy::queue_event(WlcEvent::OutputFocus(output, true));
+/// dummy::queue_event(WlcEvent::OutputRenderPost(output));
+/// let dropped = dummy::flush_matching(|event| event.name().starts_with("output_render"));
+/// assert_eq!(dropped.len(), 2);
+/// assert_eq!(dummy::pending_events(), vec![WlcEvent::OutputFocus(output, true)]);
+/// assert_eq!(simulate::dispatch_pending(), 1);
+/// assert!(dummy::pending_events().is_empty());
+/// ```
+pub fn queue_event(event: WlcEvent) {
+    state::with(|state| state.pending_events.push(event));
+}
+
+/// Gets the queued events, in the order they will be delivered.
+pub fn pending_events() -> Vec<WlcEvent> {
+    state::with(|state| state.pending_events.clone())
+}
+
+/// Drops the queued events which match a predicate, returning them.
+///
+/// The other events stay queued in order.
+pub fn flush_matching<F>(mut predicate: F) -> Vec<WlcEvent> where F: FnMut(&WlcEvent) -> bool {
+    let pending = state::with(|state| std::mem::take(&mut state.pending_events));
+    let (dropped, kept) = pending.into_iter().partition(|event| predicate(event));
+    state::with(|state| {
+        // Events queued by the predicate go after the kept ones
+        let queued = std::mem::replace(&mut state.pending_events, kept);
+        state.pending_events.extend(queued);
+    });
+    dropped
+}
+
+/// Takes the oldest queued event.
+pub(crate) fn next_pending() -> Option<WlcEvent> {
+    state::with(|state| if state.pending_events.is_empty() {
+        None
+    } else {
+        Some(state.pending_events.remove(0))
+    })
+}
+
+/// Gets the event whose callbacks are running, if any.
+///
+/// While callbacks of one event cause another, i.e. focusing a view from
+/// a `pointer_button` callback, this is the innermost one.
+pub fn current_event() -> Option<WlcEvent> {
+    state::with(|state| state.dispatching.last().cloned())
+}
+
+/// Limits on the events caused by one stimulus, see `set_watchdog`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watchdog {
+    /// Most events whose callbacks may be running at once
+    pub max_depth: usize,
+    /// Most events one stimulus may dispatch, counting itself
+    pub max_events: usize
+}
+
+impl Default for Watchdog {
+    fn default() -> Watchdog {
+        Watchdog { max_depth: 32, max_events: 1000 }
+    }
+}
+
+/// Sets the limits on the events caused by one stimulus, or `None` to stop
+/// watching.
+///
+/// A stimulus is an event dispatched while no callbacks are running, such
+/// as a simulated key press. Its callbacks may cause more events, i.e. by
+/// focusing a view, which may cause more in turn. Compositors whose
+/// callbacks undo each other, such as a `view_focus` callback which gives
+/// focus back to the view which lost it, never stop. While a watchdog is
+/// set, the event which goes over either limit panics instead, listing the
+/// events which led up to it.
+///
+/// # Example
+/// ```rust,should_panic
+/// use rustwlc::{callback, dummy, Size, WlcView};
+/// use rustwlc::dummy::Watchdog;
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let first = dummy::add_view(output);
+/// let second = dummy::add_view(output);
+/// // Whichever view gets focus hands it to the other
+/// callback::rust::view_focus(move |view, focused| if focused {
+///     if view == first { second.focus() } else { first.focus() }
+/// });
+/// dummy::set_watchdog(Some(Watchdog::default()));
+/// first.focus();
+/// ```
+pub fn set_watchdog(watchdog: Option<Watchdog>) {
+    state::with(|state| state.watchdog = watchdog);
+}
+
+/// Counts an event which is about to be dispatched against the watchdog,
+/// panicking if it goes over a limit.
+pub(crate) fn watch(event: &WlcEvent) {
+    // Enough of the trace to show what repeats
+    const TRACE: usize = 16;
+    let overrun = state::with(|state| {
+        if state.dispatching.is_empty() {
+            state.cascade.clear();
+        }
+        let watchdog = state.watchdog?;
+        state.cascade.push(*event);
+        let depth = state.dispatching.len() + 1;
+        let (message, trace): (_, Vec<_>) = if depth > watchdog.max_depth {
+            (format!("{} events nested, innermost first:", depth),
+             state.dispatching.iter().chain(Some(event)).rev().take(TRACE).collect())
+        } else if state.cascade.len() > watchdog.max_events {
+            (format!("{} events dispatched for {:?}, latest first:",
+                     state.cascade.len(), state.cascade[0]),
+             state.cascade.iter().rev().take(TRACE).collect())
+        } else {
+            return None
+        };
+        Some(trace.iter().fold(message, |message, event| {
+            format!("{}\n    {:?}", message, event)
+        }))
+    });
+    if let Some(message) = overrun {
+        panic!("dummy-rustwlc watchdog: {}", message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::*;
+
+    #[test]
+    fn event_queue() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        let output = WlcOutput::dummy(1);
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let log = seen.clone();
+        // Focusing an output queues a render
+        callback::rust::output_focus(move |output, focused| {
+            log.borrow_mut().push(focused);
+            dummy::queue_event(WlcEvent::OutputRenderPre(output));
+        });
+        dummy::queue_event(WlcEvent::OutputFocus(output, true));
+        dummy::queue_event(WlcEvent::OutputRenderPre(output));
+        dummy::queue_event(WlcEvent::OutputFocus(output, false));
+        assert_eq!(dummy::flush_matching(|event| *event == WlcEvent::OutputRenderPre(output)),
+                   vec![WlcEvent::OutputRenderPre(output)]);
+        assert_eq!(simulate::dispatch_pending(), 4);
+        assert_eq!(*seen.borrow(), vec![true, false]);
+        assert!(dummy::pending_events().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "1001 events dispatched for CompositorReady")]
+    fn watchdog_volume() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let first = dummy::add_view(output);
+        let second = dummy::add_view(output);
+        callback::rust::compositor_ready(move || {
+            for _ in 0..100 {
+                first.focus();
+                second.focus();
+            }
+        });
+        dummy::set_watchdog(Some(dummy::Watchdog { max_depth: 2, max_events: 1000 }));
+        // Each stimulus is counted on its own
+        for _ in 0..3 {
+            simulate::dispatch(WlcEvent::CompositorReady);
+        }
+        callback::rust::compositor_ready(move || loop {
+            first.focus();
+            second.focus();
+        });
+        simulate::dispatch(WlcEvent::CompositorReady);
+    }
+}
@@ -0,0 +1,135 @@
+//! Frame budgets and the statistics of rendered frames.
+
+use std::time::Duration;
+
+use super::super::handle::{WlcOutput, WlcView};
+use super::{state, update_view};
+
+/// Clock used to time frames against a `FrameBudget`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameClock {
+    /// The dummy's `time()`, which render callbacks advance to model work
+    Mock,
+    /// Wall-clock time spent in render callbacks
+    Real
+}
+
+/// Time allowed for rendering a frame of an output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameBudget {
+    /// Longest a frame may take before it is dropped
+    pub budget: Duration,
+    /// How frames are timed
+    pub clock: FrameClock
+}
+
+impl FrameBudget {
+    /// The budget of a display refreshing `hz` times a second.
+    ///
+    /// # Panics
+    /// Panics if `hz` is zero.
+    pub fn from_refresh_rate(hz: u32, clock: FrameClock) -> FrameBudget {
+        FrameBudget { budget: Duration::from_secs(1) / hz, clock }
+    }
+}
+
+/// Statistics of frames rendered by `simulate::render_frame`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FrameStats {
+    /// Number of times the output was scheduled for rendering while it
+    /// wasn't already, by `WlcOutput::schedule_render` or a geometry change
+    pub scheduled: u32,
+    /// Number of frames rendered
+    pub frames: u32,
+    /// Number of frames which went over the budget
+    pub dropped: u32,
+    /// Time taken by the longest frame
+    pub longest: Duration
+}
+
+/// Sets the time allowed for each frame, or `None` to stop budgeting.
+///
+/// While a budget is set, frames from `simulate::render_frame` which take
+/// longer than it are counted as dropped in `frame_stats`. With
+/// `FrameClock::Mock`, render callbacks model expensive work by calling
+/// `advance_time`; with `FrameClock::Real`, the time they actually take is
+/// measured, which catches pathological per-frame work in CI.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, simulate};
+/// use rustwlc::{WlcOutput, Size};
+/// use rustwlc::dummy::{FrameBudget, FrameClock};
+///
+/// fn output_render_pre(output: WlcOutput) {
+///     dummy::advance_time(20);
+/// }
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// callback::rust::output_render_pre(output_render_pre);
+/// dummy::set_frame_budget(Some(FrameBudget::from_refresh_rate(60, FrameClock::Mock)));
+/// assert!(simulate::render_frame(output));
+/// assert_eq!(dummy::frame_stats(output).dropped, 1);
+/// ```
+pub fn set_frame_budget(budget: Option<FrameBudget>) {
+    state::with(|state| state.frame_budget = budget);
+}
+
+/// Sets the time rendering a view takes, on top of the time its render
+/// callbacks take.
+///
+/// `simulate::render_frame` adds the costs of the views it renders to the
+/// time of the frame, as timed against the `FrameBudget`, so a test can
+/// model heavy views, i.e. a video which costs 6ms a frame, and check that
+/// the compositor notices the dropped frames, say by turning off effects.
+/// Views cost nothing by default.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, simulate, Size};
+/// use rustwlc::dummy::{FrameBudget, FrameClock};
+/// use std::time::Duration;
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let video = dummy::add_view(output);
+/// let player = dummy::add_view(output);
+/// dummy::set_frame_budget(Some(FrameBudget::from_refresh_rate(60, FrameClock::Mock)));
+/// dummy::set_render_cost(video, Duration::from_millis(6));
+/// dummy::set_render_cost(player, Duration::from_millis(6));
+/// assert!(!simulate::render_frame(output));
+/// dummy::set_render_cost(player, Duration::from_millis(12));
+/// assert!(simulate::render_frame(output));
+/// // Minimized views aren't rendered
+/// dummy::set_minimized(video, true);
+/// assert!(!simulate::render_frame(output));
+/// assert_eq!(dummy::frame_stats(output).longest, Duration::from_millis(18));
+/// ```
+pub fn set_render_cost(view: WlcView, cost: Duration) {
+    update_view(view, |info| info.render_cost = cost);
+}
+
+/// Gets the render statistics of an output.
+pub fn frame_stats(output: WlcOutput) -> FrameStats {
+    state::with(|state| state.frame_stats.get(&output).cloned().unwrap_or_default())
+}
+
+/// The budget frames are timed against, if any.
+pub(crate) fn frame_budget() -> Option<FrameBudget> {
+    state::with(|state| state.frame_budget)
+}
+
+/// Counts a frame which took `elapsed`, returning whether it was dropped.
+pub(crate) fn record_frame(output: WlcOutput, elapsed: Duration) -> bool {
+    state::with(|state| {
+        let dropped = state.frame_budget
+            .is_some_and(|budget| elapsed > budget.budget);
+        state.render_scheduled.remove(&output);
+        let stats = state.frame_stats.entry(output).or_default();
+        stats.frames += 1;
+        if dropped {
+            stats.dropped += 1;
+        }
+        stats.longest = stats.longest.max(elapsed);
+        dropped
+    })
+}
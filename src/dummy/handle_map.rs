@@ -0,0 +1,307 @@
+//! A persistent map keyed by handles.
+//!
+//! Handles are small integers, so the map is a radix trie with 32 children
+//! per node. Nodes are shared between clones and copied when written to, so
+//! cloning the map is O(1) and a write after a clone only copies the nodes on
+//! the path to the written key. This keeps `dummy::snapshot()` cheap no
+//! matter how many views there are.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Index;
+use std::rc::Rc;
+
+use super::super::handle::{WlcOutput, WlcView};
+
+const BITS: u32 = 5;
+const WIDTH: usize = 1 << BITS;
+const MASK: u64 = (WIDTH as u64) - 1;
+
+/// Handle types which can key a `HandleMap`.
+pub trait Handle: Copy {
+    /// Gets the raw value of the handle.
+    fn raw(self) -> usize;
+    /// Creates a handle from its raw value.
+    fn from_raw(raw: usize) -> Self;
+}
+
+impl Handle for WlcView {
+    fn raw(self) -> usize {
+        self.into_raw()
+    }
+
+    fn from_raw(raw: usize) -> WlcView {
+        WlcView::from_raw(raw)
+    }
+}
+
+impl Handle for WlcOutput {
+    fn raw(self) -> usize {
+        self.into_raw()
+    }
+
+    fn from_raw(raw: usize) -> WlcOutput {
+        WlcOutput::from_raw(raw)
+    }
+}
+
+#[derive(Clone)]
+enum Node<V> {
+    /// Children of an inner node
+    Branch(Vec<Option<Rc<Node<V>>>>),
+    /// Values at the bottom level
+    Leaf(Vec<Option<V>>)
+}
+
+impl<V: Clone> Node<V> {
+    fn new(shift: u32) -> Node<V> {
+        if shift == 0 {
+            Node::Leaf(vec![None; WIDTH])
+        } else {
+            Node::Branch(vec![None; WIDTH])
+        }
+    }
+}
+
+/// A map from handles to values with cheap clones.
+pub struct HandleMap<K, V> {
+    root: Option<Rc<Node<V>>>,
+    /// Shift of the root level; keys below `1 << (shift + BITS)` fit.
+    shift: u32,
+    len: usize,
+    key: PhantomData<K>
+}
+
+impl<K: Handle, V: Clone> HandleMap<K, V> {
+    /// Creates an empty map.
+    pub fn new() -> HandleMap<K, V> {
+        HandleMap { root: None, shift: 0, len: 0, key: PhantomData }
+    }
+
+    /// Number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    fn fits(&self, raw: u64) -> bool {
+        self.shift + BITS >= 64 || raw >> (self.shift + BITS) == 0
+    }
+
+    /// Gets the value of a key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let raw = key.raw() as u64;
+        if !self.fits(raw) {
+            return None
+        }
+        let mut node = match self.root {
+            Some(ref root) => &**root,
+            None => return None
+        };
+        let mut shift = self.shift;
+        loop {
+            let index = ((raw >> shift) & MASK) as usize;
+            match *node {
+                Node::Branch(ref children) => match children[index] {
+                    Some(ref child) => node = child,
+                    None => return None
+                },
+                Node::Leaf(ref values) => return values[index].as_ref()
+            }
+            shift -= BITS;
+        }
+    }
+
+    /// Whether the map contains a key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Gets the slot of a key, creating the path to it.
+    fn slot(&mut self, raw: u64) -> &mut Option<V> {
+        while !self.fits(raw) {
+            let mut root = Node::new(self.shift + BITS);
+            if let Node::Branch(ref mut children) = root {
+                children[0] = self.root.take();
+            }
+            self.root = Some(Rc::new(root));
+            self.shift += BITS;
+        }
+        let mut shift = self.shift;
+        let mut node = Rc::make_mut(self.root.get_or_insert_with(
+            || Rc::new(Node::new(shift))));
+        loop {
+            let index = ((raw >> shift) & MASK) as usize;
+            match *node {
+                Node::Branch(ref mut children) => {
+                    let child = children[index].get_or_insert_with(
+                        || Rc::new(Node::new(shift - BITS)));
+                    node = Rc::make_mut(child);
+                },
+                Node::Leaf(ref mut values) => return &mut values[index]
+            }
+            shift -= BITS;
+        }
+    }
+
+    /// Gets the value of a key mutably, copying any shared nodes.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        if !self.contains_key(key) {
+            return None
+        }
+        self.slot(key.raw() as u64).as_mut()
+    }
+
+    /// Sets the value of a key, returning the old value.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let old = self.slot(key.raw() as u64).replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Removes a key, returning its value.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        if !self.contains_key(key) {
+            return None
+        }
+        self.len -= 1;
+        self.slot(key.raw() as u64).take()
+    }
+
+    /// Iterates over the entries in order of their keys.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            stack: self.root.iter().map(|root| (&**root, 0, 0, self.shift))
+                .collect(),
+            key: PhantomData
+        }
+    }
+
+    /// Iterates over the keys in order.
+    pub fn keys(&self) -> impl Iterator<Item = K> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+}
+
+/// Iterator over a `HandleMap`.
+pub struct Iter<'a, K, V: 'a> {
+    /// Nodes being walked, with the next index, base key, and shift
+    stack: Vec<(&'a Node<V>, usize, u64, u32)>,
+    key: PhantomData<K>
+}
+
+impl<'a, K: Handle, V> Iterator for Iter<'a, K, V> {
+    type Item = (K, &'a V);
+
+    fn next(&mut self) -> Option<(K, &'a V)> {
+        loop {
+            let (node, index, base, shift) = match self.stack.last_mut() {
+                Some(top) => {
+                    top.1 += 1;
+                    (top.0, top.1 - 1, top.2, top.3)
+                },
+                None => return None
+            };
+            if index >= WIDTH {
+                self.stack.pop();
+                continue
+            }
+            let key = base | ((index as u64) << shift);
+            match *node {
+                Node::Branch(ref children) => {
+                    if let Some(ref child) = children[index] {
+                        self.stack.push((&**child, 0, key, shift - BITS));
+                    }
+                },
+                Node::Leaf(ref values) => {
+                    if let Some(ref value) = values[index] {
+                        return Some((K::from_raw(key as usize), value))
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<K, V> Clone for HandleMap<K, V> {
+    fn clone(&self) -> HandleMap<K, V> {
+        HandleMap {
+            root: self.root.clone(),
+            shift: self.shift,
+            len: self.len,
+            key: PhantomData
+        }
+    }
+}
+
+impl<K: Handle, V: Clone> Default for HandleMap<K, V> {
+    fn default() -> HandleMap<K, V> {
+        HandleMap::new()
+    }
+}
+
+impl<K: Handle, V: Clone> Index<&K> for HandleMap<K, V> {
+    type Output = V;
+
+    fn index(&self, key: &K) -> &V {
+        self.get(key).expect("HandleMap: no entry for handle")
+    }
+}
+
+impl<K: Handle + PartialEq, V: Clone + PartialEq> PartialEq for HandleMap<K, V> {
+    fn eq(&self, other: &HandleMap<K, V>) -> bool {
+        let shared = match (&self.root, &other.root) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b) && self.shift == other.shift,
+            (None, None) => true,
+            _ => false
+        };
+        shared || (self.len() == other.len() && self.iter().eq(other.iter()))
+    }
+}
+
+impl<K: Handle + fmt::Debug, V: Clone + fmt::Debug> fmt::Debug for HandleMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_get_remove() {
+        let mut map = HandleMap::new();
+        for raw in &[1, 31, 32, 33, 1_000, 70_000, 1 << 31] {
+            assert!(map.insert(WlcView::from_raw(*raw), *raw).is_none());
+        }
+        assert_eq!(map.len(), 7);
+        assert_eq!(map.get(&WlcView::from_raw(1_000)), Some(&1_000));
+        assert_eq!(map.get(&WlcView::from_raw(2)), None);
+        assert_eq!(map.get(&WlcView::from_raw(1 << 40)), None);
+        assert_eq!(map.keys().map(Handle::raw).collect::<Vec<_>>(),
+                   vec![1, 31, 32, 33, 1_000, 70_000, 1 << 31]);
+        assert_eq!(map.remove(&WlcView::from_raw(32)), Some(32));
+        assert_eq!(map.remove(&WlcView::from_raw(32)), None);
+        assert_eq!(map.len(), 6);
+        *map.get_mut(&WlcView::from_raw(1)).unwrap() = 5;
+        assert_eq!(map[&WlcView::from_raw(1)], 5);
+    }
+
+    #[test]
+    fn clones_are_independent() {
+        let mut map = HandleMap::new();
+        for raw in 0..100 {
+            map.insert(WlcView::from_raw(raw), raw);
+        }
+        let copy = map.clone();
+        assert!(copy == map);
+        map.insert(WlcView::from_raw(50), 0);
+        map.remove(&WlcView::from_raw(99));
+        assert!(copy != map);
+        assert_eq!(copy[&WlcView::from_raw(50)], 50);
+        assert_eq!(copy.len(), 100);
+        assert_eq!(map.len(), 99);
+    }
+}
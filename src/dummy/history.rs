@@ -0,0 +1,178 @@
+//! Snapshots of the dummy state, and its history recorded after each event.
+
+use super::super::event::WlcEvent;
+use super::super::handle::{WlcOutput, WlcView};
+use super::{state, OutputInfo, ViewInfo};
+
+/// A copy of all dummy state at one point in time.
+///
+/// Taking a snapshot is cheap, even with thousands of views. Views and
+/// outputs are shared between the snapshot and the live state, and only
+/// copied when one of them changes.
+///
+/// Snapshots compare equal when their layouts are the same: the same
+/// outputs and views with the same info, focus and pointer position.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::Size;
+/// use rustwlc::dummy;
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let view = dummy::add_view(output);
+/// let before = dummy::snapshot();
+/// view.set_mask(0);
+/// assert_eq!(before.view_info(view).unwrap().mask, 1);
+/// assert!(before != dummy::snapshot());
+/// view.set_mask(1);
+/// assert!(before == dummy::snapshot());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Snapshot(state::State);
+
+impl Snapshot {
+    /// Gets the info of a view at the time of the snapshot.
+    pub fn view_info(&self, view: WlcView) -> Option<ViewInfo> {
+        self.0.views.get(&view).cloned()
+    }
+
+    /// Gets the info of an output at the time of the snapshot.
+    pub fn output_info(&self, output: WlcOutput) -> Option<OutputInfo> {
+        self.0.output(output).cloned()
+    }
+
+    /// Gets the outputs at the time of the snapshot.
+    pub fn outputs(&self) -> Vec<WlcOutput> {
+        self.0.outputs.iter().map(|entry| entry.handle).collect()
+    }
+
+    /// Gets all views at the time of the snapshot, ordered by handle.
+    pub fn views(&self) -> Vec<WlcView> {
+        self.0.views.keys().collect()
+    }
+
+    /// Gets the view focused at the time of the snapshot.
+    pub fn focused_view(&self) -> WlcView {
+        self.0.focused_view.unwrap_or_else(WlcView::root)
+    }
+
+    /// Gets the time of the snapshot.
+    pub fn time(&self) -> u32 {
+        self.0.time
+    }
+}
+
+impl PartialEq for Snapshot {
+    fn eq(&self, other: &Snapshot) -> bool {
+        self.0.outputs == other.0.outputs && self.0.views == other.0.views &&
+            self.0.focused_view == other.0.focused_view &&
+            self.0.focused_output == other.0.focused_output &&
+            self.0.pointer == other.0.pointer
+    }
+}
+
+/// Takes a snapshot of all dummy state.
+pub fn snapshot() -> Snapshot {
+    state::with(|state| Snapshot(state.clone()))
+}
+
+/// Restores all dummy state from a snapshot.
+///
+/// Everything is restored: views, outputs, focus, the pointer, the time,
+/// registered callbacks, and settings. The recorded history is kept, so a
+/// test can rewind to any checkpoint in it.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{Size, WlcOutput};
+/// use rustwlc::dummy;
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let start = dummy::snapshot();
+/// dummy::add_view(output);
+/// dummy::remove_output(output);
+/// dummy::rewind_to(&start);
+/// assert_eq!(WlcOutput::list(), vec![output]);
+/// assert!(output.get_views().is_empty());
+/// ```
+pub fn rewind_to(snapshot: &Snapshot) {
+    state::with(|state| *state = snapshot.0.clone());
+}
+
+/// A snapshot taken just before an event was simulated.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    /// The simulated event
+    pub event: WlcEvent,
+    /// The state before the event was simulated
+    pub snapshot: Snapshot
+}
+
+/// Starts or stops recording history.
+///
+/// While recording, each function of the `simulate` module saves a
+/// `Checkpoint` before it invokes any callbacks. Stopping clears the
+/// recorded history.
+///
+/// To replay a failing sequence step by step, find the checkpoint of the
+/// event which went wrong in `history()` and pass its snapshot to
+/// `rewind_to`.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{Point, Size, WlcEvent, WlcView};
+/// use rustwlc::{dummy, simulate};
+/// use rustwlc::input::pointer;
+///
+/// dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// dummy::record_history(true);
+/// simulate::pointer_motion(Point { x: 10, y: 10 });
+/// pointer::set_position(Point { x: 10, y: 10 });
+/// simulate::pointer_motion(Point { x: 20, y: 20 });
+///
+/// let history = dummy::history();
+/// assert_eq!(history.len(), 2);
+/// assert_eq!(history[1].event, WlcEvent::PointerMotion {
+///     view: WlcView::root(), time: 0, point: Point { x: 20, y: 20 }
+/// });
+/// dummy::rewind_to(&history[1].snapshot);
+/// assert_eq!(pointer::get_position(), Point { x: 10, y: 10 });
+/// ```
+pub fn record_history(enabled: bool) {
+    state::with_history(|history| {
+        *history = if enabled {
+            history.take().or_else(|| Some(Vec::new()))
+        } else {
+            None
+        }
+    });
+}
+
+/// Gets the checkpoints recorded so far, oldest first.
+pub fn history() -> Vec<Checkpoint> {
+    state::with_history(|history| history.clone().unwrap_or_default())
+}
+
+/// Clears the recorded history, without stopping the recording.
+pub fn clear_history() {
+    state::with_history(|history| {
+        if let Some(ref mut history) = *history {
+            history.clear();
+        }
+    });
+}
+
+/// Saves a checkpoint if history is being recorded.
+pub(crate) fn checkpoint(event: WlcEvent) {
+    #[cfg(feature = "inspector")]
+    super::super::inspector::log(event);
+    if state::with_history(|history| history.is_none()) {
+        return
+    }
+    let snapshot = snapshot();
+    state::with_history(|history| {
+        if let Some(ref mut history) = *history {
+            history.push(Checkpoint { event, snapshot });
+        }
+    });
+}
@@ -0,0 +1,155 @@
+//! Hot corners, which notice the pointer staying at a corner or edge of an
+//! output.
+
+use std::rc::Rc;
+
+use super::super::callback::Callback;
+use super::super::handle::WlcOutput;
+use super::super::types::Gravity;
+use super::barriers::edges;
+use super::{pointer_output, state};
+
+/// A hot corner added with `add_hot_corner`.
+#[derive(Debug, Clone)]
+pub(crate) struct HotCorner {
+    pub output: WlcOutput,
+    pub corner: Gravity,
+    pub dwell: u32,
+    /// When the pointer arrived, if it's there
+    pub since: Option<u32>,
+    pub triggered: bool,
+    pub hook: Callback<dyn Fn(WlcOutput, Gravity)>
+}
+
+/// Adds a hot corner, calling `hook` once the pointer has stayed at a
+/// corner or edge of an output for `dwell` milliseconds.
+///
+/// The pointer is at an edge on its outermost row or column of pixels, or
+/// past it, and at a corner when it's at both of its edges;
+/// `Gravity::Center` is the whole output. The pointer arrives with
+/// `simulate::pointer_motion`, and stays while motions keep it there and
+/// `advance_time` passes. The hook is called with the output and the
+/// corner once per visit, so the pointer must leave before it triggers
+/// again.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, simulate, Gravity, Point, Size};
+/// use rustwlc::input::pointer;
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// callback::rust::pointer_motion(|_, _, point| {
+///     pointer::set_position(*point);
+///     false
+/// });
+/// let overview = Rc::new(Cell::new(0));
+/// let count = overview.clone();
+/// dummy::add_hot_corner(output, Gravity::TopLeft, 300, move |_, _| count.set(count.get() + 1));
+/// simulate::pointer_motion(Point { x: 0, y: 0 });
+/// dummy::advance_time(200);
+/// simulate::pointer_motion(Point { x: -5, y: 0 });
+/// assert_eq!(overview.get(), 0);
+/// dummy::advance_time(100);
+/// assert_eq!(overview.get(), 1);
+/// dummy::advance_time(1000);
+/// assert_eq!(overview.get(), 1);
+/// ```
+pub fn add_hot_corner<F>(output: WlcOutput, corner: Gravity, dwell: u32, hook: F)
+    where F: Fn(WlcOutput, Gravity) + 'static {
+    state::with(|state| state.hot_corners.push(HotCorner {
+        output, corner, dwell, since: None, triggered: false, hook: Callback(Rc::new(hook))
+    }));
+    update_hot_corners();
+}
+
+/// Removes all hot corners.
+pub fn clear_hot_corners() {
+    state::with(|state| state.hot_corners.clear());
+}
+
+/// Notes which hot corners the pointer is at, and calls the hooks of those
+/// it has stayed at long enough.
+pub(crate) fn update_hot_corners() {
+    let output = pointer_output();
+    let hooks = state::with(|state| {
+        let point = state.pointer;
+        let time = state.time;
+        let size = state.output(output).map(|info| info.resolution);
+        let mut hooks = Vec::new();
+        for hot in &mut state.hot_corners {
+            let at = match size {
+                Some(size) if hot.output == output => {
+                    let (horizontal, vertical) = edges(hot.corner);
+                    at_edge(horizontal, point.x, size.w) && at_edge(vertical, point.y, size.h)
+                },
+                _ => false
+            };
+            if !at {
+                hot.since = None;
+                hot.triggered = false;
+                continue
+            }
+            let since = *hot.since.get_or_insert(time);
+            if !hot.triggered && time.wrapping_sub(since) >= hot.dwell {
+                hot.triggered = true;
+                hooks.push((hot.hook.clone(), hot.output, hot.corner));
+            }
+        }
+        hooks
+    });
+    for (hook, output, corner) in hooks {
+        (hook.0)(output, corner);
+    }
+}
+
+/// Whether a coordinate is at an edge, as given by `edges`, of a length.
+fn at_edge(edge: Option<bool>, coordinate: i32, length: u32) -> bool {
+    match edge {
+        Some(false) => coordinate <= 0,
+        Some(true) => coordinate as i64 >= length as i64 - 1,
+        None => true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::*;
+
+    #[test]
+    fn hot_corners() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        callback::rust::pointer_motion(|_, _, point| {
+            input::pointer::set_position(*point);
+            false
+        });
+        let triggered = Rc::new(RefCell::new(Vec::new()));
+        let log = triggered.clone();
+        dummy::add_hot_corner(output, Gravity::Right, 100, move |_, edge| log.borrow_mut().push(edge));
+        let log = triggered.clone();
+        dummy::add_hot_corner(output, Gravity::BottomRight, 0, move |_, corner| {
+            log.borrow_mut().push(corner)
+        });
+        simulate::pointer_motion(Point { x: 799, y: 10 });
+        dummy::advance_time(50);
+        // Sliding along the edge stays at it
+        simulate::pointer_motion(Point { x: 799, y: 599 });
+        assert_eq!(*triggered.borrow(), vec![Gravity::BottomRight]);
+        dummy::advance_time(50);
+        assert_eq!(*triggered.borrow(), vec![Gravity::BottomRight, Gravity::Right]);
+        // Leaving and coming back triggers again
+        simulate::pointer_motion(Point { x: 700, y: 599 });
+        simulate::pointer_motion(Point { x: 900, y: 700 });
+        dummy::advance_time(100);
+        assert_eq!(*triggered.borrow(), vec![Gravity::BottomRight, Gravity::Right,
+                                             Gravity::BottomRight, Gravity::Right]);
+        dummy::clear_hot_corners();
+        simulate::pointer_motion(Point { x: 0, y: 0 });
+        simulate::pointer_motion(Point { x: 799, y: 599 });
+        dummy::advance_time(100);
+        assert_eq!(triggered.borrow().len(), 4);
+    }
+}
@@ -0,0 +1,100 @@
+//! A persistent log of things which happened.
+//!
+//! Logs only grow until they are cleared, so clones share one vector and
+//! each knows how many of its entries are its own. Appending to a clone
+//! which fell behind copies its entries first. Cloning a log is O(1), so
+//! the dummy state can log every event and still be checkpointed after
+//! each one.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+/// An append-only list with cheap clones.
+pub struct Log<T> {
+    /// Entries of this log and of clones which appended to it since
+    entries: Rc<RefCell<Vec<T>>>,
+    /// Number of the entries which are in this log
+    len: usize
+}
+
+impl<T: Clone> Log<T> {
+    /// Creates an empty log.
+    pub fn new() -> Log<T> {
+        Log { entries: Rc::new(RefCell::new(Vec::new())), len: 0 }
+    }
+
+    /// Number of entries in the log.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Appends an entry.
+    pub fn push(&mut self, entry: T) {
+        if self.entries.borrow().len() != self.len {
+            let own = self.entries.borrow()[..self.len].to_vec();
+            self.entries = Rc::new(RefCell::new(own));
+        }
+        self.entries.borrow_mut().push(entry);
+        self.len += 1;
+    }
+
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        *self = Log::new();
+    }
+
+    /// Copies the entries, oldest first.
+    pub fn to_vec(&self) -> Vec<T> {
+        self.entries.borrow()[..self.len].to_vec()
+    }
+}
+
+impl<T> Clone for Log<T> {
+    fn clone(&self) -> Log<T> {
+        Log { entries: self.entries.clone(), len: self.len }
+    }
+}
+
+impl<T: Clone> Default for Log<T> {
+    fn default() -> Log<T> {
+        Log::new()
+    }
+}
+
+impl<T: PartialEq> PartialEq for Log<T> {
+    fn eq(&self, other: &Log<T>) -> bool {
+        self.len == other.len &&
+            self.entries.borrow()[..self.len] == other.entries.borrow()[..other.len]
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Log<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.entries.borrow()[..self.len].iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_are_independent() {
+        let mut log = Log::new();
+        log.push(1);
+        log.push(2);
+        let before = log.clone();
+        log.push(3);
+        assert_eq!(before.to_vec(), vec![1, 2]);
+        let mut other = before.clone();
+        other.push(4);
+        log.push(5);
+        assert_eq!(log.to_vec(), vec![1, 2, 3, 5]);
+        assert_eq!(other.to_vec(), vec![1, 2, 4]);
+        other.clear();
+        assert_eq!(other.len(), 0);
+        assert_eq!(log.len(), 4);
+        assert!(before != log);
+    }
+}
@@ -0,0 +1,145 @@
+//! Misuse of the dummy compositor, and the summary of the state reported
+//! with it.
+
+use super::super::event::WlcEvent;
+use super::super::handle::{WlcOutput, WlcView};
+use super::{current_event, focused_view, seed, state};
+
+/// Sets whether misuse of the dummy compositor panics.
+///
+/// Misuse is anything a real compositor gets away with but which wlc or its
+/// clients would ignore, reject, or handle in an undefined way. It is always
+/// recorded in `violations`; in strict mode, it also panics so tests fail at
+/// the offending call. The panic message goes on with the event being
+/// dispatched, the latest events (see `recent_events`), the focused view,
+/// and the views of each output.
+pub fn set_strict(enabled: bool) {
+    state::with(|state| state.strict = enabled);
+}
+
+/// Gets the misuse recorded so far, oldest first.
+pub fn violations() -> Vec<String> {
+    state::with(|state| state.violations.to_vec())
+}
+
+/// Forgets the misuse recorded so far.
+pub fn clear_violations() {
+    state::with(|state| state.violations.clear());
+}
+
+/// Records misuse, panicking in strict mode.
+///
+/// The panic message goes on with `state_summary`.
+pub(crate) fn violation(message: String) {
+    if state::with(|state| {
+        state.violations.push(message.clone());
+        state.strict
+    }) {
+        panic!("dummy-rustwlc strict mode: {}\n{}", message, state_summary());
+    }
+}
+
+/// Reports a handle method called on a handle of the other kind, i.e. a
+/// `WlcView` method on an output converted with `WlcOutput::as_view`.
+pub(crate) fn check_kind(method: &str, handle: usize) {
+    let on = state::with(|state| if method.starts_with("WlcView::") {
+        let output = WlcOutput::from_raw(handle);
+        state.output(output).map(|_| format!("{:?}", output))
+    } else {
+        let view = WlcView::from_raw(handle);
+        state.views.get(&view).map(|_| format!("{:?}", view))
+    });
+    if let Some(on) = on {
+        violation(format!("{} was called on {}", method, on));
+    }
+}
+
+/// Number of events kept for `recent_events`.
+pub const RECENT_EVENTS: usize = 8;
+
+/// Gets the latest events dispatched, including those callbacks caused,
+/// oldest first.
+///
+/// Only the last `RECENT_EVENTS` are kept.
+pub fn recent_events() -> Vec<WlcEvent> {
+    state::with(|state| state.recent_events.iter().cloned().collect())
+}
+
+/// Describes the state for a panic message: the event being dispatched,
+/// the latest events, the focused view, the seed, and the views of each
+/// output.
+///
+/// # Example
+/// ```text
+/// while dispatching ViewFocus(WlcView(1), true)
+/// recent events, latest first:
+///     ViewFocus(WlcView(1), true)
+///     ViewCreated(WlcView(1))
+/// focused view: WlcView(1)
+/// seed: 0
+/// WlcOutput(1) "WLC-1" 800 x 600, views from the top:
+///     WlcView(1) "xterm" [400 x 300 at (0, 0)]
+/// ```
+pub(crate) fn state_summary() -> String {
+    let mut summary = String::new();
+    if let Some(event) = current_event() {
+        summary += &format!("while dispatching {:?}\n", event);
+    }
+    summary += "recent events, latest first:\n";
+    for event in recent_events().iter().rev() {
+        summary += &format!("    {:?}\n", event);
+    }
+    summary += &format!("focused view: {:?}\n", focused_view());
+    summary += &format!("seed: {}\n", seed());
+    state::with(|state| {
+        for entry in &state.outputs {
+            let output = &entry.info;
+            summary += &format!("{:?} \"{}\" {}, views from the top:\n",
+                                entry.handle, output.name, output.resolution);
+            for view in output.views.iter().rev() {
+                let info = &state.views[view];
+                let hidden = if info.is_drawn() { "" } else { " (hidden)" };
+                summary += &format!("    {:?} \"{}\" {}{}\n",
+                                    view, info.title, info.geometry, hidden);
+            }
+        }
+    });
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::*;
+
+    #[test]
+    fn panic_summary() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let view = dummy::add_view(output);
+        dummy::set_title(view, "xterm");
+        view.set_geometry(EDGE_NONE, Geometry {
+            origin: Point { x: 0, y: 0 },
+            size: Size { w: 400, h: 300 }
+        });
+        let other = dummy::add_view(output);
+        for _ in 0..dummy::RECENT_EVENTS {
+            simulate::dispatch(WlcEvent::CompositorReady);
+        }
+        dummy::set_strict(true);
+        callback::rust::view_focus(|view, _| {
+            dummy::violation(format!("{:?} misused", view));
+        });
+        let message = ::std::panic::catch_unwind(|| view.focus()).unwrap_err()
+            .downcast::<String>().unwrap();
+        assert_eq!(*message, format!(
+            "dummy-rustwlc strict mode: {0:?} misused\n\
+             while dispatching ViewFocus({0:?}, true)\n\
+             recent events, latest first:\n    \
+                 ViewFocus({0:?}, true)\n{1}\
+             focused view: {0:?}\n\
+             seed: 0\n\
+             {2:?} \"WLC-1\" 800 x 600, views from the top:\n    \
+                 {3:?} \"\" [0 x 0 at (0, 0)]\n    \
+                 {0:?} \"xterm\" [400 x 300 at (0, 0)]\n",
+            view, "    CompositorReady\n".repeat(dummy::RECENT_EVENTS - 1), output, other));
+    }
+}
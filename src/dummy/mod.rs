@@ -0,0 +1,2330 @@
+//! Controls for the state behind dummy handles.
+//!
+//! Where rustwlc asks wlc, dummy-rustwlc answers from a store kept per
+//! thread. Tests use the functions in this module to set up what the
+//! compositor will see. Since the store is thread-local, each `#[test]`
+//! starts from a clean slate; call `reset()` to start over within a test.
+//!
+//! Handles which were not created through this module (i.e. from
+//! `WlcView::dummy`) are not part of the store. Their getters return
+//! default values and their setters do nothing.
+//!
+//! # Example
+//! ```rust
+//! use rustwlc::{WlcOutput, Size};
+//! use rustwlc::dummy;
+//!
+//! let output = dummy::add_output("WLC-1", Size { w: 1920, h: 1080 });
+//! let view = dummy::add_view(output);
+//! assert_eq!(WlcOutput::list(), vec![output]);
+//! assert_eq!(output.get_views(), vec![view]);
+//! assert_eq!(view.get_output(), output);
+//! ```
+
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::thread;
+use std::time::Duration;
+
+use super::callback::Callback;
+use super::event::WlcEvent;
+use super::handle::{WlcOutput, WlcView};
+use super::render::ContextInfo;
+use super::selection;
+use super::types::{BackendType, Geometry, Point, Size, ViewState, ViewType};
+
+pub(crate) mod state;
+mod handle_map;
+pub(crate) mod log;
+mod barriers;
+mod callback_times;
+mod events;
+mod frames;
+mod history;
+mod hot_corners;
+mod misuse;
+mod pixels;
+mod placement;
+mod routing;
+mod screenshots;
+mod xwayland;
+
+pub(crate) use self::state::Position;
+pub use self::barriers::{add_barrier, clear_barriers, Barrier};
+pub(crate) use self::barriers::apply_barriers;
+pub use self::callback_times::{assert_callback_budget, callback_times, clear_callback_times,
+                               record_callback_times, CallbackTimes};
+pub(crate) use self::callback_times::time_callback;
+pub use self::events::{current_event, flush_matching, pending_events, queue_event, set_watchdog,
+                       Watchdog};
+pub(crate) use self::events::{next_pending, watch};
+pub use self::frames::{frame_stats, set_frame_budget, set_render_cost, FrameBudget, FrameClock,
+                       FrameStats};
+pub(crate) use self::frames::{frame_budget, record_frame};
+pub use self::history::{clear_history, history, record_history, rewind_to, snapshot, Checkpoint,
+                        Snapshot};
+pub(crate) use self::history::checkpoint;
+pub use self::hot_corners::{add_hot_corner, clear_hot_corners};
+pub(crate) use self::hot_corners::{update_hot_corners, HotCorner};
+pub use self::misuse::{clear_violations, recent_events, set_strict, violations, RECENT_EVENTS};
+pub(crate) use self::misuse::{check_kind, state_summary, violation};
+pub use self::pixels::{fail_allocations, failed_allocations, pixel_format, set_pixel_format,
+                       RenderAllocation};
+pub(crate) use self::pixels::{allocate, read_pixels, write_pixels};
+pub use self::placement::{set_placement, Placement, CASCADE_STEP};
+pub(crate) use self::placement::place;
+pub use self::routing::{clear_host_grabbed, host_grabbed, host_grabs, interactive_op, key_order,
+                        modal_dialog, modifier_state, motion_rate, pending_motion,
+                        set_focus_follows_pointer, set_host_grabs, set_key_order, set_modal_routing,
+                        set_modifier_state, set_motion_rate, set_pointer_follows_focus,
+                        set_scroll_inverted, set_scroll_step, set_sticky_keys, InteractiveOp,
+                        KeyOrder, ModifierState, MotionRate, DEFAULT_SCROLL_STEP};
+pub(crate) use self::routing::{end_interactive_op, grabbed_by_host, route_input, route_pointer,
+                               scroll_step, start_interactive_op};
+pub use self::screenshots::{clear_screenshots, on_screenshot, pending_screenshots, screenshots,
+                            Screenshot, ScreenshotTarget};
+pub(crate) use self::screenshots::take_screenshots;
+pub use self::xwayland::{on_xwayland_ready, set_xwayland, xwayland, XWayland};
+
+/// Stacking layers of an output.
+///
+/// Each output's stack is split into layers, from `Below` at the bottom to
+/// `Above` at the top. Restacking a view, i.e. with
+/// `WlcView::bring_to_front`, only moves it within its own layer. This
+/// models panels or OSDs which should always be on top, and desktop
+/// widgets which should always be below.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Layer {
+    /// Always below normal views
+    Below,
+    /// Where views are created
+    Normal,
+    /// Always above normal views
+    Above
+}
+
+/// What the dummy knows about a view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ViewInfo {
+    /// The output the view is on
+    pub output: WlcOutput,
+    /// Visibility mask of the view
+    pub mask: u32,
+    /// Stacking layer of the view
+    pub layer: Layer,
+    /// Geometry of the view
+    pub geometry: Geometry,
+    /// State flags of the view
+    pub state: ViewState,
+    /// Type flags of the view
+    pub view_type: ViewType,
+    /// Parent of the view, or `WlcView::root()`
+    pub parent: WlcView,
+    /// Title of the view
+    pub title: String,
+    /// Class of the view (X11 and shell surfaces)
+    pub class: String,
+    /// App id of the view (xdg surfaces)
+    pub app_id: String,
+    /// Sizes the view's client accepts
+    pub size_hints: SizeHints,
+    /// Properties set by tests with `set_property`
+    pub properties: HashMap<String, String>,
+    /// Whether the view's surface is mapped, see `set_mapped`
+    pub mapped: bool,
+    /// Whether the view is minimized, see `set_minimized`
+    pub minimized: bool,
+    /// Time rendering the view takes, see `set_render_cost`
+    pub render_cost: Duration,
+    /// Where the view takes pointer input, relative to its origin, or
+    /// `None` for everywhere; see `set_input_region`
+    pub input_region: Option<Vec<Geometry>>
+}
+
+impl ViewInfo {
+    /// Whether the view can be drawn: it is mapped and not minimized.
+    pub fn is_drawn(&self) -> bool {
+        self.mapped && !self.minimized
+    }
+
+    /// Whether the view takes pointer input at a point: it is inside its
+    /// geometry and its input region.
+    pub fn takes_input_at(&self, point: Point) -> bool {
+        let origin = self.geometry.origin;
+        let local = Point { x: point.x - origin.x, y: point.y - origin.y };
+        self.geometry.contains_point(point) && self.input_region.as_ref()
+            .is_none_or(|region| region.iter().any(|area| area.contains_point(local)))
+    }
+}
+
+/// Sizes a client accepts for its view, like X11's `WM_NORMAL_HINTS`.
+///
+/// Clients which are configured with another size pick the closest one they
+/// accept, see `set_size_hints`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SizeHints {
+    /// Smallest size
+    pub min: Option<Size>,
+    /// Size `increment` steps are counted from, or else `min`
+    pub base: Option<Size>,
+    /// Largest size
+    pub max: Option<Size>,
+    /// Steps the size grows by from `base`, i.e. a terminal's cell size
+    pub increment: Option<Size>,
+    /// Ratio of width to height the size must keep
+    pub aspect: Option<Size>
+}
+
+impl SizeHints {
+    /// Gets the size the client picks when asked for `size`.
+    ///
+    /// The size is shrunk to keep the aspect ratio, rounded down to a whole
+    /// number of increments from the base size, and then clamped between
+    /// the minimum and maximum. As in X11, the base size defaults to the
+    /// minimum size, and the other way around.
+    ///
+    /// Rounding leaves gaps: a tiled terminal rarely fills its tile.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rustwlc::Size;
+    /// use rustwlc::dummy::SizeHints;
+    ///
+    /// // 6x12 cells with a 2 pixel border, at least one cell big
+    /// let terminal = SizeHints {
+    ///     base: Some(Size { w: 4, h: 4 }),
+    ///     min: Some(Size { w: 10, h: 16 }),
+    ///     increment: Some(Size { w: 6, h: 12 }),
+    ///     .. SizeHints::default()
+    /// };
+    /// assert_eq!(terminal.constrain(Size { w: 500, h: 300 }), Size { w: 496, h: 292 });
+    /// assert_eq!(terminal.constrain(Size { w: 1, h: 1 }), Size { w: 10, h: 16 });
+    /// ```
+    pub fn constrain(&self, size: Size) -> Size {
+        let Size { mut w, mut h } = size;
+        if let Some(aspect) = self.aspect.filter(|aspect| aspect.w > 0 && aspect.h > 0) {
+            let (aw, ah) = (aspect.w as u64, aspect.h as u64);
+            if w as u64 * ah > h as u64 * aw {
+                w = (h as u64 * aw / ah) as u32;
+            } else {
+                h = (w as u64 * ah / aw) as u32;
+            }
+        }
+        let base = self.base.or(self.min).unwrap_or_default();
+        let min = self.min.or(self.base).unwrap_or_default();
+        if let Some(step) = self.increment {
+            if step.w > 0 && w > base.w {
+                w -= (w - base.w) % step.w;
+            }
+            if step.h > 0 && h > base.h {
+                h -= (h - base.h) % step.h;
+            }
+        }
+        if let Some(max) = self.max {
+            w = w.min(max.w);
+            h = h.min(max.h);
+        }
+        Size { w: w.max(min.w), h: h.max(min.h) }
+    }
+}
+
+/// Which views a bulk query returns, see `WlcOutput::get_views_by_type`.
+///
+/// The default filter matches every view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ViewFilter {
+    /// Type flags the view must all have
+    pub view_type: ViewType,
+    /// State flags the view must all have
+    pub state: ViewState,
+    /// Mask the view's mask must share a bit with, if any
+    pub mask: Option<u32>
+}
+
+impl Default for ViewFilter {
+    fn default() -> ViewFilter {
+        ViewFilter { view_type: ViewType::empty(), state: ViewState::empty(), mask: None }
+    }
+}
+
+impl ViewFilter {
+    /// Whether a view matches the filter.
+    pub fn matches(&self, info: &ViewInfo) -> bool {
+        info.view_type.contains(self.view_type) && info.state.contains(self.state) &&
+            self.mask.is_none_or(|mask| info.mask & mask != 0)
+    }
+}
+
+/// What the dummy knows about an output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputInfo {
+    /// Name of the output, i.e. "WLC-1"
+    pub name: String,
+    /// Resolution of the output in pixels
+    pub resolution: Size,
+    /// Visibility mask of the output
+    pub mask: u32,
+    /// Views on the output in stack order, bottom first
+    pub views: Vec<WlcView>,
+    /// Whether the output is sleeping
+    pub sleep: bool
+}
+
+/// Clears all dummy state, as if the thread had just started.
+///
+/// This also stops recording history.
+pub fn reset() {
+    state::with(|state| *state = state::State::default());
+    state::with_history(|history| *history = None);
+}
+
+/// Tears down the simulated compositor and starts a new session, so a test
+/// binary can run several compositors one after another on a thread.
+///
+/// If wlc is running, it is terminated first, as with `rustwlc::terminate()`,
+/// so the old compositor sees its outputs and views go. Everything else is
+/// cleared as with `reset`: callbacks, handles, outputs and views, and
+/// settings, except strict mode (`set_strict`) and the seed (`set_seed`),
+/// which is set again. Afterwards `rustwlc::init()` can be called again.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, simulate, Size, WlcEvent, WlcOutput};
+/// use rustwlc::dummy::Lifecycle;
+///
+/// for _ in 0..2 {
+///     callback::rust::view_created(|_| false);
+///     rustwlc::init().unwrap()();
+///     let output = simulate::connect_output("WLC-1", Size { w: 800, h: 600 });
+///     assert_eq!(output, WlcOutput::dummy(1));
+///     assert!(simulate::create_view().is_root());
+///     dummy::new_session();
+///     assert_eq!(dummy::lifecycle(), Lifecycle::Uninitialized);
+///     assert!(WlcOutput::list().is_empty());
+///     assert!(dummy::violations().is_empty());
+/// }
+/// assert!(simulate::dispatch(WlcEvent::ViewCreated(dummy::add_view(
+///     dummy::add_output("WLC-1", Size { w: 800, h: 600 })))));
+/// ```
+pub fn new_session() {
+    if lifecycle() == Lifecycle::Running {
+        super::terminate();
+    }
+    let (strict, seed) = state::with(|state| (state.strict, state.seed));
+    reset();
+    set_strict(strict);
+    set_seed(seed);
+}
+
+/// How the dummy gives out handles to new views and outputs.
+///
+/// Compositors often key their own data by handle, so tests can check that
+/// they don't rely on handles being in order or never coming back.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum HandlePolicy {
+    /// Handles count up from `1` and are never reused. This is the default.
+    #[default]
+    Sequential,
+    /// Handles are random, from a generator seeded with the given value, so
+    /// a failing test can be reproduced. Setting this policy seeds the
+    /// generator behind `Random`, as `set_seed` does.
+    Seeded(u64),
+    /// Handles are random, from the generator seeded with `set_seed`.
+    Random,
+    /// The most recently removed handle is given out next, like wlc reusing
+    /// the slots of its handle pool.
+    Reuse
+}
+
+/// Which threads callbacks are invoked on.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Threading {
+    /// Callbacks run on the thread which injected the event, as wlc runs
+    /// them all on its event loop. This is the default.
+    #[default]
+    LoopThread,
+    /// Experimental: the callbacks of each event run on a new thread,
+    /// which isn't the test's.
+    ///
+    /// This is a check for compositors which keep state in thread-locals,
+    /// not a concurrent dispatch: the dummy state moves to the new thread
+    /// while the thread which injected the event waits, so callbacks still
+    /// run one at a time and may use the dummy as usual. Needs the
+    /// `off-thread-callbacks` feature.
+    #[cfg(feature = "off-thread-callbacks")]
+    OffThread
+}
+
+/// Sets which threads callbacks are invoked on.
+///
+/// The choice is part of the dummy state, so it only applies to the
+/// current thread and each test picks its own.
+///
+/// # Safety
+/// With `Threading::OffThread`, every callback, handler, and hook given to
+/// the dummy runs on other threads, though the compiler can't check that
+/// they may. The thread which set the threading waits meanwhile, so
+/// sharing `Rc`s with it is fine, but until the threading is set back to
+/// `Threading::LoopThread`, nothing they use may be tied to the thread it
+/// was made on, like a `MutexGuard`, and nothing they share may be used by
+/// any third thread.
+///
+/// # Example
+/// ```rust
+/// use std::thread;
+/// use rustwlc::{callback, simulate, WlcEvent};
+/// use rustwlc::dummy::{self, Threading};
+///
+/// let test_thread = thread::current().id();
+/// callback::rust::compositor_ready(move || {
+///     assert!(thread::current().id() != test_thread);
+/// });
+/// unsafe { dummy::set_threading(Threading::OffThread) };
+/// simulate::dispatch(WlcEvent::CompositorReady);
+/// ```
+#[cfg(feature = "off-thread-callbacks")]
+pub unsafe fn set_threading(threading: Threading) {
+    state::with(|state| state.threading = threading);
+}
+
+/// Sets how handles are given to views and outputs added from now on.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::Size;
+/// use rustwlc::dummy::{self, HandlePolicy};
+///
+/// dummy::set_handle_policy(HandlePolicy::Reuse);
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let closed = dummy::add_view(output);
+/// dummy::remove_view(closed);
+/// assert_eq!(dummy::add_view(output), closed);
+/// ```
+pub fn set_handle_policy(policy: HandlePolicy) {
+    state::with(|state| {
+        state.handle_policy = policy;
+        if let HandlePolicy::Seeded(seed) = policy {
+            state.seed = seed;
+            state.rng = seed;
+        }
+    });
+}
+
+/// Seeds the generator behind all of the dummy's randomness, and starts it
+/// over.
+///
+/// Random handles (`HandlePolicy::Random`), the streams of
+/// `model::random_steps`, and `random` all draw from it, so one seed
+/// reproduces a whole run. The seed is `0` unless set, and panic messages
+/// from strict mode and the `invariants` module print it.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::dummy;
+///
+/// dummy::set_seed(42);
+/// let first = (dummy::random(), dummy::random());
+/// dummy::set_seed(42);
+/// assert_eq!((dummy::random(), dummy::random()), first);
+/// assert_eq!(dummy::seed(), 42);
+/// ```
+pub fn set_seed(seed: u64) {
+    state::with(|state| {
+        state.seed = seed;
+        state.rng = seed;
+    });
+}
+
+/// Gets the seed set with `set_seed`.
+pub fn seed() -> u64 {
+    state::with(|state| state.seed)
+}
+
+/// Draws the next number from the generator seeded with `set_seed`, for a
+/// test's or a compositor's own randomness.
+pub fn random() -> u64 {
+    state::with(|state| state.random())
+}
+
+/// How many things were created and destroyed, see `allocations`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Counts {
+    /// Number created
+    pub created: usize,
+    /// Number destroyed
+    pub destroyed: usize
+}
+
+impl Counts {
+    /// Gets the number created but not destroyed.
+    pub fn leaked(&self) -> usize {
+        self.created.saturating_sub(self.destroyed)
+    }
+}
+
+/// What the dummy compositor created and destroyed since counting
+/// started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Allocations {
+    /// Views added and removed
+    pub views: Counts,
+    /// Outputs added and removed
+    pub outputs: Counts,
+    /// Handles given user data with `set_user_data`, and those of them
+    /// which were removed
+    pub user_data: Counts,
+    /// Handlers added with `callback::add_handler` and removed
+    pub handlers: Counts
+}
+
+/// Gets what was created and destroyed since the state was reset, or since
+/// `reset_allocations`.
+pub fn allocations() -> Allocations {
+    state::with(|state| state.allocations)
+}
+
+/// Starts counting allocations from zero.
+///
+/// Things which exist already aren't counted as created, so removing them
+/// later isn't a leak either.
+pub fn reset_allocations() {
+    state::with(|state| state.allocations = Allocations::default());
+}
+
+/// Panics if anything counted in `allocations` was created but not
+/// destroyed.
+///
+/// Per-window state in a compositor usually lives as long as the view, in
+/// its user data or keyed by its handle; a suite which opens and closes
+/// thousands of views can check that the dummy's side was torn down too.
+///
+/// # Example
+/// ```rust,should_panic
+/// use rustwlc::{callback, dummy, simulate, Size};
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// dummy::reset_allocations();
+/// for _ in 0..1000 {
+///     let view = simulate::create_view();
+///     dummy::remove_view(view);
+/// }
+/// // Never removed
+/// callback::add_handler(0, |_| false);
+/// dummy::assert_no_leaks();
+/// ```
+pub fn assert_no_leaks() {
+    let allocations = allocations();
+    let leaks: Vec<String> = [("views", allocations.views), ("outputs", allocations.outputs),
+                              ("user data", allocations.user_data),
+                              ("handlers", allocations.handlers)]
+        .iter()
+        .filter(|&&(_, counts)| counts.leaked() > 0)
+        .map(|&(name, counts)| format!("{} {} ({} created, {} destroyed)",
+                                       counts.leaked(), name, counts.created, counts.destroyed))
+        .collect();
+    if !leaks.is_empty() {
+        panic!("dummy-rustwlc leaked {}", leaks.join(", "));
+    }
+}
+
+/// Stores the user data of a handle, see `WlcView::set_user_data`.
+pub(crate) fn set_user_data(handle: usize, data: usize) {
+    state::with(|state| {
+        let exists = state.views.contains_key(&WlcView::from_raw(handle)) ||
+            state.output(WlcOutput::from_raw(handle)).is_some();
+        if exists && state.user_data.insert(handle, data).is_none() {
+            state.allocations.user_data.created += 1;
+        }
+    });
+}
+
+/// Gets the user data of a handle, if any.
+pub(crate) fn user_data(handle: usize) -> Option<usize> {
+    state::with(|state| state.user_data.get(&handle).cloned())
+}
+
+/// Gets the compact index of a live view.
+///
+/// Each view gets the smallest index which no live view has, so indices
+/// stay below the number of views alive at once, and a view keeps its
+/// index until it is removed. Compositors can keep per-view state in a
+/// `Vec` by index instead of a `HashMap` by handle, as with wlc forks
+/// which hand out indices; `on_index_reuse` tells them when an index
+/// moves on to a new view.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, Size};
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let first = dummy::add_view(output);
+/// let second = dummy::add_view(output);
+/// assert_eq!(dummy::index_of(second), Some(1));
+/// dummy::remove_view(first);
+/// assert_eq!(dummy::index_of(first), None);
+/// let third = dummy::add_view(output);
+/// assert_eq!(dummy::index_of(third), Some(0));
+/// ```
+pub fn index_of(view: WlcView) -> Option<usize> {
+    state::with(|state| state.view_indices.get(&view).cloned())
+}
+
+/// Sets a callback invoked when a new view is given the index of a removed
+/// one, see `index_of`, with the index and the new view.
+///
+/// The callback runs once the view is added, before any callbacks for it.
+pub fn on_index_reuse<F>(reuse: F) where F: Fn(usize, WlcView) + 'static {
+    state::with(|state| state.index_reuse = Some(Callback(Rc::new(reuse))));
+}
+
+/// Adds an output to the dummy compositor.
+///
+/// New outputs have a mask of `1`, like wlc gives them.
+pub fn add_output(name: &str, resolution: Size) -> WlcOutput {
+    state::with(|state| {
+        let output = WlcOutput::dummy(state.next_handle());
+        state.outputs.push(Rc::new(state::Output {
+            handle: output,
+            info: OutputInfo {
+                name: name.to_string(),
+                resolution,
+                mask: 1,
+                views: Vec::new(),
+                sleep: false
+            },
+            layers: [0; 3],
+            typed: HashMap::new()
+        }));
+        state.allocations.outputs.created += 1;
+        output
+    })
+}
+
+/// Removes an output and all views on it from the dummy compositor.
+pub fn remove_output(output: WlcOutput) {
+    let dropped = state::with(|state| {
+        let mut dropped = Vec::new();
+        if let Some(index) = state.outputs.iter()
+            .position(|entry| entry.handle == output) {
+                let entry = state.outputs.remove(index);
+                state.free_handle(output.into_raw() as u32);
+                state.allocations.outputs.destroyed += 1;
+                for &view in &entry.info.views {
+                    dropped.extend(state.forget_view(view));
+                }
+                state.blanks.remove(&output);
+                if state.focused_output == Some(output) {
+                    state.focused_output = None;
+                }
+                if state.primary_output == Some(output) {
+                    state.primary_output = None;
+                }
+                state.frame_stats.remove(&output);
+                state.mode_failures.remove(&output);
+                state.render_scheduled.remove(&output);
+            }
+        dropped
+    });
+    for selection in dropped {
+        selection::changed(selection, None);
+    }
+}
+
+/// Makes an output fail to switch to a resolution.
+///
+/// When the compositor asks for `resolution` with
+/// `WlcOutput::set_resolution`, the output switches to `fallback` instead,
+/// or keeps its resolution if there is none, like a monitor with a broken
+/// EDID. Resolution changes from `simulate::output_resolution` still work.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, Size};
+///
+/// let output = dummy::add_output("HDMI-A-1", Size { w: 1024, h: 768 });
+/// let uhd = Size { w: 3840, h: 2160 };
+/// let hd = Size { w: 1920, h: 1080 };
+/// dummy::fail_resolution(output, uhd, Some(hd));
+/// output.set_resolution(uhd, 1);
+/// assert_eq!(output.get_resolution(), Some(hd));
+///
+/// dummy::fail_resolution(output, uhd, None);
+/// output.set_resolution(uhd, 1);
+/// assert_eq!(output.get_resolution(), Some(hd));
+/// ```
+pub fn fail_resolution(output: WlcOutput, resolution: Size, fallback: Option<Size>) {
+    state::with(|state| {
+        let failures = state.mode_failures.entry(output).or_default();
+        failures.retain(|&(size, _)| size != resolution);
+        failures.push((resolution, fallback));
+    });
+}
+
+/// Gets the resolution an output actually switches to when asked for one.
+pub(crate) fn mode_set(output: WlcOutput, resolution: Size) -> Option<Size> {
+    state::with(|state| match state.mode_failures.get(&output)
+                .and_then(|failures| failures.iter().find(|&&(size, _)| size == resolution)) {
+        Some(&(_, fallback)) => fallback,
+        None => Some(resolution)
+    })
+}
+
+/// Adds a view to the top of the normal layer of an output's stack.
+///
+/// The view starts mapped with the mask of its output, so it is visible.
+///
+/// # Panics
+/// If `output` was not created with `add_output`.
+pub fn add_view(output: WlcOutput) -> WlcView {
+    let (view, index, reuse) = state::with(|state| {
+        let mask = state.output(output)
+            .expect("dummy::add_view: output does not exist").mask;
+        let view = WlcView::dummy(state.next_handle());
+        state.views.insert(view, ViewInfo {
+            output, mask, layer: Layer::Normal, geometry: Geometry::default(),
+            state: ViewState::empty(), view_type: ViewType::empty(),
+            parent: WlcView::root(), title: String::new(), class: String::new(),
+            app_id: String::new(), size_hints: SizeHints::default(),
+            properties: HashMap::new(), mapped: true, minimized: false,
+            render_cost: Duration::from_millis(0), input_region: None
+        });
+        state.stack(view, Position::Top);
+        state.allocations.views.created += 1;
+        let (index, reused) = state.assign_index(view);
+        (view, index, if reused { state.index_reuse.clone() } else { None })
+    });
+    if let Some(reuse) = reuse {
+        reuse(index, view);
+    }
+    view
+}
+
+/// Removes a view from the dummy compositor.
+pub fn remove_view(view: WlcView) {
+    let dropped = state::with(|state| {
+        state.unstack(view);
+        state.forget_view(view)
+    });
+    for selection in dropped {
+        selection::changed(selection, None);
+    }
+}
+
+/// Sets the title of a view, as its client would.
+pub fn set_title(view: WlcView, title: &str) {
+    update_view(view, |info| info.title = title.to_string());
+}
+
+/// Sets the class of a view, as an X11 or wl_shell client would.
+pub fn set_class(view: WlcView, class: &str) {
+    update_view(view, |info| info.class = class.to_string());
+}
+
+/// Sets the app id of a view, as an xdg-shell client would.
+pub fn set_app_id(view: WlcView, app_id: &str) {
+    update_view(view, |info| info.app_id = app_id.to_string());
+}
+
+/// Sets a property of a view, replacing its previous value.
+///
+/// wlc has no such thing: properties let tests tag views for `find_views`.
+pub fn set_property(view: WlcView, key: &str, value: &str) {
+    update_view(view, |info| {
+        info.properties.insert(key.to_string(), value.to_string());
+    });
+}
+
+/// Minimizes or restores a view.
+///
+/// wlc has no minimized state: compositors hide views with masks. A
+/// minimized view keeps its mask, stacking, and geometry, but isn't
+/// visible, isn't under the pointer in `view_at`, and isn't rendered by
+/// `simulate::render_frame` until it is restored. Its output is scheduled
+/// for rendering whenever it is minimized or restored.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, Size};
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let view = dummy::add_view(output);
+/// dummy::set_minimized(view, true);
+/// assert!(dummy::visible_views(output).is_empty());
+/// dummy::set_minimized(view, false);
+/// assert_eq!(dummy::visible_views(output), vec![view]);
+/// ```
+pub fn set_minimized(view: WlcView, minimized: bool) {
+    set_drawn(view, |info| &mut info.minimized, minimized);
+}
+
+/// Sets where a view takes pointer input, as rectangles relative to its
+/// origin, or `None` for its whole geometry, which is the default.
+///
+/// Shaped windows take input only where they are drawn, and overlays such
+/// as on-screen displays take none, so clicks go through them to the views
+/// below. `view_at`, and so the views the `simulate` module gives pointer
+/// events, honor the region; parts of it outside the view's geometry are
+/// ignored.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, Geometry, Point, Size, EDGE_NONE};
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let window = dummy::add_view(output);
+/// let overlay = dummy::add_view(output);
+/// let whole = Geometry { origin: Point { x: 0, y: 0 }, size: Size { w: 800, h: 600 } };
+/// window.set_geometry(EDGE_NONE, whole);
+/// overlay.set_geometry(EDGE_NONE, whole);
+/// assert_eq!(dummy::view_at(output, Point { x: 10, y: 10 }), overlay);
+/// dummy::set_input_region(overlay, Some(Vec::new()));
+/// assert_eq!(dummy::view_at(output, Point { x: 10, y: 10 }), window);
+/// ```
+pub fn set_input_region(view: WlcView, region: Option<Vec<Geometry>>) {
+    update_view(view, |info| info.input_region = region);
+}
+
+/// Maps or unmaps a view's surface.
+///
+/// Views are mapped when added. An unmapped view still exists, but has no
+/// contents: `WlcView::get_geometry` returns `None`, and it isn't visible,
+/// under the pointer, rendered, or focused until it is mapped again. This
+/// doesn't invoke any callbacks: `simulate::unmap_view` and
+/// `simulate::map_view` do so as wlc would.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, Geometry, Size};
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let view = dummy::add_view(output);
+/// dummy::set_mapped(view, false);
+/// assert_eq!(view.get_geometry(), None);
+/// dummy::set_mapped(view, true);
+/// assert_eq!(view.get_geometry(), Some(Geometry::default()));
+/// ```
+pub fn set_mapped(view: WlcView, mapped: bool) {
+    set_drawn(view, |info| &mut info.mapped, mapped);
+}
+
+/// Whether a view exists and is mapped.
+pub fn is_mapped(view: WlcView) -> bool {
+    state::with(|state| state.views.get(&view).is_some_and(|info| info.mapped))
+}
+
+/// Changes a flag which decides whether a view is drawn, scheduling its
+/// output for rendering if it changed.
+fn set_drawn<F>(view: WlcView, flag: F, value: bool)
+    where F: FnOnce(&mut ViewInfo) -> &mut bool {
+    state::with(|state| {
+        let output = match state.views.get_mut(&view) {
+            Some(info) => {
+                let output = info.output;
+                let flag = flag(info);
+                if *flag == value {
+                    return
+                }
+                *flag = value;
+                output
+            },
+            None => return
+        };
+        state.schedule_render(output);
+    });
+}
+
+/// Sets the sizes a view's client accepts.
+///
+/// From now on, when the compositor gives the view a size outside its hints
+/// with `WlcView::set_geometry`, the client picks the closest size it
+/// accepts (see `SizeHints::constrain`) and the view gets that size
+/// instead. The refusal is reported as a violation (see `set_strict`).
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, Geometry, Point, Size, EDGE_NONE};
+/// use rustwlc::dummy::SizeHints;
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let view = dummy::add_view(output);
+/// dummy::set_size_hints(view, SizeHints {
+///     min: Some(Size { w: 300, h: 200 }),
+///     .. SizeHints::default()
+/// });
+/// let tiny = Geometry { origin: Point { x: 0, y: 0 }, size: Size { w: 100, h: 600 } };
+/// view.set_geometry(EDGE_NONE, tiny);
+/// assert_eq!(view.get_visible_geometry().size, Size { w: 300, h: 600 });
+/// assert_eq!(dummy::violations().len(), 1);
+/// ```
+pub fn set_size_hints(view: WlcView, hints: SizeHints) {
+    update_view(view, |info| info.size_hints = hints);
+}
+
+/// Pins a view to a stacking layer, moving it to the top of that layer.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::Size;
+/// use rustwlc::dummy::{self, Layer};
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let panel = dummy::add_view(output);
+/// let window = dummy::add_view(output);
+/// dummy::set_layer(panel, Layer::Above);
+/// window.bring_to_front();
+/// assert_eq!(output.get_views(), vec![window, panel]);
+/// ```
+pub fn set_layer(view: WlcView, layer: Layer) {
+    state::with(|state| {
+        state.unstack(view);
+        if let Some(info) = state.views.get_mut(&view) {
+            info.layer = layer;
+        }
+        state.stack(view, Position::Top);
+    });
+}
+
+/// Gets the stored info of a view, if it exists.
+pub fn view_info(view: WlcView) -> Option<ViewInfo> {
+    state::with(|state| state.views.get(&view).cloned())
+}
+
+/// Gets the views whose stored info matches a predicate, in order of their
+/// handles: the order they were added, unless `set_handle_policy` changed it.
+///
+/// The predicate is called outside of the dummy state, so it may use the
+/// rest of this module.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, fixtures};
+/// use rustwlc::Size;
+///
+/// let laptop = dummy::add_output("eDP-1", Size { w: 1920, h: 1080 });
+/// let monitor = dummy::add_output("DP-1", Size { w: 2560, h: 1440 });
+/// fixtures::mpv(laptop);
+/// let player = fixtures::mpv(monitor);
+/// let mpv_on_monitor = dummy::find_views(|info| {
+///     info.app_id == "mpv" && info.output == monitor
+/// });
+/// assert_eq!(mpv_on_monitor, vec![player]);
+/// ```
+pub fn find_views<F>(mut predicate: F) -> Vec<WlcView>
+    where F: FnMut(&ViewInfo) -> bool {
+    let views: Vec<(WlcView, ViewInfo)> = state::with(|state| {
+        state.views.iter().map(|(view, info)| (view, info.clone())).collect()
+    });
+    views.into_iter()
+        .filter(|(_, info)| predicate(info))
+        .map(|(view, _)| view)
+        .collect()
+}
+
+/// Gets the stored info of an output, if it exists.
+pub fn output_info(output: WlcOutput) -> Option<OutputInfo> {
+    state::with(|state| state.output(output).cloned())
+}
+
+/// Whether a view would be drawn on its output.
+///
+/// As in wlc, a view is visible when its mask shares a bit with the mask
+/// of its output. Minimized and unmapped views are never visible.
+pub fn is_visible(view: WlcView) -> bool {
+    state::with(|state| {
+        state.views.get(&view).and_then(|info| {
+            state.output(info.output)
+                .map(|output| info.mask & output.mask != 0 && info.is_drawn())
+        }).unwrap_or(false)
+    })
+}
+
+/// Gets the visible views of an output in stack order.
+pub fn visible_views(output: WlcOutput) -> Vec<WlcView> {
+    state::with(|state| {
+        let mask = match state.output(output) {
+            Some(info) => info.mask,
+            None => return Vec::new()
+        };
+        let filter = ViewFilter { mask: Some(mask), .. ViewFilter::default() };
+        state.filter_views(output, |info| filter.matches(info) && info.is_drawn())
+    })
+}
+
+/// Gets the topmost visible view of an output which contains a point.
+///
+/// Views whose input region (see `set_input_region`) leaves out the point
+/// are passed over. Returns `WlcView::root()` if no view is there.
+pub fn view_at(output: WlcOutput, point: Point) -> WlcView {
+    state::with(|state| {
+        let mask = match state.output(output) {
+            Some(info) => info.mask,
+            None => return WlcView::root()
+        };
+        let stack = &state.output(output).unwrap().views;
+        stack.iter().rev()
+            .find(|view| {
+                let info = &state.views[view];
+                info.mask & mask != 0 && info.is_drawn() && info.takes_input_at(point)
+            })
+            .cloned().unwrap_or_else(WlcView::root)
+    })
+}
+
+/// Gets the focused view, or `WlcView::root()` if no view is focused.
+pub fn focused_view() -> WlcView {
+    state::with(|state| state.focused_view).unwrap_or_else(WlcView::root)
+}
+
+/// A keyboard focus event delivered to a client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyboardDelivery {
+    /// The view's client was given the keyboard
+    Enter {
+        /// The view which gained the keyboard
+        view: WlcView,
+        /// Serial of the event
+        serial: u32
+    },
+    /// The view's client lost the keyboard
+    Leave {
+        /// The view which lost the keyboard
+        view: WlcView,
+        /// Serial of the event
+        serial: u32
+    }
+}
+
+/// An input event which hit no view, delivered to an output's root.
+///
+/// wlc gives such events to callbacks with `WlcView::root()`, which doesn't
+/// tell which output was clicked. The dummy records it here, so desktop
+/// menus and wallpaper clicks can be tested per output.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RootDelivery {
+    /// Output whose background received the event
+    pub output: WlcOutput,
+    /// The event, as given to callbacks
+    pub event: WlcEvent
+}
+
+/// Gets the key, button, and scroll events delivered to the root of an
+/// output, oldest first.
+///
+/// Pointer events go to the root of the output under the pointer, and keys
+/// go to the root of the focused output when no view is focused.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, simulate, WlcView, WlcOutput, Point, Size, ButtonState};
+/// use rustwlc::input::keys::BTN_RIGHT;
+///
+/// dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let second = dummy::add_output("WLC-2", Size { w: 800, h: 600 });
+/// WlcOutput::focus(Some(second));
+/// simulate::pointer_button(BTN_RIGHT, ButtonState::Pressed);
+/// let delivery = dummy::root_deliveries()[0];
+/// assert_eq!(delivery.output, second);
+/// assert_eq!(delivery.event.view(), Some(WlcView::root()));
+/// ```
+pub fn root_deliveries() -> Vec<RootDelivery> {
+    state::with(|state| state.root_deliveries.to_vec())
+}
+
+/// Forgets the events delivered to roots so far.
+pub fn clear_root_deliveries() {
+    state::with(|state| state.root_deliveries.clear());
+}
+
+/// Records an input event if it was given to the root view.
+pub(crate) fn deliver_root(output: WlcOutput, view: WlcView, event: WlcEvent) {
+    if view.is_root() {
+        state::with(|state| state.root_deliveries.push(RootDelivery { output, event }));
+    }
+}
+
+/// A pointer warp made by the compositor with `input::pointer::set_position`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Warp {
+    /// Where the pointer was moved to
+    pub point: Point,
+    /// Time of the warp, from `time()`
+    pub time: u32,
+    /// Event whose callback made the warp, or `None` outside of callbacks
+    pub origin: Option<WlcEvent>
+}
+
+/// Gets the pointer warps made so far, oldest first.
+///
+/// wlc only moves the pointer when the compositor warps it, so every
+/// `pointer_motion` callback should warp exactly once.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, simulate, Point, Size, WlcEvent, WlcView};
+/// use rustwlc::input::pointer;
+///
+/// fn pointer_motion(view: WlcView, time: u32, point: &Point) -> bool {
+///     pointer::set_position(*point);
+///     false
+/// }
+///
+/// dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// callback::rust::pointer_motion(pointer_motion);
+/// simulate::pointer_motion(Point { x: 10, y: 10 });
+/// let warps = dummy::warps();
+/// assert_eq!(warps.len(), 1);
+/// assert_eq!(warps[0].origin, Some(WlcEvent::PointerMotion {
+///     view: WlcView::root(), time: 0, point: Point { x: 10, y: 10 }
+/// }));
+/// ```
+pub fn warps() -> Vec<Warp> {
+    state::with(|state| state.warps.to_vec())
+}
+
+/// Forgets the pointer warps made so far.
+pub fn clear_warps() {
+    state::with(|state| state.warps.clear());
+}
+
+/// Gets the view which has the keyboard from its client's point of view.
+///
+/// This follows the enter and leave events in `keyboard_deliveries`, so it
+/// can lag behind `focused_view` while `view_focus` callbacks are running.
+/// Returns `WlcView::root()` if no client has the keyboard.
+pub fn keyboard_focus() -> WlcView {
+    state::with(|state| state.keyboard_focus).unwrap_or_else(WlcView::root)
+}
+
+/// Gets the keyboard enter and leave events delivered to clients so far.
+///
+/// When focus moves, as in wlc, the old view is sent a leave before its
+/// `view_focus` callback and the new view is sent an enter before its own.
+/// A view which is removed while it has the keyboard is not sent a leave.
+/// Each event has a fresh serial.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::Size;
+/// use rustwlc::dummy::{self, KeyboardDelivery};
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let first = dummy::add_view(output);
+/// let second = dummy::add_view(output);
+/// first.focus();
+/// second.focus();
+/// assert_eq!(dummy::keyboard_deliveries(), vec![
+///     KeyboardDelivery::Enter { view: first, serial: 1 },
+///     KeyboardDelivery::Leave { view: first, serial: 2 },
+///     KeyboardDelivery::Enter { view: second, serial: 3 }
+/// ]);
+/// assert_eq!(dummy::keyboard_focus(), second);
+/// ```
+pub fn keyboard_deliveries() -> Vec<KeyboardDelivery> {
+    state::with(|state| state.keyboard_deliveries.to_vec())
+}
+
+/// Forgets the keyboard events delivered so far.
+pub fn clear_keyboard_deliveries() {
+    state::with(|state| state.keyboard_deliveries.clear());
+}
+
+/// Delivers a keyboard enter or leave to a view's client.
+pub(crate) fn deliver_keyboard(view: WlcView, enter: bool) {
+    let serial = next_serial();
+    state::with(|state| {
+        if enter {
+            state.keyboard_focus = Some(view);
+            state.keyboard_deliveries.push(KeyboardDelivery::Enter { view, serial });
+        } else {
+            if state.keyboard_focus == Some(view) {
+                state.keyboard_focus = None;
+            }
+            state.keyboard_deliveries.push(KeyboardDelivery::Leave { view, serial });
+        }
+    });
+}
+
+/// A pointer focus event delivered to a client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerDelivery {
+    /// The pointer entered the view
+    Enter {
+        /// The view the pointer entered
+        view: WlcView,
+        /// Serial of the event
+        serial: u32,
+        /// Position of the pointer on the output
+        point: Point
+    },
+    /// The pointer left the view
+    Leave {
+        /// The view the pointer left
+        view: WlcView,
+        /// Serial of the event
+        serial: u32
+    }
+}
+
+/// Gets the view which has the pointer from its client's point of view.
+///
+/// As in Wayland, pointer focus is separate from keyboard focus: it follows
+/// the pointer rather than `WlcView::focus`, so with click-to-focus, the
+/// view under the pointer may not be the one keys go to. Button and scroll
+/// events are given to this view, and keys to `focused_view`.
+///
+/// Pointer focus is updated when pointer events are simulated, after
+/// `pointer_motion` callbacks warp the pointer, so a view which moves under
+/// a still pointer only gets it on the next event. Returns
+/// `WlcView::root()` if the pointer isn't over any view.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, simulate, Geometry, Point, Size, EDGE_NONE};
+/// use rustwlc::input::pointer;
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let left = dummy::add_view(output);
+/// let right = dummy::add_view(output);
+/// left.set_geometry(EDGE_NONE, Geometry { origin: Point { x: 0, y: 0 }, size: Size { w: 400, h: 600 } });
+/// right.set_geometry(EDGE_NONE, Geometry { origin: Point { x: 400, y: 0 }, size: Size { w: 400, h: 600 } });
+/// callback::rust::pointer_motion(|_, _, point| { pointer::set_position(*point); false });
+/// left.focus();
+/// simulate::pointer_motion(Point { x: 600, y: 300 });
+/// assert_eq!(dummy::pointer_focus(), right);
+/// assert_eq!(dummy::keyboard_focus(), left);
+/// ```
+pub fn pointer_focus() -> WlcView {
+    state::with(|state| state.pointer_focus).unwrap_or_else(WlcView::root)
+}
+
+/// Gets the pointer enter and leave events delivered to clients so far.
+///
+/// When the pointer moves from one view to another, the old view is sent a
+/// leave before the new view is sent an enter. A view which is removed
+/// while it has the pointer is not sent a leave. Each event has a fresh
+/// serial.
+pub fn pointer_deliveries() -> Vec<PointerDelivery> {
+    state::with(|state| state.pointer_deliveries.to_vec())
+}
+
+/// Forgets the pointer events delivered so far.
+pub fn clear_pointer_deliveries() {
+    state::with(|state| state.pointer_deliveries.clear());
+}
+
+/// Gives pointer focus to the view under the pointer, delivering a leave
+/// and an enter if it changed. Returns the view.
+pub(crate) fn update_pointer_focus() -> WlcView {
+    let point = state::with(|state| state.pointer);
+    let under = view_at(pointer_output(), point);
+    let new = Some(under).filter(WlcView::is_window);
+    let old = state::with(|state| state.pointer_focus);
+    if old == new {
+        return under
+    }
+    if let Some(view) = old {
+        let serial = next_serial();
+        state::with(|state| state.pointer_deliveries.push(PointerDelivery::Leave { view, serial }));
+    }
+    let serial = new.map(|_| next_serial());
+    state::with(|state| {
+        state.pointer_focus = new;
+        if let (Some(view), Some(serial)) = (new, serial) {
+            state.pointer_deliveries.push(PointerDelivery::Enter { view, serial, point });
+        }
+    });
+    under
+}
+
+/// Gets the last serial given to a client event or request.
+///
+/// Serials start at zero and increase by one for each keyboard or pointer
+/// delivery and each request from `simulate`.
+pub fn last_serial() -> u32 {
+    state::with(|state| state.serial)
+}
+
+pub(crate) fn next_serial() -> u32 {
+    state::with(state::State::next_serial)
+}
+
+/// A request from a client which the compositor should answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientRequest {
+    /// Answered by `WlcView::set_geometry` with the same geometry
+    Geometry(Geometry),
+    /// Answered by `WlcView::set_state` with the same state and toggle
+    State(ViewState, bool)
+}
+
+impl ClientRequest {
+    /// Whether a newer request of this kind supersedes `other`.
+    fn same_kind(self, other: ClientRequest) -> bool {
+        match (self, other) {
+            (ClientRequest::Geometry(_), ClientRequest::Geometry(_)) => true,
+            (ClientRequest::State(state, _), ClientRequest::State(other, _)) =>
+                state == other,
+            _ => false
+        }
+    }
+}
+
+/// Gets the unanswered requests of a view with their serials, oldest first.
+///
+/// Requests are made with `simulate::request_geometry` and
+/// `simulate::request_state`. The compositor answers a request by applying
+/// what was asked for, which also drops older requests of the same kind:
+/// any geometry request, or a state request for the same flags.
+///
+/// Since rustwlc's setters don't take serials, an answer is matched to a
+/// request by its value. Answering a request after a newer one of the same
+/// kind arrived, e.g. applying a stale geometry from a deferred queue, is a
+/// violation (see `set_strict`). Setters which match no request are the
+/// compositor's own changes and are always allowed.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, simulate};
+/// use rustwlc::{Geometry, Point, Size, EDGE_NONE};
+/// use rustwlc::dummy::ClientRequest;
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let view = dummy::add_view(output);
+/// let small = Geometry { origin: Point { x: 0, y: 0 }, size: Size { w: 10, h: 10 } };
+/// let large = Geometry { size: Size { w: 500, h: 500 }, .. small };
+/// simulate::request_geometry(view, small);
+/// let serial = simulate::request_geometry(view, large);
+/// assert_eq!(dummy::pending_requests(view).len(), 2);
+///
+/// view.set_geometry(EDGE_NONE, small);
+/// assert_eq!(dummy::violations().len(), 1);
+/// view.set_geometry(EDGE_NONE, large);
+/// assert!(dummy::pending_requests(view).is_empty());
+/// ```
+pub fn pending_requests(view: WlcView) -> Vec<(u32, ClientRequest)> {
+    state::with(|state| state.requests.get(&view).cloned().unwrap_or_default())
+}
+
+/// Records a request of a view, returning its serial.
+pub(crate) fn push_request(view: WlcView, request: ClientRequest) -> u32 {
+    let serial = next_serial();
+    state::with(|state| if state.views.contains_key(&view) {
+        state.requests.entry(view).or_default().push((serial, request));
+    });
+    serial
+}
+
+/// Answers the request of a view which asked for `answer`, if any.
+pub(crate) fn answer_request(view: WlcView, answer: ClientRequest) {
+    let stale = state::with(|state| {
+        let requests = state.requests.get_mut(&view)?;
+        let answered = requests.iter().rev()
+            .find(|&&(_, request)| request == answer)?.0;
+        let latest = requests.iter().rev()
+            .find(|&&(_, request)| request.same_kind(answer))?.0;
+        requests.retain(|&(serial, request)| serial > answered ||
+                        !request.same_kind(answer));
+        if answered != latest { Some((answered, latest)) } else { None }
+    });
+    if let Some((answered, latest)) = stale {
+        violation(format!("{:?} answered request {} after newer request {}",
+                          view, answered, latest));
+    }
+}
+
+/// Where the simulated compositor is in its lifecycle.
+///
+/// `rustwlc::init()` initializes it, running the function it returns runs
+/// it, and `rustwlc::terminate()` terminates it. Calls out of this order,
+/// such as running wlc twice or registering callbacks while it runs, are
+/// reported as misuse (see `set_strict`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Lifecycle {
+    /// Before `rustwlc::init()`. This is the default.
+    #[default]
+    Uninitialized,
+    /// After `rustwlc::init()`, before wlc runs
+    Initialized,
+    /// While wlc runs
+    Running,
+    /// After `rustwlc::terminate()`, until `new_session`
+    Terminated
+}
+
+/// Gets where the simulated compositor is in its lifecycle.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, InitError};
+/// use rustwlc::dummy::Lifecycle;
+///
+/// assert_eq!(dummy::lifecycle(), Lifecycle::Uninitialized);
+/// let run = rustwlc::try_init().unwrap();
+/// assert_eq!(rustwlc::try_init().err(), Some(InitError::AlreadyInitialized));
+/// run();
+/// assert_eq!(dummy::lifecycle(), Lifecycle::Running);
+/// run();
+/// assert_eq!(dummy::violations(), vec!["wlc was run twice".to_string()]);
+/// ```
+pub fn lifecycle() -> Lifecycle {
+    state::with(|state| state.lifecycle)
+}
+
+/// Gets the events dispatched by the last call to `rustwlc::terminate()`.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, Size, WlcEvent};
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let view = dummy::add_view(output);
+/// rustwlc::init().unwrap()();
+/// rustwlc::terminate();
+/// assert_eq!(dummy::shutdown_sequence(), vec![
+///     WlcEvent::ViewDestroyed(view),
+///     WlcEvent::OutputDestroyed(output),
+///     WlcEvent::CompositorTerminate
+/// ]);
+/// ```
+pub fn shutdown_sequence() -> Vec<WlcEvent> {
+    state::with(|state| state.shutdown.to_vec())
+}
+
+/// Gets the current time in milliseconds.
+///
+/// This is the time given to callbacks by the `simulate` module. It starts
+/// at zero and only changes with `advance_time`.
+pub fn time() -> u32 {
+    state::with(|state| state.time)
+}
+
+/// How fast the mock clock runs against the wall clock.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ClockSpeed {
+    /// Time passes at once, as fast as the test runs
+    #[default]
+    FastForward,
+    /// Time passes in step with the wall clock, this many times faster, so
+    /// `1.0` is real time and `0.5` is half speed
+    RealTime(f64)
+}
+
+/// Sets how fast the mock clock runs.
+///
+/// With `ClockSpeed::RealTime`, `advance_time` sleeps until the wall clock
+/// catches up, so a developer watching a compositor, i.e. with the
+/// inspector, sees timeouts and animations as a user would.
+///
+/// # Panics
+/// If the ratio of `ClockSpeed::RealTime` isn't positive.
+pub fn set_clock_speed(speed: ClockSpeed) {
+    if let ClockSpeed::RealTime(ratio) = speed {
+        assert!(ratio > 0.0, "dummy-rustwlc clock speed of {}", ratio);
+    }
+    state::with(|state| state.clock_speed = speed);
+}
+
+/// Gets how fast the mock clock runs.
+pub fn clock_speed() -> ClockSpeed {
+    state::with(|state| state.clock_speed)
+}
+
+/// Moves the current time forward.
+///
+/// Clients ack any configures which become due, see `set_configure_delay`,
+/// scheduling their outputs for rendering. Sleeps first if the clock runs
+/// in real time, see `set_clock_speed`.
+pub fn advance_time(millis: u32) {
+    if let ClockSpeed::RealTime(ratio) = clock_speed() {
+        thread::sleep(Duration::from_secs_f64(millis as f64 / 1000.0 / ratio));
+    }
+    state::with(|state| {
+        state.time = state.time.wrapping_add(millis);
+        let time = state.time;
+        let (due, pending) = state.configures.drain(..)
+            .partition::<Vec<_>, _>(|configure| configure.due <= time);
+        state.configures = pending;
+        for configure in due {
+            if let Some(info) = state.views.get_mut(&configure.view) {
+                info.geometry = configure.geometry;
+                let output = info.output;
+                state.schedule_render(output);
+            }
+        }
+    });
+    update_hot_corners();
+}
+
+/// Sets how long clients take to ack new geometry, or `None` to apply it
+/// instantly.
+///
+/// By default, `WlcView::set_geometry` changes the geometry at once. With a
+/// delay, it sends the client a configure instead, and the view keeps its
+/// old geometry until the client acks `millis` later, as `advance_time`
+/// passes. Real clients resize asynchronously, so this reproduces bugs in
+/// compositors which assume that resizes are instant.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::dummy;
+/// use rustwlc::{Geometry, Point, Size, EDGE_NONE};
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let view = dummy::add_view(output);
+/// let geometry = Geometry { origin: Point { x: 0, y: 0 }, size: Size { w: 10, h: 10 } };
+/// dummy::set_configure_delay(Some(16));
+/// view.set_geometry(EDGE_NONE, geometry);
+/// assert_eq!(view.get_geometry(), Some(Geometry::default()));
+/// assert_eq!(dummy::pending_configures(view).len(), 1);
+///
+/// dummy::advance_time(16);
+/// assert_eq!(view.get_geometry(), Some(geometry));
+/// ```
+pub fn set_configure_delay(millis: Option<u32>) {
+    state::with(|state| state.configure_delay = millis);
+}
+
+/// Gets the configures sent to a view's client which it hasn't acked yet,
+/// with their serials, oldest first.
+pub fn pending_configures(view: WlcView) -> Vec<(u32, Geometry)> {
+    state::with(|state| state.configures.iter()
+                .filter(|configure| configure.view == view)
+                .map(|configure| (configure.serial, configure.geometry))
+                .collect())
+}
+
+/// Sets the geometry of many views at once, as `WlcView::set_geometry`
+/// would.
+///
+/// Each output with a changed view is scheduled for rendering once, rather
+/// than once per view, so layout engines can apply a whole layout at a
+/// time. With a configure delay, the clients ack the whole layout together.
+/// Comparing `snapshot()`s before and after checks the resulting layout.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::dummy;
+/// use rustwlc::{Geometry, Point, Size};
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let left = dummy::add_view(output);
+/// let right = dummy::add_view(output);
+/// let half = Size { w: 400, h: 600 };
+/// dummy::apply_layout(&[
+///     (left, Geometry { origin: Point { x: 0, y: 0 }, size: half }),
+///     (right, Geometry { origin: Point { x: 400, y: 0 }, size: half })
+/// ]);
+/// assert_eq!(right.get_visible_geometry().origin, Point { x: 400, y: 0 });
+/// assert_eq!(dummy::frame_stats(output).scheduled, 1);
+/// ```
+pub fn apply_layout(layout: &[(WlcView, Geometry)]) {
+    let mut changed = Vec::new();
+    for &(view, requested) in layout {
+        changed_while_rendering(view, "moved or resized");
+        let geometry = Geometry {
+            size: view_info(view).map_or(requested.size,
+                                         |info| info.size_hints.constrain(requested.size)),
+            .. requested
+        };
+        if geometry != requested {
+            violation(format!("{:?} refused size {} and took {} instead",
+                              view, requested.size, geometry.size));
+        }
+        state::with(|state| {
+            let delay = match state.configure_delay {
+                Some(delay) if state.views.contains_key(&view) => delay,
+                _ => {
+                    if let Some(info) = state.views.get_mut(&view) {
+                        info.geometry = geometry;
+                        changed.push(info.output);
+                    }
+                    return
+                }
+            };
+            let (serial, due) = (state.next_serial(), state.time.wrapping_add(delay));
+            state.configures.push(state::Configure { view, serial, geometry, due });
+        });
+        answer_request(view, ClientRequest::Geometry(requested));
+    }
+    state::with(|state| for output in changed {
+        state.schedule_render(output);
+    });
+}
+
+/// Sets the backend wlc reports running on, see `rustwlc::get_backend_type`.
+///
+/// With `BackendType::X11`, the compositor runs nested in an X server,
+/// whose window manager grabs some chords for itself, see
+/// `set_host_grabs`.
+pub fn set_backend(backend: BackendType) {
+    state::with(|state| state.backend = backend);
+}
+
+/// Sets the renderer information reported by `WlcOutput::get_context_info`.
+///
+/// Pass `Some(output)` to configure a single output, or `None` to change the
+/// default for all outputs without their own configuration.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::WlcOutput;
+/// use rustwlc::dummy;
+/// use rustwlc::render::ContextInfo;
+///
+/// let output = WlcOutput::dummy(1);
+/// let nvidia = ContextInfo {
+///     vendor: "NVIDIA Corporation".to_string(),
+///     extensions: vec![],
+///     .. ContextInfo::default()
+/// };
+/// dummy::set_context_info(Some(output), nvidia.clone());
+/// assert_eq!(output.get_context_info(), nvidia);
+/// assert_eq!(WlcOutput::dummy(2).get_context_info(), ContextInfo::default());
+/// ```
+pub fn set_context_info(output: Option<WlcOutput>, info: ContextInfo) {
+    state::with(|state| match output {
+        Some(output) => { state.output_context_info.insert(output, info); },
+        None => state.context_info = info
+    });
+}
+
+/// What happens when views are moved, resized or restacked while a frame
+/// renders.
+///
+/// In wlc, changing geometry or stacking from a render callback schedules
+/// another frame, whose callbacks change it again, so the compositor
+/// renders in a loop.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RenderMutations {
+    /// Reported as misuse (see `set_strict`). This is the default.
+    #[default]
+    Report,
+    /// Panics, even outside strict mode
+    Panic,
+    /// Allowed
+    Allow
+}
+
+/// Sets what happens when views are moved, resized or restacked while
+/// `simulate::render_frame` renders, from its callbacks or screenshot
+/// handler.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, simulate, Size, EDGE_NONE};
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let view = dummy::add_view(output);
+/// callback::rust::view_render_pre(|view| {
+///     let geometry = view.get_geometry().unwrap();
+///     view.set_geometry(EDGE_NONE, geometry);
+///     view.bring_to_front();
+/// });
+/// simulate::render_frame(output);
+/// assert_eq!(dummy::violations(), vec![
+///     format!("{:?} was moved or resized during view_render_pre of {:?}", view, output),
+///     format!("{:?} was restacked during view_render_pre of {:?}", view, output)
+/// ]);
+/// ```
+pub fn set_render_mutations(policy: RenderMutations) {
+    state::with(|state| state.render_mutations = policy);
+}
+
+/// Reports a view being changed while a frame renders, see
+/// `set_render_mutations`.
+fn changed_while_rendering(view: WlcView, change: &str) {
+    let (policy, rendering, event) = state::with(|state| {
+        (state.render_mutations, state.rendering, state.dispatching.last().cloned())
+    });
+    let output = match rendering {
+        Some(output) if policy != RenderMutations::Allow => output,
+        _ => return
+    };
+    let during = event.map_or("the frame", |event| event.name());
+    let message = format!("{:?} was {} during {} of {:?}", view, change, during, output);
+    match policy {
+        RenderMutations::Panic => {
+            panic!("dummy-rustwlc render mutation: {}\n{}", message, state_summary())
+        },
+        _ => violation(message)
+    }
+}
+
+/// Sets how long outputs stay blank after their resolution changes, or
+/// `None` not to blank them, which is the default.
+///
+/// Real outputs go dark for a moment while they change mode. While an
+/// output is blank, `simulate::render_frame` doesn't render it, and keys,
+/// pointer input, and touches from `simulate` while it's the pointer's
+/// output are queued, then delivered in order at the first frame or input
+/// after the blank ends, as `advance_time` passes. Compositors whose
+/// animations span a mode change can check that they cope with the
+/// missing frames.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, simulate, KeyState, Size, WlcOutput};
+/// use rustwlc::input::keys;
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let (frames, keys) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(0)));
+/// let (frame_count, key_count) = (frames.clone(), keys.clone());
+/// callback::rust::output_render_pre(move |_| frame_count.set(frame_count.get() + 1));
+/// callback::rust::keyboard_key(move |_, _, _, _, _| {
+///     key_count.set(key_count.get() + 1);
+///     false
+/// });
+/// dummy::set_modeset_blank(Some(50));
+/// simulate::output_resolution(output, Size { w: 1920, h: 1080 });
+/// assert!(dummy::is_blanked(output));
+/// simulate::render_frame(output);
+/// simulate::keyboard_key(keys::KEY_A, KeyState::Pressed);
+/// assert_eq!((frames.get(), keys.get()), (0, 0));
+/// dummy::advance_time(50);
+/// assert!(!dummy::is_blanked(output));
+/// simulate::render_frame(output);
+/// assert_eq!((frames.get(), keys.get()), (1, 1));
+/// ```
+pub fn set_modeset_blank(millis: Option<u32>) {
+    state::with(|state| state.modeset_blank = millis);
+}
+
+/// Whether an output is blank after changing mode, see
+/// `set_modeset_blank`.
+pub fn is_blanked(output: WlcOutput) -> bool {
+    state::with(|state| state.blanks.get(&output)
+                .is_some_and(|&(since, millis)| state.time.wrapping_sub(since) < millis))
+}
+
+/// Blanks an output which changed mode, if outputs are blanked.
+pub(crate) fn blank(output: WlcOutput) {
+    state::with(|state| if let Some(millis) = state.modeset_blank {
+        let time = state.time;
+        state.blanks.insert(output, (time, millis));
+    });
+}
+
+/// Sets the compositor's policy on which clients may create virtual
+/// keyboards with `simulate::create_virtual_keyboard`.
+///
+/// The policy is given the view of the client asking. Without a policy,
+/// every client is denied.
+pub fn set_virtual_keyboard_policy<F>(policy: F) where F: Fn(WlcView) -> bool + 'static {
+    state::with(|state| state.virtual_keyboard_policy = Some(Callback(Rc::new(policy))));
+}
+
+/// Gets the view of the client whose virtual keyboard sent the key being
+/// delivered, if it came from one rather than from the keyboard.
+pub fn virtual_keyboard_source() -> Option<WlcView> {
+    state::with(|state| state.virtual_keyboard_source)
+}
+
+pub(crate) fn context_info(output: WlcOutput) -> ContextInfo {
+    state::with(|state| state.output_context_info.get(&output)
+                .unwrap_or(&state.context_info).clone())
+}
+
+/// The output the pointer is on: the focused output, or else the first one.
+pub(crate) fn pointer_output() -> WlcOutput {
+    state::with(|state| {
+        state.focused_output
+            .or_else(|| state.outputs.first().map(|entry| entry.handle))
+            .unwrap_or_else(|| WlcOutput::dummy(0))
+    })
+}
+
+pub(crate) fn primary_output() -> WlcOutput {
+    state::with(|state| {
+        state.primary_output
+            .or_else(|| state.outputs.first().map(|entry| entry.handle))
+            .unwrap_or_else(|| WlcOutput::dummy(0))
+    })
+}
+
+pub(crate) fn outputs() -> Vec<WlcOutput> {
+    state::with(|state| state.outputs.iter().map(|entry| entry.handle).collect())
+}
+
+pub(crate) fn update_view<F>(view: WlcView, f: F) where F: FnOnce(&mut ViewInfo) {
+    state::with(|state| state.views.get_mut(&view).map(f));
+}
+
+pub(crate) fn update_output<F>(output: WlcOutput, f: F) where F: FnOnce(&mut OutputInfo) {
+    state::with(|state| state.output_mut(output).map(|entry| f(&mut entry.info)));
+}
+
+pub(crate) fn set_views(output: WlcOutput, views: &[WlcView])
+                        -> Result<(), &'static str> {
+    state::with(|state| {
+        let mut old = match state.output(output) {
+            Some(info) => info.views.clone(),
+            None => return Err("Output is not part of the dummy compositor")
+        };
+        let mut new = views.to_vec();
+        old.sort();
+        new.sort();
+        if old != new {
+            return Err("Views must be a reordering of the output's views")
+        }
+        state.set_stack(output, views.to_vec());
+        Ok(())
+    })
+}
+
+pub(crate) fn views_matching(output: WlcOutput, filter: ViewFilter) -> Vec<WlcView> {
+    state::with(|state| state.matching_views(output, filter))
+}
+
+pub(crate) fn set_view_type(view: WlcView, view_type: ViewType, toggle: bool) {
+    state::with(|state| {
+        let mut new = match state.views.get(&view) {
+            Some(info) => info.view_type,
+            None => return
+        };
+        if toggle {
+            new.insert(view_type);
+        } else {
+            new.remove(view_type);
+        }
+        state.set_view_type(view, new);
+    });
+}
+
+pub(crate) fn z_index(view: WlcView) -> Option<usize> {
+    state::with(|state| {
+        let info = state.views.get(&view)?;
+        state.output(info.output)?.views.iter().position(|&other| other == view)
+    })
+}
+
+/// Moves a view within its output's stack.
+///
+/// Stacking a view below or above a view on another output is ignored, as
+/// wlc leaves it undefined, and reported as a violation.
+pub(crate) fn restack(view: WlcView, position: Position) {
+    changed_while_rendering(view, "restacked");
+    let across = state::with(|state| {
+        let (other, relation) = match position {
+            Position::Below(other) => (other, "below"),
+            Position::Above(other) => (other, "above"),
+            _ => return None
+        };
+        match (state.views.get(&view), state.views.get(&other)) {
+            (Some(info), Some(other_info)) if info.output != other_info.output =>
+                Some((other, relation, info.output, other_info.output)),
+            _ => None
+        }
+    });
+    if let Some((other, relation, output, other_output)) = across {
+        violation(format!("{:?} on {:?} was stacked {} {:?} on {:?}",
+                          view, output, relation, other, other_output));
+    }
+    state::with(|state| state.restack(view, position));
+}
+
+/// Moves a view to the top of another output's stack.
+pub(crate) fn move_view(view: WlcView, output: WlcOutput) {
+    state::with(|state| {
+        match state.views.get(&view) {
+            Some(info) if info.output != output => {},
+            _ => return
+        }
+        if state.output(output).is_none() {
+            return
+        }
+        state.unstack(view);
+        if let Some(info) = state.views.get_mut(&view) {
+            info.output = output;
+        }
+        state.stack(view, Position::Top);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::super::render::ContextInfo;
+
+    #[test]
+    fn state_requests() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let view = dummy::add_view(output);
+        let maximize = simulate::request_state(view, VIEW_MAXIMIZED, true);
+        let fullscreen = simulate::request_state(view, VIEW_FULLSCREEN, true);
+        assert_eq!(fullscreen, maximize + 1);
+        view.set_state(VIEW_ACTIVATED, true);
+        view.set_state(VIEW_FULLSCREEN, true);
+        assert_eq!(view.get_state(), VIEW_ACTIVATED | VIEW_FULLSCREEN);
+        assert_eq!(dummy::pending_requests(view), vec![
+            (maximize, dummy::ClientRequest::State(VIEW_MAXIMIZED, true))
+        ]);
+        view.set_state(VIEW_MAXIMIZED, true);
+        assert!(dummy::pending_requests(view).is_empty());
+        assert!(dummy::violations().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "answered request 1 after newer request 2")]
+    fn strict_stale_answer() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let view = dummy::add_view(output);
+        dummy::set_strict(true);
+        simulate::request_state(view, VIEW_MAXIMIZED, true);
+        simulate::request_state(view, VIEW_MAXIMIZED, false);
+        view.set_state(VIEW_MAXIMIZED, true);
+    }
+
+    #[test]
+    fn configure_acks() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let view = dummy::add_view(output);
+        let other = dummy::add_view(output);
+        let size = |w| Geometry { origin: Point { x: 0, y: 0 }, size: Size { w, h: w } };
+        dummy::set_configure_delay(Some(10));
+        view.set_geometry(EDGE_NONE, size(1));
+        dummy::advance_time(5);
+        view.set_geometry(EDGE_NONE, size(2));
+        other.set_geometry(EDGE_NONE, size(3));
+        dummy::advance_time(5);
+        assert_eq!(view.get_visible_geometry(), size(1));
+        assert_eq!(dummy::pending_configures(view), vec![(2, size(2))]);
+        dummy::remove_view(other);
+        assert!(dummy::pending_configures(other).is_empty());
+        dummy::advance_time(5);
+        assert_eq!(view.get_visible_geometry(), size(2));
+        dummy::set_configure_delay(None);
+        view.set_geometry(EDGE_NONE, size(4));
+        assert_eq!(view.get_visible_geometry(), size(4));
+    }
+
+    #[test]
+    fn render_scheduling() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let view = dummy::add_view(output);
+        let geometry = Geometry { origin: Point { x: 1, y: 1 }, size: Size { w: 1, h: 1 } };
+        output.schedule_render();
+        view.set_geometry(EDGE_NONE, geometry);
+        assert_eq!(dummy::frame_stats(output).scheduled, 1);
+        simulate::render_frame(output);
+        dummy::set_configure_delay(Some(1));
+        dummy::apply_layout(&[(view, Geometry::default()), (view, geometry)]);
+        assert_eq!(dummy::frame_stats(output).scheduled, 1);
+        dummy::advance_time(1);
+        assert_eq!(dummy::frame_stats(output).scheduled, 2);
+        WlcOutput::dummy(9).schedule_render();
+        assert_eq!(dummy::frame_stats(WlcOutput::dummy(9)).scheduled, 0);
+    }
+
+    #[test]
+    fn seeded_handles() {
+        let handles = || {
+            dummy::reset();
+            dummy::set_handle_policy(dummy::HandlePolicy::Seeded(7));
+            let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+            (output, dummy::add_view(output), dummy::add_view(output))
+        };
+        let (output, first, second) = handles();
+        assert_eq!(handles(), (output, first, second));
+        assert!(first != second && first.is_window() && second.is_window());
+        assert_eq!(output.get_views(), vec![first, second]);
+        // The same seed gives the same handles, and the same steps
+        let run = || {
+            dummy::reset();
+            dummy::set_seed(7);
+            dummy::set_handle_policy(dummy::HandlePolicy::Random);
+            let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+            (output, dummy::add_view(output), model::random_steps(4))
+        };
+        let (random_output, view, steps) = run();
+        assert_eq!(run(), (random_output, view, steps));
+        assert_eq!((random_output, view), (output, first));
+    }
+
+    #[test]
+    fn size_hints() {
+        let video = dummy::SizeHints {
+            max: Some(Size { w: 1280, h: 1280 }),
+            aspect: Some(Size { w: 16, h: 9 }),
+            .. dummy::SizeHints::default()
+        };
+        assert_eq!(video.constrain(Size { w: 1920, h: 1200 }), Size { w: 1280, h: 1080 });
+        assert_eq!(video.constrain(Size { w: 800, h: 900 }), Size { w: 800, h: 450 });
+        assert_eq!(dummy::SizeHints::default().constrain(Size { w: 7, h: 3 }),
+                   Size { w: 7, h: 3 });
+    }
+
+    #[test]
+    fn context_info_defaults() {
+        let output = WlcOutput::dummy(1);
+        let mesa = ContextInfo {
+            vendor: "Mesa".to_string(),
+            .. ContextInfo::default()
+        };
+        dummy::set_context_info(None, mesa.clone());
+        assert_eq!(output.get_context_info(), mesa);
+        dummy::set_context_info(Some(output), ContextInfo::default());
+        assert_eq!(output.get_context_info(), ContextInfo::default());
+        assert_eq!(WlcOutput::dummy(2).get_context_info(), mesa);
+        dummy::reset();
+        assert_eq!(WlcOutput::dummy(2).get_context_info(),
+                   ContextInfo::default());
+    }
+
+    #[test]
+    fn views_and_outputs() {
+        let left = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let right = dummy::add_output("WLC-2", Size { w: 800, h: 600 });
+        let view = dummy::add_view(left);
+        let other = dummy::add_view(left);
+        assert_eq!(WlcOutput::list(), vec![left, right]);
+        assert_eq!(left.get_name(), "WLC-1");
+        assert_eq!(left.get_views(), vec![view, other]);
+
+        view.set_output(right);
+        assert_eq!(view.get_output(), right);
+        assert_eq!(left.get_views(), vec![other]);
+        assert_eq!(right.get_views(), vec![view]);
+
+        view.set_mask(2);
+        assert!(!dummy::is_visible(view));
+        right.set_mask(3);
+        assert_eq!(dummy::visible_views(right), vec![view]);
+
+        dummy::remove_output(right);
+        assert!(dummy::view_info(view).is_none());
+        assert_eq!(view.get_mask(), 0);
+        dummy::remove_view(other);
+        assert!(left.get_views().is_empty());
+    }
+
+    #[test]
+    fn layered_stacking() {
+        use super::Layer;
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let desktop = dummy::add_view(output);
+        let first = dummy::add_view(output);
+        let panel = dummy::add_view(output);
+        let second = dummy::add_view(output);
+        dummy::set_layer(desktop, Layer::Below);
+        dummy::set_layer(panel, Layer::Above);
+        assert_eq!(output.get_views(), vec![desktop, first, second, panel]);
+
+        first.bring_to_front();
+        assert_eq!(output.get_views(), vec![desktop, second, first, panel]);
+        first.send_to_back();
+        assert_eq!(output.get_views(), vec![desktop, first, second, panel]);
+        first.bring_above(panel);
+        assert_eq!(output.get_views(), vec![desktop, second, first, panel]);
+        first.send_below(desktop);
+        assert_eq!(output.get_views(), vec![desktop, first, second, panel]);
+        second.send_below(first);
+        assert_eq!(output.get_views(), vec![desktop, second, first, panel]);
+        desktop.bring_to_front();
+        assert_eq!(output.get_views(), vec![desktop, second, first, panel]);
+
+        let mut views = vec![panel, first, second, desktop];
+        output.set_views(&mut views).unwrap();
+        assert_eq!(output.get_views(), vec![desktop, first, second, panel]);
+    }
+
+    #[test]
+    fn stable_stack_order() {
+        use super::Layer;
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let views: Vec<_> = (0..5).map(|_| dummy::add_view(output)).collect();
+        let geometry = Geometry { origin: Point { x: 1, y: 1 }, size: Size { w: 1, h: 1 } };
+        views[1].set_geometry(EDGE_NONE, geometry);
+        views[2].set_mask(0);
+        views[3].focus();
+        views[3].set_state(VIEW_ACTIVATED, true);
+        dummy::set_minimized(views[4], true);
+        dummy::set_mapped(views[0], false);
+        assert_eq!(output.get_views(), views);
+        assert_eq!(views[3].get_z_index(), Some(3));
+        // Moving one view keeps the others in order
+        views[1].bring_to_front();
+        dummy::set_layer(views[3], Layer::Below);
+        dummy::remove_view(views[2]);
+        assert_eq!(output.get_views(), vec![views[3], views[0], views[4], views[1]]);
+        assert_eq!(views[1].get_z_index(), Some(3));
+        assert_eq!(views[2].get_z_index(), None);
+    }
+
+    #[test]
+    fn many_views() {
+        use super::Layer;
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let views: Vec<_> = (0..10_000).map(|_| dummy::add_view(output)).collect();
+        for view in views.iter().step_by(10) {
+            dummy::set_layer(*view, Layer::Above);
+        }
+        views[1].bring_to_front();
+        views[2].send_to_back();
+        let stack = output.get_views();
+        assert_eq!(stack.len(), 10_000);
+        assert_eq!(stack[0], views[2]);
+        assert_eq!(stack[8_999], views[1]);
+        assert_eq!(stack[9_000], views[0]);
+        for view in &views[..5_000] {
+            dummy::remove_view(*view);
+        }
+        assert_eq!(output.get_views().len(), 5_000);
+        assert_eq!(output.get_views()[4_499], views[9_999]);
+    }
+
+    #[test]
+    fn find_views() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let first = dummy::add_view(output);
+        let second = dummy::add_view(output);
+        let third = dummy::add_view(output);
+        dummy::set_property(first, "role", "terminal");
+        dummy::set_property(third, "role", "terminal");
+        dummy::set_property(third, "role", "editor");
+        let role = |role: &'static str| dummy::find_views(move |info| {
+            info.properties.get("role").map(String::as_str) == Some(role)
+        });
+        assert_eq!(role("terminal"), vec![first]);
+        assert_eq!(role("editor"), vec![third]);
+        second.bring_to_front();
+        dummy::remove_view(first);
+        assert_eq!(dummy::find_views(|_| true), vec![second, third]);
+        // The predicate can use the dummy state
+        assert_eq!(dummy::find_views(|info| info.mask == output.get_mask()).len(), 2);
+    }
+
+    #[test]
+    fn index_reuse() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let reused = Rc::new(RefCell::new(Vec::new()));
+        let log = reused.clone();
+        dummy::on_index_reuse(move |index, view| log.borrow_mut().push((index, view)));
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let views: Vec<WlcView> = (0..4).map(|_| dummy::add_view(output)).collect();
+        assert!(reused.borrow().is_empty());
+        dummy::remove_view(views[2]);
+        dummy::remove_view(views[0]);
+        // The smallest free index goes first
+        let first = dummy::add_view(output);
+        let second = dummy::add_view(output);
+        let third = dummy::add_view(output);
+        assert_eq!(*reused.borrow(), vec![(0, first), (2, second)]);
+        assert_eq!(dummy::index_of(third), Some(4));
+        assert_eq!(dummy::index_of(views[3]), Some(3));
+        dummy::remove_output(output);
+        assert_eq!(dummy::index_of(views[3]), None);
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        assert_eq!(dummy::index_of(dummy::add_view(output)), Some(0));
+    }
+
+    #[test]
+    fn user_data_allocations() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let view = dummy::add_view(output);
+        let data = Box::into_raw(Box::new(42u32));
+        unsafe {
+            view.set_user_data(&*data);
+            output.set_user_data(&*data);
+            *view.get_user_data::<u32>() += 1;
+            assert_eq!(*output.get_user_data::<u32>(), 43);
+        }
+        let allocations = dummy::allocations();
+        assert_eq!(allocations.user_data, dummy::Counts { created: 2, destroyed: 0 });
+        assert_eq!(allocations.views.leaked(), 1);
+        dummy::remove_output(output);
+        drop(unsafe { Box::from_raw(data) });
+        let allocations = dummy::allocations();
+        assert_eq!(allocations.user_data.leaked(), 0);
+        assert_eq!(allocations.views, dummy::Counts { created: 1, destroyed: 1 });
+        dummy::assert_no_leaks();
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        dummy::reset_allocations();
+        dummy::remove_output(output);
+        dummy::assert_no_leaks();
+    }
+
+    #[test]
+    fn restack_across_outputs() {
+        let left = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let right = dummy::add_output("WLC-2", Size { w: 800, h: 600 });
+        let below = dummy::add_view(left);
+        let above = dummy::add_view(left);
+        let other = dummy::add_view(right);
+        above.send_below(other);
+        below.bring_above(other);
+        assert_eq!(left.get_views(), vec![below, above]);
+        assert_eq!(dummy::violations().len(), 2);
+        assert!(dummy::violations()[0].contains("was stacked below"));
+        dummy::set_strict(true);
+        above.send_below(below);
+        assert_eq!(left.get_views(), vec![above, below]);
+        let result = std::panic::catch_unwind(|| other.bring_above(above));
+        assert!(result.is_err());
+        assert_eq!(right.get_views(), vec![other]);
+    }
+
+    #[test]
+    fn input_region() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let below = dummy::add_view(output);
+        let shaped = dummy::add_view(output);
+        below.set_geometry(EDGE_NONE, Geometry {
+            origin: Point { x: 0, y: 0 },
+            size: Size { w: 800, h: 600 }
+        });
+        shaped.set_geometry(EDGE_NONE, Geometry {
+            origin: Point { x: 100, y: 100 },
+            size: Size { w: 200, h: 200 }
+        });
+        // A round clock face, roughly, and a region past the view's edge
+        dummy::set_input_region(shaped, Some(vec![
+            Geometry { origin: Point { x: 50, y: 0 }, size: Size { w: 100, h: 200 } },
+            Geometry { origin: Point { x: 0, y: 50 }, size: Size { w: 400, h: 100 } }
+        ]));
+        assert_eq!(dummy::view_at(output, Point { x: 105, y: 105 }), below);
+        assert_eq!(dummy::view_at(output, Point { x: 160, y: 105 }), shaped);
+        assert_eq!(dummy::view_at(output, Point { x: 105, y: 160 }), shaped);
+        assert_eq!(dummy::view_at(output, Point { x: 320, y: 160 }), below);
+        // Pointer focus follows the region
+        callback::rust::pointer_motion(|_, _, point| {
+            input::pointer::set_position(*point);
+            false
+        });
+        simulate::pointer_motion(Point { x: 105, y: 105 });
+        assert_eq!(dummy::pointer_focus(), below);
+        dummy::set_input_region(shaped, None);
+        assert_eq!(dummy::view_at(output, Point { x: 105, y: 105 }), shaped);
+    }
+
+    #[test]
+    fn minimized_views() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let below = dummy::add_view(output);
+        let above = dummy::add_view(output);
+        let geometry = Geometry { origin: Point { x: 0, y: 0 }, size: Size { w: 100, h: 100 } };
+        below.set_geometry(EDGE_NONE, geometry);
+        above.set_geometry(EDGE_NONE, geometry);
+        let rendered = Rc::new(RefCell::new(Vec::new()));
+        let log = rendered.clone();
+        callback::rust::view_render_pre(move |view| log.borrow_mut().push(view));
+
+        dummy::set_minimized(above, true);
+        assert!(dummy::view_info(above).unwrap().minimized);
+        assert_eq!(dummy::view_at(output, Point { x: 50, y: 50 }), below);
+        simulate::render_frame(output);
+        assert_eq!(*rendered.borrow(), vec![below]);
+        // Restored where it was
+        dummy::set_minimized(above, false);
+        assert_eq!(dummy::view_at(output, Point { x: 50, y: 50 }), above);
+        assert_eq!(above.get_mask(), output.get_mask());
+        simulate::render_frame(output);
+        assert_eq!(*rendered.borrow(), vec![below, below, above]);
+    }
+
+    #[test]
+    fn lifecycle() {
+        use super::super::dummy::Lifecycle;
+        terminate();
+        assert_eq!(dummy::violations(), vec!["terminate() was called before wlc ran".to_string()]);
+        assert_eq!(dummy::lifecycle(), Lifecycle::Terminated);
+        assert!(init().is_none());
+        dummy::reset();
+        let run = init().unwrap();
+        callback::rust::view_created(|_| true);
+        run();
+        assert!(dummy::violations().is_empty());
+        dummy::set_strict(true);
+        let registered = std::panic::catch_unwind(|| callback::rust::view_created(|_| false));
+        let message = registered.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.starts_with("dummy-rustwlc strict mode: callback::view_created was \
+                                     called while wlc was running"), "{}", message);
+    }
+
+    #[test]
+    fn sessions() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+        let terminated = Rc::new(Cell::new(0));
+        for session in 0..3 {
+            let count = terminated.clone();
+            callback::rust::compositor_terminate(move || count.set(count.get() + 1));
+            let run = init().unwrap();
+            dummy::set_strict(true);
+            dummy::set_seed(7);
+            // Only run the first two sessions
+            if session < 2 {
+                run();
+            }
+            let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+            assert_eq!(dummy::add_view(output), WlcView::dummy(2));
+            dummy::new_session();
+            assert!(dummy::find_views(|_| true).is_empty());
+            assert!(dummy::state::with(|state| state.strict));
+            assert_eq!(dummy::seed(), 7);
+        }
+        assert_eq!(terminated.get(), 2);
+    }
+
+    #[test]
+    fn render_mutations() {
+        use super::super::dummy::RenderMutations;
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let below = dummy::add_view(output);
+        dummy::add_view(output);
+        callback::rust::output_render_post(move |_| below.bring_to_front());
+        dummy::set_render_mutations(RenderMutations::Allow);
+        simulate::render_frame(output);
+        assert!(dummy::violations().is_empty());
+        // Outside a frame, restacking is fine
+        below.send_to_back();
+        dummy::set_render_mutations(RenderMutations::Panic);
+        let rendered = std::panic::catch_unwind(|| simulate::render_frame(output));
+        let message = rendered.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.starts_with(&format!("dummy-rustwlc render mutation: {:?} was restacked \
+                                              during output_render_post of {:?}\n",
+                                             below, output)), "{}", message);
+    }
+
+    #[test]
+    fn views_by_type() {
+        use super::super::dummy::{Layer, ViewFilter};
+        let left = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let right = dummy::add_output("WLC-2", Size { w: 800, h: 600 });
+        let views: Vec<WlcView> = (0..8).map(|_| dummy::add_view(left)).collect();
+        for (index, view) in views.iter().enumerate() {
+            view.set_type(ViewType::from_bits_truncate(index as u32 % 4 * 8), true);
+        }
+        let check = |output: WlcOutput| for &view_type in &[VIEW_BIT_MODAL, VIEW_BIT_POPUP,
+                                                            VIEW_BIT_MODAL | VIEW_BIT_POPUP] {
+            let filter = ViewFilter { view_type, .. ViewFilter::default() };
+            let expected: Vec<WlcView> = output.get_views().into_iter()
+                .filter(|view| view.get_type().contains(view_type)).collect();
+            assert_eq!(output.get_views_by_type(filter), expected, "{:?}", view_type);
+        };
+        check(left);
+        views[3].send_to_back();
+        views[1].bring_above(views[6]);
+        views[5].send_below(views[2]);
+        check(left);
+        views[2].set_type(VIEW_BIT_POPUP, false);
+        views[4].set_type(VIEW_BIT_MODAL, true);
+        dummy::set_layer(views[7], Layer::Above);
+        views[0].bring_to_front();
+        check(left);
+        views[6].set_output(right);
+        views[1].set_output(right);
+        let mut stack = left.get_views();
+        stack.reverse();
+        left.set_views(&mut stack).unwrap();
+        check(left);
+        check(right);
+        dummy::remove_view(views[3]);
+        check(left);
+    }
+}
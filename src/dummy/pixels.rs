@@ -0,0 +1,217 @@
+//! Pixel formats of output framebuffers, and renderer allocations which
+//! can fail.
+
+use super::super::handle::WlcOutput;
+use super::super::raster;
+use super::super::render::wlc_pixel_format;
+use super::super::types::{Geometry, Point, Size};
+use super::{state, violation};
+
+/// Reads pixels of the output being rendered, see `render::read_pixels`.
+pub(crate) fn read_pixels(format: wlc_pixel_format, geometry: Geometry) -> (Geometry, Vec<u8>) {
+    let output = match state::with(|state| state.rendering) {
+        Some(output) => output,
+        None => {
+            violation("read pixels outside of a render callback".to_string());
+            return (Geometry::default(), Vec::new())
+        }
+    };
+    if let Err(message) = check_pixel_format("read", format, output) {
+        violation(message);
+        return (Geometry::default(), Vec::new())
+    }
+    if !allocate(output, RenderAllocation::Pixels) {
+        return (Geometry::default(), Vec::new())
+    }
+    let size = output.get_resolution().unwrap_or_default();
+    let image = raster::rasterize(output, size);
+    let x0 = geometry.origin.x.max(0).min(size.w as i32);
+    let y0 = geometry.origin.y.max(0).min(size.h as i32);
+    let x1 = (geometry.origin.x as i64 + geometry.size.w as i64).max(x0 as i64)
+        .min(size.w as i64) as i32;
+    let y1 = (geometry.origin.y as i64 + geometry.size.h as i64).max(y0 as i64)
+        .min(size.h as i64) as i32;
+    let mut pixels = Vec::with_capacity((x1 - x0) as usize * (y1 - y0) as usize *
+                                        format.bytes_per_pixel());
+    for y in y0..y1 {
+        for x in x0..x1 {
+            pixels.extend(format.encode(image.color(Point { x, y }).unwrap_or_default()));
+        }
+    }
+    let read = Geometry {
+        origin: Point { x: x0, y: y0 },
+        size: Size { w: (x1 - x0) as u32, h: (y1 - y0) as u32 }
+    };
+    state::with(|state| if let Some(ref mut reads) = state.screenshot_reads {
+        reads.push((read, pixels.clone()));
+    });
+    (read, pixels)
+}
+
+/// Checks a write of pixels, see `render::write_pixels`.
+pub(crate) fn write_pixels(format: wlc_pixel_format, geometry: Geometry, data: &[u8]) {
+    let output = match state::with(|state| state.rendering) {
+        Some(output) => output,
+        None => return violation("wrote pixels outside of a render callback".to_string())
+    };
+    let size = geometry.size.w as u64 * geometry.size.h as u64 * format.bytes_per_pixel() as u64;
+    if let Err(message) = check_pixel_format("wrote", format, output) {
+        violation(message);
+    } else if data.len() as u64 != size {
+        violation(format!("wrote {} bytes of {:?} pixels to {}", data.len(), format,
+                          geometry.size));
+    } else {
+        allocate(output, RenderAllocation::Pixels);
+    }
+}
+
+/// Checks that pixels are read or written in an output's format.
+fn check_pixel_format(verb: &str, format: wlc_pixel_format, output: WlcOutput)
+                      -> Result<(), String> {
+    match pixel_format(output) {
+        expected if expected == format => Ok(()),
+        expected => Err(format!("{} pixels as {:?} on {:?}, whose framebuffer is {:?}",
+                                verb, format, output, expected))
+    }
+}
+
+/// Sets the pixel format of an output's framebuffer, which is
+/// `WLC_RGBA8888` by default.
+///
+/// `render::read_pixels` and `render::write_pixels` must use the format of
+/// the output being rendered.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, simulate, Geometry, Point, Size, WlcOutput};
+/// use rustwlc::render::{self, wlc_pixel_format};
+///
+/// fn output_render_post(output: WlcOutput) {
+///     let area = Geometry { origin: Point { x: 0, y: 0 }, size: Size { w: 2, h: 1 } };
+///     let (_, pixels) = render::read_pixels(dummy::pixel_format(output), area);
+///     assert_eq!(pixels.len(), 4);
+///     render::write_pixels(wlc_pixel_format::WLC_RGB565, area, &pixels);
+/// }
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// dummy::set_pixel_format(output, wlc_pixel_format::WLC_RGB565);
+/// callback::rust::output_render_post(output_render_post);
+/// simulate::render_frame(output);
+/// assert!(dummy::violations().is_empty());
+/// ```
+pub fn set_pixel_format(output: WlcOutput, format: wlc_pixel_format) {
+    state::with(|state| state.pixel_formats.insert(output, format));
+}
+
+/// Gets the pixel format of an output's framebuffer, see `set_pixel_format`.
+pub fn pixel_format(output: WlcOutput) -> wlc_pixel_format {
+    state::with(|state| state.pixel_formats.get(&output).cloned()
+                .unwrap_or(wlc_pixel_format::WLC_RGBA8888))
+}
+
+/// Memory the renderer allocates for an output, which can fail, see
+/// `fail_allocations`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RenderAllocation {
+    /// The framebuffer of a frame. Without it, `simulate::render_frame`
+    /// renders nothing and drops the frame.
+    Framebuffer,
+    /// A buffer for `render::read_pixels` or `render::write_pixels`.
+    /// Without it, nothing is read or written.
+    Pixels
+}
+
+/// Makes the next `count` allocations of a kind for an output fail, as
+/// when the GPU is out of memory, or stops failing them if `count` is 0.
+///
+/// Failed allocations aren't violations: wlc copes with them, and so
+/// should the compositor, i.e. by drawing a frame without borders. They
+/// are counted in `failed_allocations`.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, simulate, Geometry, Point, Size, WlcOutput};
+/// use rustwlc::dummy::RenderAllocation;
+/// use rustwlc::render::{self, wlc_pixel_format};
+///
+/// fn output_render_post(_: WlcOutput) {
+///     let area = Geometry { origin: Point { x: 0, y: 0 }, size: Size { w: 1, h: 1 } };
+///     let (read, pixels) = render::read_pixels(wlc_pixel_format::WLC_RGBA8888, area);
+///     // Out of memory: skip the border rather than crash
+///     if pixels.is_empty() {
+///         return
+///     }
+///     assert_eq!(read, area);
+/// }
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// callback::rust::output_render_post(output_render_post);
+/// dummy::fail_allocations(output, RenderAllocation::Framebuffer, 1);
+/// dummy::fail_allocations(output, RenderAllocation::Pixels, 1);
+/// assert!(simulate::render_frame(output));
+/// simulate::render_frame(output);
+/// simulate::render_frame(output);
+/// assert_eq!(dummy::failed_allocations(output, RenderAllocation::Framebuffer), 1);
+/// assert_eq!(dummy::failed_allocations(output, RenderAllocation::Pixels), 1);
+/// assert_eq!(dummy::frame_stats(output).frames, 2);
+/// assert!(dummy::violations().is_empty());
+/// ```
+pub fn fail_allocations(output: WlcOutput, allocation: RenderAllocation, count: u32) {
+    state::with(|state| state.allocation_failures.entry((output, allocation)).or_default().0 =
+                count);
+}
+
+/// Gets the number of allocations of a kind which failed for an output,
+/// see `fail_allocations`.
+pub fn failed_allocations(output: WlcOutput, allocation: RenderAllocation) -> u32 {
+    state::with(|state| state.allocation_failures.get(&(output, allocation))
+                .map_or(0, |&(_, failed)| failed))
+}
+
+/// Allocates memory for an output, returning whether that succeeded.
+pub(crate) fn allocate(output: WlcOutput, allocation: RenderAllocation) -> bool {
+    state::with(|state| match state.allocation_failures.get_mut(&(output, allocation)) {
+        Some(&mut (ref mut remaining, ref mut failed)) if *remaining > 0 => {
+            *remaining -= 1;
+            *failed += 1;
+            false
+        },
+        _ => true
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::*;
+
+    #[test]
+    fn pixel_formats() {
+        use super::super::super::render::{self, wlc_pixel_format};
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let other = dummy::add_output("WLC-2", Size { w: 800, h: 600 });
+        dummy::set_pixel_format(output, wlc_pixel_format::WLC_RGB565);
+        assert_eq!(dummy::pixel_format(other), wlc_pixel_format::WLC_RGBA8888);
+        callback::rust::output_render_post(|output| {
+            let area = Geometry { origin: Point { x: 0, y: 0 }, size: Size { w: 2, h: 2 } };
+            let (read, pixels) = render::read_pixels(wlc_pixel_format::WLC_RGBA8888, area);
+            if output == WlcOutput::dummy(1) {
+                assert_eq!((read, pixels), (Geometry::default(), Vec::new()));
+                let (_, pixels) = render::read_pixels(wlc_pixel_format::WLC_RGB565, area);
+                assert_eq!(pixels.len(), 8);
+                render::write_pixels(wlc_pixel_format::WLC_RGBA8888, area, &[0; 16]);
+                render::write_pixels(wlc_pixel_format::WLC_RGB565, area, &[0; 16]);
+            } else {
+                assert_eq!(pixels.len(), 16);
+            }
+        });
+        simulate::render_frame(output);
+        simulate::render_frame(other);
+        assert_eq!(dummy::violations(), vec![
+            "read pixels as WLC_RGBA8888 on WlcOutput(1), whose framebuffer is WLC_RGB565"
+                .to_string(),
+            "wrote pixels as WLC_RGBA8888 on WlcOutput(1), whose framebuffer is WLC_RGB565"
+                .to_string(),
+            "wrote 16 bytes of WLC_RGB565 pixels to 2 x 2".to_string()
+        ]);
+    }
+}
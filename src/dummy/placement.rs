@@ -0,0 +1,91 @@
+//! Placement of views the compositor doesn't place itself.
+
+use super::super::handle::WlcView;
+use super::super::types::Point;
+use super::state;
+
+/// Where views are placed when the compositor doesn't place them.
+///
+/// wlc leaves a view where its client put it, usually the top left of the
+/// output, until the compositor sets its geometry. Other clients and
+/// toolkits center or cascade their windows, or open them at the pointer.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    /// Views stay where their clients put them. This is the default.
+    #[default]
+    Client,
+    /// Views are centered on their output
+    Centered,
+    /// Each view is placed below and to the right of the last one placed,
+    /// starting over at the top left when it wouldn't fit
+    Cascade,
+    /// Views are centered on the pointer, kept inside their output
+    AtPointer
+}
+
+/// Distance between cascaded views, on both axes.
+pub const CASCADE_STEP: i32 = 32;
+
+/// Sets where new views are placed when the compositor doesn't place them.
+///
+/// When `simulate::create_view` or `simulate::map_view` invoke
+/// `view_created`, and the callback accepts the view without setting its
+/// geometry, the view is moved according to the placement. Its size is
+/// kept. This lets tests cover both compositors which place views
+/// themselves and those which leave it to clients.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, simulate, Geometry, Point, Size, EDGE_NONE};
+/// use rustwlc::dummy::Placement;
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let view = dummy::add_view(output);
+/// view.set_geometry(EDGE_NONE, Geometry { origin: Point { x: 0, y: 0 }, size: Size { w: 200, h: 100 } });
+/// simulate::unmap_view(view);
+/// dummy::set_placement(Placement::Centered);
+/// simulate::map_view(view);
+/// assert_eq!(view.get_visible_geometry().origin, Point { x: 300, y: 250 });
+/// ```
+pub fn set_placement(placement: Placement) {
+    state::with(|state| state.placement = placement);
+}
+
+/// Moves a view according to the placement.
+pub(crate) fn place(view: WlcView) {
+    state::with(|state| {
+        let (output, size) = match state.views.get(&view) {
+            Some(info) => (info.output, info.geometry.size),
+            None => return
+        };
+        let resolution = match state.output(output) {
+            Some(info) => info.resolution,
+            None => return
+        };
+        // Furthest the view can go while staying inside the output
+        let max = Point {
+            x: (resolution.w as i32 - size.w as i32).max(0),
+            y: (resolution.h as i32 - size.h as i32).max(0)
+        };
+        let origin = match state.placement {
+            Placement::Client => return,
+            Placement::Centered => Point { x: max.x / 2, y: max.y / 2 },
+            Placement::Cascade => {
+                let mut offset = state.cascade_offset;
+                if offset > max.x || offset > max.y {
+                    offset = 0;
+                }
+                state.cascade_offset = offset + CASCADE_STEP;
+                Point { x: offset, y: offset }
+            },
+            Placement::AtPointer => Point {
+                x: (state.pointer.x - (size.w / 2) as i32).max(0).min(max.x),
+                y: (state.pointer.y - (size.h / 2) as i32).max(0).min(max.y)
+            }
+        };
+        if let Some(info) = state.views.get_mut(&view) {
+            info.geometry.origin = origin;
+        }
+        state.schedule_render(output);
+    });
+}
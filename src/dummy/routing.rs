@@ -0,0 +1,568 @@
+//! How input reaches the compositor: key order, host grabs, modifiers,
+//! scrolling, focus following, modal dialogs, interactive operations, and
+//! pointer motion rates.
+
+use super::super::handle::WlcView;
+use super::super::input::keys::{self, Key};
+use super::super::keybindings::Chord;
+use super::super::types::{BackendType, KeyboardLed, KeyboardModifiers, KeyMod, KeyState, Point,
+                          ResizeEdge};
+use super::super::types::{CAPS_LOCK, MOD_ALT, MOD_CAPS, MOD_CTRL, MOD_MOD2, MOD_MOD4, MOD_SHIFT,
+                          NUM_LOCK, VIEW_BIT_MODAL};
+use super::state;
+
+/// What happens to keys pressed twice, or released without being pressed.
+///
+/// A real keyboard can't do either, so these come from scenarios or virtual
+/// keyboards which got out of step.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum KeyOrder {
+    /// They are delivered as they are, and reported as misuse (see
+    /// `set_strict`). This is the default.
+    #[default]
+    Report,
+    /// They are corrected without being reported: a key pressed again is
+    /// released first, and a release without a press is dropped.
+    Correct
+}
+
+/// Sets what happens to keys pressed twice, or released without being
+/// pressed, in `simulate::keyboard_key`.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, simulate, KeyState};
+/// use rustwlc::dummy::KeyOrder;
+/// use rustwlc::input::keys;
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+///
+/// let seen = Rc::new(RefCell::new(Vec::new()));
+/// let log = seen.clone();
+/// callback::rust::keyboard_key(move |_, _, _, _, state| { log.borrow_mut().push(state); false });
+/// simulate::keyboard_key(keys::KEY_A, KeyState::Released);
+/// assert_eq!(dummy::violations(), vec!["Key(30) was released without being pressed"]);
+///
+/// dummy::set_key_order(KeyOrder::Correct);
+/// simulate::keyboard_key(keys::KEY_B, KeyState::Released);
+/// simulate::keyboard_key(keys::KEY_B, KeyState::Pressed);
+/// simulate::keyboard_key(keys::KEY_B, KeyState::Pressed);
+/// assert_eq!(*seen.borrow(), vec![KeyState::Released, KeyState::Pressed,
+///                                 KeyState::Released, KeyState::Pressed]);
+/// assert_eq!(dummy::violations().len(), 1);
+/// ```
+pub fn set_key_order(order: KeyOrder) {
+    state::with(|state| state.key_order = order);
+}
+
+/// Gets what happens to keys out of order, see `set_key_order`.
+pub fn key_order() -> KeyOrder {
+    state::with(|state| state.key_order)
+}
+
+/// Turns sticky keys on or off.
+///
+/// With sticky keys, as in the accessibility setting, a modifier key which
+/// is pressed and released on its own latches its modifier: the modifier
+/// applies to the next key press, then is released. Pressing it again
+/// while it is latched locks it until it is pressed a third time. The
+/// `KeyboardModifiers` given to callbacks by `simulate::keyboard_key`
+/// include latched and locked modifiers (see `modifier_state`), so
+/// keybindings can be tested with sticky keys on. Turning sticky keys off
+/// releases the modifiers it latched or locked.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, simulate};
+/// use rustwlc::{KeyState, MOD_CTRL};
+/// use rustwlc::input::keys;
+///
+/// callback::rust::keyboard_key(|_, _, mods, key, state| {
+///     key == keys::KEY_Q.get_code() && mods.mods == MOD_CTRL
+/// });
+/// dummy::set_sticky_keys(true);
+/// simulate::keyboard_key(keys::KEY_LEFTCTRL, KeyState::Pressed);
+/// simulate::keyboard_key(keys::KEY_LEFTCTRL, KeyState::Released);
+/// assert!(simulate::keyboard_key(keys::KEY_Q, KeyState::Pressed));
+/// simulate::keyboard_key(keys::KEY_Q, KeyState::Released);
+/// assert!(!simulate::keyboard_key(keys::KEY_Q, KeyState::Pressed));
+/// ```
+pub fn set_sticky_keys(enabled: bool) {
+    state::with(|state| {
+        state.sticky_keys = enabled;
+        if !enabled {
+            let sticky = MOD_SHIFT | MOD_CTRL | MOD_ALT | MOD_MOD4;
+            state.modifiers.latched.remove(sticky);
+            state.modifiers.locked.remove(sticky);
+        }
+    });
+}
+
+/// Sets the chords the host X server's window manager grabs, when running
+/// nested with `BackendType::X11`. They are Alt+Tab and Alt+F4 by default.
+///
+/// Keys of a grabbed chord, pressed and released, go to the host instead,
+/// so `simulate::keyboard_key` doesn't invoke the callback for them, and
+/// records the chord in `host_grabbed`. Chords match regardless of caps
+/// lock and num lock, as X window managers grab them. Under DRM, the
+/// chords reach the compositor as usual.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, simulate, BackendType, KeyState, MOD_ALT};
+/// use rustwlc::input::keys;
+/// use rustwlc::keybindings::Chord;
+///
+/// // Switches views with Alt+Tab, or Mod4+Tab as a fallback
+/// callback::rust::keyboard_key(|_, _, _, key, _| key == keys::KEY_TAB.get_code());
+/// dummy::set_backend(BackendType::X11);
+/// simulate::keyboard_key(keys::KEY_LEFTALT, KeyState::Pressed);
+/// assert!(!simulate::keyboard_key(keys::KEY_TAB, KeyState::Pressed));
+/// assert!(!simulate::keyboard_key(keys::KEY_TAB, KeyState::Released));
+/// assert_eq!(dummy::host_grabbed(), vec![Chord { mods: MOD_ALT, key: keys::KEY_TAB }]);
+/// simulate::keyboard_key(keys::KEY_LEFTALT, KeyState::Released);
+/// simulate::keyboard_key(keys::KEY_LEFTMETA, KeyState::Pressed);
+/// assert!(simulate::keyboard_key(keys::KEY_TAB, KeyState::Pressed));
+/// ```
+pub fn set_host_grabs(chords: Vec<Chord>) {
+    state::with(|state| state.host_grabs = Some(chords));
+}
+
+/// Gets the chords the host X server grabs, see `set_host_grabs`.
+pub fn host_grabs() -> Vec<Chord> {
+    state::with(|state| state.host_grabs.clone()).unwrap_or_else(|| vec![
+        Chord { mods: MOD_ALT, key: keys::KEY_TAB },
+        Chord { mods: MOD_ALT, key: keys::KEY_F4 }
+    ])
+}
+
+/// Gets the chords the host X server took so far, oldest first.
+pub fn host_grabbed() -> Vec<Chord> {
+    state::with(|state| state.host_grabbed.to_vec())
+}
+
+/// Forgets the chords the host X server took so far.
+pub fn clear_host_grabbed() {
+    state::with(|state| state.host_grabbed.clear());
+}
+
+/// Whether the host X server takes a key, given the modifiers held as it
+/// is pressed, see `set_host_grabs`.
+pub(crate) fn grabbed_by_host(key: Key, key_state: KeyState, mods: KeyMod) -> bool {
+    if state::with(|state| state.backend) != BackendType::X11 {
+        return false
+    }
+    let chord = Chord { mods: mods - (MOD_CAPS | MOD_MOD2), key };
+    let grabbed = key_state == KeyState::Pressed && host_grabs().contains(&chord);
+    state::with(|state| {
+        if grabbed {
+            state.grabbed_keys.push(key);
+            state.host_grabbed.push(chord);
+            return true
+        }
+        match state.grabbed_keys.iter().position(|&other| other == key) {
+            // The release goes where the press went
+            Some(index) if key_state == KeyState::Released => {
+                state.grabbed_keys.remove(index);
+                true
+            },
+            _ => false
+        }
+    })
+}
+
+/// Modifier state of the simulated keyboard, kept in parts as xkb does.
+///
+/// `simulate::keyboard_key` keeps it up to date: modifier keys are
+/// depressed while held, caps lock and num lock toggle `MOD_CAPS` and
+/// `MOD_MOD2` in `locked`, and sticky keys latch and lock modifiers. Every
+/// input callback is given the same `keyboard_modifiers()`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ModifierState {
+    /// Modifiers of the keys held down
+    pub depressed: KeyMod,
+    /// Modifiers which apply to the next key press
+    pub latched: KeyMod,
+    /// Modifiers which apply until they are unlocked
+    pub locked: KeyMod,
+    /// Active layout group. wlc doesn't give it to callbacks.
+    pub group: u32
+}
+
+impl ModifierState {
+    /// The modifiers in effect: depressed, latched, or locked.
+    pub fn effective(&self) -> KeyMod {
+        self.depressed | self.latched | self.locked
+    }
+
+    /// The LEDs lit by locked modifiers: caps lock for `MOD_CAPS`, and num
+    /// lock for `MOD_MOD2`.
+    pub fn leds(&self) -> KeyboardLed {
+        let mut leds = KeyboardLed::empty();
+        if self.locked.contains(MOD_CAPS) {
+            leds.insert(CAPS_LOCK);
+        }
+        if self.locked.contains(MOD_MOD2) {
+            leds.insert(NUM_LOCK);
+        }
+        leds
+    }
+
+    /// The modifiers as given to callbacks.
+    pub fn keyboard_modifiers(&self) -> KeyboardModifiers {
+        KeyboardModifiers { leds: self.leds(), mods: self.effective() }
+    }
+}
+
+/// Gets the modifier state of the simulated keyboard.
+pub fn modifier_state() -> ModifierState {
+    state::with(|state| state.modifiers)
+}
+
+/// Sets the modifier state of the simulated keyboard, as if another seat
+/// or client had changed it.
+///
+/// Modifiers set `depressed` stay so until a key with that modifier is
+/// released.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, simulate, KeyState, MOD_CAPS, CAPS_LOCK};
+/// use rustwlc::dummy::ModifierState;
+/// use rustwlc::input::keys;
+///
+/// dummy::set_modifier_state(ModifierState { locked: MOD_CAPS, group: 1, .. Default::default() });
+/// assert_eq!(dummy::modifier_state().keyboard_modifiers().leds, CAPS_LOCK);
+/// simulate::keyboard_key(keys::KEY_CAPSLOCK, KeyState::Pressed);
+/// assert!(dummy::modifier_state().locked.is_empty());
+/// assert_eq!(dummy::modifier_state().group, 1);
+/// ```
+pub fn set_modifier_state(modifiers: ModifierState) {
+    state::with(|state| state.modifiers = modifiers);
+}
+
+/// Scroll amount of a wheel click unless changed with `set_scroll_step`.
+pub const DEFAULT_SCROLL_STEP: f64 = 10.0;
+
+/// Sets the scroll amount of a wheel click in `simulate::scroll_wheel`.
+///
+/// wlc reports `10.0` per click by default, but the step depends on the
+/// mouse and its configuration.
+pub fn set_scroll_step(step: f64) {
+    state::with(|state| state.scroll_step = Some(step));
+}
+
+pub(crate) fn scroll_step() -> f64 {
+    state::with(|state| state.scroll_step).unwrap_or(DEFAULT_SCROLL_STEP)
+}
+
+/// Sets whether the vertical and horizontal scroll axes are inverted, as
+/// by libinput's natural scrolling.
+///
+/// Scrolls from `simulate::pointer_scroll` and `simulate::scroll_wheel`
+/// are inverted on these axes before callbacks see them, including the
+/// clicks of `ScrollSource::Wheel`. A compositor which also applies its own
+/// natural scrolling setting would then invert them back.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, simulate};
+/// use rustwlc::{WlcView, KeyboardModifiers, ScrollAxis, ScrollSource};
+///
+/// fn pointer_scroll(view: WlcView, time: u32, mods: &KeyboardModifiers,
+///                   axis: ScrollAxis, amount: [f64; 2]) -> bool {
+///     amount[0] < 0.0
+/// }
+///
+/// callback::rust::pointer_scroll(pointer_scroll);
+/// assert!(!simulate::pointer_scroll(ScrollSource::Finger, [1.0, 0.0]));
+/// dummy::set_scroll_inverted([true, false]);
+/// assert!(simulate::pointer_scroll(ScrollSource::Finger, [1.0, 0.0]));
+/// ```
+pub fn set_scroll_inverted(inverted: [bool; 2]) {
+    state::with(|state| state.scroll_inverted = inverted);
+}
+
+/// Sets whether pointer motion focuses the view under the pointer.
+///
+/// When enabled, `simulate::pointer_motion` focuses the view under the
+/// pointer after the `pointer_motion` callback has run, like a compositor
+/// with focus-follows-mouse. wlc itself does not do this. The pointer
+/// position used is the one set by the compositor with
+/// `input::pointer::set_position`.
+pub fn set_focus_follows_pointer(enabled: bool) {
+    state::with(|state| state.focus_follows_pointer = enabled);
+}
+
+/// Sets whether focusing a view warps the pointer to its center.
+///
+/// When enabled, focus changes from `WlcView::focus` move the pointer to
+/// the center of the newly focused view after the `view_focus` callbacks
+/// have run. The warp does not invoke the `pointer_motion` callback, and
+/// isn't one of the compositor's `warps`.
+pub fn set_pointer_follows_focus(enabled: bool) {
+    state::with(|state| state.pointer_follows_focus = enabled);
+}
+
+/// Sets whether modal dialogs take the input of the views they belong to.
+///
+/// wlc leaves modal dialogs to the compositor. When enabled, keys, pointer
+/// motion, buttons and scrolls going to a view with an open modal dialog
+/// (see `modal_dialog`) go to the dialog instead, and focus-follows-pointer
+/// focuses the dialog. A compositor which focuses the view itself is
+/// reported with a violation, though the view is still focused.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, fixtures, simulate, KeyState, Size};
+/// use rustwlc::input::keys;
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 1920, h: 1080 });
+/// let player = fixtures::mpv(output);
+/// let dialog = fixtures::mpv_dialog(player);
+/// dummy::set_modal_routing(true);
+/// let target = Rc::new(Cell::new(player));
+/// let seen = target.clone();
+/// callback::rust::keyboard_key(move |view, _, _, _, _| { seen.set(view); false });
+/// player.focus();
+/// simulate::keyboard_key(keys::KEY_ENTER, KeyState::Pressed);
+/// assert_eq!(target.get(), dialog);
+/// assert_eq!(dummy::violations().len(), 1);
+/// ```
+pub fn set_modal_routing(enabled: bool) {
+    state::with(|state| state.modal_routing = enabled);
+}
+
+/// Gets the open modal dialog which blocks a view, if any.
+///
+/// That is a drawn view with `VIEW_BIT_MODAL` whose parent is the view, the
+/// one with the highest handle if there are several, or the dialog which
+/// blocks that one in turn.
+pub fn modal_dialog(view: WlcView) -> Option<WlcView> {
+    if !view.is_window() {
+        return None
+    }
+    state::with(|state| {
+        let (mut dialog, mut parent) = (None, view);
+        // Bounded, in case parents loop
+        for _ in 0..state.views.len() {
+            let child = state.views.iter()
+                .filter(|&(_, info)| {
+                    info.parent == parent && info.view_type.contains(VIEW_BIT_MODAL) &&
+                        info.is_drawn()
+                })
+                .map(|(child, _)| child).last();
+            match child {
+                Some(child) if Some(child) != dialog && child != view => {
+                    dialog = Some(child);
+                    parent = child;
+                },
+                _ => break
+            }
+        }
+        dialog
+    })
+}
+
+/// Gets the view input meant for a view goes to, see `set_modal_routing`.
+pub(crate) fn route_input(view: WlcView) -> WlcView {
+    if !state::with(|state| state.modal_routing) {
+        return view
+    }
+    modal_dialog(view).unwrap_or(view)
+}
+
+/// An interactive move or resize, started by a client's request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InteractiveOp {
+    /// A move, from `simulate::request_move`
+    Move {
+        /// The view being moved
+        view: WlcView,
+        /// The pointer position when the move started
+        origin: Point
+    },
+    /// A resize, from `simulate::request_resize`
+    Resize {
+        /// The view being resized
+        view: WlcView,
+        /// The edges being dragged
+        edge: ResizeEdge,
+        /// The pointer position when the resize started
+        origin: Point
+    }
+}
+
+impl InteractiveOp {
+    /// Gets the view being moved or resized.
+    pub fn view(&self) -> WlcView {
+        match *self {
+            InteractiveOp::Move { view, .. } | InteractiveOp::Resize { view, .. } => view
+        }
+    }
+}
+
+/// Gets the interactive move or resize in progress, if any.
+///
+/// A client's request to be moved or resized (`simulate::request_move` and
+/// `simulate::request_resize`) starts one, as the user drags its title bar
+/// or border. Until it ends, pointer motion and buttons go to its view,
+/// wherever the pointer is. It ends with the next button release, after
+/// the release is delivered, or when its view is removed. A compositor
+/// tracking its own drag can check it ends along with this one.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, simulate, ButtonState, Point, Size};
+/// use rustwlc::dummy::InteractiveOp;
+/// use rustwlc::input::keys::BTN_LEFT;
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let view = dummy::add_view(output);
+/// simulate::pointer_button(BTN_LEFT, ButtonState::Pressed);
+/// simulate::request_move(view, Point { x: 0, y: 0 });
+/// assert_eq!(dummy::interactive_op(),
+///            Some(InteractiveOp::Move { view, origin: Point { x: 0, y: 0 } }));
+/// simulate::pointer_button(BTN_LEFT, ButtonState::Released);
+/// assert_eq!(dummy::interactive_op(), None);
+/// ```
+pub fn interactive_op() -> Option<InteractiveOp> {
+    state::with(|state| state.interactive)
+}
+
+/// Starts an interactive move or resize, replacing any in progress.
+pub(crate) fn start_interactive_op(op: InteractiveOp) {
+    state::with(|state| if state.views.contains_key(&op.view()) {
+        state.interactive = Some(op);
+    });
+}
+
+/// Ends the interactive move or resize in progress, if any.
+pub(crate) fn end_interactive_op() {
+    state::with(|state| state.interactive = None);
+}
+
+/// Gets the view pointer input over a view goes to: the view of the
+/// interactive move or resize in progress, or as `route_input`.
+pub(crate) fn route_pointer(view: WlcView) -> WlcView {
+    match interactive_op() {
+        Some(op) => op.view(),
+        None => route_input(view)
+    }
+}
+
+/// How `simulate::pointer_motion` delivers motion to the compositor.
+///
+/// Real pointers report motion far more often than compositors render, and
+/// both extremes break compositors: one which only reacts to the last
+/// motion before a frame may miss updates when motions are coalesced, and
+/// one which does work per motion may fall behind in a storm of them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MotionRate {
+    /// Each motion is delivered at once. This is the default.
+    #[default]
+    Immediate,
+    /// Motions are held until the next frame of `simulate::render_frame`,
+    /// or the next key, button or scroll, and then only the last is
+    /// delivered, as compositors which batch input per frame see it.
+    Coalesce,
+    /// Each motion is delivered as this many motions along the way from
+    /// the pointer's position, the last one at the point.
+    Flood(u32)
+}
+
+/// Sets how motion is delivered, see `MotionRate`.
+///
+/// Motion held by `MotionRate::Coalesce` is still delivered at the next
+/// frame, key, button or scroll after switching to another rate.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, simulate, Point, Size};
+/// use rustwlc::dummy::MotionRate;
+/// use rustwlc::input::pointer;
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let motions = Rc::new(Cell::new(0));
+/// let count = motions.clone();
+/// callback::rust::pointer_motion(move |_, _, point| {
+///     count.set(count.get() + 1);
+///     pointer::set_position(*point);
+///     false
+/// });
+/// dummy::set_motion_rate(MotionRate::Coalesce);
+/// for x in 0..10 {
+///     simulate::pointer_motion(Point { x, y: 0 });
+/// }
+/// assert_eq!(dummy::pending_motion(), Some(Point { x: 9, y: 0 }));
+/// simulate::render_frame(output);
+/// assert_eq!(motions.get(), 1);
+///
+/// dummy::set_motion_rate(MotionRate::Flood(100));
+/// simulate::pointer_motion(Point { x: 400, y: 300 });
+/// assert_eq!(motions.get(), 101);
+/// assert_eq!(pointer::get_position(), Point { x: 400, y: 300 });
+/// ```
+pub fn set_motion_rate(rate: MotionRate) {
+    state::with(|state| state.motion_rate = rate);
+}
+
+/// Gets how motion is delivered.
+pub fn motion_rate() -> MotionRate {
+    state::with(|state| state.motion_rate)
+}
+
+/// Gets the motion held by `MotionRate::Coalesce`, if any.
+pub fn pending_motion() -> Option<Point> {
+    state::with(|state| state.pending_motion)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::*;
+
+    #[test]
+    fn modal_dialogs() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        let output = dummy::add_output("WLC-1", Size { w: 1920, h: 1080 });
+        let player = fixtures::mpv(output);
+        let dialog = fixtures::mpv_dialog(player);
+        // A dialog of the dialog
+        let confirm = fixtures::mpv_dialog(dialog);
+        assert_eq!(dummy::modal_dialog(player), Some(confirm));
+        assert_eq!(dummy::modal_dialog(confirm), None);
+        let buttons = Rc::new(RefCell::new(Vec::new()));
+        let log = buttons.clone();
+        callback::rust::pointer_button(move |view, _, _, _, _, _| {
+            log.borrow_mut().push(view);
+            false
+        });
+        // Off by default
+        let corner = Point { x: 10, y: 10 };
+        input::pointer::set_position(corner);
+        simulate::pointer_button(input::keys::BTN_LEFT, ButtonState::Pressed);
+        player.focus();
+        assert!(dummy::violations().is_empty());
+        dummy::set_modal_routing(true);
+        simulate::pointer_button(input::keys::BTN_LEFT, ButtonState::Released);
+        dummy::set_mapped(confirm, false);
+        simulate::pointer_button(input::keys::BTN_LEFT, ButtonState::Pressed);
+        assert_eq!(*buttons.borrow(), vec![player, confirm, dialog]);
+        // Pointer focus stays with the view under the pointer
+        assert_eq!(dummy::pointer_focus(), player);
+        player.focus();
+        assert_eq!(dummy::violations(),
+                   vec![format!("{:?} was focused while its modal dialog {:?} is open",
+                                player, dialog)]);
+        dummy::clear_violations();
+        dialog.focus();
+        dummy::remove_view(dialog);
+        player.focus();
+        assert!(dummy::violations().is_empty());
+    }
+}
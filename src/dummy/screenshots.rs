@@ -0,0 +1,152 @@
+//! Screenshots clients ask the compositor for.
+
+use std::rc::Rc;
+
+use super::super::callback::Callback;
+use super::super::handle::{WlcOutput, WlcView};
+use super::super::types::Geometry;
+use super::state;
+
+/// What a client asked for a screenshot of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenshotTarget {
+    /// A whole output
+    Output(WlcOutput),
+    /// A view, on its output
+    View(WlcView)
+}
+
+/// A screenshot taken by the compositor's handler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Screenshot {
+    /// The serial of the request, from `simulate::request_screenshot`
+    pub serial: u32,
+    /// What was asked for
+    pub target: ScreenshotTarget,
+    /// The areas the handler read with `render::read_pixels`, with their
+    /// pixels, in order
+    pub reads: Vec<(Geometry, Vec<u8>)>
+}
+
+/// Sets the compositor's screenshot handler.
+///
+/// wlc has no screenshot protocol, so compositors take screenshots with
+/// `render::read_pixels` while an output renders. When a client asks for a
+/// screenshot with `simulate::request_screenshot`, the handler is called
+/// with the serial of the request and its target during the next frame of
+/// the target's output, after `output_render_post`, and what it reads is
+/// kept in `screenshots`. Requests whose target is gone by then are
+/// dropped.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, simulate, Geometry, Point, Size};
+/// use rustwlc::dummy::ScreenshotTarget;
+/// use rustwlc::render::{self, wlc_pixel_format};
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 4, h: 2 });
+/// dummy::on_screenshot(|_, target| if let ScreenshotTarget::Output(output) = target {
+///     let size = output.get_resolution().unwrap();
+///     let area = Geometry { origin: Point { x: 0, y: 0 }, size };
+///     render::read_pixels(wlc_pixel_format::WLC_RGBA8888, area);
+/// });
+/// let serial = simulate::request_screenshot(ScreenshotTarget::Output(output));
+/// assert!(dummy::screenshots().is_empty());
+/// simulate::render_frame(output);
+/// let screenshot = &dummy::screenshots()[0];
+/// assert_eq!(screenshot.serial, serial);
+/// assert_eq!(screenshot.reads[0].1.len(), 4 * 2 * 4);
+/// ```
+pub fn on_screenshot<F>(handler: F) where F: Fn(u32, ScreenshotTarget) + 'static {
+    state::with(|state| state.screenshot_handler = Some(Callback(Rc::new(handler))));
+}
+
+/// Gets the screenshot requests waiting for a frame, oldest first.
+pub fn pending_screenshots() -> Vec<(u32, ScreenshotTarget)> {
+    state::with(|state| state.pending_screenshots.clone())
+}
+
+/// Gets the screenshots taken so far, oldest first.
+pub fn screenshots() -> Vec<Screenshot> {
+    state::with(|state| state.screenshots.to_vec())
+}
+
+/// Forgets the screenshots taken so far.
+pub fn clear_screenshots() {
+    state::with(|state| state.screenshots.clear());
+}
+
+/// Takes the screenshots waiting for an output's frame.
+pub(crate) fn take_screenshots(output: WlcOutput) {
+    let (handler, requests) = state::with(|state| {
+        let views = &state.views;
+        let outputs: Vec<WlcOutput> = state.outputs.iter().map(|entry| entry.handle).collect();
+        let target_output = |target: ScreenshotTarget| match target {
+            ScreenshotTarget::Output(output) => {
+                Some(output).filter(|output| outputs.contains(output))
+            },
+            ScreenshotTarget::View(view) => views.get(&view).map(|info| info.output)
+        };
+        let mut requests = Vec::new();
+        let mut pending = Vec::new();
+        for (serial, target) in state.pending_screenshots.drain(..) {
+            match target_output(target) {
+                Some(target_output) if target_output == output => requests.push((serial, target)),
+                Some(_) => pending.push((serial, target)),
+                None => {}
+            }
+        }
+        state.pending_screenshots = pending;
+        (state.screenshot_handler.clone(), requests)
+    });
+    for (serial, target) in requests {
+        state::with(|state| state.screenshot_reads = Some(Vec::new()));
+        if let Some(ref handler) = handler {
+            (handler.0)(serial, target);
+        }
+        state::with(|state| {
+            let reads = state.screenshot_reads.take().unwrap_or_default();
+            state.screenshots.push(Screenshot { serial, target, reads });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::*;
+
+    #[test]
+    fn screenshots() {
+        use super::super::super::dummy::ScreenshotTarget;
+        use super::super::super::render::{self, wlc_pixel_format};
+        let left = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let right = dummy::add_output("WLC-2", Size { w: 800, h: 600 });
+        let view = dummy::add_view(right);
+        view.set_geometry(EDGE_NONE, Geometry {
+            origin: Point { x: 10, y: 10 },
+            size: Size { w: 2, h: 2 }
+        });
+        let gone = dummy::add_view(right);
+        dummy::on_screenshot(|_, target| if let ScreenshotTarget::View(view) = target {
+            render::read_pixels(wlc_pixel_format::WLC_RGBA8888, view.get_geometry().unwrap());
+        });
+        let serial = simulate::request_screenshot(ScreenshotTarget::View(view));
+        simulate::request_screenshot(ScreenshotTarget::View(gone));
+        dummy::remove_view(gone);
+        simulate::render_frame(left);
+        assert_eq!(dummy::pending_screenshots(), vec![(serial, ScreenshotTarget::View(view))]);
+        simulate::render_frame(right);
+        assert!(dummy::pending_screenshots().is_empty());
+        let screenshots = dummy::screenshots();
+        assert_eq!(screenshots.len(), 1);
+        assert_eq!(screenshots[0].serial, serial);
+        let (area, ref pixels) = screenshots[0].reads[0];
+        assert_eq!(area.origin, Point { x: 10, y: 10 });
+        let border = raster::PALETTE[2];
+        assert_eq!(&pixels[..4], &[border[0], border[1], border[2], 0xff]);
+        // Only inside render callbacks
+        assert_eq!(render::read_pixels(wlc_pixel_format::WLC_RGBA8888, area).1, Vec::<u8>::new());
+        assert_eq!(dummy::violations(),
+                   vec!["read pixels outside of a render callback".to_string()]);
+    }
+}
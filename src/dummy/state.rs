@@ -0,0 +1,733 @@
+//! The per-thread store backing dummy handles.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+#[cfg(feature = "off-thread-callbacks")]
+use std::mem;
+use std::ops::Range;
+#[cfg(feature = "off-thread-callbacks")]
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+#[cfg(feature = "off-thread-callbacks")]
+use std::thread;
+use std::time::Duration;
+
+use super::super::callback::{Callback, Callbacks, Handler};
+use super::super::calls::Call;
+use super::super::contract::Warning as ContractWarning;
+use super::super::event::WlcEvent;
+use super::super::fixtures::Template;
+use super::super::handle::{WlcOutput, WlcView};
+use super::super::invariants::Invariant;
+use super::super::keybindings::{Binding, Chord};
+use super::super::raster::{LayoutRenderer, Recording};
+use super::super::selection::{Hook as SelectionHook, Offer, Selection};
+use super::super::simulate::Input;
+use super::super::input::keys::Key;
+use super::super::render::{wlc_pixel_format, ContextInfo};
+use super::super::types::{BackendType, Geometry, Point, Size, ViewType};
+use super::{RECENT_EVENTS, Allocations, Barrier, Checkpoint, ClockSpeed, ClientRequest, FrameBudget, HandlePolicy, FrameStats, HotCorner, InteractiveOp, KeyboardDelivery, KeyOrder, Layer, Lifecycle, ModifierState, MotionRate, PointerDelivery, RootDelivery, OutputInfo, Placement, RenderAllocation, RenderMutations, Screenshot, ScreenshotTarget, Threading, ViewFilter, ViewInfo, Warp, Watchdog, XWayland};
+use super::handle_map::HandleMap;
+use super::log::Log;
+
+/// Where to move a view within its output's stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Position {
+    /// Top of the view's layer
+    Top,
+    /// Bottom of the view's layer
+    Bottom,
+    /// Directly below another view, if the layer allows it
+    Below(WlcView),
+    /// Directly above another view, if the layer allows it
+    Above(WlcView)
+}
+
+/// Geometry sent to a client which it hasn't acked yet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Configure {
+    /// The view being configured
+    pub view: WlcView,
+    /// Serial of the configure
+    pub serial: u32,
+    /// Geometry the view takes once acked
+    pub geometry: Geometry,
+    /// Time at which the client acks
+    pub due: u32
+}
+
+/// A client behavior attached with `clients::attach`.
+#[derive(Debug, Clone)]
+pub struct Behavior {
+    /// The view the client owns
+    pub view: WlcView,
+    /// Events dispatched since the behavior was attached
+    pub events: u32,
+    /// The behavior, given the view and `events`
+    pub run: Callback<RefCell<dyn FnMut(WlcView, u32)>>
+}
+
+/// An output with the bookkeeping for its stack.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Output {
+    /// Handle of the output
+    pub handle: WlcOutput,
+    /// What the output looks like to tests
+    pub info: OutputInfo,
+    /// Number of views in each layer of `info.views`, which holds the
+    /// `Below` views first, then the `Normal` views, then the `Above` views.
+    pub layers: [usize; 3],
+    /// The views with each bit of `ViewType` set, in stack order. Views
+    /// without a type are only in the stack.
+    pub typed: HashMap<u32, Vec<WlcView>>
+}
+
+impl Output {
+    /// Gets the range of the stack holding a layer.
+    fn layer_range(&self, layer: Layer) -> Range<usize> {
+        let index = layer as usize;
+        let start = self.layers[..index].iter().sum();
+        start..start + self.layers[index]
+    }
+}
+
+/// Gets the bits set in a view type.
+fn type_bits(view_type: ViewType) -> Vec<u32> {
+    (0..32).map(|shift| 1 << shift).filter(|bit| view_type.bits() & bit != 0).collect()
+}
+
+/// Everything dummy-rustwlc remembers between calls.
+///
+/// Cloning the state is cheap: outputs are shared until changed, views are
+/// kept in a persistent map, and logs of what happened are shared between
+/// clones.
+///
+/// Records which only grow belong in a `Log`, not a `Vec`, or every
+/// checkpoint of the history would copy all of them.
+#[derive(Debug, Default, Clone)]
+pub struct State {
+    /// Last handle given out. Views and outputs share handles, as in wlc.
+    pub last_handle: u32,
+    /// Outputs in creation order
+    pub outputs: Vec<Rc<Output>>,
+    /// All live views
+    pub views: HandleMap<WlcView, ViewInfo>,
+    /// Names of mask bits, indexed by their bit
+    pub mask_names: Vec<String>,
+    /// Registered compositor callbacks
+    pub callbacks: Callbacks,
+    /// How handles are given out
+    pub handle_policy: HandlePolicy,
+    /// Which threads callbacks are invoked on
+    pub threading: Threading,
+    /// Handles of removed views and outputs, for `HandlePolicy::Reuse`
+    pub free_handles: Vec<u32>,
+    /// Compact indices of live views, see `dummy::index_of`
+    pub view_indices: HandleMap<WlcView, usize>,
+    /// Indices of removed views, largest first
+    pub free_indices: Vec<usize>,
+    /// Index the next view gets if none are free
+    pub next_index: usize,
+    /// Callback from `dummy::on_index_reuse`
+    pub index_reuse: Option<Callback<dyn Fn(usize, WlcView)>>,
+    /// Pointers from `set_user_data`, by raw handle
+    pub user_data: HashMap<usize, usize>,
+    /// What was created and destroyed, see `dummy::allocations`
+    pub allocations: Allocations,
+    /// Seed of the random number generator, see `dummy::set_seed`
+    pub seed: u64,
+    /// State of the random number generator
+    pub rng: u64,
+    /// Handlers from `callback::add_handler`, by descending priority
+    pub handlers: Vec<Handler>,
+    /// Id of the next handler
+    pub next_handler: u32,
+    /// Current time in milliseconds
+    pub time: u32,
+    /// How fast the mock clock runs
+    pub clock_speed: ClockSpeed,
+    /// Times of each kind of event's callbacks, by name, while timing
+    pub callback_times: Option<HashMap<&'static str, Log<Duration>>>,
+    /// The focused view, if any
+    pub focused_view: Option<WlcView>,
+    /// The focused output, if any
+    pub focused_output: Option<WlcOutput>,
+    /// The output designated primary, if any
+    pub primary_output: Option<WlcOutput>,
+    /// The view which has the keyboard, as clients see it
+    pub keyboard_focus: Option<WlcView>,
+    /// Keyboard enter/leave events delivered to clients
+    pub keyboard_deliveries: Log<KeyboardDelivery>,
+    /// The view which has the pointer, as clients see it
+    pub pointer_focus: Option<WlcView>,
+    /// Pointer enter/leave events delivered to clients
+    pub pointer_deliveries: Log<PointerDelivery>,
+    /// Input events which hit no view, with the output of the root
+    pub root_deliveries: Log<RootDelivery>,
+    /// Last serial given to a client event or request
+    pub serial: u32,
+    /// Unanswered client requests of each view, oldest first
+    pub requests: HashMap<WlcView, Vec<(u32, ClientRequest)>>,
+    /// Delay before clients ack configures, if they don't apply instantly
+    pub configure_delay: Option<u32>,
+    /// Configures not acked yet, oldest first
+    pub configures: Vec<Configure>,
+    /// Whether misuse panics
+    pub strict: bool,
+    /// Misuse recorded so far
+    pub violations: Log<String>,
+    /// Position of the pointer on the focused output
+    pub pointer: Point,
+    /// Pointer warps made by the compositor
+    pub warps: Log<Warp>,
+    /// Events queued to be dispatched, oldest first
+    pub pending_events: Vec<WlcEvent>,
+    /// Events whose callbacks are running, innermost last
+    pub dispatching: Vec<WlcEvent>,
+    /// Latest dispatched events, oldest first, for panic messages
+    pub recent_events: VecDeque<WlcEvent>,
+    /// Limits on the events caused by one stimulus, if any
+    pub watchdog: Option<Watchdog>,
+    /// Events dispatched for the current stimulus while watched, oldest first
+    pub cascade: Vec<WlcEvent>,
+    /// Where the compositor is in its lifecycle
+    pub lifecycle: Lifecycle,
+    /// Whether the `contract` module checks the compositor
+    pub contract_checks: bool,
+    /// Broken rules found by the `contract` module
+    pub contract_warnings: Log<ContractWarning>,
+    /// Views whose masks were set while contract checks are on
+    pub masks_set: HashSet<WlcView>,
+    /// Invariants checked after every simulated event
+    pub invariants: Vec<Invariant>,
+    /// Number of events the invariants were checked after
+    pub invariant_events: u32,
+    /// Whether the `calls` module records handle calls
+    pub recording_calls: bool,
+    /// Handle calls recorded by the `calls` module, oldest first
+    pub calls: Log<Call>,
+    /// Frames of each output rendered while recording calls
+    pub frames_rendered: HashMap<WlcOutput, u32>,
+    /// Frames each view was rendered in while recording calls
+    pub views_rendered: HashMap<WlcView, u32>,
+    /// Keys held down, in the order they were pressed
+    pub held_keys: Vec<Key>,
+    /// Pointer buttons held down, in the order they were pressed
+    pub held_buttons: Vec<Key>,
+    /// Touch points held down and where they are, by slot, in the order
+    /// they touched
+    pub touch_points: Vec<(i32, Point)>,
+    /// What happens to keys pressed twice or released without a press
+    pub key_order: KeyOrder,
+    /// Whether sticky keys latch and lock modifiers
+    pub sticky_keys: bool,
+    /// Modifiers of the keyboard
+    pub modifiers: ModifierState,
+    /// Whether another key was pressed while a modifier was held
+    pub modifier_used: bool,
+    /// The backend wlc reports
+    pub backend: BackendType,
+    /// Chords the host X server grabs, if not the default ones
+    pub host_grabs: Option<Vec<Chord>>,
+    /// Keys whose press the host X server took, until they are released
+    pub grabbed_keys: Vec<Key>,
+    /// Chords the host X server took
+    pub host_grabbed: Log<Chord>,
+    /// Scroll amount of a wheel click, if not the default
+    pub scroll_step: Option<f64>,
+    /// Whether the vertical and horizontal scroll axes are inverted
+    pub scroll_inverted: [bool; 2],
+    /// Whether motion focuses the view under the pointer
+    pub focus_follows_pointer: bool,
+    /// Whether focusing a view warps the pointer to it
+    pub pointer_follows_focus: bool,
+    /// Whether modal dialogs take the input of their parents
+    pub modal_routing: bool,
+    /// The interactive move or resize in progress
+    pub interactive: Option<InteractiveOp>,
+    /// Barriers, with their pressure
+    pub barriers: Vec<(Barrier, u32)>,
+    /// Hot corners from `dummy::add_hot_corner`
+    pub hot_corners: Vec<HotCorner>,
+    /// How pointer motion is delivered
+    pub motion_rate: MotionRate,
+    /// Motion held until the next frame
+    pub pending_motion: Option<Point>,
+    /// How long outputs go blank after changing mode
+    pub modeset_blank: Option<u32>,
+    /// Blank outputs, with when they went blank and for how long
+    pub blanks: HashMap<WlcOutput, (u32, u32)>,
+    /// Input held while its output is blank
+    pub blanked_input: Vec<(WlcOutput, Input)>,
+    /// The clipboard, if any client offered it
+    pub clipboard: Option<Offer>,
+    /// The primary selection, if any client offered it
+    pub primary: Option<Offer>,
+    /// Whether the primary selection is turned off
+    pub primary_disabled: bool,
+    /// Hook from `selection::on_change`
+    pub selection_hook: Option<SelectionHook>,
+    /// The output `simulate::render_frame` is rendering, if any
+    pub rendering: Option<WlcOutput>,
+    /// What happens when views change while a frame renders
+    pub render_mutations: RenderMutations,
+    /// Whether XWayland runs
+    pub xwayland: XWayland,
+    /// Hook from `dummy::on_xwayland_ready`
+    pub xwayland_hook: Option<Callback<dyn Fn()>>,
+    /// X11 views waiting for XWayland, in the order they were opened
+    pub xwayland_waiting: Vec<WlcView>,
+    /// Handler from `dummy::on_screenshot`
+    pub screenshot_handler: Option<Callback<dyn Fn(u32, ScreenshotTarget)>>,
+    /// Screenshots waiting for a frame of their output
+    pub pending_screenshots: Vec<(u32, ScreenshotTarget)>,
+    /// Screenshots taken
+    pub screenshots: Log<Screenshot>,
+    /// Pixels read by the screenshot being taken
+    pub screenshot_reads: Option<Vec<(Geometry, Vec<u8>)>>,
+    /// Policy from `dummy::set_virtual_keyboard_policy`
+    pub virtual_keyboard_policy: Option<Callback<dyn Fn(WlcView) -> bool>>,
+    /// The client whose virtual keyboard sent the key being delivered
+    pub virtual_keyboard_source: Option<WlcView>,
+    /// Bindings declared with `keybindings::bind`
+    pub keybindings: Vec<Binding>,
+    /// Templates defined with `fixtures::define`
+    pub templates: HashMap<String, Template>,
+    /// Where views are placed when the compositor doesn't place them
+    pub placement: Placement,
+    /// Offset of the next cascaded view
+    pub cascade_offset: i32,
+    /// Context info reported for outputs without their own
+    pub context_info: ContextInfo,
+    /// Per-output context info overrides
+    pub output_context_info: HashMap<WlcOutput, ContextInfo>,
+    /// Pixel formats set for outputs' framebuffers
+    pub pixel_formats: HashMap<WlcOutput, wlc_pixel_format>,
+    /// Render allocations left to fail, and those which failed
+    pub allocation_failures: HashMap<(WlcOutput, RenderAllocation), (u32, u32)>,
+    /// Time allowed for each frame, if frames are being budgeted
+    pub frame_budget: Option<FrameBudget>,
+    /// Per-output render statistics
+    pub frame_stats: HashMap<WlcOutput, FrameStats>,
+    /// Resolutions outputs fail to switch to, with the one they fall back to
+    pub mode_failures: HashMap<WlcOutput, Vec<(Size, Option<Size>)>>,
+    /// Client behaviors, in the order they were attached
+    pub behaviors: Vec<Behavior>,
+    /// Whether client behaviors are running, so the events they cause
+    /// don't run them again
+    pub running_behaviors: bool,
+    /// Events dispatched by the last `terminate()`
+    pub shutdown: Log<WlcEvent>,
+    /// Outputs which will be rendered next frame
+    pub render_scheduled: HashSet<WlcOutput>,
+    /// Frames drawn while recording, see `raster::record`
+    pub recording: Option<Recording>,
+    /// Renderers run after every rendered frame
+    pub renderers: Vec<Callback<RefCell<dyn LayoutRenderer>>>,
+    /// Latest simulated events, for the inspector
+    #[cfg(feature = "inspector")]
+    pub event_log: VecDeque<WlcEvent>
+}
+
+impl State {
+    /// Keeps an event among the latest dispatched.
+    pub fn note_event(&mut self, event: WlcEvent) {
+        if self.recent_events.len() == RECENT_EVENTS {
+            self.recent_events.pop_front();
+        }
+        self.recent_events.push_back(event);
+    }
+
+    /// Gives out the serial of a new client event or request.
+    pub fn next_serial(&mut self) -> u32 {
+        self.serial = self.serial.wrapping_add(1);
+        self.serial
+    }
+
+    /// Gives out a fresh, non-root handle, following the handle policy.
+    pub fn next_handle(&mut self) -> u32 {
+        if let HandlePolicy::Reuse = self.handle_policy {
+            if let Some(handle) = self.free_handles.pop() {
+                return handle
+            }
+        }
+        loop {
+            let handle = match self.handle_policy {
+                HandlePolicy::Seeded(_) | HandlePolicy::Random => self.random() as u32,
+                _ => {
+                    self.last_handle = self.last_handle.wrapping_add(1);
+                    self.last_handle
+                }
+            };
+            if handle != 0 && !self.handle_in_use(handle) {
+                return handle
+            }
+        }
+    }
+
+    /// Makes a removed handle available again, if the policy reuses them,
+    /// and forgets its user data.
+    pub fn free_handle(&mut self, handle: u32) {
+        if self.user_data.remove(&(handle as usize)).is_some() {
+            self.allocations.user_data.destroyed += 1;
+        }
+        if let HandlePolicy::Reuse = self.handle_policy {
+            self.free_handles.push(handle);
+        }
+    }
+
+    /// Gives a new view the smallest free index, returning whether the
+    /// index was used before.
+    pub fn assign_index(&mut self, view: WlcView) -> (usize, bool) {
+        let (index, reused) = match self.free_indices.pop() {
+            Some(index) => (index, true),
+            None => {
+                self.next_index += 1;
+                (self.next_index - 1, false)
+            }
+        };
+        self.view_indices.insert(view, index);
+        (index, reused)
+    }
+
+    /// Frees the index of a removed view.
+    pub fn free_index(&mut self, view: WlcView) {
+        if let Some(index) = self.view_indices.remove(&view) {
+            let position = self.free_indices.iter().position(|&free| free < index)
+                .unwrap_or(self.free_indices.len());
+            self.free_indices.insert(position, index);
+        }
+    }
+
+    /// Whether a view or output has a handle.
+    fn handle_in_use(&self, handle: u32) -> bool {
+        self.views.contains_key(&WlcView::dummy(handle)) ||
+            self.outputs.iter().any(|entry| entry.handle == WlcOutput::dummy(handle))
+    }
+
+    /// Gives out the next random number.
+    pub fn random(&mut self) -> u64 {
+        splitmix(&mut self.rng)
+    }
+
+    /// Schedules a live output for rendering, if it isn't already.
+    pub fn schedule_render(&mut self, output: WlcOutput) {
+        if self.output(output).is_some() && self.render_scheduled.insert(output) {
+            self.frame_stats.entry(output).or_default().scheduled += 1;
+        }
+    }
+
+    /// Gets the info of a live output.
+    pub fn output(&self, output: WlcOutput) -> Option<&OutputInfo> {
+        self.outputs.iter().find(|entry| entry.handle == output)
+            .map(|entry| &entry.info)
+    }
+
+    /// Gets a live output and its bookkeeping mutably.
+    ///
+    /// Views must only be moved in or out of the stack by the methods on
+    /// `State`, which keep the layers in order.
+    pub fn output_mut(&mut self, output: WlcOutput) -> Option<&mut Output> {
+        self.outputs.iter_mut().find(|entry| entry.handle == output)
+            .map(Rc::make_mut)
+    }
+
+    /// Gets the views of an output which match a predicate, in stack order.
+    ///
+    /// Views are looked at in place, without copying their info.
+    pub fn filter_views<F>(&self, output: WlcOutput, mut predicate: F) -> Vec<WlcView>
+        where F: FnMut(&ViewInfo) -> bool {
+        match self.output(output) {
+            Some(info) => info.views.iter()
+                .filter(|view| predicate(&self.views[view]))
+                .cloned().collect(),
+            None => Vec::new()
+        }
+    }
+
+    /// Gets the views of an output which match a filter, in stack order.
+    ///
+    /// A filter with a type only looks at the views with the rarest of its
+    /// bits, from the output's type index.
+    pub fn matching_views(&self, output: WlcOutput, filter: ViewFilter) -> Vec<WlcView> {
+        let entry = match self.outputs.iter().find(|entry| entry.handle == output) {
+            Some(entry) => entry,
+            None => return Vec::new()
+        };
+        let candidates = type_bits(filter.view_type).into_iter()
+            .map(|bit| entry.typed.get(&bit).map_or(&[][..], |views| &views[..]))
+            .min_by_key(|views| views.len())
+            .unwrap_or(&entry.info.views[..]);
+        candidates.iter().filter(|view| filter.matches(&self.views[view])).cloned().collect()
+    }
+
+    /// Changes a view's type, keeping its output's type index in order.
+    pub fn set_view_type(&mut self, view: WlcView, view_type: ViewType) {
+        self.unindex(view);
+        if let Some(info) = self.views.get_mut(&view) {
+            info.view_type = view_type;
+        }
+        self.index(view);
+    }
+
+    /// Adds a stacked view to its output's type index.
+    fn index(&mut self, view: WlcView) {
+        let (output, bits) = match self.views.get(&view) {
+            Some(info) if !info.view_type.is_empty() => (info.output, type_bits(info.view_type)),
+            _ => return
+        };
+        let views = &self.views;
+        let entry = match self.outputs.iter_mut().find(|entry| entry.handle == output) {
+            Some(entry) => Rc::make_mut(entry),
+            None => return
+        };
+        let stack = &entry.info.views;
+        let at = match stack.iter().position(|&other| other == view) {
+            Some(at) => at,
+            None => return
+        };
+        for bit in bits {
+            let has_bit = |other: &&WlcView| views[*other].view_type.bits() & bit != 0;
+            let typed = entry.typed.entry(bit).or_default();
+            // Count the typed views on the shorter side of the view
+            let index = if at <= stack.len() / 2 {
+                stack[..at].iter().filter(has_bit).count()
+            } else {
+                typed.len() - stack[at + 1..].iter().filter(has_bit).count()
+            };
+            typed.insert(index, view);
+        }
+    }
+
+    /// Takes a view out of its output's type index.
+    fn unindex(&mut self, view: WlcView) {
+        let (output, bits) = match self.views.get(&view) {
+            Some(info) if !info.view_type.is_empty() => (info.output, type_bits(info.view_type)),
+            _ => return
+        };
+        if let Some(entry) = self.output_mut(output) {
+            for bit in bits {
+                if let Some(typed) = entry.typed.get_mut(&bit) {
+                    typed.retain(|&other| other != view);
+                    if typed.is_empty() {
+                        entry.typed.remove(&bit);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Takes a view out of its output's stack.
+    ///
+    /// The view keeps its info, and must be put back with `stack`.
+    pub fn unstack(&mut self, view: WlcView) {
+        let (output, layer) = match self.views.get(&view) {
+            Some(info) => (info.output, info.layer),
+            None => return
+        };
+        self.unindex(view);
+        if let Some(entry) = self.output_mut(output) {
+            let range = entry.layer_range(layer);
+            if let Some(index) = entry.info.views[range.clone()].iter()
+                .position(|&other| other == view) {
+                    entry.info.views.remove(range.start + index);
+                    entry.layers[layer as usize] -= 1;
+                }
+        }
+    }
+
+    /// Puts a view into the stack of its output, inside its layer.
+    pub fn stack(&mut self, view: WlcView, position: Position) {
+        let (output, layer) = match self.views.get(&view) {
+            Some(info) => (info.output, info.layer),
+            None => return
+        };
+        if let Some(entry) = self.output_mut(output) {
+            let range = entry.layer_range(layer);
+            let views = &mut entry.info.views;
+            let index = match position {
+                Position::Top => range.end,
+                Position::Bottom => range.start,
+                Position::Below(other) => views.iter()
+                    .position(|&v| v == other).unwrap_or(range.end),
+                Position::Above(other) => views.iter()
+                    .position(|&v| v == other).map(|index| index + 1)
+                    .unwrap_or(range.end)
+            };
+            views.insert(index.max(range.start).min(range.end), view);
+            entry.layers[layer as usize] += 1;
+        }
+        self.index(view);
+    }
+
+    /// Gets a selection.
+    pub fn selection_mut(&mut self, selection: Selection) -> &mut Option<Offer> {
+        match selection {
+            Selection::Clipboard => &mut self.clipboard,
+            Selection::Primary => &mut self.primary
+        }
+    }
+
+    /// Forgets a removed view, once it is out of its output's stack,
+    /// returning the selections it offered, which are dropped.
+    ///
+    /// Everything kept about a view is cleaned up here, so views removed on
+    /// their own and those removed with their output are treated alike.
+    pub fn forget_view(&mut self, view: WlcView) -> Vec<Selection> {
+        if self.views.remove(&view).is_some() {
+            self.free_handle(view.into_raw() as u32);
+            self.free_index(view);
+            self.allocations.views.destroyed += 1;
+        }
+        if self.focused_view == Some(view) {
+            self.focused_view = None;
+        }
+        if self.interactive.is_some_and(|op| op.view() == view) {
+            self.interactive = None;
+        }
+        self.xwayland_waiting.retain(|&waiting| waiting != view);
+        // The client is gone, so it isn't sent a leave
+        if self.keyboard_focus == Some(view) {
+            self.keyboard_focus = None;
+        }
+        if self.pointer_focus == Some(view) {
+            self.pointer_focus = None;
+        }
+        self.requests.remove(&view);
+        self.configures.retain(|configure| configure.view != view);
+        self.behaviors.retain(|behavior| behavior.view != view);
+        self.drop_selections(view)
+    }
+
+    /// Drops the selections a view offered, returning them.
+    pub fn drop_selections(&mut self, view: WlcView) -> Vec<Selection> {
+        let mut dropped = Vec::new();
+        for &selection in &[Selection::Clipboard, Selection::Primary] {
+            let offer = self.selection_mut(selection);
+            if offer.as_ref().is_some_and(|offer| offer.source == view) {
+                *offer = None;
+                dropped.push(selection);
+            }
+        }
+        dropped
+    }
+
+    /// Moves a view within its output's stack, keeping it inside its layer.
+    ///
+    /// Does nothing if a `Below` or `Above` view is not in the same stack.
+    pub fn restack(&mut self, view: WlcView, position: Position) {
+        match position {
+            Position::Below(other) | Position::Above(other) => {
+                let same_output = match (self.views.get(&view),
+                                         self.views.get(&other)) {
+                    (Some(info), Some(other_info)) =>
+                        info.output == other_info.output,
+                    _ => false
+                };
+                if other == view || !same_output {
+                    return
+                }
+            },
+            _ => {}
+        }
+        self.unstack(view);
+        self.stack(view, position);
+    }
+
+    /// Replaces an output's stack with a reordering of it.
+    ///
+    /// The order is only kept within each layer.
+    pub fn set_stack(&mut self, output: WlcOutput, mut stack: Vec<WlcView>) {
+        let views = &self.views;
+        stack.sort_by_key(|view| views[view].layer);
+        let mut layers = [0; 3];
+        let mut typed: HashMap<u32, Vec<WlcView>> = HashMap::new();
+        for view in &stack {
+            layers[views[view].layer as usize] += 1;
+            for bit in type_bits(views[view].view_type) {
+                typed.entry(bit).or_default().push(*view);
+            }
+        }
+        if let Some(entry) = self.output_mut(output) {
+            entry.info.views = stack;
+            entry.layers = layers;
+            entry.typed = typed;
+        }
+    }
+}
+
+type History = Option<Vec<Checkpoint>>;
+
+thread_local! {
+    static STATE: RefCell<State> = RefCell::new(State::default());
+    static HISTORY: RefCell<History> = const { RefCell::new(None) };
+}
+
+/// Runs `f` with the current thread's state.
+///
+/// Callbacks must not be invoked from within `f`, as they are free to call
+/// back into methods which need the state.
+pub fn with<F, R>(f: F) -> R where F: FnOnce(&mut State) -> R {
+    STATE.with(|state| f(&mut state.borrow_mut()))
+}
+
+/// Runs `f` with the recorded history, if history is being recorded.
+///
+/// The history is kept apart from the state so snapshots don't contain it.
+pub fn with_history<F, R>(f: F) -> R where F: FnOnce(&mut History) -> R {
+    HISTORY.with(|history| f(&mut history.borrow_mut()))
+}
+
+/// A thread's state and history, moved to another thread for a while, see
+/// `off_thread`.
+#[cfg(feature = "off-thread-callbacks")]
+struct Handoff(State, History);
+
+// Only the thread holding the handoff uses the state: the thread which
+// gave it away waits until it's handed back. The callbacks in it are run
+// on the other thread, which `dummy::set_threading` makes its callers
+// vouch for.
+#[cfg(feature = "off-thread-callbacks")]
+unsafe impl Send for Handoff {}
+
+#[cfg(feature = "off-thread-callbacks")]
+impl Handoff {
+    /// Takes the current thread's state and history, leaving it empty ones.
+    fn take() -> Handoff {
+        Handoff(STATE.with(|state| mem::take(&mut *state.borrow_mut())),
+                HISTORY.with(|history| history.borrow_mut().take()))
+    }
+
+    /// Makes these the current thread's state and history.
+    fn restore(self) {
+        let Handoff(new_state, new_history) = self;
+        STATE.with(|state| *state.borrow_mut() = new_state);
+        HISTORY.with(|history| *history.borrow_mut() = new_history);
+    }
+}
+
+/// Runs `f` on a new thread and waits for it. The state and history move
+/// to that thread while `f` runs, and back once it's done, even if it
+/// panicked; panics in `f` are then resumed on this thread.
+#[cfg(feature = "off-thread-callbacks")]
+pub(crate) fn off_thread<F, R>(f: F) -> R where F: FnOnce() -> R + Send, R: Send {
+    let handoff = Handoff::take();
+    let (handoff, result) = thread::scope(|scope| {
+        scope.spawn(move || {
+            handoff.restore();
+            let result = panic::catch_unwind(AssertUnwindSafe(f));
+            (Handoff::take(), result)
+        }).join().expect("dummy-rustwlc: lost the state on another thread")
+    });
+    handoff.restore();
+    result.unwrap_or_else(|payload| panic::resume_unwind(payload))
+}
+
+/// Advances a splitmix64 generator, giving out its next number.
+pub(crate) fn splitmix(rng: &mut u64) -> u64 {
+    *rng = rng.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *rng;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,63 @@
+//! XWayland, which wlc starts for X11 clients.
+
+use std::rc::Rc;
+
+use super::super::callback::Callback;
+use super::state;
+
+/// Whether XWayland runs, for X11 clients.
+///
+/// wlc starts XWayland in the background, so X11 clients which connect
+/// early wait until it is ready. See `simulate::create_x11_view`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum XWayland {
+    /// Ready for X11 clients. This is the default.
+    #[default]
+    Ready,
+    /// Starting: X11 views wait until `simulate::xwayland_ready`
+    Starting,
+    /// Not running: X11 clients can't open views
+    Disabled
+}
+
+/// Sets whether XWayland runs.
+///
+/// Setting it `Starting` models XWayland starting in the background, until
+/// `simulate::xwayland_ready` says it is ready. Setting it `Ready` directly
+/// doesn't open the X11 views waiting for it.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, simulate, Size};
+/// use rustwlc::dummy::XWayland;
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+///
+/// dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// dummy::set_xwayland(XWayland::Starting);
+/// let ready = Rc::new(Cell::new(false));
+/// let seen = ready.clone();
+/// dummy::on_xwayland_ready(move || seen.set(true));
+/// let early = simulate::create_x11_view();
+/// assert!(!dummy::is_mapped(early));
+/// simulate::xwayland_ready();
+/// assert!(ready.get() && dummy::is_mapped(early));
+/// assert_eq!(dummy::xwayland(), XWayland::Ready);
+/// ```
+pub fn set_xwayland(xwayland: XWayland) {
+    state::with(|state| state.xwayland = xwayland);
+}
+
+/// Gets whether XWayland runs, see `set_xwayland`.
+pub fn xwayland() -> XWayland {
+    state::with(|state| state.xwayland)
+}
+
+/// Sets a hook, called when XWayland becomes ready with
+/// `simulate::xwayland_ready`, before the X11 views waiting for it open.
+///
+/// A compositor which starts X11 programs, or sets up X11 window rules,
+/// once XWayland is ready can run that here.
+pub fn on_xwayland_ready<F>(hook: F) where F: Fn() + 'static {
+    state::with(|state| state.xwayland_hook = Some(Callback(Rc::new(hook))));
+}
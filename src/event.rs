@@ -0,0 +1,283 @@
+//! Contains `WlcEvent`, a single type for everything wlc reports through
+//! callbacks.
+
+use super::handle::{WlcOutput, WlcView};
+use super::types::*;
+
+/// What produced a scroll event.
+///
+/// Compositors usually switch workspaces on discrete wheel clicks, but
+/// scroll smoothly with fingers on a touchpad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollSource {
+    /// A mouse wheel, which moved by whole clicks
+    Wheel {
+        /// Clicks on the vertical and horizontal axes
+        clicks: [i32; 2]
+    },
+    /// Fingers on a touchpad
+    Finger,
+    /// A continuous device, such as a trackball in scroll mode
+    Continuous
+}
+
+/// An event wlc reports to the compositor, with the arguments given to its
+/// callback.
+///
+/// Each variant corresponds to the callback of the same name in the
+/// `callback` module. References passed to callbacks are stored by value.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{WlcEvent, WlcView};
+///
+/// let event = WlcEvent::ViewFocus(WlcView::dummy(1), true);
+/// assert_eq!(event.name(), "view_focus");
+/// assert_eq!(event.view(), Some(WlcView::dummy(1)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WlcEvent {
+    /// An output was created
+    OutputCreated(WlcOutput),
+    /// An output was destroyed
+    OutputDestroyed(WlcOutput),
+    /// An output gained or lost focus
+    OutputFocus(WlcOutput, bool),
+    /// An output's resolution changed
+    OutputResolution {
+        /// The output
+        output: WlcOutput,
+        /// The previous resolution
+        old_size: Size,
+        /// The new resolution
+        new_size: Size
+    },
+    /// An output's context was created
+    OutputContextCreated(WlcOutput),
+    /// An output's context was destroyed
+    OutputContextDestroyed(WlcOutput),
+    /// An output is about to be rendered
+    OutputRenderPre(WlcOutput),
+    /// An output was rendered
+    OutputRenderPost(WlcOutput),
+    /// A view was created
+    ViewCreated(WlcView),
+    /// A view was destroyed
+    ViewDestroyed(WlcView),
+    /// A view gained or lost focus
+    ViewFocus(WlcView, bool),
+    /// A view switched outputs
+    ViewMoveToOutput {
+        /// The view
+        view: WlcView,
+        /// The output the view was on
+        old_output: WlcOutput,
+        /// The output the view is now on
+        new_output: WlcOutput
+    },
+    /// A view requested a geometry
+    ViewRequestGeometry {
+        /// The view
+        view: WlcView,
+        /// The requested geometry
+        geometry: Geometry
+    },
+    /// A view requested a state
+    ViewRequestState {
+        /// The view
+        view: WlcView,
+        /// The requested state
+        state: ViewState,
+        /// Whether the state is being set or unset
+        handled: bool
+    },
+    /// A view requested an interactive move
+    ViewRequestMove {
+        /// The view
+        view: WlcView,
+        /// Where the move started
+        destination: Point
+    },
+    /// A view requested an interactive resize
+    ViewRequestResize {
+        /// The view
+        view: WlcView,
+        /// The edge being dragged
+        edge: ResizeEdge,
+        /// Where the resize started
+        location: Point
+    },
+    /// A view is about to be rendered
+    ViewRenderPre(WlcView),
+    /// A view was rendered
+    ViewRenderPost(WlcView),
+    /// A key was pressed or released
+    KeyboardKey {
+        /// The focused view, or the root window
+        view: WlcView,
+        /// Timestamp in milliseconds
+        time: u32,
+        /// Modifiers at the time of the event
+        mods: KeyboardModifiers,
+        /// The key code
+        key: u32,
+        /// Whether the key was pressed or released
+        state: KeyState
+    },
+    /// A pointer button was pressed or released
+    PointerButton {
+        /// The view under the pointer, or the root window
+        view: WlcView,
+        /// Timestamp in milliseconds
+        time: u32,
+        /// Modifiers at the time of the event
+        mods: KeyboardModifiers,
+        /// The button code
+        button: u32,
+        /// Whether the button was pressed or released
+        state: ButtonState,
+        /// Position of the pointer
+        point: Point
+    },
+    /// The pointer scrolled
+    PointerScroll {
+        /// The view under the pointer, or the root window
+        view: WlcView,
+        /// Timestamp in milliseconds
+        time: u32,
+        /// Modifiers at the time of the event
+        mods: KeyboardModifiers,
+        /// Which axes scrolled
+        axis: ScrollAxis,
+        /// Scroll amounts for the vertical and horizontal axes
+        amount: [f64; 2],
+        /// What produced the scroll. wlc doesn't give this to callbacks.
+        source: ScrollSource
+    },
+    /// The pointer moved
+    PointerMotion {
+        /// The view under the new position, or the root window
+        view: WlcView,
+        /// Timestamp in milliseconds
+        time: u32,
+        /// The new position
+        point: Point
+    },
+    /// A touch event happened
+    Touch {
+        /// The view touched, or the root window
+        view: WlcView,
+        /// Timestamp in milliseconds
+        time: u32,
+        /// Modifiers at the time of the event
+        mods: KeyboardModifiers,
+        /// What kind of touch event it was
+        touch: TouchType,
+        /// Which finger caused the event
+        slot: i32,
+        /// Where the touch happened
+        point: Point
+    },
+    /// The compositor is ready
+    CompositorReady,
+    /// The compositor is terminating
+    CompositorTerminate
+}
+
+impl WlcEvent {
+    /// Gets the name of the callback this event is given to,
+    /// i.e. `"view_focus"`.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            WlcEvent::OutputCreated(..) => "output_created",
+            WlcEvent::OutputDestroyed(..) => "output_destroyed",
+            WlcEvent::OutputFocus(..) => "output_focus",
+            WlcEvent::OutputResolution { .. } => "output_resolution",
+            WlcEvent::OutputContextCreated(..) => "output_context_created",
+            WlcEvent::OutputContextDestroyed(..) => "output_context_destroyed",
+            WlcEvent::OutputRenderPre(..) => "output_render_pre",
+            WlcEvent::OutputRenderPost(..) => "output_render_post",
+            WlcEvent::ViewCreated(..) => "view_created",
+            WlcEvent::ViewDestroyed(..) => "view_destroyed",
+            WlcEvent::ViewFocus(..) => "view_focus",
+            WlcEvent::ViewMoveToOutput { .. } => "view_move_to_output",
+            WlcEvent::ViewRequestGeometry { .. } => "view_request_geometry",
+            WlcEvent::ViewRequestState { .. } => "view_request_state",
+            WlcEvent::ViewRequestMove { .. } => "view_request_move",
+            WlcEvent::ViewRequestResize { .. } => "view_request_resize",
+            WlcEvent::ViewRenderPre(..) => "view_render_pre",
+            WlcEvent::ViewRenderPost(..) => "view_render_post",
+            WlcEvent::KeyboardKey { .. } => "keyboard_key",
+            WlcEvent::PointerButton { .. } => "pointer_button",
+            WlcEvent::PointerScroll { .. } => "pointer_scroll",
+            WlcEvent::PointerMotion { .. } => "pointer_motion",
+            WlcEvent::Touch { .. } => "touch",
+            WlcEvent::CompositorReady => "compositor_ready",
+            WlcEvent::CompositorTerminate => "compositor_terminate"
+        }
+    }
+
+    /// Gets the view the event is about, if any.
+    ///
+    /// Input events report the view they are delivered to, which may be
+    /// the root window.
+    pub fn view(&self) -> Option<WlcView> {
+        match *self {
+            WlcEvent::ViewCreated(view) | WlcEvent::ViewDestroyed(view) |
+            WlcEvent::ViewFocus(view, _) | WlcEvent::ViewRenderPre(view) |
+            WlcEvent::ViewRenderPost(view) |
+            WlcEvent::ViewMoveToOutput { view, .. } |
+            WlcEvent::ViewRequestGeometry { view, .. } |
+            WlcEvent::ViewRequestState { view, .. } |
+            WlcEvent::ViewRequestMove { view, .. } |
+            WlcEvent::ViewRequestResize { view, .. } |
+            WlcEvent::KeyboardKey { view, .. } |
+            WlcEvent::PointerButton { view, .. } |
+            WlcEvent::PointerScroll { view, .. } |
+            WlcEvent::PointerMotion { view, .. } |
+            WlcEvent::Touch { view, .. } => Some(view),
+            _ => None
+        }
+    }
+
+    /// Gets the output the event is about, if any.
+    ///
+    /// For `ViewMoveToOutput`, this is the new output.
+    pub fn output(&self) -> Option<WlcOutput> {
+        match *self {
+            WlcEvent::OutputCreated(output) | WlcEvent::OutputDestroyed(output) |
+            WlcEvent::OutputFocus(output, _) |
+            WlcEvent::OutputContextCreated(output) |
+            WlcEvent::OutputContextDestroyed(output) |
+            WlcEvent::OutputRenderPre(output) |
+            WlcEvent::OutputRenderPost(output) |
+            WlcEvent::OutputResolution { output, .. } => Some(output),
+            WlcEvent::ViewMoveToOutput { new_output, .. } => Some(new_output),
+            _ => None
+        }
+    }
+
+    /// Whether the event's callback can block or reject it.
+    ///
+    /// Input callbacks can block events from reaching the view, and
+    /// `output_created` and `view_created` can reject the new handle.
+    pub fn is_vetoable(&self) -> bool {
+        matches!(*self,
+            WlcEvent::OutputCreated(..) | WlcEvent::ViewCreated(..) |
+            WlcEvent::KeyboardKey { .. } | WlcEvent::PointerButton { .. } |
+            WlcEvent::PointerScroll { .. } | WlcEvent::PointerMotion { .. } |
+            WlcEvent::Touch { .. })
+    }
+
+    /// Gets the timestamp of input events.
+    pub fn time(&self) -> Option<u32> {
+        match *self {
+            WlcEvent::KeyboardKey { time, .. } |
+            WlcEvent::PointerButton { time, .. } |
+            WlcEvent::PointerScroll { time, .. } |
+            WlcEvent::PointerMotion { time, .. } |
+            WlcEvent::Touch { time, .. } => Some(time),
+            _ => None
+        }
+    }
+}
@@ -0,0 +1,261 @@
+//! Realistic views for testing window rules.
+//!
+//! Each function adds a view to the dummy compositor which looks like a
+//! well-known application to a compositor: its type flags, parent, title,
+//! class, app id, and initial geometry match what wlc reports for it. As in
+//! wlc, native Wayland clients have an app id but no class, and X11 clients
+//! have a class but no app id.
+//!
+//! The views are added with `dummy::add_view`, so no callbacks run. To run
+//! a compositor's rules, dispatch `WlcEvent::ViewCreated` for them.
+//!
+//! Kinds of clients which a test suite uses over and over can be defined
+//! once as a `Template`, with their type flags, size, and scripted
+//! behavior, and added by name with `from_template`, or from a scenario
+//! file with the `client` step of the `script` module. `gtk-app`,
+//! `x11-game` and `splash` are defined to begin with, and can be redefined.
+//!
+//! # Example
+//! ```rust
+//! use rustwlc::{dummy, fixtures, simulate};
+//! use rustwlc::{Size, WlcEvent, VIEW_BIT_MODAL};
+//!
+//! let output = dummy::add_output("WLC-1", Size { w: 1920, h: 1080 });
+//! let player = fixtures::mpv(output);
+//! let dialog = fixtures::mpv_dialog(player);
+//! assert_eq!(dialog.get_app_id(), "mpv");
+//! assert_eq!(dialog.get_parent(), player);
+//! assert!(dialog.get_type().contains(VIEW_BIT_MODAL));
+//! simulate::dispatch(WlcEvent::ViewCreated(dialog));
+//! ```
+
+use super::clients::{self, Action};
+use super::dummy::{self, state, SizeHints};
+use super::handle::{WlcOutput, WlcView};
+use super::types::{Geometry, Point, Size, ViewType};
+use super::types::{VIEW_BIT_MODAL, VIEW_BIT_OVERRIDE_REDIRECT, VIEW_BIT_SPLASH,
+                   VIEW_BIT_UNMANAGED, VIEW_FULLSCREEN};
+use super::types::EDGE_NONE;
+
+/// Firefox running natively on Wayland.
+pub fn firefox(output: WlcOutput) -> WlcView {
+    view(output, ViewType::empty(), "Mozilla Firefox", "", "firefox",
+         Size { w: 1280, h: 720 })
+}
+
+/// mpv playing a video, running natively on Wayland.
+pub fn mpv(output: WlcOutput) -> WlcView {
+    view(output, ViewType::empty(), "video.mkv - mpv", "", "mpv",
+         Size { w: 1920, h: 1080 })
+}
+
+/// mpv's floating "Open File" dialog, a modal child of `player`.
+///
+/// The dialog is on the same output as `player`, centered over it.
+pub fn mpv_dialog(player: WlcView) -> WlcView {
+    let output = player.get_output();
+    let dialog = view(output, VIEW_BIT_MODAL, "Open File", "", "mpv",
+                      Size { w: 640, h: 480 });
+    dialog.set_parent(player);
+    dialog.set_geometry(EDGE_NONE, centered(player.get_visible_geometry(),
+                                            Size { w: 640, h: 480 }));
+    dialog
+}
+
+/// xterm, an X11 client, at its default 80x24 size.
+///
+/// Like xterm, the view only accepts sizes of whole 6x13 character cells
+/// plus a 4 pixel border, and at least one cell.
+pub fn xterm(output: WlcOutput) -> WlcView {
+    let term = view(output, ViewType::empty(), "xterm", "XTerm", "",
+                    Size { w: 484, h: 316 });
+    dummy::set_size_hints(term, SizeHints {
+        base: Some(Size { w: 4, h: 4 }),
+        min: Some(Size { w: 10, h: 17 }),
+        increment: Some(Size { w: 6, h: 13 }),
+        .. SizeHints::default()
+    });
+    term
+}
+
+/// Steam's X11 splash screen, shown while it updates.
+///
+/// The splash is centered on its output.
+pub fn steam_splash(output: WlcOutput) -> WlcView {
+    let size = Size { w: 384, h: 240 };
+    let splash = view(output, VIEW_BIT_SPLASH, "Steam", "Steam", "", size);
+    let screen = Geometry {
+        origin: Point { x: 0, y: 0 },
+        size: output.get_resolution().unwrap_or(size)
+    };
+    splash.set_geometry(EDGE_NONE, centered(screen, size));
+    splash
+}
+
+/// An unmanaged, override-redirect Java AWT window, such as a tooltip.
+pub fn java_awt_window(output: WlcOutput) -> WlcView {
+    view(output, VIEW_BIT_OVERRIDE_REDIRECT | VIEW_BIT_UNMANAGED, "",
+         "sun-awt-X11-XWindowPeer", "", Size { w: 200, h: 24 })
+}
+
+/// A kind of client, whose views are added by name with `from_template`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    /// Type flags of the views
+    pub view_type: ViewType,
+    /// Class of the views, for X11 clients
+    pub class: String,
+    /// App id of the views, for native Wayland clients
+    pub app_id: String,
+    /// Initial size of the views
+    pub size: Size,
+    /// What the client does with each view, see `clients::script`
+    pub script: Vec<(u32, Action)>
+}
+
+/// Defines a template, replacing any template with the same name.
+pub fn define(name: &str, template: Template) {
+    state::with(|state| state.templates.insert(name.to_string(), template));
+}
+
+/// Gets a template by name, if it is defined.
+pub fn template(name: &str) -> Option<Template> {
+    state::with(|state| state.templates.get(name).cloned()).or_else(|| builtin(name))
+}
+
+/// Adds a view with a title to `output` from a template, and attaches the
+/// template's script to its client.
+///
+/// As with the other fixtures, no callbacks run.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, fixtures, Size, ViewType};
+/// use rustwlc::clients::Action;
+/// use rustwlc::fixtures::Template;
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 1920, h: 1080 });
+/// fixtures::define("flaky", Template {
+///     view_type: ViewType::empty(),
+///     class: String::new(),
+///     app_id: "org.example.Flaky".to_string(),
+///     size: Size { w: 640, h: 480 },
+///     script: vec![(3, Action::Crash)]
+/// });
+/// let flaky = fixtures::from_template(output, "flaky", "Flaky").unwrap();
+/// assert_eq!(flaky.get_app_id(), "org.example.Flaky");
+/// let game = fixtures::from_template(output, "x11-game", "Quake").unwrap();
+/// assert_eq!(game.get_class(), "Game");
+/// assert_eq!(fixtures::from_template(output, "emacs", "").unwrap_err(),
+///            "no client template named \"emacs\"");
+/// ```
+pub fn from_template(output: WlcOutput, name: &str, title: &str) -> Result<WlcView, String> {
+    let template = template(name)
+        .ok_or_else(|| format!("no client template named \"{}\"", name))?;
+    let view = view(output, template.view_type, title, &template.class, &template.app_id,
+                    template.size);
+    if !template.script.is_empty() {
+        clients::script(view, template.script);
+    }
+    Ok(view)
+}
+
+/// The templates defined to begin with.
+fn builtin(name: &str) -> Option<Template> {
+    let (view_type, class, app_id, size, script) = match name {
+        // Asks to go fullscreen as soon as it's mapped
+        "x11-game" => (ViewType::empty(), "Game", "", Size { w: 1280, h: 720 },
+                       vec![(1, Action::RequestState(VIEW_FULLSCREEN, true))]),
+        "gtk-app" => (ViewType::empty(), "", "org.gtk.Example", Size { w: 800, h: 600 },
+                      Vec::new()),
+        // Closes once the application has loaded
+        "splash" => (VIEW_BIT_SPLASH, "Splash", "", Size { w: 384, h: 240 },
+                     vec![(10, Action::Close)]),
+        _ => return None
+    };
+    Some(Template {
+        view_type,
+        class: class.to_string(),
+        app_id: app_id.to_string(),
+        size,
+        script
+    })
+}
+
+/// Adds a view with the given properties at the top left of `output`.
+fn view(output: WlcOutput, view_type: ViewType, title: &str, class: &str,
+        app_id: &str, size: Size) -> WlcView {
+    let view = dummy::add_view(output);
+    view.set_type(view_type, true);
+    dummy::set_title(view, title);
+    dummy::set_class(view, class);
+    dummy::set_app_id(view, app_id);
+    view.set_geometry(EDGE_NONE, Geometry { origin: Point { x: 0, y: 0 }, size });
+    view
+}
+
+/// Geometry of `size` centered over `over`.
+fn centered(over: Geometry, size: Size) -> Geometry {
+    Geometry {
+        origin: Point {
+            x: over.origin.x + (over.size.w as i32 - size.w as i32) / 2,
+            y: over.origin.y + (over.size.h as i32 - size.h as i32) / 2
+        },
+        size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn fixtures() {
+        let output = dummy::add_output("WLC-1", Size { w: 1920, h: 1080 });
+        let browser = fixtures::firefox(output);
+        let term = fixtures::xterm(output);
+        let splash = fixtures::steam_splash(output);
+        let tooltip = fixtures::java_awt_window(output);
+        assert_eq!((browser.get_class(), browser.get_app_id()),
+                   ("".to_string(), "firefox".to_string()));
+        assert_eq!((term.get_class(), term.get_app_id()),
+                   ("XTerm".to_string(), "".to_string()));
+        assert_eq!(splash.get_visible_geometry().origin, Point { x: 768, y: 420 });
+        assert_eq!(tooltip.get_type(), VIEW_BIT_OVERRIDE_REDIRECT | VIEW_BIT_UNMANAGED);
+        assert_eq!(output.get_views(), vec![browser, term, splash, tooltip]);
+        term.set_geometry(EDGE_NONE, Geometry {
+            origin: Point { x: 0, y: 0 }, size: Size { w: 960, h: 1080 }
+        });
+        assert_eq!(term.get_visible_geometry().size, Size { w: 958, h: 1070 });
+        term.set_geometry(EDGE_NONE, Geometry::default());
+        assert_eq!(term.get_visible_geometry().size, Size { w: 10, h: 17 });
+    }
+
+    #[test]
+    fn templates() {
+        callback::rust::view_created(|_| true);
+        script::run("output WLC-1 1920x1080\n\
+                     client x11-game Quake III Arena\n\
+                     client splash Loading").unwrap();
+        let game = dummy::find_views(|info| info.title == "Quake III Arena")[0];
+        let splash = dummy::find_views(|info| info.title == "Loading")[0];
+        assert_eq!(game.get_visible_geometry().size, Size { w: 1280, h: 720 });
+        assert_eq!(dummy::pending_requests(game).len(), 1);
+        assert_eq!(splash.get_type(), VIEW_BIT_SPLASH);
+        // Redefined, and no longer closing
+        fixtures::define("splash", fixtures::Template {
+            script: Vec::new(),
+            .. fixtures::template("splash").unwrap()
+        });
+        let kept = fixtures::from_template(WlcOutput::get_primary(), "splash", "Kept").unwrap();
+        for _ in 0..10 {
+            simulate::dispatch(WlcEvent::CompositorReady);
+        }
+        assert_eq!(dummy::view_info(splash), None);
+        assert!(dummy::view_info(kept).is_some());
+        assert_eq!(script::run("client"),
+                   Err("line 1: wrong number of arguments to \"client\"".to_string()));
+        assert_eq!(script::run("client vim"),
+                   Err("line 1: no client template named \"vim\"".to_string()));
+    }
+}
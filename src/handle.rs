@@ -5,9 +5,18 @@
 //! - **Eq, Ord**: compare the underlying `uintptr_t` handle
 //! - **Clone**: View handles can safely be cloned.
 
+#[cfg(feature = "c-compat")]
 use libc::{uintptr_t};
+#[cfg(not(feature = "c-compat"))]
+#[allow(non_camel_case_types)]
+type uintptr_t = usize;
 
-use super::types::{Geometry, ResizeEdge, Point, Size, ViewType, ViewState};
+use super::types::{Geometry, ResizeEdge, Size, ViewType, ViewState};
+use super::render::ContextInfo;
+use super::dummy::{self, ClientRequest, Position, ViewFilter};
+use super::calls;
+use super::contract;
+use super::simulate;
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -20,18 +29,6 @@ pub struct WlcView(uintptr_t);
 /// Represents a handle to a wlc output.
 pub struct WlcOutput(uintptr_t);
 
-impl From<WlcView> for WlcOutput {
-    fn from(view: WlcView) -> Self {
-        WlcOutput(view.0)
-    }
-}
-
-impl From<WlcOutput> for WlcView {
-    fn from(output: WlcOutput) -> Self {
-        WlcView(output.0)
-    }
-}
-
 static ZERO_RES: Size = Size { w: 0, h: 0 };
 
 impl WlcOutput {
@@ -42,8 +39,21 @@ impl WlcOutput {
     /// If for some reason a conversion between the two was required,
     /// this function could be called. If this is the case please submit
     /// a bug report.
+    ///
+    /// Calling a `WlcView` method which changes the compositor on the
+    /// result is misuse, see `dummy::set_strict`.
+    ///
+    /// # Example
+    /// ```rust,should_panic
+    /// use rustwlc::{dummy, Size};
+    ///
+    /// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+    /// dummy::set_strict(true);
+    /// // Panics: "WlcView::focus was called on WlcOutput(1)"
+    /// output.as_view().focus();
+    /// ```
     pub fn as_view(self) -> WlcView {
-        return WlcView::from(self)
+        WlcView::from_raw(self.into_raw())
     }
 
     /// Create a dummy WlcOutput for testing purposes.
@@ -51,11 +61,10 @@ impl WlcOutput {
     /// # Unsafety
     /// The following operations on a dummy WlcOutput will cause crashes:
     ///
-    /// - `WlcOutput::focused` when wlc is not running
-    /// - `WlcOutput::list` when wlc is not running
     /// - `WlcOutput::set_resolution` on a dummy output
     ///
-    /// In addition, `WlcOutput::set_views` will return an error.
+    /// In addition, `WlcOutput::set_views` will return an error unless the
+    /// output was created with `dummy::add_output`.
     ///
     /// All other methods can be used on dummy outputs.
     ///
@@ -71,9 +80,42 @@ impl WlcOutput {
         WlcOutput(code as uintptr_t)
     }
 
+    /// Gets the output with a raw handle, i.e. one read back from an IPC
+    /// message.
+    ///
+    /// The handle isn't checked, see `try_from_raw`.
+    pub fn from_raw(raw: uintptr_t) -> WlcOutput {
+        WlcOutput(raw)
+    }
+
+    /// Gets the output with a raw handle, if it exists in the dummy
+    /// compositor.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rustwlc::{dummy, Size, WlcOutput};
+    ///
+    /// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+    /// let message = format!("{{\"output\": {}}}", output.into_raw());
+    /// assert_eq!(message, "{\"output\": 1}");
+    /// assert_eq!(WlcOutput::try_from_raw(1), Some(output));
+    /// dummy::remove_output(output);
+    /// assert_eq!(WlcOutput::try_from_raw(1), None);
+    /// ```
+    pub fn try_from_raw(raw: uintptr_t) -> Option<WlcOutput> {
+        let output = WlcOutput(raw);
+        dummy::output_info(output).map(|_| output)
+    }
+
+    /// Gets the raw handle of the output, i.e. to send it in an IPC
+    /// message.
+    pub fn into_raw(self) -> uintptr_t {
+        self.0
+    }
+
     /// Gets user-specified data.
     ///
-    /// # Unsafety
+    /// # Safety
     /// The wlc implementation of this method uses `void*` pointers
     /// for raw C data. This function will internaly do a conversion
     /// between the input `T` and a `libc::c_void`.
@@ -82,13 +124,19 @@ impl WlcOutput {
     /// such, usage of these functions requires an understanding of
     /// what data they will have. Please review wlc's usage of these
     /// functions before attempting to use them yourself.
+    ///
+    /// The dummy keeps the pointer given to `set_user_data` until the
+    /// handle is removed, and panics if none was given.
+    #[allow(clippy::mut_from_ref)] // rustwlc's signature
     pub unsafe fn get_user_data<T>(&self) -> &mut T {
-        unimplemented!()
+        let data = dummy::user_data(self.0)
+            .expect("WlcOutput::get_user_data: no user data was set");
+        &mut *(data as *mut T)
     }
 
     /// Sets user-specified data.
     ///
-    /// # Unsafety
+    /// # Safety
     /// The wlc implementation of this method uses `void*` pointers
     /// for raw C data. This function will internaly do a conversion
     /// between the input `T` and a `libc::c_void`.
@@ -98,7 +146,8 @@ impl WlcOutput {
     /// what data they will have. Please review wlc's usage of these
     /// functions before attempting to use them yourself.
     pub unsafe fn set_user_data<T>(&self, data: &T) {
-        unimplemented!()
+        calls::called("WlcOutput::set_user_data", self.0, &[&(data as *const T)]);
+        dummy::set_user_data(self.0, data as *const T as usize);
     }
 
     /// Schedules output for rendering next frame.
@@ -107,22 +156,57 @@ impl WlcOutput {
     /// a no-op; if output is currently rendering,
     /// it will render immediately after.
     pub fn schedule_render(&self) {
+        calls::called("WlcOutput::schedule_render", self.0, &[]);
+        dummy::state::with(|state| state.schedule_render(*self));
     }
 
     /// Gets a list of the current outputs.
     ///
-    /// # Safety
-    /// This function will crash the program if run when wlc is not running.
+    /// Outputs are added with `dummy::add_output`.
     pub fn list() -> Vec<WlcOutput> {
-        unimplemented!()
+        dummy::outputs()
     }
 
     /// Gets the currently focused output.
     ///
-    /// # Safety
-    /// This function will crash the program if run when wlc is not running.
+    /// Returns `WlcOutput::dummy(0)` if no output is focused.
     pub fn focused() -> WlcOutput {
-        unimplemented!()
+        dummy::state::with(|state| state.focused_output)
+            .unwrap_or_else(|| WlcOutput::dummy(0))
+    }
+
+    /// Gets the primary output.
+    ///
+    /// This is a dummy extension, like xrandr's primary output: an output
+    /// the compositor designates with `set_primary` for new views and
+    /// panels. If none was designated, or it was disconnected, the first
+    /// output is primary. Returns `WlcOutput::dummy(0)` if there are no
+    /// outputs.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rustwlc::{dummy, simulate, Size, WlcOutput};
+    ///
+    /// let laptop = dummy::add_output("eDP-1", Size { w: 1920, h: 1080 });
+    /// let monitor = dummy::add_output("DP-1", Size { w: 2560, h: 1440 });
+    /// assert_eq!(WlcOutput::get_primary(), laptop);
+    /// monitor.set_primary();
+    /// assert_eq!(simulate::create_view().get_output(), monitor);
+    /// dummy::remove_output(monitor);
+    /// assert_eq!(WlcOutput::get_primary(), laptop);
+    /// ```
+    pub fn get_primary() -> WlcOutput {
+        dummy::primary_output()
+    }
+
+    /// Makes this output the primary output, see `get_primary`.
+    ///
+    /// Does nothing if the output is not part of the dummy compositor.
+    pub fn set_primary(&self) {
+        calls::called("WlcOutput::set_primary", self.0, &[]);
+        dummy::state::with(|state| if state.output(*self).is_some() {
+            state.primary_output = Some(*self);
+        });
     }
 
     /// Gets the name of the WlcOutput.
@@ -130,7 +214,7 @@ impl WlcOutput {
     /// Names are usually assigned in the format WLC-n,
     /// where the first output is WLC-1.
     pub fn get_name(&self) -> String {
-        "".to_string()
+        dummy::output_info(*self).map(|info| info.name).unwrap_or_default()
     }
 
     /// Gets the sleep status of the output.
@@ -138,23 +222,37 @@ impl WlcOutput {
     /// Returns `true` if the monitor is sleeping,
     /// such as having been set with `set_sleep`.
     pub fn get_sleep(&self) -> bool {
-        false
+        dummy::output_info(*self).is_some_and(|info| info.sleep)
     }
 
     /// Sets the sleep status of the output.
     pub fn set_sleep(&self, sleep: bool) {
+        calls::called("WlcOutput::set_sleep", self.0, &[&sleep]);
+        dummy::update_output(*self, |info| info.sleep = sleep);
     }
 
     /// Gets the output resolution in pixels.
     pub fn get_resolution(&self) -> Option<Size> {
-        Some(ZERO_RES)
+        dummy::output_info(*self).map(|info| info.resolution).or(Some(ZERO_RES))
     }
 
     /// Sets the resolution of the output.
     ///
-    /// # Safety
-    /// This method will crash the program if use when wlc is not running.
+    /// Invokes the `output_resolution` callback if the resolution changed.
+    /// The output may not support the resolution, see
+    /// `dummy::fail_resolution`. The dummy ignores `scaling`.
     pub fn set_resolution(&self, size: Size, scaling: u32) {
+        calls::called("WlcOutput::set_resolution", self.0, &[&size, &scaling]);
+        if let Some(size) = dummy::mode_set(*self, size) {
+            simulate::output_resolution(*self, size);
+        }
+    }
+
+    /// Gets information about the renderer wlc is using for this output.
+    ///
+    /// The reported strings can be changed with `dummy::set_context_info`.
+    pub fn get_context_info(&self) -> ContextInfo {
+        dummy::context_info(*self)
     }
 
     /// Get views in stack order.
@@ -163,17 +261,51 @@ impl WlcOutput {
     /// For example tiling wms, may want to use this to keep their tiling order separated
     /// from floating order.
     /// This handles `wlc_output_get_views` and `wlc_output_get_mutable_views`.
+    ///
+    /// Views are listed bottom first.
+    ///
+    /// The order only changes when views are added, removed, moved between
+    /// outputs or layers, or restacked with `set_views` or the stacking
+    /// methods of `WlcView`. Other views keep their order relative to each
+    /// other, and nothing else (geometry, masks, focus, state, or unmapping
+    /// and minimizing) changes it.
     pub fn get_views(&self) -> Vec<WlcView> {
-        Vec::new()
+        dummy::output_info(*self).map(|info| info.views).unwrap_or_default()
+    }
+
+    /// Gets the views which match a filter, in stack order.
+    ///
+    /// This is a dummy extension, which is faster than filtering
+    /// `get_views` with `dummy::view_info` on outputs with many views. Each
+    /// output keeps the views of each type in stack order, so a filter with
+    /// a type only looks at views of that type.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rustwlc::{dummy, Size, VIEW_BIT_POPUP};
+    /// use rustwlc::dummy::ViewFilter;
+    ///
+    /// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+    /// let window = dummy::add_view(output);
+    /// let popup = dummy::add_view(output);
+    /// popup.set_type(VIEW_BIT_POPUP, true);
+    /// let popups = ViewFilter { view_type: VIEW_BIT_POPUP, .. ViewFilter::default() };
+    /// assert_eq!(output.get_views_by_type(popups), vec![popup]);
+    /// assert_eq!(output.get_views_by_type(ViewFilter::default()), vec![window, popup]);
+    /// ```
+    pub fn get_views_by_type(&self, filter: ViewFilter) -> Vec<WlcView> {
+        dummy::views_matching(*self, filter)
     }
 
     /// Gets the mask of this output
     pub fn get_mask(&self) -> u32 {
-        0
+        dummy::output_info(*self).map(|info| info.mask).unwrap_or(0)
     }
 
     /// Sets the mask for this output
     pub fn set_mask(&self, mask: u32) {
+        calls::called("WlcOutput::set_mask", self.0, &[&mask]);
+        dummy::update_output(*self, |info| info.mask = mask);
     }
 
     /// # Deprecated
@@ -186,14 +318,21 @@ impl WlcOutput {
     ///
     /// Returns success if operation succeeded. An error will be returned
     /// if something went wrong or if wlc isn't running.
+    ///
+    /// Views are given bottom first. Views stay inside their `dummy::Layer`,
+    /// so the new order is only kept within each layer.
+    #[allow(clippy::ptr_arg)] // rustwlc's signature
     pub fn set_views(&self, views: &mut Vec<WlcView>) -> Result<(), &'static str> {
-        Err("Currently running dummy-rustwlc")
+        calls::called("WlcOutput::set_views", self.0, &[views]);
+        dummy::set_views(*self, views)
     }
 
     /// Focuses compositor on a specific output.
     ///
     /// Pass in Option::None for no focus.
     pub fn focus(output: Option<WlcOutput>) {
+        calls::called("WlcOutput::focus", 0, &[&output]);
+        simulate::focus_output(output);
     }
 }
 
@@ -205,22 +344,18 @@ impl WlcView {
     /// If for some reason a conversion between the two was required,
     /// this function could be called. If this is the case please submit
     /// a bug report.
+    ///
+    /// Calling a `WlcOutput` method which changes the compositor on the
+    /// result is misuse, see `dummy::set_strict`.
     pub fn as_output(self) -> WlcOutput {
-        WlcOutput::from(self)
+        WlcOutput::from_raw(self.into_raw())
     }
 
     /// Create a dummy WlcView for testing purposes.
     ///
     /// # Unsafety
-    /// The following methods on views may crash the program:
-    ///
-    /// - `WlcView::focus` if wlc is not running
-    /// - `WlcView::send_to_back` if wlc is not running
-    /// - `WlcView::send_below` if wlc is not running
-    /// - `WlcView::bring_above` if wlc is not running
-    /// - `WlcView::bring_to_font` if wlc is not running
-    ///
-    /// All other methods can be used on dummy views.
+    /// All methods can be used on dummy views. Views which were not created
+    /// with `dummy::add_view` have no state and can't be focused.
     ///
     /// # Note
     /// `WlcView::root()` is equivalent to `WlcView::dummy(0)`.
@@ -241,6 +376,38 @@ impl WlcView {
         WlcView(code as uintptr_t)
     }
 
+    /// Gets the view with a raw handle, i.e. one read back from an IPC
+    /// message.
+    ///
+    /// The handle isn't checked, see `try_from_raw`.
+    pub fn from_raw(raw: uintptr_t) -> WlcView {
+        WlcView(raw)
+    }
+
+    /// Gets the view with a raw handle, if it is the root or exists in the
+    /// dummy compositor.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rustwlc::{dummy, Size, WlcView};
+    ///
+    /// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+    /// let view = dummy::add_view(output);
+    /// assert_eq!(WlcView::try_from_raw(view.into_raw()), Some(view));
+    /// // The raw handle of the output isn't a view's
+    /// assert_eq!(WlcView::try_from_raw(output.into_raw()), None);
+    /// assert_eq!(WlcView::try_from_raw(0), Some(WlcView::root()));
+    /// ```
+    pub fn try_from_raw(raw: uintptr_t) -> Option<WlcView> {
+        let view = WlcView(raw);
+        if view.is_root() || dummy::view_info(view).is_some() { Some(view) } else { None }
+    }
+
+    /// Gets the raw handle of the view, i.e. to send it in an IPC message.
+    pub fn into_raw(self) -> uintptr_t {
+        self.0
+    }
+
     /// Returns a reference to the root window (desktop background).
     ///
     /// # Example
@@ -286,7 +453,7 @@ impl WlcView {
 
     /// Gets user-specified data.
     ///
-    /// # Unsafety
+    /// # Safety
     /// The wlc implementation of this method uses `void*` pointers
     /// for raw C data. This function will internaly do a conversion
     /// between the input `T` and a `libc::c_void`.
@@ -295,13 +462,19 @@ impl WlcView {
     /// such, usage of these functions requires an understanding of
     /// what data they will have. Please review wlc's usage of these
     /// functions before attempting to use them yourself.
+    ///
+    /// The dummy keeps the pointer given to `set_user_data` until the
+    /// handle is removed, and panics if none was given.
+    #[allow(clippy::mut_from_ref)] // rustwlc's signature
     pub unsafe fn get_user_data<T>(&self) -> &mut T {
-        unimplemented!()
+        let data = dummy::user_data(self.0)
+            .expect("WlcView::get_user_data: no user data was set");
+        &mut *(data as *mut T)
     }
 
     /// Sets user-specified data.
     ///
-    /// # Unsafety
+    /// # Safety
     /// The wlc implementation of this method uses `void*` pointers
     /// for raw C data. This function will internaly do a conversion
     /// between the input `T` and a `libc::c_void`.
@@ -311,7 +484,8 @@ impl WlcView {
     /// what data they will have. Please review wlc's usage of these
     /// functions before attempting to use them yourself.
     pub unsafe fn set_user_data<T>(&self, data: &T) {
-        unimplemented!()
+        calls::called("WlcView::set_user_data", self.0, &[&(data as *const T)]);
+        dummy::set_user_data(self.0, data as *const T as usize);
     }
 
     /// Closes this view.
@@ -321,116 +495,175 @@ impl WlcView {
     /// # Behavior
     /// This function will not do anything if `view.is_root()`.
     pub fn close(&self) {
+        calls::called("WlcView::close", self.0, &[]);
     }
 
     /// Gets the WlcOutput this view is currently part of.
     pub fn get_output(&self) -> WlcOutput {
-        WlcOutput::dummy(0)
+        dummy::view_info(*self).map(|info| info.output)
+            .unwrap_or(WlcOutput::dummy(0))
     }
 
     /// Sets the output that the view renders on.
     ///
     /// This may not be supported by wlc at this time.
     pub fn set_output(&self, output: WlcOutput) {
+        calls::called("WlcView::set_output", self.0, &[&output]);
+        dummy::move_view(*self, output);
     }
 
     /// Brings this view to focus.
     ///
     /// Can be called on `WlcView::root()` to lose all focus.
     pub fn focus(&self) {
+        calls::called("WlcView::focus", self.0, &[]);
+        simulate::focus_view(*self);
     }
 
     /// Sends the view to the back of the compositor
+    ///
+    /// The view stays above views in a lower `dummy::Layer`.
     pub fn send_to_back(&self) {
+        calls::called("WlcView::send_to_back", self.0, &[]);
+        dummy::restack(*self, Position::Bottom);
     }
 
     /// Sends this view underneath another.
+    ///
+    /// The view will not leave its `dummy::Layer` to do so. A view on
+    /// another output is ignored, and reported as a violation (see
+    /// `dummy::set_strict`).
     pub fn send_below(&self, other: WlcView) {
+        calls::called("WlcView::send_below", self.0, &[&other]);
+        dummy::restack(*self, Position::Below(other));
     }
 
     /// Brings this view above another.
+    ///
+    /// The view will not leave its `dummy::Layer` to do so. A view on
+    /// another output is ignored, and reported as a violation (see
+    /// `dummy::set_strict`).
     pub fn bring_above(&self, other: WlcView) {
+        calls::called("WlcView::bring_above", self.0, &[&other]);
+        dummy::restack(*self, Position::Above(other));
     }
 
     /// Brings this view to the front of the stack
     /// within its WlcOutput.
+    ///
+    /// The view stays below views in a higher `dummy::Layer`.
     pub fn bring_to_front(&self) {
+        calls::called("WlcView::bring_to_front", self.0, &[]);
+        dummy::restack(*self, Position::Top);
+    }
+
+    /// Gets the position of the view in its output's stack, as in
+    /// `WlcOutput::get_views`.
+    ///
+    /// The bottom view is at `0`. Returns `None` if the view doesn't exist.
+    pub fn get_z_index(&self) -> Option<usize> {
+        dummy::z_index(*self)
     }
 
     // TODO Get masks enum working properly
     /// Gets the current visibilty bitmask for the view.
     pub fn get_mask(&self) -> u32 {
-        0
+        dummy::view_info(*self).map(|info| info.mask).unwrap_or(0)
     }
 
     // TODO Get masks enum working properly
     /// Sets the visibilty bitmask for the view.
     pub fn set_mask(&self, mask: u32) {
+        calls::called("WlcView::set_mask", self.0, &[&mask]);
+        contract::mask_set(*self);
+        dummy::update_view(*self, |info| info.mask = mask);
     }
 
     /// Gets the geometry of the view.
+    ///
+    /// Returns `None` if the view is unmapped (see `dummy::set_mapped`) or
+    /// doesn't exist.
     pub fn get_geometry(&self) -> Option<Geometry> {
-        Some(Geometry {
-            origin: Point { x: 0, y: 0},
-            size:   Size  { w: 0, h: 0}
-        })
+        dummy::view_info(*self).filter(|info| info.mapped).map(|info| info.geometry)
     }
 
     /// Gets the geometry of the view (that wlc displays).
     pub fn get_visible_geometry(&self) -> Geometry {
-        let geo = Geometry { origin: Point { x: 0, y: 0}, size: Size { w: 0, h: 0 }};
-        return geo;
+        dummy::view_info(*self).map(|info| info.geometry).unwrap_or_default()
     }
 
     /// Sets the geometry of the view.
     ///
     /// Set edges if geometry is caused by interactive resize.
+    ///
+    /// If the dummy delays configures (see `dummy::set_configure_delay`),
+    /// the geometry changes once the client acks. The view's output is
+    /// scheduled for rendering when the geometry changes.
     pub fn set_geometry(&self, edges: ResizeEdge, geometry: Geometry) {
+        calls::called("WlcView::set_geometry", self.0, &[&edges, &geometry]);
+        dummy::apply_layout(&[(*self, geometry)]);
     }
 
     /// Gets the type bitfield of the curent view
     pub fn get_type(&self) -> ViewType {
-        ViewType::empty()
+        dummy::view_info(*self).map_or(ViewType::empty(), |info| info.view_type)
     }
 
     /// Set flag in the type field. Toggle indicates whether it is set.
     pub fn set_type(&self, view_type: ViewType, toggle: bool) {
+        calls::called("WlcView::set_type", self.0, &[&view_type, &toggle]);
+        dummy::set_view_type(*self, view_type, toggle);
     }
 
     // TODO get bitflags enums
     /// Get the current ViewState bitfield.
     pub fn get_state(&self) -> ViewState {
-        ViewState::empty()
+        dummy::view_info(*self).map_or(ViewState::empty(), |info| info.state)
     }
 
     /// Set ViewState bit. Toggle indicates whether it is set or not.
     pub fn set_state(&self, state: ViewState, toggle: bool) {
+        calls::called("WlcView::set_state", self.0, &[&state, &toggle]);
+        dummy::update_view(*self, |info| if toggle {
+            info.state.insert(state)
+        } else {
+            info.state.remove(state)
+        });
+        dummy::answer_request(*self, ClientRequest::State(state, toggle));
     }
 
     /// Gets parent view, returns `WlcView::root()` if this view has no parent.
     pub fn get_parent(&self) -> WlcView {
-        WlcView::root()
+        dummy::view_info(*self).map_or(WlcView::root(), |info| info.parent)
     }
 
     /// Set the parent of this view.
     ///
     /// Call with `WlcView::root()` to make its parent the root window.
     pub fn set_parent(&self, parent: WlcView) {
+        calls::called("WlcView::set_parent", self.0, &[&parent]);
+        dummy::update_view(*self, |info| info.parent = parent);
     }
 
     /// Get the title of the view
+    ///
+    /// Set by clients, or in tests with `dummy::set_title`.
     pub fn get_title(&self) -> String {
-        "".to_string()
+        dummy::view_info(*self).map(|info| info.title).unwrap_or_default()
     }
 
     /// Get class (shell surface only).
+    ///
+    /// Set by clients, or in tests with `dummy::set_class`.
     pub fn get_class(&self) -> String {
-        "".to_string()
+        dummy::view_info(*self).map(|info| info.class).unwrap_or_default()
     }
 
     /// Get app id (xdg-surface only).
+    ///
+    /// Set by clients, or in tests with `dummy::set_app_id`.
     pub fn get_app_id(&self) -> String {
-        "".to_string()
+        dummy::view_info(*self).map(|info| info.app_id).unwrap_or_default()
     }
 }
 
@@ -451,15 +684,15 @@ mod tests {
         let output = dummy.get_output();
         assert!(output == WlcOutput::dummy(0));
         dummy.set_output(output);
-        // dummy.focus(); // SEGFAULTS
-        // dummy.send_to_back();
-        // dummy.send_below(&dummy);
-        // dummy.bring_above(&dummy);
-        // dummy.bring_to_front();
+        dummy.focus();
+        dummy.send_to_back();
+        dummy.send_below(dummy);
+        dummy.bring_above(dummy);
+        dummy.bring_to_front();
         let mask = dummy.get_mask();
         dummy.set_mask(mask);
-        let geometry = dummy.get_geometry();
-        dummy.set_geometry(EDGE_NONE, &Geometry {
+        assert_eq!(dummy.get_geometry(), None);
+        dummy.set_geometry(EDGE_NONE, Geometry {
             origin: Point { x: 0, y: 0 },
             size: Size { w: 0, h: 0 }
         });
@@ -474,6 +707,21 @@ mod tests {
         dummy.set_parent(parent);
     }
 
+    #[test]
+    fn view_geometry() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let view = dummy::add_view(output);
+        let geometry = Geometry { origin: Point { x: 5, y: 5 }, size: Size { w: 10, h: 10 } };
+        view.set_geometry(EDGE_NONE, geometry);
+        assert_eq!(view.get_geometry(), Some(geometry));
+        dummy::set_mapped(view, false);
+        assert_eq!(view.get_geometry(), None);
+        assert_eq!(view.get_visible_geometry(), geometry);
+        dummy::set_mapped(view, true);
+        dummy::remove_view(view);
+        assert_eq!(view.get_geometry(), None);
+    }
+
     #[test]
     fn dummy_outputs() {
         let dummy = WlcOutput::dummy(1);
@@ -491,4 +739,20 @@ mod tests {
         dummy.set_mask(mask);
         WlcOutput::focus(Some(dummy));
     }
+
+    #[test]
+    fn cross_kind() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let view = dummy::add_view(output);
+        assert_eq!(view.as_output().as_view(), view);
+        view.as_output().set_mask(2);
+        output.as_view().set_mask(2);
+        // Unknown handles could be either
+        WlcView::dummy(100).set_mask(2);
+        assert_eq!(dummy::violations(), vec![
+            format!("WlcOutput::set_mask was called on {:?}", view),
+            format!("WlcView::set_mask was called on {:?}", output)
+        ]);
+        assert_eq!(output.get_mask(), 1);
+    }
 }
@@ -0,0 +1,148 @@
+//! Evdev keycodes, as given to the `keyboard_key` callback.
+//!
+//! wlc reports keys by their Linux evdev code, independent of the keymap.
+//! These are the codes from `linux/input-event-codes.h`, with the same
+//! names, so handlers and tests don't need magic numbers. To find what a
+//! key produces in the current keymap, see
+//! `input::keyboard::get_keysym_for_key`.
+//!
+//! Pointer buttons share the same code space, so the `BTN_` codes given to
+//! the `pointer_button` callback are here too.
+//!
+//! # Example
+//! ```rust
+//! use rustwlc::input::keys::{self, Key};
+//!
+//! assert_eq!(Key::from(28), keys::KEY_ENTER);
+//! assert_eq!(u32::from(keys::KEY_F1), 59);
+//! assert_eq!(keys::KEY_Q.get_code(), 16);
+//! ```
+
+#![allow(missing_docs)]
+
+/// An evdev keycode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Key(u32);
+
+impl Key {
+    /// Gets the `Key` as a `u32`.
+    pub fn get_code(&self) -> u32 {
+        self.0
+    }
+}
+
+impl From<u32> for Key {
+    fn from(code: u32) -> Key {
+        Key(code)
+    }
+}
+
+impl From<Key> for u32 {
+    fn from(key: Key) -> u32 {
+        key.0
+    }
+}
+
+pub const KEY_ESC: Key = Key(1);
+pub const KEY_1: Key = Key(2);
+pub const KEY_2: Key = Key(3);
+pub const KEY_3: Key = Key(4);
+pub const KEY_4: Key = Key(5);
+pub const KEY_5: Key = Key(6);
+pub const KEY_6: Key = Key(7);
+pub const KEY_7: Key = Key(8);
+pub const KEY_8: Key = Key(9);
+pub const KEY_9: Key = Key(10);
+pub const KEY_0: Key = Key(11);
+pub const KEY_MINUS: Key = Key(12);
+pub const KEY_EQUAL: Key = Key(13);
+pub const KEY_BACKSPACE: Key = Key(14);
+pub const KEY_TAB: Key = Key(15);
+pub const KEY_Q: Key = Key(16);
+pub const KEY_W: Key = Key(17);
+pub const KEY_E: Key = Key(18);
+pub const KEY_R: Key = Key(19);
+pub const KEY_T: Key = Key(20);
+pub const KEY_Y: Key = Key(21);
+pub const KEY_U: Key = Key(22);
+pub const KEY_I: Key = Key(23);
+pub const KEY_O: Key = Key(24);
+pub const KEY_P: Key = Key(25);
+pub const KEY_LEFTBRACE: Key = Key(26);
+pub const KEY_RIGHTBRACE: Key = Key(27);
+pub const KEY_ENTER: Key = Key(28);
+pub const KEY_LEFTCTRL: Key = Key(29);
+pub const KEY_A: Key = Key(30);
+pub const KEY_S: Key = Key(31);
+pub const KEY_D: Key = Key(32);
+pub const KEY_F: Key = Key(33);
+pub const KEY_G: Key = Key(34);
+pub const KEY_H: Key = Key(35);
+pub const KEY_J: Key = Key(36);
+pub const KEY_K: Key = Key(37);
+pub const KEY_L: Key = Key(38);
+pub const KEY_SEMICOLON: Key = Key(39);
+pub const KEY_APOSTROPHE: Key = Key(40);
+pub const KEY_GRAVE: Key = Key(41);
+pub const KEY_LEFTSHIFT: Key = Key(42);
+pub const KEY_BACKSLASH: Key = Key(43);
+pub const KEY_Z: Key = Key(44);
+pub const KEY_X: Key = Key(45);
+pub const KEY_C: Key = Key(46);
+pub const KEY_V: Key = Key(47);
+pub const KEY_B: Key = Key(48);
+pub const KEY_N: Key = Key(49);
+pub const KEY_M: Key = Key(50);
+pub const KEY_COMMA: Key = Key(51);
+pub const KEY_DOT: Key = Key(52);
+pub const KEY_SLASH: Key = Key(53);
+pub const KEY_RIGHTSHIFT: Key = Key(54);
+pub const KEY_KPASTERISK: Key = Key(55);
+pub const KEY_LEFTALT: Key = Key(56);
+pub const KEY_SPACE: Key = Key(57);
+pub const KEY_CAPSLOCK: Key = Key(58);
+pub const KEY_F1: Key = Key(59);
+pub const KEY_F2: Key = Key(60);
+pub const KEY_F3: Key = Key(61);
+pub const KEY_F4: Key = Key(62);
+pub const KEY_F5: Key = Key(63);
+pub const KEY_F6: Key = Key(64);
+pub const KEY_F7: Key = Key(65);
+pub const KEY_F8: Key = Key(66);
+pub const KEY_F9: Key = Key(67);
+pub const KEY_F10: Key = Key(68);
+pub const KEY_NUMLOCK: Key = Key(69);
+pub const KEY_SCROLLLOCK: Key = Key(70);
+pub const KEY_F11: Key = Key(87);
+pub const KEY_F12: Key = Key(88);
+pub const KEY_RIGHTCTRL: Key = Key(97);
+pub const KEY_SYSRQ: Key = Key(99);
+pub const KEY_RIGHTALT: Key = Key(100);
+pub const KEY_HOME: Key = Key(102);
+pub const KEY_UP: Key = Key(103);
+pub const KEY_PAGEUP: Key = Key(104);
+pub const KEY_LEFT: Key = Key(105);
+pub const KEY_RIGHT: Key = Key(106);
+pub const KEY_END: Key = Key(107);
+pub const KEY_DOWN: Key = Key(108);
+pub const KEY_PAGEDOWN: Key = Key(109);
+pub const KEY_INSERT: Key = Key(110);
+pub const KEY_DELETE: Key = Key(111);
+pub const KEY_MUTE: Key = Key(113);
+pub const KEY_VOLUMEDOWN: Key = Key(114);
+pub const KEY_VOLUMEUP: Key = Key(115);
+pub const KEY_POWER: Key = Key(116);
+pub const KEY_PAUSE: Key = Key(119);
+pub const KEY_LEFTMETA: Key = Key(125);
+pub const KEY_RIGHTMETA: Key = Key(126);
+pub const KEY_COMPOSE: Key = Key(127);
+pub const KEY_NEXTSONG: Key = Key(163);
+pub const KEY_PLAYPAUSE: Key = Key(164);
+pub const KEY_PREVIOUSSONG: Key = Key(165);
+pub const KEY_BRIGHTNESSDOWN: Key = Key(224);
+pub const KEY_BRIGHTNESSUP: Key = Key(225);
+pub const BTN_LEFT: Key = Key(0x110);
+pub const BTN_RIGHT: Key = Key(0x111);
+pub const BTN_MIDDLE: Key = Key(0x112);
+pub const BTN_SIDE: Key = Key(0x113);
+pub const BTN_EXTRA: Key = Key(0x114);
@@ -0,0 +1,98 @@
+//! Contains methods for interacting with the pointer
+//! and keyboard of wlc.
+
+pub mod keys;
+pub mod seat;
+
+pub use self::seat::Seat;
+
+pub mod pointer {
+//! Methods for interacting with the mouse
+    use super::super::types::{Point};
+    use super::Seat;
+
+    /// Gets the current position of the mouse.
+    pub fn get_position() -> Point {
+        Seat::default().pointer().position()
+    }
+
+    /// Sets the current mouse position. Required on mouse move callback.
+    ///
+    /// The dummy records each call in `dummy::warps`.
+    pub fn set_position(point: Point) {
+        Seat::default().pointer().set_position(point)
+    }
+
+    /// Gets the codes of the held buttons, in the order they were pressed.
+    ///
+    /// The dummy tracks the buttons pressed and released with
+    /// `simulate::pointer_button`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rustwlc::{dummy, simulate, ButtonState, Size};
+    /// use rustwlc::input::keys::{BTN_LEFT, BTN_RIGHT};
+    /// use rustwlc::input::pointer;
+    ///
+    /// dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+    /// simulate::pointer_button(BTN_RIGHT, ButtonState::Pressed);
+    /// simulate::pointer_button(BTN_LEFT, ButtonState::Pressed);
+    /// simulate::pointer_button(BTN_RIGHT, ButtonState::Released);
+    /// assert_eq!(pointer::get_held_buttons(), vec![BTN_LEFT.get_code()]);
+    /// ```
+    pub fn get_held_buttons() -> Vec<u32> {
+        Seat::default().pointer().held_buttons().iter().map(|button| button.get_code()).collect()
+    }
+}
+
+pub mod keyboard {
+//! Methods for interacting with the keyboard
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    use super::super::types::{KeyboardModifiers};
+    use super::super::xkb::Keysym;
+    use super::Seat;
+
+    thread_local! {
+        /// Each set of held keys given out, see `get_current_keys`.
+        static CURRENT_KEYS: RefCell<HashMap<Vec<u32>, &'static [u32]>> =
+            RefCell::new(HashMap::new());
+    }
+
+    /// Get currently held keys.
+    ///
+    /// The dummy gives the codes of the keys held on the default seat, in
+    /// the order they were pressed. The slices are never freed, as they
+    /// may live forever, so each set of held keys is kept once per thread.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rustwlc::{simulate, KeyState};
+    /// use rustwlc::input::{keyboard, keys};
+    ///
+    /// simulate::keyboard_key(keys::KEY_LEFTCTRL, KeyState::Pressed);
+    /// simulate::keyboard_key(keys::KEY_C, KeyState::Pressed);
+    /// assert_eq!(keyboard::get_current_keys(), &[29, 46]);
+    /// ```
+    pub fn get_current_keys<'a>() -> &'a[u32] {
+        let keys: Vec<u32> = Seat::default().keyboard().held_keys().iter()
+            .map(|key| key.get_code()).collect();
+        CURRENT_KEYS.with(|current| *current.borrow_mut().entry(keys.clone())
+                          .or_insert_with(|| Box::leak(keys.into_boxed_slice())))
+    }
+
+    /// Gets a keysym given a key and modifiers.
+    ///
+    /// The dummy uses the default seat's keymap, see `seat::Keyboard::keysym`.
+    pub fn get_keysym_for_key(key: u32, modifiers: KeyboardModifiers) -> Keysym {
+        Seat::default().keyboard().keysym(key.into(), modifiers)
+    }
+
+    /// Gets a UTF32 value for a given key and modifiers.
+    ///
+    /// The dummy uses the default seat's keymap, see `seat::Keyboard::utf32`.
+    pub fn get_utf32_for_key(key: u32, modifiers: KeyboardModifiers) -> u32 {
+        Seat::default().keyboard().utf32(key.into(), modifiers)
+    }
+}
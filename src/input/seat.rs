@@ -0,0 +1,297 @@
+//! The seat: the keyboard, pointer, and touchscreen of one user.
+//!
+//! wlc has a single seat, `seat0`, which `Seat::default()` gives. Each of
+//! its devices can be queried and given input, which is injected as with
+//! the `simulate` module. The free functions in `input::pointer` and
+//! `input::keyboard` use the default seat.
+//!
+//! # Example
+//! ```rust
+//! use rustwlc::{dummy, ButtonState, KeyState, Point, Size};
+//! use rustwlc::input::Seat;
+//! use rustwlc::input::keys::{self, BTN_LEFT};
+//!
+//! let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+//! let view = dummy::add_view(output);
+//! let seat = Seat::default();
+//! seat.keyboard().key(keys::KEY_LEFTSHIFT, KeyState::Pressed);
+//! seat.pointer().set_position(Point { x: 10, y: 10 });
+//! seat.pointer().button(BTN_LEFT, ButtonState::Pressed);
+//! seat.touch().down(0, Point { x: 20, y: 20 });
+//! assert_eq!(seat.keyboard().held_keys(), vec![keys::KEY_LEFTSHIFT]);
+//! assert_eq!(seat.pointer().held_buttons(), vec![BTN_LEFT]);
+//! assert_eq!(seat.touch().points(), vec![(0, Point { x: 20, y: 20 })]);
+//! ```
+
+use super::super::dummy::{self, state, Warp};
+use super::super::event::ScrollSource;
+use super::super::handle::WlcView;
+use super::super::simulate;
+use super::super::types::{ButtonState, KeyboardModifiers, KeyState, Point, TouchType,
+                          MOD_CAPS, MOD_SHIFT};
+use super::super::user;
+use super::super::xkb::Keysym;
+use super::super::xkb::keysyms;
+use super::keys::{self, Key};
+
+/// A seat, see the module documentation.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Seat {
+    _private: ()
+}
+
+impl Seat {
+    /// Gets the name of the seat.
+    pub fn name(&self) -> &'static str {
+        "seat0"
+    }
+
+    /// Gets the seat's keyboard.
+    pub fn keyboard(&self) -> Keyboard {
+        Keyboard { _private: () }
+    }
+
+    /// Gets the seat's pointer.
+    pub fn pointer(&self) -> Pointer {
+        Pointer { _private: () }
+    }
+
+    /// Gets the seat's touchscreen.
+    pub fn touch(&self) -> Touch {
+        Touch { _private: () }
+    }
+}
+
+/// The keyboard of a seat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keyboard {
+    _private: ()
+}
+
+impl Keyboard {
+    /// Gets the held keys, in the order they were pressed.
+    pub fn held_keys(&self) -> Vec<Key> {
+        state::with(|state| state.held_keys.clone())
+    }
+
+    /// Gets the modifiers, as given to the `keyboard_key` callback.
+    pub fn modifiers(&self) -> KeyboardModifiers {
+        dummy::modifier_state().keyboard_modifiers()
+    }
+
+    /// Gets the view with keyboard focus, see `dummy::keyboard_focus`.
+    pub fn focus(&self) -> WlcView {
+        dummy::keyboard_focus()
+    }
+
+    /// Presses or releases a key, see `simulate::keyboard_key`.
+    pub fn key(&self, key: Key, state: KeyState) -> bool {
+        simulate::keyboard_key(key, state)
+    }
+
+    /// Gets the keysym a key gives with some modifiers.
+    ///
+    /// The dummy's keymap is US QWERTY. Shift and caps lock are applied;
+    /// keys which type nothing, other than escape and backspace, give
+    /// `KEY_NoSymbol`.
+    pub fn keysym(&self, key: Key, modifiers: KeyboardModifiers) -> Keysym {
+        let shift = modifiers.mods.contains(MOD_SHIFT);
+        match user::char_for(key, shift) {
+            Some('\n') => keysyms::KEY_Return,
+            Some('\t') => keysyms::KEY_Tab,
+            Some(c) if c.is_ascii_alphabetic() && modifiers.mods.contains(MOD_CAPS) => {
+                let swapped = if shift { c.to_ascii_lowercase() } else { c.to_ascii_uppercase() };
+                Keysym::from(swapped as u32)
+            },
+            Some(c) => Keysym::from(c as u32),
+            None if key == keys::KEY_ESC => keysyms::KEY_Escape,
+            None if key == keys::KEY_BACKSPACE => keysyms::KEY_BackSpace,
+            None => keysyms::KEY_NoSymbol
+        }
+    }
+
+    /// Gets the UTF-32 character a key types with some modifiers, or `0`
+    /// if it types none, see `keysym`.
+    pub fn utf32(&self, key: Key, modifiers: KeyboardModifiers) -> u32 {
+        let keysym = self.keysym(key, modifiers);
+        match keysym {
+            keysyms::KEY_Return => '\r' as u32,
+            keysyms::KEY_Tab => '\t' as u32,
+            keysyms::KEY_Escape => 0x1b,
+            keysyms::KEY_BackSpace => 0x08,
+            _ => keysym.get_code()
+        }
+    }
+}
+
+/// The pointer of a seat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pointer {
+    _private: ()
+}
+
+impl Pointer {
+    /// Gets the position of the pointer.
+    pub fn position(&self) -> Point {
+        state::with(|state| state.pointer)
+    }
+
+    /// Moves the pointer, as compositors do in the `pointer_motion`
+    /// callback.
+    ///
+    /// The dummy records each call in `dummy::warps`.
+    pub fn set_position(&self, point: Point) {
+        state::with(|state| {
+            let origin = state.dispatching.last().cloned();
+            state.warps.push(Warp { point, time: state.time, origin });
+            state.pointer = point;
+        });
+    }
+
+    /// Gets the held buttons, in the order they were pressed.
+    pub fn held_buttons(&self) -> Vec<Key> {
+        state::with(|state| state.held_buttons.clone())
+    }
+
+    /// Gets the view with pointer focus, see `dummy::pointer_focus`.
+    pub fn focus(&self) -> WlcView {
+        dummy::pointer_focus()
+    }
+
+    /// Moves the pointer by hand, see `simulate::pointer_motion`.
+    pub fn motion(&self, point: Point) -> bool {
+        simulate::pointer_motion(point)
+    }
+
+    /// Presses or releases a button, see `simulate::pointer_button`.
+    pub fn button(&self, button: Key, state: ButtonState) -> bool {
+        simulate::pointer_button(button, state)
+    }
+
+    /// Scrolls, see `simulate::pointer_scroll`.
+    pub fn scroll(&self, source: ScrollSource, amount: [f64; 2]) -> bool {
+        simulate::pointer_scroll(source, amount)
+    }
+}
+
+/// The touchscreen of a seat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Touch {
+    _private: ()
+}
+
+impl Touch {
+    /// Gets the slots of the points held down and where they are, in the
+    /// order they touched.
+    pub fn points(&self) -> Vec<(i32, Point)> {
+        state::with(|state| state.touch_points.clone())
+    }
+
+    /// Touches a point with a slot, see `simulate::touch`.
+    pub fn down(&self, slot: i32, point: Point) -> bool {
+        simulate::touch(TouchType::Down, slot, point)
+    }
+
+    /// Moves a slot's point, see `simulate::touch`.
+    pub fn motion(&self, slot: i32, point: Point) -> bool {
+        simulate::touch(TouchType::Motion, slot, point)
+    }
+
+    /// Lifts a slot's point where it last was, see `simulate::touch`.
+    ///
+    /// Does nothing, returning `false`, if the slot isn't held down.
+    pub fn up(&self, slot: i32) -> bool {
+        match self.points().into_iter().find(|&(other, _)| other == slot) {
+            Some((_, point)) => simulate::touch(TouchType::Up, slot, point),
+            None => false
+        }
+    }
+
+    /// Ends a group of touches which happened at once, see
+    /// `simulate::touch`.
+    pub fn frame(&self) -> bool {
+        simulate::touch(TouchType::Frame, 0, Point { x: 0, y: 0 })
+    }
+
+    /// Cancels the touches, lifting all points, see `simulate::touch`.
+    pub fn cancel(&self) -> bool {
+        simulate::touch(TouchType::Cancel, 0, Point { x: 0, y: 0 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::*;
+    use super::super::super::input::Seat;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn touch() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let left = dummy::add_view(output);
+        let right = dummy::add_view(output);
+        left.set_geometry(EDGE_NONE, Geometry {
+            origin: Point { x: 0, y: 0 }, size: Size { w: 400, h: 600 }
+        });
+        right.set_geometry(EDGE_NONE, Geometry {
+            origin: Point { x: 400, y: 0 }, size: Size { w: 400, h: 600 }
+        });
+        let touches = Rc::new(RefCell::new(Vec::new()));
+        let log = touches.clone();
+        callback::rust::touch(move |view, _, _, touch, slot, point| {
+            log.borrow_mut().push((view, touch, slot, *point));
+            touch == TouchType::Down
+        });
+        let touch = Seat::default().touch();
+        assert!(touch.down(0, Point { x: 10, y: 10 }));
+        touch.down(1, Point { x: 500, y: 10 });
+        touch.frame();
+        touch.motion(0, Point { x: 20, y: 10 });
+        assert_eq!(touch.points(), vec![(0, Point { x: 20, y: 10 }), (1, Point { x: 500, y: 10 })]);
+        touch.up(1);
+        assert!(!touch.up(1));
+        assert_eq!(touch.points(), vec![(0, Point { x: 20, y: 10 })]);
+        touch.cancel();
+        assert!(touch.points().is_empty());
+        let origin = Point { x: 0, y: 0 };
+        assert_eq!(*touches.borrow(), vec![
+            (left, TouchType::Down, 0, Point { x: 10, y: 10 }),
+            (right, TouchType::Down, 1, Point { x: 500, y: 10 }),
+            (WlcView::root(), TouchType::Frame, 0, origin),
+            (left, TouchType::Motion, 0, Point { x: 20, y: 10 }),
+            (right, TouchType::Up, 1, Point { x: 500, y: 10 }),
+            (WlcView::root(), TouchType::Cancel, 0, origin)
+        ]);
+        // The free functions use the default seat
+        input::pointer::set_position(Point { x: 5, y: 5 });
+        assert_eq!(Seat::default().pointer().position(), Point { x: 5, y: 5 });
+        assert_eq!(Seat::default().name(), "seat0");
+    }
+
+    #[test]
+    fn keyboard() {
+        use super::super::super::xkb::{keysyms, Keysym};
+        let keyboard = Seat::default().keyboard();
+        keyboard.key(input::keys::KEY_LEFTSHIFT, KeyState::Pressed);
+        keyboard.key(input::keys::KEY_A, KeyState::Pressed);
+        // The free functions read the default seat
+        assert_eq!(input::keyboard::get_current_keys(), &[42, 30]);
+        let modifiers = keyboard.modifiers();
+        assert_eq!(input::keyboard::get_keysym_for_key(30, modifiers), Keysym::from('A' as u32));
+        assert_eq!(input::keyboard::get_utf32_for_key(30, modifiers), 'A' as u32);
+        keyboard.key(input::keys::KEY_LEFTSHIFT, KeyState::Released);
+        assert_eq!(input::keyboard::get_current_keys(), &[30]);
+        keyboard.key(input::keys::KEY_A, KeyState::Released);
+        assert!(input::keyboard::get_current_keys().is_empty());
+        let none = KeyboardModifiers { leds: KeyboardLed::empty(), mods: KeyMod::empty() };
+        let caps = KeyboardModifiers { leds: KeyboardLed::empty(), mods: MOD_CAPS };
+        assert_eq!(keyboard.utf32(input::keys::KEY_A, none), 'a' as u32);
+        assert_eq!(keyboard.utf32(input::keys::KEY_A, caps), 'A' as u32);
+        assert_eq!(keyboard.utf32(input::keys::KEY_1, caps), '1' as u32);
+        assert_eq!(keyboard.keysym(input::keys::KEY_ENTER, none), keysyms::KEY_Return);
+        assert_eq!(keyboard.utf32(input::keys::KEY_ENTER, none), '\r' as u32);
+        assert_eq!(keyboard.keysym(input::keys::KEY_LEFTCTRL, none), keysyms::KEY_NoSymbol);
+        assert_eq!(keyboard.utf32(input::keys::KEY_LEFTCTRL, none), 0);
+    }
+}
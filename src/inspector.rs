@@ -0,0 +1,162 @@
+//! A live view of the dummy compositor in the terminal.
+//!
+//! Running a compositor against the dummy means never seeing its screen.
+//! With the `inspector` feature, an `Inspector` takes over the terminal and
+//! draws each output with the rectangles of its visible views, the focused
+//! view, and the latest simulated events, so a developer can watch what
+//! their compositor does, i.e. while driving it with a `script::Watcher`.
+//!
+//! Outputs are drawn side by side, scaled to fit the terminal, as
+//! `raster::ascii` draws them.
+//!
+//! # Example
+//! ```rust,no_run
+//! use rustwlc::inspector::Inspector;
+//! use rustwlc::script::Watcher;
+//! use std::io;
+//! use std::thread;
+//! use std::time::Duration;
+//!
+//! // Register the compositor's callbacks here
+//! let mut watcher = Watcher::new("scenario.txt");
+//! let mut inspector = Inspector::new(io::stdout()).unwrap();
+//! loop {
+//!     watcher.poll().unwrap();
+//!     inspector.draw().unwrap();
+//!     thread::sleep(Duration::from_millis(100));
+//! }
+//! ```
+
+use std::io::{self, Write};
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+
+use super::dummy::{self, state};
+use super::event::WlcEvent;
+use super::handle::WlcOutput;
+use super::input::pointer;
+use super::raster;
+use super::types::Size;
+
+/// Number of events kept for the log.
+const LOG_SIZE: usize = 64;
+
+/// Most rows the log of events takes up.
+const LOG_ROWS: usize = 8;
+
+/// Draws the dummy compositor on a terminal.
+///
+/// The terminal is switched to its alternate screen while the inspector
+/// exists, and switched back when it is dropped.
+#[derive(Debug)]
+pub struct Inspector<W: Write> {
+    out: W
+}
+
+impl<W: Write> Inspector<W> {
+    /// Takes over a terminal.
+    pub fn new(mut out: W) -> io::Result<Inspector<W>> {
+        crossterm::execute!(out, EnterAlternateScreen, Hide)?;
+        Ok(Inspector { out })
+    }
+
+    /// Draws the current state, filling the terminal.
+    pub fn draw(&mut self) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        for (row, line) in render(width as usize, height as usize).iter().enumerate() {
+            crossterm::queue!(self.out, MoveTo(0, row as u16), Print(line),
+                              Clear(ClearType::UntilNewLine))?;
+        }
+        self.out.flush()
+    }
+}
+
+impl<W: Write> Drop for Inspector<W> {
+    fn drop(&mut self) {
+        let _ = crossterm::execute!(self.out, Show, LeaveAlternateScreen);
+    }
+}
+
+/// Renders the current state as `height` lines of at most `width`
+/// characters, as `Inspector::draw` shows it.
+pub fn render(width: usize, height: usize) -> Vec<String> {
+    let events: Vec<WlcEvent> = state::with(|state| state.event_log.iter().cloned().collect());
+    let log_rows = LOG_ROWS.min(height / 3);
+    let output_rows = height.saturating_sub(1 + log_rows);
+    let mut lines = vec![truncate(&status(), width)];
+    let outputs = WlcOutput::list();
+    let mut area = vec![Vec::new(); output_rows];
+    if !outputs.is_empty() {
+        let column = width.saturating_sub(outputs.len() - 1) / outputs.len();
+        for (index, &output) in outputs.iter().enumerate() {
+            let size = Size { w: column as u32, h: output_rows as u32 };
+            for (row, line) in raster::ascii(output, size).into_iter().enumerate() {
+                if index > 0 {
+                    area[row].push(' ');
+                }
+                area[row].extend(line.chars());
+            }
+        }
+    }
+    lines.extend(area.into_iter().map(|row| row.into_iter().collect()));
+    if log_rows > 0 {
+        lines.push(truncate("events:", width));
+        let shown = events.len().min(log_rows - 1);
+        for event in &events[events.len() - shown..] {
+            lines.push(truncate(&format!("  {:?}", event), width));
+        }
+    }
+    lines.resize(height, String::new());
+    lines
+}
+
+/// Notes a simulated event for the log.
+pub(crate) fn log(event: WlcEvent) {
+    state::with(|state| {
+        if state.event_log.len() == LOG_SIZE {
+            state.event_log.pop_front();
+        }
+        state.event_log.push_back(event);
+    });
+}
+
+/// The line about focus, the pointer and the time.
+fn status() -> String {
+    let focused = dummy::focused_view();
+    format!("focused: {}  pointer: {}  time: {}ms",
+            if focused.is_root() { "none".to_string() } else { raster::label(focused) },
+            pointer::get_position(), dummy::time())
+}
+
+/// Cuts a line down to a width.
+fn truncate(line: &str, width: usize) -> String {
+    line.chars().take(width).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn render() {
+        let output = simulate::connect_output("WLC-1", Size { w: 800, h: 600 });
+        let view = dummy::add_view(output);
+        dummy::set_title(view, "xterm");
+        view.set_geometry(EDGE_NONE, Geometry {
+            origin: Point { x: 0, y: 0 },
+            size: Size { w: 400, h: 300 }
+        });
+        view.focus();
+        let lines = inspector::render(20, 12);
+        assert_eq!(lines.len(), 12);
+        assert_eq!(lines[0], "focused: xterm  poin");
+        assert_eq!(lines[1], "WLC-1 800 x 600     ");
+        assert_eq!(lines[2], "#xterm####          ");
+        assert_eq!(lines[3], "#        #          ");
+        assert_eq!(lines[4], "##########          ");
+        assert_eq!(lines[8], "events:");
+        assert_eq!(lines[9], "  OutputCreated(WlcO");
+    }
+}
@@ -0,0 +1,128 @@
+//! Invariants of a compositor, checked after every simulated event.
+//!
+//! A test which only checks the state at its end misses a compositor
+//! which breaks its rules halfway through and then recovers. Invariants
+//! added here are checked after every event injected through the
+//! `simulate` module once its callbacks have run, including each step of a
+//! scenario from the `scenarios` module. The first one which doesn't hold
+//! panics with its name and the offending event, the index of that event
+//! among those simulated since the first invariant was added, the latest
+//! events, and the views of each output.
+//!
+//! Events which callbacks cause are checked as part of the event which
+//! caused them, since the compositor may break an invariant while it
+//! handles one.
+//!
+//! # Example
+//! ```rust,should_panic
+//! use rustwlc::{callback, dummy, invariants, simulate, Size, WlcView};
+//!
+//! // Forgets to give the view VIEW_ACTIVATED
+//! fn view_created(view: WlcView) -> bool {
+//!     view.focus();
+//!     true
+//! }
+//!
+//! invariants::add("focused view is activated", invariants::focused_view_activated);
+//! let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+//! callback::rust::view_created(view_created);
+//! simulate::create_view();
+//! ```
+
+use std::rc::Rc;
+
+use super::callback::Callback;
+use super::dummy::{self, state};
+use super::event::WlcEvent;
+use super::handle::WlcOutput;
+use super::types::VIEW_ACTIVATED;
+
+/// An invariant added with `add`.
+#[derive(Debug, Clone)]
+pub(crate) struct Invariant {
+    pub name: String,
+    pub holds: Callback<dyn Fn() -> bool>
+}
+
+/// Adds an invariant, which must return `true` after every event.
+pub fn add<F>(name: &str, holds: F) where F: Fn() -> bool + 'static {
+    state::with(|state| state.invariants.push(Invariant {
+        name: name.to_string(),
+        holds: Callback(Rc::new(holds))
+    }));
+}
+
+/// Removes all invariants, and starts counting events from zero again.
+pub fn clear() {
+    state::with(|state| {
+        state.invariants.clear();
+        state.invariant_events = 0;
+    });
+}
+
+/// Checks the invariants after an event, panicking if one doesn't hold.
+pub(crate) fn check(event: &WlcEvent) {
+    let invariants = state::with(|state| {
+        if !state.dispatching.is_empty() || state.invariants.is_empty() {
+            return None
+        }
+        state.invariant_events += 1;
+        Some((state.invariants.clone(), state.invariant_events - 1))
+    });
+    let (invariants, index) = match invariants {
+        Some(invariants) => invariants,
+        None => return
+    };
+    for invariant in invariants {
+        if !(invariant.holds)() {
+            panic!("dummy-rustwlc invariant \"{}\" broken by event {}: {:?}\n{}",
+                   invariant.name, index, event, dummy::state_summary());
+        }
+    }
+}
+
+/// Whether the focused view, if any, is the only view with
+/// `VIEW_ACTIVATED`.
+pub fn focused_view_activated() -> bool {
+    let focused = dummy::focused_view();
+    let activated = dummy::find_views(|info| info.state.contains(VIEW_ACTIVATED));
+    if focused.is_root() {
+        activated.is_empty()
+    } else {
+        activated == vec![focused]
+    }
+}
+
+/// Whether every visible view is inside its output.
+pub fn views_inside_outputs() -> bool {
+    WlcOutput::list().into_iter().all(|output| {
+        let size = output.get_resolution().unwrap_or_default();
+        dummy::visible_views(output).into_iter().all(|view| {
+            let geometry = view.get_visible_geometry();
+            geometry.origin.x >= 0 && geometry.origin.y >= 0 &&
+                geometry.origin.x as i64 + geometry.size.w as i64 <= size.w as i64 &&
+                geometry.origin.y as i64 + geometry.size.h as i64 <= size.h as i64
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    #[should_panic(expected = "\"inside\" broken by event 2: OutputResolution")]
+    fn event_index() {
+        invariants::add("inside", invariants::views_inside_outputs);
+        invariants::add("activated", invariants::focused_view_activated);
+        callback::rust::view_focus(|view, focused| view.set_state(VIEW_ACTIVATED, focused));
+        let output = simulate::connect_output("WLC-1", Size { w: 800, h: 600 });
+        let view = simulate::create_view();
+        view.set_geometry(EDGE_NONE, Geometry {
+            origin: Point { x: 400, y: 0 },
+            size: Size { w: 400, h: 600 }
+        });
+        view.focus();
+        simulate::output_resolution(output, Size { w: 640, h: 480 });
+    }
+}
@@ -0,0 +1,259 @@
+//! Checks the keybindings a compositor declares for conflicts.
+//!
+//! Keybindings usually come from a user's config, and mistakes in it only
+//! show when a key does the wrong thing. A compositor can declare its
+//! bindings here as it reads its config, i.e. in a test which loads a
+//! sample config, and `check` reports:
+//!
+//! * duplicates: a sequence of chords bound more than once, so only one
+//!   of its actions runs
+//! * shadowed bindings: a sequence which starts with another bound
+//!   sequence, so that one runs first and the rest is never reached
+//! * VT switches: a binding using a chord which wlc keeps for switching
+//!   virtual terminals (`vt_switch_chords`), which never reaches the
+//!   compositor, unless it runs nested in X11 (see `dummy::set_backend`)
+//! * host grabs: when running nested in X11 (see `dummy::set_backend`), a
+//!   binding using a chord which the host grabs (`dummy::host_grabs`)
+//!
+//! Chords match exactly, so `Mod4+Shift+Q` and `Mod4+Q` don't conflict.
+//!
+//! # Example
+//! ```rust
+//! use rustwlc::{keybindings, MOD_ALT, MOD_CTRL, MOD_MOD4};
+//! use rustwlc::input::keys;
+//! use rustwlc::keybindings::Chord;
+//!
+//! let close = Chord { mods: MOD_MOD4, key: keys::KEY_Q };
+//! keybindings::bind(&[close], "close");
+//! keybindings::bind(&[close], "quit");
+//! keybindings::bind(&[Chord { mods: MOD_CTRL | MOD_ALT, key: keys::KEY_F2 }], "lock");
+//! let report = keybindings::check();
+//! assert_eq!(report.duplicates[0].actions, vec!["close".to_string(), "quit".to_string()]);
+//! assert_eq!(report.vt_switches[0].action, "lock");
+//! assert!(!report.is_clean());
+//! ```
+
+use std::fmt;
+
+use super::dummy::{self, state};
+use super::get_backend_type;
+use super::input::keys::{self, Key};
+use super::types::{BackendType, KeyMod, MOD_ALT, MOD_CAPS, MOD_CTRL, MOD_MOD2, MOD_MOD3,
+                   MOD_MOD4, MOD_MOD5, MOD_SHIFT};
+
+/// A key pressed while holding modifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Chord {
+    /// The modifiers, exactly
+    pub mods: KeyMod,
+    /// The key
+    pub key: Key
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names = [(MOD_SHIFT, "Shift"), (MOD_CAPS, "Caps"), (MOD_CTRL, "Ctrl"),
+                     (MOD_ALT, "Alt"), (MOD_MOD2, "Mod2"), (MOD_MOD3, "Mod3"),
+                     (MOD_MOD4, "Mod4"), (MOD_MOD5, "Mod5")];
+        for &(modifier, name) in &names {
+            if self.mods.contains(modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        write!(f, "key {}", self.key.get_code())
+    }
+}
+
+/// A sequence of chords bound to an action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Binding {
+    /// The chords, pressed one after another
+    pub sequence: Vec<Chord>,
+    /// The name of the action
+    pub action: String
+}
+
+/// A sequence bound to more than one action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Duplicate {
+    /// The sequence
+    pub sequence: Vec<Chord>,
+    /// The actions, in the order they were bound
+    pub actions: Vec<String>
+}
+
+/// A binding which can't be reached, since a shorter one runs first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shadowed {
+    /// The binding which can't be reached
+    pub binding: Binding,
+    /// The binding whose sequence it starts with
+    pub by: Binding
+}
+
+/// The conflicts among the declared bindings.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Sequences bound more than once
+    pub duplicates: Vec<Duplicate>,
+    /// Bindings which start with another binding
+    pub shadowed: Vec<Shadowed>,
+    /// Bindings which use a VT switch chord
+    pub vt_switches: Vec<Binding>,
+    /// Bindings which use a chord the host X server grabs
+    pub host_grabs: Vec<Binding>
+}
+
+impl Report {
+    /// Whether there are no conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.duplicates.is_empty() && self.shadowed.is_empty() && self.vt_switches.is_empty() &&
+            self.host_grabs.is_empty()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_clean() {
+            return write!(f, "no keybinding conflicts")
+        }
+        for duplicate in &self.duplicates {
+            writeln!(f, "{} is bound to {}", Sequence(&duplicate.sequence),
+                     duplicate.actions.join(", "))?;
+        }
+        for shadowed in &self.shadowed {
+            writeln!(f, "{} ({}) is shadowed by {} ({})",
+                     Sequence(&shadowed.binding.sequence), shadowed.binding.action,
+                     Sequence(&shadowed.by.sequence), shadowed.by.action)?;
+        }
+        for binding in &self.vt_switches {
+            writeln!(f, "{} ({}) uses a VT switch chord", Sequence(&binding.sequence),
+                     binding.action)?;
+        }
+        for binding in &self.host_grabs {
+            writeln!(f, "{} ({}) uses a chord the host X server grabs",
+                     Sequence(&binding.sequence), binding.action)?;
+        }
+        Ok(())
+    }
+}
+
+/// Displays a sequence of chords, separated by commas.
+struct Sequence<'a>(&'a [Chord]);
+
+impl<'a> fmt::Display for Sequence<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, chord) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", chord)?;
+        }
+        Ok(())
+    }
+}
+
+/// Declares a binding of a sequence of chords, usually just one, to an
+/// action.
+///
+/// # Panics
+/// If the sequence is empty.
+pub fn bind(sequence: &[Chord], action: &str) {
+    assert!(!sequence.is_empty(), "dummy-rustwlc keybinding \"{}\" has no chords", action);
+    state::with(|state| state.keybindings.push(Binding {
+        sequence: sequence.to_vec(),
+        action: action.to_string()
+    }));
+}
+
+/// Gets the declared bindings, in the order they were declared.
+pub fn bindings() -> Vec<Binding> {
+    state::with(|state| state.keybindings.clone())
+}
+
+/// Removes all declared bindings.
+pub fn clear() {
+    state::with(|state| state.keybindings.clear());
+}
+
+/// The chords wlc keeps for switching virtual terminals: `Ctrl+Alt+F1`
+/// to `Ctrl+Alt+F12`.
+pub fn vt_switch_chords() -> Vec<Chord> {
+    [keys::KEY_F1, keys::KEY_F2, keys::KEY_F3, keys::KEY_F4, keys::KEY_F5, keys::KEY_F6,
+     keys::KEY_F7, keys::KEY_F8, keys::KEY_F9, keys::KEY_F10, keys::KEY_F11, keys::KEY_F12]
+        .iter().map(|&key| Chord { mods: MOD_CTRL | MOD_ALT, key }).collect()
+}
+
+/// Checks the declared bindings for conflicts.
+///
+/// Each conflict is reported once, in the order the bindings were declared.
+pub fn check() -> Report {
+    let bindings = bindings();
+    let (vt, host) = if get_backend_type() == BackendType::X11 {
+        (Vec::new(), dummy::host_grabs())
+    } else {
+        (vt_switch_chords(), Vec::new())
+    };
+    let mut report = Report::default();
+    for (index, binding) in bindings.iter().enumerate() {
+        let same = |other: &&Binding| other.sequence == binding.sequence;
+        if !bindings[..index].iter().any(|other| same(&other)) {
+            let actions: Vec<String> = bindings[index..].iter().filter(same)
+                .map(|other| other.action.clone()).collect();
+            if actions.len() > 1 {
+                report.duplicates.push(Duplicate { sequence: binding.sequence.clone(), actions });
+            }
+        }
+        let by = bindings.iter().find(|other| {
+            other.sequence.len() < binding.sequence.len() &&
+                binding.sequence.starts_with(&other.sequence)
+        });
+        if let Some(by) = by {
+            report.shadowed.push(Shadowed { binding: binding.clone(), by: by.clone() });
+        }
+        if binding.sequence.iter().any(|chord| vt.contains(chord)) {
+            report.vt_switches.push(binding.clone());
+        }
+        if binding.sequence.iter().any(|chord| host.contains(chord)) {
+            report.host_grabs.push(binding.clone());
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::super::input::keys;
+    use super::super::keybindings::Chord;
+
+    #[test]
+    fn report() {
+        let leader = Chord { mods: MOD_MOD4, key: keys::KEY_W };
+        let split = Chord { mods: MOD_NONE, key: keys::KEY_V };
+        keybindings::bind(&[leader, split], "split");
+        keybindings::bind(&[leader], "workspaces");
+        keybindings::bind(&[Chord { mods: MOD_MOD4 | MOD_SHIFT, key: keys::KEY_W }], "move");
+        keybindings::bind(&[leader, split], "split");
+        let report = keybindings::check();
+        assert_eq!(report.duplicates.len(), 1);
+        assert_eq!(report.duplicates[0].actions, vec!["split".to_string(), "split".to_string()]);
+        // Both declarations of the split are shadowed
+        assert_eq!(report.shadowed.len(), 2);
+        assert_eq!(report.shadowed[0].by.action, "workspaces");
+        assert!(report.vt_switches.is_empty());
+        assert_eq!(report.to_string(),
+                   "Mod4+key 17, key 47 is bound to split, split\n\
+                    Mod4+key 17, key 47 (split) is shadowed by Mod4+key 17 (workspaces)\n\
+                    Mod4+key 17, key 47 (split) is shadowed by Mod4+key 17 (workspaces)\n");
+        keybindings::clear();
+        let switch = Chord { mods: MOD_ALT, key: keys::KEY_TAB };
+        keybindings::bind(&[switch], "next view");
+        assert!(keybindings::check().is_clean());
+        dummy::set_backend(BackendType::X11);
+        assert_eq!(keybindings::check().host_grabs[0].action, "next view");
+        keybindings::clear();
+        assert!(keybindings::check().is_clean());
+        assert_eq!(keybindings::check().to_string(), "no keybinding conflicts");
+    }
+}
@@ -59,22 +59,59 @@
 #![warn(missing_docs)]
 #![allow(unused_variables)]
 
+#[cfg(feature = "c-compat")]
 extern crate libc;
 
 #[macro_use]
 extern crate bitflags;
 
+#[cfg(feature = "inspector")]
+extern crate crossterm;
+
+use std::fmt;
+#[cfg(feature = "c-compat")]
 use std::ffi;
 
 pub mod handle;
 pub mod callback;
+pub mod event;
+// bitflags 0.6 expands to `try!`
+#[allow(deprecated)]
 pub mod types;
 pub mod input;
+pub mod render;
 pub mod wayland;
 pub mod xkb;
+pub mod dummy;
+pub mod simulate;
+pub mod masks;
+pub mod workspaces;
+pub mod scenarios;
+pub mod fixtures;
+pub mod user;
+pub mod animation;
+pub mod clients;
+pub mod contract;
+pub mod calls;
+pub mod differential;
+pub mod invariants;
+pub mod model;
+pub mod script;
+pub mod raster;
+pub mod keybindings;
+pub mod selection;
+#[cfg(feature = "inspector")]
+pub mod inspector;
+#[cfg(feature = "conformance")]
+pub mod conformance;
+#[cfg(feature = "real-wlc")]
+pub mod capture;
 
 pub use types::*;
 pub use handle::{WlcOutput, WlcView};
+pub use event::{ScrollSource, WlcEvent};
+
+use dummy::Lifecycle;
 
 /// Query backend wlc is using.
 ///
@@ -82,8 +119,10 @@ pub use handle::{WlcOutput, WlcView};
 /// * None: Unknown backend type
 /// * DRM: "Direct Rendering Manager" - running on tty
 /// * X11: Running inside an X server
+///
+/// The dummy reports `None` unless set with `dummy::set_backend`.
 pub fn get_backend_type() -> BackendType {
-    BackendType::None
+    dummy::state::with(|state| state.backend)
 }
 
 /// Initialize wlc's callbacks and logger with a `WlcInterface`.
@@ -128,8 +167,58 @@ pub fn get_backend_type() -> BackendType {
 ///
 /// run_wlc();
 /// ```
+///
+/// # Dummy
+/// Initializing again, or after wlc ran, is reported as misuse (see
+/// `dummy::set_strict`) and returns `None`. `try_init` says why.
 pub fn init() -> Option<fn() -> ()> {
-    Some(run_wlc)
+    try_init().map_err(|error| dummy::violation(format!("rustwlc::init() failed: {}", error)))
+        .ok()
+}
+
+/// Why `try_init` failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitError {
+    /// `init()` was already called
+    AlreadyInitialized,
+    /// wlc is running
+    Running,
+    /// `terminate()` was called. `dummy::new_session` starts over.
+    Terminated
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            InitError::AlreadyInitialized => write!(f, "wlc is already initialized"),
+            InitError::Running => write!(f, "wlc is running"),
+            InitError::Terminated => write!(f, "wlc was terminated")
+        }
+    }
+}
+
+/// Initializes wlc as `init()` does, saying why it failed.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::InitError;
+///
+/// let run_wlc = rustwlc::try_init().unwrap();
+/// run_wlc();
+/// assert_eq!(rustwlc::try_init().err(), Some(InitError::Running));
+/// rustwlc::terminate();
+/// assert_eq!(rustwlc::try_init().unwrap_err().to_string(), "wlc was terminated");
+/// ```
+pub fn try_init() -> Result<fn() -> (), InitError> {
+    dummy::state::with(|state| match state.lifecycle {
+        Lifecycle::Uninitialized => {
+            state.lifecycle = Lifecycle::Initialized;
+            Ok(run_wlc as fn() -> ())
+        },
+        Lifecycle::Initialized => Err(InitError::AlreadyInitialized),
+        Lifecycle::Running => Err(InitError::Running),
+        Lifecycle::Terminated => Err(InitError::Terminated)
+    })
 }
 
 /// Deprecated alias to init().
@@ -146,11 +235,39 @@ pub fn init2() -> Option<fn() -> ()> {
 /// The initialize functions will return this function in an Option.
 /// Only then can it be called to being wlc's main event loop.
 fn run_wlc() {
-    println!("Attempted to run wlc!");
+    let previous = dummy::state::with(|state| {
+        let previous = state.lifecycle;
+        if previous == Lifecycle::Initialized {
+            state.lifecycle = Lifecycle::Running;
+        }
+        previous
+    });
+    match previous {
+        Lifecycle::Initialized => println!("Attempted to run wlc!"),
+        Lifecycle::Running => dummy::violation("wlc was run twice".to_string()),
+        Lifecycle::Terminated => dummy::violation("wlc was run after terminate()".to_string()),
+        Lifecycle::Uninitialized => dummy::violation("wlc was run before init()".to_string())
+    }
 }
 
 /// Halts execution of wlc.
+///
+/// The dummy tears down the simulated compositor in a fixed order: for
+/// each output in `WlcOutput::list()` order, `view_destroyed` is invoked
+/// for its views from the top of the stack down, then `output_destroyed`
+/// for the output. Finally `compositor_terminate` is invoked. The sequence
+/// can be checked with `dummy::shutdown_sequence()`.
+///
+/// Terminating before wlc runs, or twice, is reported as misuse (see
+/// `dummy::set_strict`).
 pub fn terminate() {
+    match dummy::lifecycle() {
+        Lifecycle::Running => {},
+        Lifecycle::Terminated => dummy::violation("terminate() was called twice".to_string()),
+        _ => dummy::violation("terminate() was called before wlc ran".to_string())
+    }
+    simulate::terminate();
+    dummy::state::with(|state| state.lifecycle = Lifecycle::Terminated);
 }
 
 /// Registers a C callback for wlc logging.
@@ -162,18 +279,20 @@ pub fn terminate() {
 /// The `handler` callback has two parameters:
 /// * `type`: The `LogType` of the message being printed.
 /// * `text`: The text to be logged, currently in C form. One may call `rustwlc::pointer_to_string`
-/// to convert it to a Rust String.
+///   to convert it to a Rust String.
 ///
 /// # Safety
 /// The callback function (like other callbacks in `rustwlc`) must be marked as extern as it is called
 /// from C code.
 ///
 /// In addition, `unsafe` will be required to convert the text into a Rust String.
+///
+/// Only available with the `c-compat` feature.
+#[cfg(feature = "c-compat")]
 pub fn log_set_handler(handler: extern "C" fn(type_: LogType, text: *const libc::c_char)) {
 }
 
 /// Registers a Rust callback for wlc logging.
-
 /// This is a nice convenience function that should be used in place of
 /// `log_set_handler`. That way you can just pass a safe Rust `&str`
 /// and not depend on libc`.
@@ -212,14 +331,24 @@ pub fn log_set_default_handler() {
 /// Converts a `*const libc::c_char` to an owned `String`.
 /// Useful for log callbacks.
 ///
+/// # Safety
+/// `pointer` must be null or point to a nul-terminated C string.
+///
 /// # Example
 /// Standard usage may be for the log callbacks.
 /// ```rust
+/// # extern crate libc;
+/// # extern crate rustwlc;
+/// # use rustwlc::{LogType, pointer_to_string};
 /// extern "C" fn default_log_callback(log_type: LogType, text: *const libc::c_char) {
 ///     let string = unsafe { pointer_to_string(text) };
 ///     println!("wlc [{:?}]: {}", log_type, string);
 /// }
+/// # fn main() { }
 /// ```
+///
+/// Only available with the `c-compat` feature.
+#[cfg(feature = "c-compat")]
 pub unsafe fn pointer_to_string(pointer: *const libc::c_char) -> String {
     if pointer.is_null() {
         return "".to_string();
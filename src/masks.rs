@@ -0,0 +1,97 @@
+//! Named bits of view and output masks.
+//!
+//! wlc draws a view on an output when their masks share a bit, and leaves
+//! the meaning of each bit to the compositor. Juggling raw `u32`s makes it
+//! easy to hand the same bit out twice or to run out of bits unnoticed.
+//! This module keeps a registry of named bits, which the compositor and the
+//! `workspaces` module share, so each name gets its own bit and masks can
+//! be printed by name.
+//!
+//! Bits are handed out in order of first use, starting from `1`, which
+//! outputs and views have by default. There are only 32 bits in a mask.
+//!
+//! # Example
+//! ```rust
+//! use rustwlc::masks;
+//!
+//! let main = masks::allocate("main").unwrap();
+//! let scratchpad = masks::allocate("scratchpad").unwrap();
+//! assert_eq!(main, 1);
+//! assert_eq!(masks::allocate("main"), Some(main));
+//! assert_eq!(masks::format(main | scratchpad | 0x100), "main|scratchpad|0x100");
+//! assert_eq!(masks::remaining(), 30);
+//! ```
+
+use super::dummy::state;
+
+/// Number of bits in a mask.
+pub const BITS: usize = 32;
+
+/// Gets the bit of a name, allocating one if it's new.
+///
+/// Returns `None` if all bits are in use.
+pub fn allocate(name: &str) -> Option<u32> {
+    state::with(|state| {
+        if let Some(index) = state.mask_names.iter().position(|other| other == name) {
+            return Some(1 << index)
+        }
+        if state.mask_names.len() >= BITS {
+            return None
+        }
+        state.mask_names.push(name.to_string());
+        Some(1 << (state.mask_names.len() - 1))
+    })
+}
+
+/// Gets the bit of a name, if it has one.
+pub fn bit(name: &str) -> Option<u32> {
+    state::with(|state| {
+        state.mask_names.iter().position(|other| other == name).map(|index| 1 << index)
+    })
+}
+
+/// Gets the names of all bits allocated so far, in order of their bits.
+pub fn names() -> Vec<String> {
+    state::with(|state| state.mask_names.clone())
+}
+
+/// Gets the names of the bits set in a mask, in order of their bits.
+///
+/// Bits which have no name are skipped.
+pub fn names_in(mask: u32) -> Vec<String> {
+    state::with(|state| {
+        state.mask_names.iter().enumerate()
+            .filter(|&(index, _)| mask & (1 << index) != 0)
+            .map(|(_, name)| name.clone())
+            .collect()
+    })
+}
+
+/// Gets the number of bits which haven't been allocated.
+///
+/// When this reaches zero, `allocate` fails for new names.
+pub fn remaining() -> usize {
+    BITS - state::with(|state| state.mask_names.len())
+}
+
+/// Formats a mask with the names of its bits.
+///
+/// Names are joined with `|`, followed by any bits which have no name in
+/// hex. An empty mask is formatted as `0`.
+pub fn format(mask: u32) -> String {
+    if mask == 0 {
+        return "0".to_string()
+    }
+    let mut parts = names_in(mask);
+    let named = named_bits();
+    if mask & !named != 0 {
+        parts.push(format!("{:#x}", mask & !named));
+    }
+    parts.join("|")
+}
+
+/// The bits which have a name.
+fn named_bits() -> u32 {
+    let count = state::with(|state| state.mask_names.len());
+    if count >= BITS { !0 } else { (1 << count) - 1 }
+}
@@ -0,0 +1,256 @@
+//! Checks a compositor against a reference window manager.
+//!
+//! Hand-written scenarios only cover the sequences their authors thought
+//! of. This module generates random streams of client and user activity
+//! from a seed, runs them against both the compositor under test and a
+//! small reference floating window manager, and reports the first step
+//! after which they disagree on what should be the same in any window
+//! manager: which view has focus, and which views are activated. This
+//! catches mistakes such as leaving focus on a closed view, or forgetting
+//! to focus another view after one closes.
+//!
+//! The reference (see `register_reference`) focuses new views, focuses and
+//! raises views when they are clicked, and focuses the topmost remaining
+//! view on the output when the focused view closes. Compositors with
+//! different rules, i.e. which don't focus new dialogs, are expected to
+//! diverge; a failing seed is a starting point for a regression test.
+//!
+//! Clicks move the pointer with `simulate::pointer_motion`, so streams can
+//! be run with motion coalesced or flooded by `dummy::set_motion_rate`.
+//!
+//! # Example
+//! ```rust
+//! use rustwlc::{dummy, model, Size};
+//!
+//! let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+//! output.set_primary();
+//! // The reference agrees with itself
+//! for seed in 0..8 {
+//!     let steps = model::steps(seed, 20);
+//!     model::check(&steps, model::register_reference).unwrap();
+//! }
+//! ```
+
+use std::fmt;
+
+use super::callback;
+use super::dummy::{self, state};
+use super::event::WlcEvent;
+use super::handle::{WlcOutput, WlcView};
+use super::input::keys::BTN_LEFT;
+use super::input::pointer;
+use super::simulate;
+use super::types::{ButtonState, Geometry, Point, Size, EDGE_NONE, VIEW_ACTIVATED};
+
+/// One step of a random stream.
+///
+/// Views are picked by index among the views which can take the step,
+/// ordered by handle, wrapping around; a step with no views to pick from
+/// does nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// A client opens a view on the primary output with a geometry
+    Open(Geometry),
+    /// The client of a mapped view closes it
+    Close(usize),
+    /// The user clicks the center of a visible view
+    Click(usize)
+}
+
+/// What the compositor and the reference should agree on after a step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Observation {
+    /// The focused view, or the root view
+    pub focused: WlcView,
+    /// Views with `VIEW_ACTIVATED`, ordered by handle
+    pub activated: Vec<WlcView>
+}
+
+/// The first step after which the compositor and the reference disagree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// Index of the step
+    pub index: usize,
+    /// The step
+    pub step: Step,
+    /// What the reference did
+    pub reference: Observation,
+    /// What the compositor did
+    pub compositor: Observation
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "step {} ({:?}) diverges:\n    reference: {:?}\n    compositor: {:?}",
+               self.index, self.step, self.reference, self.compositor)
+    }
+}
+
+/// Generates a random stream of steps from a seed.
+///
+/// The same seed always gives the same steps. Views are opened inside an
+/// 800x600 area, so they fit on typical outputs.
+pub fn steps(seed: u64, count: usize) -> Vec<Step> {
+    let mut rng = seed;
+    (0..count).map(|_| {
+        let roll = state::splitmix(&mut rng);
+        let pick = (roll >> 8) as usize;
+        match roll % 4 {
+            0 | 1 => {
+                let size = Size {
+                    w: 100 + (state::splitmix(&mut rng) % 300) as u32,
+                    h: 100 + (state::splitmix(&mut rng) % 200) as u32
+                };
+                let origin = Point {
+                    x: (state::splitmix(&mut rng) % (800 - size.w as u64)) as i32,
+                    y: (state::splitmix(&mut rng) % (600 - size.h as u64)) as i32
+                };
+                Step::Open(Geometry { origin, size })
+            },
+            2 => Step::Close(pick),
+            _ => Step::Click(pick)
+        }
+    }).collect()
+}
+
+/// Generates a random stream of steps from the dummy's generator, seeded
+/// with `dummy::set_seed`, see `steps`.
+pub fn random_steps(count: usize) -> Vec<Step> {
+    steps(dummy::random(), count)
+}
+
+/// Runs steps against the reference and against a compositor, comparing
+/// them after each step.
+///
+/// `register` registers the compositor's callbacks. Both start from the
+/// current state, with the callbacks registered so far, and the current
+/// state is restored afterwards, as with `differential::compare`.
+///
+/// # Panics
+/// If there is no primary output when a view is opened.
+pub fn check<R>(steps: &[Step], register: R) -> Result<(), Divergence> where R: FnOnce() {
+    let start = dummy::snapshot();
+    register_reference();
+    let reference = run(steps);
+    dummy::rewind_to(&start);
+    register();
+    let compositor = run(steps);
+    dummy::rewind_to(&start);
+    reference.into_iter().zip(compositor).enumerate()
+        .find(|(_, (reference, compositor))| reference != compositor)
+        .map_or(Ok(()), |(index, (reference, compositor))| Err(Divergence {
+            index, step: steps[index], reference, compositor
+        }))
+}
+
+/// Registers the callbacks of the reference window manager.
+pub fn register_reference() {
+    callback::rust::view_created(|view| {
+        view.set_mask(view.get_output().get_mask());
+        view.bring_to_front();
+        view.focus();
+        true
+    });
+    callback::rust::view_destroyed(|view| {
+        if dummy::focused_view() != view {
+            return
+        }
+        let output = view.get_output();
+        let next = output.get_views().into_iter().rev()
+            .find(|&other| other != view && other.get_mask() & output.get_mask() != 0);
+        match next {
+            Some(next) => next.focus(),
+            None => WlcView::root().focus()
+        }
+    });
+    callback::rust::view_focus(|view, focused| view.set_state(VIEW_ACTIVATED, focused));
+    callback::rust::pointer_motion(|_, _, point| {
+        pointer::set_position(*point);
+        false
+    });
+    callback::rust::pointer_button(|view, _, _, _, state, _| {
+        if state == ButtonState::Pressed && !view.is_root() {
+            view.bring_to_front();
+            view.focus();
+        }
+        false
+    });
+}
+
+/// Runs steps, observing the state after each.
+fn run(steps: &[Step]) -> Vec<Observation> {
+    steps.iter().map(|&step| {
+        perform(step);
+        observe()
+    }).collect()
+}
+
+/// Performs a step.
+fn perform(step: Step) {
+    match step {
+        Step::Open(geometry) => {
+            let view = dummy::add_view(WlcOutput::get_primary());
+            view.set_geometry(EDGE_NONE, geometry);
+            if !simulate::dispatch(WlcEvent::ViewCreated(view)) {
+                dummy::remove_view(view);
+            }
+        },
+        Step::Close(index) => {
+            if let Some(view) = pick(index, dummy::is_mapped) {
+                simulate::dispatch(WlcEvent::ViewDestroyed(view));
+                dummy::remove_view(view);
+            }
+        },
+        Step::Click(index) => {
+            if let Some(view) = pick(index, dummy::is_visible) {
+                simulate::pointer_motion(view.get_visible_geometry().center());
+                simulate::pointer_button(BTN_LEFT, ButtonState::Pressed);
+                simulate::pointer_button(BTN_LEFT, ButtonState::Released);
+            }
+        }
+    }
+}
+
+/// Picks a view by index among those which pass `filter`, ordered by
+/// handle.
+fn pick<F>(index: usize, filter: F) -> Option<WlcView> where F: Fn(WlcView) -> bool {
+    let mut views = dummy::find_views(|_| true);
+    views.retain(|&view| filter(view));
+    views.sort();
+    if views.is_empty() { None } else { Some(views[index % views.len()]) }
+}
+
+/// Observes the state after a step.
+fn observe() -> Observation {
+    let mut activated = dummy::find_views(|info| info.state.contains(VIEW_ACTIVATED));
+    activated.sort();
+    Observation { focused: dummy::focused_view(), activated }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn divergence() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        output.set_primary();
+        // Leaves focus to wlc when the focused view closes
+        let careless = || {
+            model::register_reference();
+            callback::rust::view_destroyed(|_| {});
+        };
+        let steps = vec![
+            model::Step::Open(Geometry { origin: Point { x: 0, y: 0 }, size: Size { w: 200, h: 200 } }),
+            model::Step::Open(Geometry { origin: Point { x: 300, y: 0 }, size: Size { w: 200, h: 200 } }),
+            model::Step::Close(1)
+        ];
+        let divergence = model::check(&steps, careless).unwrap_err();
+        assert_eq!(divergence.index, 2);
+        assert_eq!(divergence.compositor.focused, WlcView::root());
+        assert_eq!(divergence.reference.focused, divergence.reference.activated[0]);
+        // Neither is left registered
+        assert!(simulate::dispatch(WlcEvent::ViewCreated(dummy::add_view(output))));
+        assert!(dummy::focused_view().is_root());
+    }
+}
@@ -0,0 +1,704 @@
+//! Pictures of the layout, and animated GIFs of how it changed.
+//!
+//! `rasterize` draws an output as an image: its views are filled
+//! rectangles in stack order, with a border which is highlighted for the
+//! focused view. While recording (see `record`), a frame is drawn after
+//! every simulated event, or after every frame rendered with
+//! `simulate::render_frame`, and `write_gif` assembles the frames into an
+//! animated GIF timed with the dummy clock. Failing CI runs can attach it
+//! as a visual replay of what the compositor did.
+//!
+//! Outputs can also be drawn as text with `ascii`, as SVG with `svg`, and
+//! as PNG with `write_png`. Each of these has a `LayoutRenderer`, which
+//! draws after every frame of the dummy's frame loop once added with
+//! `add_renderer`; compositors can add their own.
+//!
+//! # Example
+//! ```rust
+//! use rustwlc::{raster, simulate, Size};
+//! use rustwlc::raster::Capture;
+//!
+//! let output = simulate::connect_output("WLC-1", Size { w: 800, h: 600 });
+//! raster::record(output, Size { w: 160, h: 120 }, Capture::Event);
+//! simulate::create_view();
+//! simulate::create_view();
+//!
+//! let frames = raster::frames();
+//! assert_eq!(frames.len(), 2);
+//! let mut gif = Vec::new();
+//! raster::write_gif(&frames, &mut gif).unwrap();
+//! assert!(gif.starts_with(b"GIF89a"));
+//! ```
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Write as FmtWrite;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use super::callback::Callback;
+use super::dummy::{self, state};
+use super::dummy::log::Log;
+use super::event::WlcEvent;
+use super::handle::{WlcOutput, WlcView};
+use super::types::{Point, Size};
+
+/// Colors of the pixels of an image, as RGB.
+///
+/// Index `0` is outside of the output, `1` the output's background, `2`
+/// the border of a view and `3` the border of the focused view. Views are
+/// filled with the remaining colors, picked by handle.
+pub const PALETTE: [[u8; 3]; 16] = [
+    [0x00, 0x00, 0x00], [0x30, 0x30, 0x30], [0xd0, 0xd0, 0xd0], [0xff, 0xc0, 0x00],
+    [0x4e, 0x79, 0xa7], [0xf2, 0x8e, 0x2b], [0xe1, 0x57, 0x59], [0x76, 0xb7, 0xb2],
+    [0x59, 0xa1, 0x4f], [0xed, 0xc9, 0x48], [0xb0, 0x7a, 0xa1], [0xff, 0x9d, 0xa7],
+    [0x9c, 0x75, 0x5f], [0xba, 0xb0, 0xac], [0x1f, 0x3a, 0x5f], [0x6b, 0x3e, 0x8e]
+];
+
+/// A picture of an output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    /// Size in pixels
+    pub size: Size,
+    /// Indices into `PALETTE`, row by row from the top left
+    pub pixels: Vec<u8>
+}
+
+impl Image {
+    /// Gets the color of a pixel, if it's inside the image.
+    pub fn color(&self, point: Point) -> Option<[u8; 3]> {
+        if point.x < 0 || point.y < 0 ||
+            point.x as u32 >= self.size.w || point.y as u32 >= self.size.h {
+                return None
+            }
+        let index = point.y as usize * self.size.w as usize + point.x as usize;
+        Some(PALETTE[self.pixels[index] as usize])
+    }
+}
+
+/// When frames are drawn while recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Capture {
+    /// After every simulated event, once its callbacks have run
+    #[default]
+    Event,
+    /// After every frame of the output rendered with
+    /// `simulate::render_frame`
+    Frame
+}
+
+/// A frame drawn while recording.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// Time the frame was drawn, from `dummy::time()`
+    pub time: u32,
+    /// The picture
+    pub image: Image
+}
+
+/// An ongoing recording, see `record`.
+#[derive(Debug, Clone)]
+pub(crate) struct Recording {
+    pub output: WlcOutput,
+    pub size: Size,
+    pub capture: Capture,
+    pub frames: Log<Frame>
+}
+
+/// Shortest time a frame of a GIF is shown, in hundredths of a second.
+///
+/// Many events happen at the same time on the dummy clock; browsers show
+/// shorter frames for this long anyway.
+pub const MIN_DELAY: u16 = 10;
+
+/// Draws an output as an image of `size`.
+///
+/// The output is scaled to fit, keeping its aspect ratio, and centered.
+/// Outputs which are not part of the dummy compositor are drawn empty.
+pub fn rasterize(output: WlcOutput, size: Size) -> Image {
+    let mut image = Image {
+        size,
+        pixels: vec![0; size.w as usize * size.h as usize]
+    };
+    let resolution = match output.get_resolution() {
+        Some(resolution) if resolution.w > 0 && resolution.h > 0 => resolution,
+        _ => return image
+    };
+    let content = resolution.fit_within(&size);
+    let offset = Point {
+        x: ((size.w - content.w) / 2) as i32,
+        y: ((size.h - content.h) / 2) as i32
+    };
+    let scale = |point: Point| Point {
+        x: offset.x + (point.x as i64 * content.w as i64 / resolution.w as i64) as i32,
+        y: offset.y + (point.y as i64 * content.h as i64 / resolution.h as i64) as i32
+    };
+    let corner = Point { x: offset.x + content.w as i32, y: offset.y + content.h as i32 };
+    fill(&mut image, offset, corner, 1);
+    let focused = dummy::focused_view();
+    for view in dummy::visible_views(output) {
+        let geometry = view.get_visible_geometry();
+        let start = scale(geometry.origin);
+        let end = scale(Point {
+            x: geometry.origin.x + geometry.size.w as i32,
+            y: geometry.origin.y + geometry.size.h as i32
+        });
+        let border = if view == focused { 3 } else { 2 };
+        fill(&mut image, start, end, border);
+        fill(&mut image, Point { x: start.x + 1, y: start.y + 1 },
+             Point { x: end.x - 1, y: end.y - 1 }, fill_index(view));
+    }
+    image
+}
+
+/// Fills the pixels from `start` up to, but not including, `end`.
+fn fill(image: &mut Image, start: Point, end: Point, color: u8) {
+    let (w, h) = (image.size.w as i32, image.size.h as i32);
+    for y in start.y.max(0)..end.y.min(h) {
+        for x in start.x.max(0)..end.x.min(w) {
+            image.pixels[(y * w + x) as usize] = color;
+        }
+    }
+}
+
+/// Draws an output as text of `size`, in characters.
+///
+/// The first line names the output. Below it, the output is stretched to
+/// fill the remaining lines, with views drawn bottom to top with `+`, `-`
+/// and `|`, the focused view with `#`, and their titles on their top
+/// edges.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, raster, Geometry, Point, Size, EDGE_NONE};
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let view = dummy::add_view(output);
+/// view.set_geometry(EDGE_NONE, Geometry { origin: Point { x: 0, y: 0 }, size: Size { w: 800, h: 600 } });
+/// let text = raster::ascii(output, Size { w: 12, h: 4 });
+/// assert_eq!(text, vec!["WLC-1 800 x ", "+WlcView(2)+", "|          |", "+----------+"]);
+/// ```
+pub fn ascii(output: WlcOutput, size: Size) -> Vec<String> {
+    let (width, height) = (size.w as usize, size.h as usize);
+    let mut grid = vec![vec![' '; width]; height];
+    if height == 0 || width == 0 {
+        return grid.into_iter().map(|row| row.into_iter().collect()).collect()
+    }
+    let resolution = output.get_resolution().unwrap_or_default();
+    let header = format!("{} {}", output.get_name(), resolution);
+    for (cell, c) in grid[0].iter_mut().zip(header.chars()) {
+        *cell = c;
+    }
+    let (rows, columns) = (height as i64 - 1, width as i64);
+    if rows > 0 && resolution.w > 0 && resolution.h > 0 {
+        let focused = dummy::focused_view();
+        for view in dummy::visible_views(output) {
+            let geometry = view.get_visible_geometry();
+            let scale_x = |x: i64| (x * columns / resolution.w as i64).max(0).min(columns - 1);
+            let scale_y = |y: i64| (y * rows / resolution.h as i64).max(0).min(rows - 1);
+            let (left, top) = (geometry.origin.x as i64, geometry.origin.y as i64);
+            let x0 = scale_x(left) as usize;
+            let x1 = scale_x(left + geometry.size.w as i64 - 1) as usize;
+            let y0 = scale_y(top) as usize + 1;
+            let y1 = scale_y(top + geometry.size.h as i64 - 1) as usize + 1;
+            let edge = |c| if view == focused { '#' } else { c };
+            for (y, row) in grid.iter_mut().enumerate().take(y1 + 1).skip(y0) {
+                for (x, cell) in row.iter_mut().enumerate().take(x1 + 1).skip(x0) {
+                    let vertical = x == x0 || x == x1;
+                    let horizontal = y == y0 || y == y1;
+                    *cell = match (horizontal, vertical) {
+                        (true, true) => edge('+'),
+                        (true, false) => edge('-'),
+                        (false, true) => edge('|'),
+                        (false, false) => ' '
+                    };
+                }
+            }
+            if x1 > x0 {
+                for (cell, c) in grid[y0][x0 + 1..x1].iter_mut().zip(label(view).chars()) {
+                    *cell = c;
+                }
+            }
+        }
+    }
+    grid.into_iter().map(|row| row.into_iter().collect()).collect()
+}
+
+/// Draws an output as an SVG document, in the output's coordinates.
+///
+/// Views are rectangles in the colors `rasterize` uses, with their titles.
+pub fn svg(output: WlcOutput) -> String {
+    let resolution = output.get_resolution().unwrap_or_default();
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+                           viewBox=\"0 0 {w} {h}\">\n<rect width=\"{w}\" height=\"{h}\" fill=\"{}\"/>\n",
+                          hex(PALETTE[1]), w = resolution.w, h = resolution.h);
+    let focused = dummy::focused_view();
+    for view in dummy::visible_views(output) {
+        let geometry = view.get_visible_geometry();
+        let border = if view == focused { PALETTE[3] } else { PALETTE[2] };
+        let _ = writeln!(svg, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" \
+                               stroke=\"{}\"/>",
+                         geometry.origin.x, geometry.origin.y, geometry.size.w, geometry.size.h,
+                         hex(fill_color(view)), hex(border));
+        let _ = writeln!(svg, "<text x=\"{}\" y=\"{}\" font-size=\"16\">{}</text>",
+                         geometry.origin.x + 4, geometry.origin.y + 20, escape(&label(view)));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// The title of a view, or its handle if it has none.
+pub(crate) fn label(view: WlcView) -> String {
+    let title = view.get_title();
+    if title.is_empty() { format!("{:?}", view) } else { title }
+}
+
+/// The color a view is filled with.
+fn fill_color(view: WlcView) -> [u8; 3] {
+    PALETTE[fill_index(view) as usize]
+}
+
+/// The index of the color a view is filled with.
+fn fill_index(view: WlcView) -> u8 {
+    4 + (view.into_raw() % 12) as u8
+}
+
+/// Formats a color as `#rrggbb`.
+fn hex(color: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+/// Escapes text for XML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Starts recording an output, forgetting any earlier frames.
+///
+/// Frames are drawn at `size`, see `rasterize`.
+pub fn record(output: WlcOutput, size: Size, capture: Capture) {
+    state::with(|state| state.recording = Some(Recording {
+        output, size, capture, frames: Log::new()
+    }));
+}
+
+/// Stops recording, returning the frames drawn.
+pub fn stop() -> Vec<Frame> {
+    state::with(|state| state.recording.take().map(|recording| recording.frames.to_vec()))
+        .unwrap_or_default()
+}
+
+/// Gets the frames drawn so far, oldest first.
+pub fn frames() -> Vec<Frame> {
+    state::with(|state| state.recording.as_ref().map(|recording| recording.frames.to_vec()))
+        .unwrap_or_default()
+}
+
+/// Draws a frame after a simulated event, if recording calls for one, and
+/// runs the renderers after a rendered frame.
+pub(crate) fn captured(event: &WlcEvent) {
+    if let WlcEvent::OutputRenderPost(output) = *event {
+        let renderers = state::with(|state| if state.dispatching.is_empty() {
+            state.renderers.clone()
+        } else {
+            Vec::new()
+        });
+        for renderer in renderers {
+            renderer.borrow_mut().render(output, dummy::time());
+        }
+    }
+    let target = state::with(|state| {
+        if !state.dispatching.is_empty() {
+            return None
+        }
+        state.recording.as_ref().and_then(|recording| match recording.capture {
+            Capture::Frame if *event != WlcEvent::OutputRenderPost(recording.output) => None,
+            _ => Some((recording.output, recording.size))
+        })
+    });
+    if let Some((output, size)) = target {
+        let frame = Frame { time: dummy::time(), image: rasterize(output, size) };
+        state::with(|state| if let Some(ref mut recording) = state.recording {
+            recording.frames.push(frame);
+        });
+    }
+}
+
+/// Draws the layout after every frame the dummy renders.
+///
+/// Renderers added with `add_renderer` are given each output after
+/// `simulate::render_frame` renders it, once its callbacks have run, so
+/// downstream projects can plug in their own drawing, i.e. their actual
+/// border code, next to the renderers here.
+pub trait LayoutRenderer {
+    /// Draws an output, at a time from `dummy::time()`.
+    fn render(&mut self, output: WlcOutput, time: u32);
+}
+
+/// Adds a renderer to the frame loop, see `LayoutRenderer`.
+///
+/// Returns the renderer, shared with the frame loop, so its results can be
+/// read back.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{raster, simulate, Size};
+/// use rustwlc::raster::AsciiRenderer;
+///
+/// let output = simulate::connect_output("WLC-1", Size { w: 800, h: 600 });
+/// let ascii = raster::add_renderer(AsciiRenderer::new(Size { w: 40, h: 10 }));
+/// simulate::render_frame(output);
+/// simulate::render_frame(output);
+/// assert_eq!(ascii.borrow().frames.len(), 2);
+/// ```
+pub fn add_renderer<R>(renderer: R) -> Rc<RefCell<R>> where R: LayoutRenderer + 'static {
+    let renderer = Rc::new(RefCell::new(renderer));
+    state::with(|state| state.renderers.push(Callback(renderer.clone())));
+    renderer
+}
+
+/// Removes all renderers from the frame loop.
+pub fn clear_renderers() {
+    state::with(|state| state.renderers.clear());
+}
+
+/// A renderer which draws nothing, i.e. to time the frame loop alone.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullRenderer;
+
+impl LayoutRenderer for NullRenderer {
+    fn render(&mut self, _output: WlcOutput, _time: u32) {}
+}
+
+/// Draws frames as text, see `ascii`.
+#[derive(Debug, Clone, Default)]
+pub struct AsciiRenderer {
+    /// Size of the frames, in characters
+    pub size: Size,
+    /// The frames drawn, oldest first
+    pub frames: Vec<(WlcOutput, Vec<String>)>
+}
+
+impl AsciiRenderer {
+    /// Makes a renderer which draws frames of `size`.
+    pub fn new(size: Size) -> AsciiRenderer {
+        AsciiRenderer { size, frames: Vec::new() }
+    }
+}
+
+impl LayoutRenderer for AsciiRenderer {
+    fn render(&mut self, output: WlcOutput, _time: u32) {
+        self.frames.push((output, ascii(output, self.size)));
+    }
+}
+
+/// Draws frames as SVG documents, see `svg`.
+#[derive(Debug, Clone, Default)]
+pub struct SvgRenderer {
+    /// The frames drawn, oldest first
+    pub frames: Vec<(WlcOutput, String)>
+}
+
+impl LayoutRenderer for SvgRenderer {
+    fn render(&mut self, output: WlcOutput, _time: u32) {
+        self.frames.push((output, svg(output)));
+    }
+}
+
+/// Draws frames as PNG files, see `rasterize` and `write_png`.
+#[derive(Debug, Clone, Default)]
+pub struct PngRenderer {
+    /// Size of the frames, in pixels
+    pub size: Size,
+    /// The frames drawn, oldest first
+    pub frames: Vec<(WlcOutput, Vec<u8>)>
+}
+
+impl PngRenderer {
+    /// Makes a renderer which draws frames of `size`.
+    pub fn new(size: Size) -> PngRenderer {
+        PngRenderer { size, frames: Vec::new() }
+    }
+}
+
+impl LayoutRenderer for PngRenderer {
+    fn render(&mut self, output: WlcOutput, _time: u32) {
+        let mut png = Vec::new();
+        // Writing to a Vec can't fail
+        let _ = write_png(&rasterize(output, self.size), &mut png);
+        self.frames.push((output, png));
+    }
+}
+
+/// Collects the frames of one output for an animated GIF, see `write_gif`.
+#[derive(Debug, Clone)]
+pub struct GifRenderer {
+    /// The output drawn
+    pub output: WlcOutput,
+    /// Size of the frames, in pixels
+    pub size: Size,
+    /// The frames drawn, oldest first
+    pub frames: Vec<Frame>
+}
+
+impl GifRenderer {
+    /// Makes a renderer which draws frames of an output at `size`.
+    pub fn new(output: WlcOutput, size: Size) -> GifRenderer {
+        GifRenderer { output, size, frames: Vec::new() }
+    }
+
+    /// Writes the frames drawn so far as an animated GIF.
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_gif(&self.frames, writer)
+    }
+}
+
+impl LayoutRenderer for GifRenderer {
+    fn render(&mut self, output: WlcOutput, time: u32) {
+        if output == self.output {
+            self.frames.push(Frame { time, image: rasterize(output, self.size) });
+        }
+    }
+}
+
+/// Writes frames as a looping animated GIF.
+///
+/// Each frame is shown until the time of the next one, but at least for
+/// `MIN_DELAY`; the last is shown for a second. The GIF takes the size of
+/// the first frame, which all frames should share.
+pub fn write_gif<W: Write>(frames: &[Frame], writer: &mut W) -> io::Result<()> {
+    let size = frames.first().map_or(Size::default(), |frame| frame.image.size);
+    writer.write_all(b"GIF89a")?;
+    writer.write_all(&u16le(size.w))?;
+    writer.write_all(&u16le(size.h))?;
+    // Global color table of 16 colors, 8 bits per primary
+    writer.write_all(&[0xf3, 0, 0])?;
+    for color in &PALETTE {
+        writer.write_all(color)?;
+    }
+    writer.write_all(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+    for (index, frame) in frames.iter().enumerate() {
+        let delay = frames.get(index + 1)
+            .map_or(100, |next| (next.time.wrapping_sub(frame.time) / 10).min(0xffff) as u16)
+            .max(MIN_DELAY);
+        writer.write_all(&[0x21, 0xf9, 0x04, 0x00])?;
+        writer.write_all(&delay.to_le_bytes())?;
+        writer.write_all(&[0x00, 0x00])?;
+        writer.write_all(&[0x2c, 0, 0, 0, 0])?;
+        writer.write_all(&u16le(frame.image.size.w))?;
+        writer.write_all(&u16le(frame.image.size.h))?;
+        writer.write_all(&[0x00, MIN_CODE_SIZE])?;
+        for block in lzw(&frame.image.pixels).chunks(255) {
+            writer.write_all(&[block.len() as u8])?;
+            writer.write_all(block)?;
+        }
+        writer.write_all(&[0x00])?;
+    }
+    writer.write_all(&[0x3b])
+}
+
+/// Writes an image as a PNG file, with the colors of `PALETTE`.
+///
+/// The pixels are stored without compression, which keeps this simple;
+/// images of layouts are small.
+pub fn write_png<W: Write>(image: &Image, writer: &mut W) -> io::Result<()> {
+    writer.write_all(b"\x89PNG\r\n\x1a\n")?;
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&image.size.w.to_be_bytes());
+    header.extend_from_slice(&image.size.h.to_be_bytes());
+    // 8 bits per pixel, indexed color, deflate, no interlacing
+    header.extend_from_slice(&[8, 3, 0, 0, 0]);
+    write_chunk(writer, b"IHDR", &header)?;
+    let palette: Vec<u8> = PALETTE.iter().flat_map(|color| color.iter().cloned()).collect();
+    write_chunk(writer, b"PLTE", &palette)?;
+    // Each row starts with filter type 0, none
+    let mut raw = Vec::with_capacity(image.pixels.len() + image.size.h as usize);
+    if image.size.w > 0 {
+        for row in image.pixels.chunks(image.size.w as usize) {
+            raw.push(0);
+            raw.extend_from_slice(row);
+        }
+    }
+    write_chunk(writer, b"IDAT", &zlib_stored(&raw))?;
+    write_chunk(writer, b"IEND", &[])
+}
+
+/// Writes a PNG chunk with its length and checksum.
+fn write_chunk<W: Write>(writer: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data));
+    writer.write_all(&crc.to_be_bytes())
+}
+
+/// Wraps data in a zlib stream of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        zlib.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let length = block.len() as u16;
+        zlib.push(last as u8);
+        zlib.extend_from_slice(&length.to_le_bytes());
+        zlib.extend_from_slice(&(!length).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    zlib.extend_from_slice(&((b << 16) | a).to_be_bytes());
+    zlib
+}
+
+/// The CRC-32 PNG checksums chunks with.
+fn crc32<'a, I>(bytes: I) -> u32 where I: IntoIterator<Item = &'a u8> {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb88320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// Bits per pixel the LZW data of a GIF starts from, for 16 colors.
+const MIN_CODE_SIZE: u8 = 4;
+
+/// A dimension as a GIF stores it.
+fn u16le(value: u32) -> [u8; 2] {
+    (value.min(0xffff) as u16).to_le_bytes()
+}
+
+/// Compresses pixels as GIF's variant of LZW.
+fn lzw(pixels: &[u8]) -> Vec<u8> {
+    let clear = 1u16 << MIN_CODE_SIZE;
+    let end = clear + 1;
+    let mut bits = BitWriter::default();
+    let mut size = MIN_CODE_SIZE as u32 + 1;
+    let mut codes: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = end + 1;
+    bits.write(clear, size);
+    let mut pixels = pixels.iter();
+    let mut current = match pixels.next() {
+        Some(&pixel) => pixel as u16,
+        None => {
+            bits.write(end, size);
+            return bits.finish()
+        }
+    };
+    for &pixel in pixels {
+        if let Some(&code) = codes.get(&(current, pixel)) {
+            current = code;
+            continue
+        }
+        bits.write(current, size);
+        if next < 4096 {
+            codes.insert((current, pixel), next);
+            next += 1;
+            // The decoder adds codes one behind, so it widens one late
+            if next > 1 << size {
+                size += 1;
+            }
+        } else {
+            bits.write(clear, size);
+            codes.clear();
+            next = end + 1;
+            size = MIN_CODE_SIZE as u32 + 1;
+        }
+        current = pixel as u16;
+    }
+    bits.write(current, size);
+    if next == 1 << size && size < 12 {
+        size += 1;
+    }
+    bits.write(end, size);
+    bits.finish()
+}
+
+/// Packs codes least significant bit first, as GIF does.
+#[derive(Debug, Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u32
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u32) {
+        self.buffer |= (code as u32) << self.count;
+        self.count += size;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::super::raster::Capture;
+
+    #[test]
+    fn capture_frames() {
+        let output = simulate::connect_output("WLC-1", Size { w: 800, h: 400 });
+        raster::record(output, Size { w: 80, h: 80 }, Capture::Frame);
+        let view = simulate::create_view();
+        view.set_geometry(EDGE_NONE, Geometry {
+            origin: Point { x: 0, y: 0 },
+            size: Size { w: 400, h: 400 }
+        });
+        view.focus();
+        assert!(raster::frames().is_empty());
+        simulate::render_frame(output);
+        dummy::advance_time(500);
+        simulate::render_frame(output);
+        let frames = raster::stop();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].time, 500);
+        // Letterboxed into the middle 80x40, with the view on the left half
+        let image = &frames[0].image;
+        assert_eq!(image.color(Point { x: 0, y: 10 }), Some(raster::PALETTE[0]));
+        assert_eq!(image.color(Point { x: 0, y: 20 }), Some(raster::PALETTE[3]));
+        assert_eq!(image.color(Point { x: 60, y: 40 }), Some(raster::PALETTE[1]));
+        assert!(raster::frames().is_empty());
+    }
+
+    #[test]
+    fn renderers() {
+        let output = simulate::connect_output("WLC-1", Size { w: 800, h: 600 });
+        let other = simulate::connect_output("WLC-2", Size { w: 800, h: 600 });
+        let view = simulate::create_view();
+        dummy::set_title(view, "<xterm>");
+        raster::add_renderer(raster::NullRenderer);
+        let svg = raster::add_renderer(raster::SvgRenderer::default());
+        let png = raster::add_renderer(raster::PngRenderer::new(Size { w: 40, h: 30 }));
+        let gif = raster::add_renderer(raster::GifRenderer::new(output, Size { w: 40, h: 30 }));
+        simulate::create_view();
+        simulate::render_frame(output);
+        simulate::render_frame(other);
+        assert_eq!(svg.borrow().frames.len(), 2);
+        assert!(svg.borrow().frames[0].1.contains("&lt;xterm&gt;"));
+        assert_eq!(png.borrow().frames[1].0, other);
+        assert!(png.borrow().frames[1].1.starts_with(b"\x89PNG"));
+        assert_eq!(gif.borrow().frames.len(), 1);
+        raster::clear_renderers();
+        simulate::render_frame(output);
+        assert_eq!(svg.borrow().frames.len(), 2);
+    }
+}
@@ -0,0 +1,137 @@
+//! Contains information about the renderer wlc uses for outputs, and
+//! reads and writes their pixels.
+
+use super::dummy;
+use super::types::Geometry;
+
+/// Describes the EGL/GL context wlc created for an output.
+///
+/// Compositors can inspect these strings to work around driver quirks.
+/// dummy-rustwlc reports a generic renderer unless a test configures
+/// another one with `dummy::set_context_info`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextInfo {
+    /// The GL vendor string, i.e. "Intel Open Source Technology Center"
+    pub vendor: String,
+    /// The GL renderer string, i.e. "Mesa DRI Intel(R) HD Graphics 520"
+    pub renderer: String,
+    /// The GL version string, i.e. "OpenGL ES 2.0 Mesa 12.0.1"
+    pub version: String,
+    /// Supported GL and EGL extensions
+    pub extensions: Vec<String>
+}
+
+impl ContextInfo {
+    /// Whether the context supports the named extension.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rustwlc::render::ContextInfo;
+    ///
+    /// let info = ContextInfo::default();
+    /// assert!(info.has_extension("EGL_WL_bind_wayland_display"));
+    /// assert!(!info.has_extension("GL_NV_not_a_real_extension"));
+    /// ```
+    pub fn has_extension(&self, name: &str) -> bool {
+        self.extensions.iter().any(|ext| ext == name)
+    }
+}
+
+impl Default for ContextInfo {
+    fn default() -> ContextInfo {
+        ContextInfo {
+            vendor: "dummy-rustwlc".to_string(),
+            renderer: "Dummy Renderer".to_string(),
+            version: "OpenGL ES 2.0 dummy-rustwlc".to_string(),
+            extensions: vec!["EGL_WL_bind_wayland_display".to_string(),
+                             "GL_OES_EGL_image".to_string(),
+                             "GL_EXT_texture_format_BGRA8888".to_string()]
+        }
+    }
+}
+
+/// Formats of pixels read or written with `read_pixels` and `write_pixels`.
+///
+/// Each output's framebuffer has one format, set with
+/// `dummy::set_pixel_format`.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub enum wlc_pixel_format {
+    /// Red, green, blue and alpha, a byte each
+    WLC_RGBA8888,
+    /// Red, green and blue in 5, 6 and 5 bits of a little-endian `u16`,
+    /// red in the highest bits
+    WLC_RGB565
+}
+
+impl wlc_pixel_format {
+    /// Number of bytes a pixel takes.
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            wlc_pixel_format::WLC_RGBA8888 => 4,
+            wlc_pixel_format::WLC_RGB565 => 2
+        }
+    }
+
+    /// Encodes an opaque color as a pixel in this format.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rustwlc::render::wlc_pixel_format;
+    ///
+    /// assert_eq!(wlc_pixel_format::WLC_RGBA8888.encode([0xff, 0x80, 0x00]),
+    ///            vec![0xff, 0x80, 0x00, 0xff]);
+    /// assert_eq!(wlc_pixel_format::WLC_RGB565.encode([0xff, 0x80, 0x00]), vec![0x00, 0xfc]);
+    /// ```
+    pub fn encode(self, [r, g, b]: [u8; 3]) -> Vec<u8> {
+        match self {
+            wlc_pixel_format::WLC_RGBA8888 => vec![r, g, b, 0xff],
+            wlc_pixel_format::WLC_RGB565 => {
+                let pixel = (r as u16 >> 3) << 11 | (g as u16 >> 2) << 5 | b as u16 >> 3;
+                pixel.to_le_bytes().to_vec()
+            }
+        }
+    }
+}
+
+/// Reads the pixels of an area of the output being rendered.
+///
+/// Returns the area actually read, clipped to the output, and its pixels,
+/// row by row from the top left. As in wlc, this only works in the render
+/// callbacks of an output, and during a screenshot (see
+/// `dummy::on_screenshot`); elsewhere it reads nothing, and is reported as
+/// a violation (see `dummy::set_strict`). The dummy's pixels are those
+/// `raster::rasterize` draws.
+///
+/// Reading in a format other than the output's (see
+/// `dummy::set_pixel_format`) reads nothing, and is a violation too.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, simulate, Geometry, Point, Size, WlcOutput};
+/// use rustwlc::render::{self, wlc_pixel_format};
+///
+/// fn output_render_post(output: WlcOutput) {
+///     let area = Geometry { origin: Point { x: 790, y: 0 }, size: Size { w: 20, h: 1 } };
+///     let (read, pixels) = render::read_pixels(wlc_pixel_format::WLC_RGBA8888, area);
+///     assert_eq!(read.size, Size { w: 10, h: 1 });
+///     assert_eq!(&pixels[..4], &[0x30, 0x30, 0x30, 0xff]);
+/// }
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// callback::rust::output_render_post(output_render_post);
+/// simulate::render_frame(output);
+/// ```
+pub fn read_pixels(format: wlc_pixel_format, geometry: Geometry) -> (Geometry, Vec<u8>) {
+    dummy::read_pixels(format, geometry)
+}
+
+/// Writes pixels over an area of the output being rendered.
+///
+/// The dummy draws nothing, but this is a violation outside the render
+/// callbacks of an output, as with `read_pixels`, if the format isn't the
+/// output's, or if `data` is the wrong size for the area in that format.
+pub fn write_pixels(format: wlc_pixel_format, geometry: Geometry, data: &[u8]) {
+    dummy::write_pixels(format, geometry, data)
+}
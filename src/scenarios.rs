@@ -0,0 +1,180 @@
+//! Canned sequences of hardware events, for regression tests.
+//!
+//! Each scenario scripts something users do with real monitors through the
+//! `simulate` module: outputs are connected and disconnected, change
+//! resolution, and go to sleep, a second apart on the dummy clock. The
+//! registered callbacks see the same events they would from wlc. `nested`
+//! sets up a compositor running in a window instead.
+//!
+//! Scenarios call `after_step` with a short description after every step,
+//! so a test can check its compositor's invariants throughout, not just at
+//! the end. They return the outputs they connected, which may no longer
+//! exist.
+//!
+//! # Example
+//! ```rust
+//! use rustwlc::{dummy, scenarios};
+//! use rustwlc::WlcOutput;
+//!
+//! let outputs = scenarios::laptop_dock(|step| {
+//!     assert!(dummy::violations().is_empty(), "after {}", step);
+//! });
+//! assert_eq!(WlcOutput::list(), vec![outputs.internal]);
+//! assert!(!outputs.internal.get_sleep());
+//! ```
+
+use super::dummy;
+use super::handle::WlcOutput;
+use super::simulate;
+use super::types::{BackendType, Size};
+
+/// Time between the steps of a scenario, in milliseconds.
+pub const STEP_DELAY: u32 = 1000;
+
+/// The outputs used by a scenario.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outputs {
+    /// The laptop's own screen
+    pub internal: WlcOutput,
+    /// The monitor or projector plugged into the laptop
+    pub external: WlcOutput
+}
+
+const LAPTOP: Size = Size { w: 1920, h: 1080 };
+
+/// Docks a laptop to a monitor, closes the lid, and undocks it again.
+///
+/// # Steps
+/// 1. "laptop connected": `eDP-1` is connected at 1920x1080
+/// 2. "dock connected": `DP-1` is connected at 2560x1440
+/// 3. "lid closed": `eDP-1` goes to sleep
+/// 4. "dock disconnected": `DP-1` is disconnected
+/// 5. "lid opened": `eDP-1` wakes up
+pub fn laptop_dock<F>(mut after_step: F) -> Outputs where F: FnMut(&str) {
+    let internal = step(&mut after_step, "laptop connected",
+                        || simulate::connect_output("eDP-1", LAPTOP));
+    let external = step(&mut after_step, "dock connected",
+                        || simulate::connect_output("DP-1", Size { w: 2560, h: 1440 }));
+    step(&mut after_step, "lid closed", || internal.set_sleep(true));
+    step(&mut after_step, "dock disconnected", || simulate::disconnect_output(external));
+    step(&mut after_step, "lid opened", || internal.set_sleep(false));
+    Outputs { internal, external }
+}
+
+/// Connects a projector, mirrors the laptop to it, and disconnects it.
+///
+/// # Steps
+/// 1. "laptop connected": `eDP-1` is connected at 1920x1080
+/// 2. "projector connected": `HDMI-A-1` is connected at 1024x768
+/// 3. "projector mode set": `HDMI-A-1` switches to 1280x720
+/// 4. "laptop mirrored": `eDP-1` switches to 1280x720
+/// 5. "projector disconnected": `HDMI-A-1` is disconnected
+/// 6. "laptop restored": `eDP-1` switches back to 1920x1080
+pub fn projector_connect<F>(mut after_step: F) -> Outputs where F: FnMut(&str) {
+    let projector = Size { w: 1280, h: 720 };
+    let internal = step(&mut after_step, "laptop connected",
+                        || simulate::connect_output("eDP-1", LAPTOP));
+    let external = step(&mut after_step, "projector connected",
+                        || simulate::connect_output("HDMI-A-1", Size { w: 1024, h: 768 }));
+    step(&mut after_step, "projector mode set",
+         || simulate::output_resolution(external, projector));
+    step(&mut after_step, "laptop mirrored",
+         || simulate::output_resolution(internal, projector));
+    step(&mut after_step, "projector disconnected",
+         || simulate::disconnect_output(external));
+    step(&mut after_step, "laptop restored",
+         || simulate::output_resolution(internal, LAPTOP));
+    Outputs { internal, external }
+}
+
+/// A compositor running nested in a window of another, see `nested`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Nested {
+    /// The output showing the host window
+    pub output: WlcOutput
+}
+
+impl Nested {
+    /// Resizes the host window, as a user dragging its edge, invoking
+    /// `output_resolution`.
+    pub fn resize(&self, size: Size) {
+        simulate::output_resolution(self.output, size);
+    }
+}
+
+/// Sets up a compositor running nested in a window of another, the usual
+/// setup while developing one.
+///
+/// wlc reports `BackendType::X11`, so the host grabs some chords (see
+/// `dummy::set_host_grabs`) and VTs aren't switched, and shows the window
+/// as its only output, `X11-1`, at the size of the window. Unlike the other
+/// scenarios, this is a single step, and takes no delay.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{scenarios, Size};
+///
+/// let nested = scenarios::nested(Size { w: 1280, h: 720 });
+/// nested.resize(Size { w: 800, h: 600 });
+/// assert_eq!(nested.output.get_resolution(), Some(Size { w: 800, h: 600 }));
+/// assert_eq!(rustwlc::get_backend_type(), rustwlc::BackendType::X11);
+/// ```
+pub fn nested(window: Size) -> Nested {
+    dummy::set_backend(BackendType::X11);
+    Nested { output: simulate::connect_output("X11-1", window) }
+}
+
+/// Runs a step after the step delay, then reports it.
+fn step<F, S, T>(after_step: &mut F, name: &str, run: S) -> T
+    where F: FnMut(&str), S: FnOnce() -> T {
+    dummy::advance_time(STEP_DELAY);
+    let result = run();
+    after_step(name);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use std::cell::RefCell;
+
+    thread_local! {
+        static RESOLUTIONS: RefCell<Vec<(Size, Size)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    // Written as for rustwlc, which the dummy still accepts.
+    extern "C" fn output_resolution(output: WlcOutput, old: &Size, new: &Size) {
+        RESOLUTIONS.with(|log| log.borrow_mut().push((*old, *new)));
+    }
+
+    #[test]
+    fn projector_connect() {
+        callback::output_resolution(output_resolution);
+        let mut steps = Vec::new();
+        let outputs = scenarios::projector_connect(|step| steps.push(step.to_string()));
+        assert_eq!(steps.len(), 6);
+        assert_eq!(dummy::time(), 6 * scenarios::STEP_DELAY);
+        assert_eq!(WlcOutput::list(), vec![outputs.internal]);
+        assert_eq!(outputs.internal.get_resolution(), Some(Size { w: 1920, h: 1080 }));
+        RESOLUTIONS.with(|log| assert_eq!(log.borrow().len(), 3));
+    }
+
+    #[test]
+    fn nested() {
+        use super::super::input::keys;
+        use super::super::keybindings::Chord;
+        callback::output_resolution(output_resolution);
+        let nested = scenarios::nested(Size { w: 1280, h: 720 });
+        assert_eq!(WlcOutput::list(), vec![nested.output]);
+        nested.resize(Size { w: 1920, h: 1080 });
+        RESOLUTIONS.with(|log| assert_eq!(*log.borrow(), vec![
+            (Size { w: 1280, h: 720 }, Size { w: 1920, h: 1080 })
+        ]));
+        // Ctrl+Alt+F2 reaches the compositor, Alt+Tab doesn't
+        keybindings::bind(&[Chord { mods: MOD_CTRL | MOD_ALT, key: keys::KEY_F2 }], "workspace 2");
+        keybindings::bind(&[Chord { mods: MOD_ALT, key: keys::KEY_TAB }], "next view");
+        let report = keybindings::check();
+        assert!(report.vt_switches.is_empty());
+        assert_eq!(report.host_grabs.len(), 1);
+    }
+}
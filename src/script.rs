@@ -0,0 +1,523 @@
+//! Scenarios written as text files, which can be watched as they grow.
+//!
+//! Each line of a script is one step, run through the `simulate` and
+//! `user` modules. Blank lines and lines starting with `#` are skipped.
+//!
+//! | Line | Step |
+//! |------|------|
+//! | `output NAME WxH` | Connects an output |
+//! | `disconnect NAME` | Disconnects an output |
+//! | `resolution NAME WxH` | Changes the resolution of an output |
+//! | `view TITLE` | Opens a view with a title on the primary output |
+//! | `client TEMPLATE TITLE` | Opens a view from a `fixtures` template, as `view` |
+//! | `close TITLE` | Closes the first view with a title |
+//! | `motion X Y` | Moves the pointer |
+//! | `click TITLE` | Clicks the topmost visible view with a title |
+//! | `type TEXT` | Types text |
+//! | `wait DURATION` | Advances the dummy clock, by i.e. `250ms` or `2s` |
+//! | `version N` | Declares the version of the format, as the first step |
+//!
+//! # Versions
+//! The format has a version, `VERSION`, which changes whenever a step
+//! would mean something else or stop parsing. Scripts declare the version
+//! they were written for with a `version` step, and ones without it are
+//! version `1`, from before versions were declared. Older scripts run as
+//! they were written, upgraded a line at a time as they load, and
+//! `migrate` rewrites them for the current version, so a collection of
+//! them can be upgraded for good.
+//!
+//! | Version | Changes |
+//! |---------|---------|
+//! | `1` | `wait MILLIS` takes a bare number of milliseconds |
+//! | `2` | `wait DURATION` takes a unit, and `version N` is added |
+//!
+//! For interactive development, a `Watcher` runs the lines appended to a
+//! file since it last looked, so a developer can drive their compositor by
+//! adding lines to the file in an editor while it runs against the dummy.
+//! A `Player` runs a script a step at a time, pausing at breakpoints, so
+//! timing-sensitive behavior can be stepped through, along with
+//! `dummy::set_clock_speed` to make `wait` take real time.
+//!
+//! # Example
+//! ```rust
+//! use rustwlc::{dummy, script};
+//!
+//! script::run("version 2\n\
+//!              output WLC-1 800x600\n\
+//!              view xterm\n\
+//!              wait 100ms").unwrap();
+//! assert_eq!(dummy::find_views(|info| info.title == "xterm").len(), 1);
+//! assert_eq!(dummy::time(), 100);
+//! assert_eq!(script::run("view\nfly away"), Err("line 2: unknown step \"fly\"".to_string()));
+//! ```
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::thread;
+use std::time::Duration;
+
+use super::callback::Callback;
+use super::clients::{self, Action};
+use super::dummy;
+use super::fixtures;
+use super::handle::{WlcOutput, WlcView};
+use super::simulate;
+use super::types::{Point, Size};
+use super::user;
+
+/// The current version of the format.
+pub const VERSION: u32 = 2;
+
+/// Runs the steps of a script, stopping at the first one which fails.
+///
+/// Scripts for older versions are upgraded as they run. Errors start with
+/// the number of the line, counting from `1`.
+pub fn run(text: &str) -> Result<(), String> {
+    let (version, header) = header(text)?;
+    for (index, line) in text.lines().enumerate() {
+        if Some(index) != header {
+            run_line(&upgrade(version, line))
+                .map_err(|error| format!("line {}: {}", index + 1, error))?;
+        }
+    }
+    Ok(())
+}
+
+/// Gets the version a script was written for.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::script;
+///
+/// assert_eq!(script::version("# Docking\nversion 2\noutput WLC-1 800x600"), Ok(2));
+/// assert_eq!(script::version("output WLC-1 800x600"), Ok(1));
+/// assert_eq!(script::version("version 99"),
+///            Err("line 1: version 99 is newer than this dummy-rustwlc's 2".to_string()));
+/// ```
+pub fn version(text: &str) -> Result<u32, String> {
+    header(text).map(|(version, _)| version)
+}
+
+/// Rewrites a script for the current version, declaring it on the first
+/// line.
+///
+/// Comments and blank lines are kept.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::script;
+///
+/// assert_eq!(script::migrate("# Slow start\nwait 2000\n").unwrap(),
+///            "version 2\n# Slow start\nwait 2000ms\n");
+/// ```
+pub fn migrate(text: &str) -> Result<String, String> {
+    let (version, header) = header(text)?;
+    let mut migrated = format!("version {}\n", VERSION);
+    for (index, line) in text.lines().enumerate() {
+        if Some(index) != header {
+            migrated.push_str(&upgrade(version, line));
+            migrated.push('\n');
+        }
+    }
+    Ok(migrated)
+}
+
+/// Runs one line of a script, for the current version.
+pub fn run_line(line: &str) -> Result<(), String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(())
+    }
+    let (step, rest) = match line.find(char::is_whitespace) {
+        Some(index) => (&line[..index], line[index..].trim_start()),
+        None => (line, "")
+    };
+    let args: Vec<&str> = rest.split_whitespace().collect();
+    match (step, args.as_slice()) {
+        ("output", &[name, size]) => {
+            simulate::connect_output(name, parse_size(size)?);
+        },
+        ("disconnect", &[name]) => simulate::disconnect_output(output(name)?),
+        ("resolution", &[name, size]) => {
+            simulate::output_resolution(output(name)?, parse_size(size)?);
+        },
+        ("view", _) => {
+            if WlcOutput::list().is_empty() {
+                return Err("no output to open the view on".to_string())
+            }
+            simulate::open_view(rest);
+        },
+        ("client", &[name, ..]) => {
+            if WlcOutput::list().is_empty() {
+                return Err("no output to open the view on".to_string())
+            }
+            let title = rest[name.len()..].trim_start();
+            simulate::open(fixtures::from_template(WlcOutput::get_primary(), name, title)?);
+        },
+        ("close", _) => clients::perform(view(rest)?, &Action::Close),
+        ("motion", &[x, y]) => {
+            simulate::pointer_motion(Point { x: parse(x)?, y: parse(y)? });
+        },
+        ("click", _) => user::click_on_title(rest)?,
+        ("type", _) => user::type_str(rest)?,
+        ("wait", &[duration]) => dummy::advance_time(parse_duration(duration)?),
+        ("version", _) => return Err("\"version\" must be the first step".to_string()),
+        ("output", _) | ("disconnect", _) | ("resolution", _) | ("client", _) | ("motion", _) |
+        ("wait", _) => {
+            return Err(format!("wrong number of arguments to \"{}\"", step))
+        },
+        _ => return Err(format!("unknown step \"{}\"", step))
+    }
+    Ok(())
+}
+
+/// Plays a script, pausing at breakpoints.
+///
+/// Breakpoints pause before a line, or after any step once a predicate
+/// holds, i.e. once a view has been focused. Playing again resumes where
+/// it paused.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, script};
+/// use rustwlc::script::{Player, Playback};
+///
+/// let mut player = Player::new("version 2\n\
+///                               output WLC-1 800x600\n\
+///                               view xterm\n\
+///                               wait 100ms\n\
+///                               view firefox").unwrap();
+/// player.break_at(4);
+/// player.break_when(|| dummy::find_views(|_| true).len() == 2);
+/// assert_eq!(player.play(), Ok(Playback::Paused { line: 4 }));
+/// assert_eq!(dummy::time(), 0);
+/// assert_eq!(player.play(), Ok(Playback::Paused { line: 6 }));
+/// assert_eq!(player.play(), Ok(Playback::Finished));
+/// assert_eq!(dummy::time(), 100);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Player {
+    lines: Vec<String>,
+    next: usize,
+    lines_to_break: Vec<usize>,
+    predicates: Vec<Callback<dyn Fn() -> bool>>
+}
+
+/// Where playing a script stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Playback {
+    /// Paused at a breakpoint, before a line, counting from `1`
+    Paused {
+        /// The line which runs next
+        line: usize
+    },
+    /// Every line has run
+    Finished
+}
+
+impl Player {
+    /// Loads a script, to play from its first line.
+    ///
+    /// Scripts for older versions are upgraded as they load. Fails if the
+    /// script declares a version it can't be upgraded from.
+    pub fn new(text: &str) -> Result<Player, String> {
+        let (version, header) = header(text)?;
+        let lines = text.lines().enumerate().map(|(index, line)| {
+            if Some(index) == header { String::new() } else { upgrade(version, line) }
+        }).collect();
+        Ok(Player { lines, next: 0, lines_to_break: Vec::new(), predicates: Vec::new() })
+    }
+
+    /// Pauses before running a line, counting from `1`.
+    pub fn break_at(&mut self, line: usize) {
+        self.lines_to_break.push(line);
+    }
+
+    /// Pauses after any step once `predicate` holds.
+    pub fn break_when<F>(&mut self, predicate: F) where F: Fn() -> bool + 'static {
+        self.predicates.push(Callback(Rc::new(predicate)));
+    }
+
+    /// Removes all breakpoints.
+    pub fn clear_breakpoints(&mut self) {
+        self.lines_to_break.clear();
+        self.predicates.clear();
+    }
+
+    /// Gets the line which runs next, counting from `1`.
+    pub fn line(&self) -> usize {
+        self.next + 1
+    }
+
+    /// Runs steps until a breakpoint or the end of the script.
+    ///
+    /// The first step always runs, so playing from a breakpoint resumes.
+    /// Errors are numbered as in `run`, and the line which failed isn't
+    /// run again.
+    pub fn play(&mut self) -> Result<Playback, String> {
+        let mut first = true;
+        while self.next < self.lines.len() {
+            if !first && self.lines_to_break.contains(&self.line()) {
+                return Ok(Playback::Paused { line: self.line() })
+            }
+            first = false;
+            self.step()?;
+            if self.predicates.iter().any(|predicate| (predicate.0)()) {
+                return Ok(Playback::Paused { line: self.line() })
+            }
+        }
+        Ok(Playback::Finished)
+    }
+
+    /// Runs the next line, ignoring breakpoints.
+    ///
+    /// Returns whether any lines are left.
+    pub fn step(&mut self) -> Result<bool, String> {
+        if let Some(line) = self.lines.get(self.next) {
+            self.next += 1;
+            run_line(line).map_err(|error| format!("line {}: {}", self.next, error))?;
+        }
+        Ok(self.next < self.lines.len())
+    }
+}
+
+/// Runs the lines appended to a script file since it last looked.
+///
+/// Only whole lines are run, so a line being typed is run once it ends.
+/// Lines which were already run are not run again if they are edited. If
+/// the file shrinks, i.e. because it was cleared, the watcher carries on
+/// from its new end.
+///
+/// The version of the file is read from its first step, as in `run`.
+#[derive(Debug, Clone)]
+pub struct Watcher {
+    path: PathBuf,
+    offset: u64,
+    line: usize,
+    version: Option<u32>
+}
+
+impl Watcher {
+    /// Starts watching a file from its beginning.
+    ///
+    /// The file doesn't need to exist yet.
+    pub fn new<P: AsRef<Path>>(path: P) -> Watcher {
+        Watcher { path: path.as_ref().to_path_buf(), offset: 0, line: 0, version: None }
+    }
+
+    /// Runs the new whole lines of the file, if any.
+    ///
+    /// Returns the errors of the lines which failed, numbered as in `run`.
+    /// The other lines still run.
+    pub fn poll(&mut self) -> io::Result<Vec<String>> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error)
+        };
+        let length = file.metadata()?.len();
+        if length < self.offset {
+            self.offset = length;
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut text = String::new();
+        file.read_to_string(&mut text)?;
+        let whole = match text.rfind('\n') {
+            Some(end) => &text[..end + 1],
+            None => return Ok(Vec::new())
+        };
+        self.offset += whole.len() as u64;
+        let mut errors = Vec::new();
+        for line in whole.lines() {
+            self.line += 1;
+            let result = match self.version {
+                Some(version) => run_line(&upgrade(version, line)),
+                None if line.trim().is_empty() || line.trim().starts_with('#') => Ok(()),
+                None => match declared(line) {
+                    Some(version) => version.map(|version| self.version = Some(version)),
+                    None => {
+                        self.version = Some(1);
+                        run_line(&upgrade(1, line))
+                    }
+                }
+            };
+            if let Err(error) = result {
+                errors.push(format!("line {}: {}", self.line, error));
+            }
+        }
+        Ok(errors)
+    }
+
+    /// Polls the file every `interval`, forever, printing errors to
+    /// standard error.
+    ///
+    /// Returns only if the file can't be read.
+    pub fn watch(&mut self, interval: Duration) -> io::Result<()> {
+        loop {
+            for error in self.poll()? {
+                eprintln!("{}: {}", self.path.display(), error);
+            }
+            thread::sleep(interval);
+        }
+    }
+}
+
+/// Finds the version a script declares, and the index of the line which
+/// declares it, if any.
+fn header(text: &str) -> Result<(u32, Option<usize>), String> {
+    let first = text.lines().enumerate()
+        .find(|&(_, line)| !line.trim().is_empty() && !line.trim().starts_with('#'));
+    let (index, line) = match first {
+        Some(first) => first,
+        None => return Ok((1, None))
+    };
+    match declared(line) {
+        Some(version) => version.map(|version| (version, Some(index)))
+            .map_err(|error| format!("line {}: {}", index + 1, error)),
+        None => Ok((1, None))
+    }
+}
+
+/// Parses a `version` step, if the line is one.
+fn declared(line: &str) -> Option<Result<u32, String>> {
+    let mut words = line.split_whitespace();
+    if words.next() != Some("version") {
+        return None
+    }
+    Some(match (words.next(), words.next()) {
+        (Some(version), None) => parse(version).and_then(|version| match version {
+            0 => Err("there is no version 0".to_string()),
+            version if version > VERSION => {
+                Err(format!("version {} is newer than this dummy-rustwlc's {}", version, VERSION))
+            },
+            version => Ok(version)
+        }),
+        _ => Err("wrong number of arguments to \"version\"".to_string())
+    })
+}
+
+/// Upgrades a line of a script for a version to the current version.
+fn upgrade(version: u32, line: &str) -> String {
+    let mut line = line.to_string();
+    if version < 2 {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if let ["wait", millis] = words.as_slice() {
+            if millis.parse::<u32>().is_ok() {
+                line = format!("wait {}ms", millis);
+            }
+        }
+    }
+    line
+}
+
+/// Finds an output by name.
+fn output(name: &str) -> Result<WlcOutput, String> {
+    WlcOutput::list().into_iter().find(|output| output.get_name() == name)
+        .ok_or_else(|| format!("no output named \"{}\"", name))
+}
+
+/// Finds the first view with a title, by handle.
+fn view(title: &str) -> Result<WlcView, String> {
+    dummy::find_views(|info| info.title == title).into_iter().min()
+        .ok_or_else(|| format!("no view titled \"{}\"", title))
+}
+
+/// Parses a size such as `1920x1080`.
+fn parse_size(text: &str) -> Result<Size, String> {
+    let mut parts = text.splitn(2, 'x');
+    match (parts.next(), parts.next()) {
+        (Some(w), Some(h)) => Ok(Size { w: parse(w)?, h: parse(h)? }),
+        _ => Err(format!("\"{}\" is not a size such as 800x600", text))
+    }
+}
+
+/// Parses a duration such as `250ms` or `2s`, in milliseconds.
+fn parse_duration(text: &str) -> Result<u32, String> {
+    let millis = if let Some(millis) = text.strip_suffix("ms") {
+        millis.parse().ok()
+    } else if let Some(secs) = text.strip_suffix('s') {
+        secs.parse::<u32>().ok().and_then(|secs| secs.checked_mul(1000))
+    } else {
+        None
+    };
+    millis.ok_or_else(|| format!("\"{}\" is not a duration such as 250ms or 2s", text))
+}
+
+/// Parses a number.
+fn parse<T: ::std::str::FromStr>(text: &str) -> Result<T, String> {
+    text.parse().map_err(|_| format!("\"{}\" is not a number", text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+
+    #[test]
+    fn watcher() {
+        let path = std::env::temp_dir()
+            .join(format!("dummy-rustwlc-watcher-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut watcher = script::Watcher::new(&path);
+        assert_eq!(watcher.poll().unwrap(), Vec::<String>::new());
+        let mut file = OpenOptions::new().create(true).append(true).open(&path).unwrap();
+        write!(file, "output WLC-1 800x600\nview xterm\nview fire").unwrap();
+        assert!(watcher.poll().unwrap().is_empty());
+        assert_eq!(dummy::find_views(|_| true).len(), 1);
+        // The line being typed runs once it ends
+        write!(file, "fox\nclose nothing\n").unwrap();
+        assert_eq!(watcher.poll().unwrap(), vec!["line 4: no view titled \"nothing\"".to_string()]);
+        assert_eq!(dummy::find_views(|info| info.title == "firefox").len(), 1);
+        assert!(watcher.poll().unwrap().is_empty());
+        assert_eq!(dummy::find_views(|_| true).len(), 2);
+        drop(file);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn player() {
+        use super::super::dummy::ClockSpeed;
+        use super::super::script::{Player, Playback};
+        use std::time::{Duration, Instant};
+        let mut player = Player::new("output WLC-1 800x600\n\
+                                      wait 500\n\
+                                      close nothing\n\
+                                      view xterm").unwrap();
+        player.break_at(2);
+        assert_eq!(player.play(), Ok(Playback::Paused { line: 2 }));
+        // Half a second of mock time in 5ms
+        dummy::set_clock_speed(ClockSpeed::RealTime(100.0));
+        let start = Instant::now();
+        assert_eq!(player.step(), Ok(true));
+        assert!(start.elapsed() >= Duration::from_millis(5));
+        assert_eq!(dummy::time(), 500);
+        assert_eq!(player.play(), Err("line 3: no view titled \"nothing\"".to_string()));
+        assert_eq!(player.line(), 4);
+        assert_eq!(player.play(), Ok(Playback::Finished));
+        assert_eq!(player.step(), Ok(false));
+        assert_eq!(dummy::find_views(|_| true).len(), 1);
+    }
+
+    #[test]
+    fn versions() {
+        let old = "output WLC-1 800x600\n\nwait 1500\nwait 2s\n";
+        assert_eq!(script::version(old), Ok(1));
+        let migrated = script::migrate(old).unwrap();
+        assert_eq!(migrated, "version 2\noutput WLC-1 800x600\n\nwait 1500ms\nwait 2s\n");
+        assert_eq!(script::migrate(&migrated).unwrap(), migrated);
+        script::run(old).unwrap();
+        assert_eq!(dummy::time(), 3500);
+        script::run(&migrated).unwrap();
+        assert_eq!(dummy::time(), 7000);
+        assert_eq!(script::run("version 2\nwait 5"),
+                   Err("line 2: \"5\" is not a duration such as 250ms or 2s".to_string()));
+        assert_eq!(script::run("wait 5\nversion 2"),
+                   Err("line 2: \"version\" must be the first step".to_string()));
+        assert_eq!(script::version("version two"),
+                   Err("line 1: \"two\" is not a number".to_string()));
+        assert!(script::Player::new("version 3").is_err());
+    }
+}
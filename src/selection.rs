@@ -0,0 +1,267 @@
+//! The clipboard and primary selections between fake clients.
+//!
+//! wlc passes the selections between clients itself and tells compositors
+//! nothing about them, so this models them for compositors which manage
+//! them through a client of their own, i.e. a clipboard manager which
+//! keeps the clipboard alive after its client closes. A client offers data
+//! under one or more MIME types, a reader negotiates the type it prefers
+//! out of those offered, and reads the data in chunks, as through the pipe
+//! of a real transfer. Fake clients can offer with
+//! `clients::Action::SetSelection`.
+//!
+//! The clipboard holds what was copied explicitly, and the primary
+//! selection what was last selected, for pasting with the middle button.
+//! They are separate: setting one leaves the other alone. Compositors
+//! which make the primary selection a setting can turn it off with
+//! `set_primary_enabled`.
+//!
+//! A selection is dropped when the view which offered it is removed, as
+//! when its client disconnects.
+//!
+//! # Example
+//! ```rust
+//! use rustwlc::{dummy, selection, Size};
+//! use rustwlc::selection::Selection;
+//!
+//! let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+//! let editor = dummy::add_view(output);
+//! selection::offer(Selection::Clipboard, editor, vec![
+//!     ("text/html".to_string(), b"<b>bold</b>".to_vec()),
+//!     ("text/plain;charset=utf-8".to_string(), b"bold".to_vec())
+//! ]);
+//! let mime = selection::negotiate(Selection::Clipboard,
+//!                                 &["text/plain;charset=utf-8", "text/plain"]).unwrap();
+//! let chunks: Vec<Vec<u8>> = selection::transfer(Selection::Clipboard, &mime, 3)
+//!     .unwrap().collect();
+//! assert_eq!(chunks, vec![b"bol".to_vec(), b"d".to_vec()]);
+//! assert_eq!(selection::source(Selection::Primary), None);
+//!
+//! dummy::remove_view(editor);
+//! assert_eq!(selection::source(Selection::Clipboard), None);
+//! ```
+
+use std::rc::Rc;
+
+use super::callback::Callback;
+use super::dummy::state;
+use super::handle::WlcView;
+
+/// Which selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Selection {
+    /// The clipboard, for copy and paste
+    Clipboard,
+    /// The primary selection, for selecting and pasting with the middle
+    /// button
+    Primary
+}
+
+/// A hook from `on_change`.
+pub(crate) type Hook = Callback<dyn Fn(Selection, Option<WlcView>)>;
+
+/// A selection offered by a client.
+#[derive(Debug, Clone)]
+pub(crate) struct Offer {
+    pub source: WlcView,
+    pub offers: Vec<(String, Rc<Vec<u8>>)>
+}
+
+/// Sets a selection, as a view's client copying data under some MIME
+/// types, in the order it prefers them.
+///
+/// This replaces what the selection held, and calls the hook from
+/// `on_change`. Returns `false`, doing nothing, if the selection is the
+/// primary selection and it is turned off.
+pub fn offer(selection: Selection, view: WlcView, offers: Vec<(String, Vec<u8>)>) -> bool {
+    let set = state::with(|state| {
+        if selection == Selection::Primary && state.primary_disabled {
+            return false
+        }
+        *state.selection_mut(selection) = Some(Offer {
+            source: view,
+            offers: offers.into_iter().map(|(mime, data)| (mime, Rc::new(data))).collect()
+        });
+        true
+    });
+    if set {
+        changed(selection, Some(view));
+    }
+    set
+}
+
+/// Clears a selection, calling the hook from `on_change` if it held
+/// anything.
+pub fn clear(selection: Selection) {
+    if state::with(|state| state.selection_mut(selection).take()).is_some() {
+        changed(selection, None);
+    }
+}
+
+/// Turns the primary selection on or off. It is on by default.
+///
+/// Turning it off clears it.
+pub fn set_primary_enabled(enabled: bool) {
+    state::with(|state| state.primary_disabled = !enabled);
+    if !enabled {
+        clear(Selection::Primary);
+    }
+}
+
+/// Gets the view whose client offered a selection, if any.
+pub fn source(selection: Selection) -> Option<WlcView> {
+    state::with(|state| state.selection_mut(selection).as_ref().map(|offer| offer.source))
+}
+
+/// Gets the MIME types a selection is offered as, in the order its client
+/// prefers them.
+pub fn mime_types(selection: Selection) -> Vec<String> {
+    state::with(|state| state.selection_mut(selection).iter()
+                .flat_map(|offer| offer.offers.iter().map(|offer| offer.0.clone()))
+                .collect())
+}
+
+/// Picks the first of a reader's preferred MIME types which a selection is
+/// offered as, if any.
+pub fn negotiate(selection: Selection, preferred: &[&str]) -> Option<String> {
+    let offered = mime_types(selection);
+    preferred.iter().find(|mime| offered.iter().any(|offer| offer == *mime))
+        .map(|mime| mime.to_string())
+}
+
+/// Starts reading a selection as a MIME type, in chunks of at most
+/// `chunk` bytes.
+///
+/// The data is read as it was when the transfer started, even if the
+/// selection changes meanwhile.
+///
+/// # Panics
+/// If `chunk` is zero.
+pub fn transfer(selection: Selection, mime: &str, chunk: usize) -> Result<Transfer, String> {
+    assert!(chunk > 0, "dummy-rustwlc selection transfer with chunks of zero bytes");
+    let data = state::with(|state| {
+        let offer = state.selection_mut(selection).as_ref()
+            .ok_or_else(|| format!("{:?} is empty", selection))?;
+        offer.offers.iter().find(|offer| offer.0 == mime).map(|offer| offer.1.clone())
+            .ok_or_else(|| format!("{:?} is not offered as \"{}\"", selection, mime))
+    })?;
+    Ok(Transfer { data, offset: 0, chunk })
+}
+
+/// Sets a hook, called with a selection and its new source whenever it
+/// changes or is dropped.
+///
+/// A clipboard manager would read the new clipboard and offer it again
+/// from its own view.
+pub fn on_change<F>(hook: F) where F: Fn(Selection, Option<WlcView>) + 'static {
+    state::with(|state| state.selection_hook = Some(Callback(Rc::new(hook))));
+}
+
+/// Calls the hook from `on_change`.
+pub(crate) fn changed(selection: Selection, source: Option<WlcView>) {
+    if let Some(hook) = state::with(|state| state.selection_hook.clone()) {
+        (hook.0)(selection, source);
+    }
+}
+
+/// A transfer of the selection, giving its data in chunks.
+#[derive(Debug, Clone)]
+pub struct Transfer {
+    data: Rc<Vec<u8>>,
+    offset: usize,
+    chunk: usize
+}
+
+impl Transfer {
+    /// Number of bytes left to read.
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.offset
+    }
+}
+
+impl Iterator for Transfer {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Vec<u8>> {
+        if self.offset == self.data.len() {
+            return None
+        }
+        let end = self.data.len().min(self.offset + self.chunk);
+        let chunk = self.data[self.offset..end].to_vec();
+        self.offset = end;
+        Some(chunk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::super::clients::Action;
+    use super::super::selection::Selection::{Clipboard, Primary};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn clipboard_manager() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let manager = dummy::add_view(output);
+        let client = dummy::add_view(output);
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let log = changes.clone();
+        // Keeps a copy of plain text copied by other clients
+        selection::on_change(move |selection, source| {
+            log.borrow_mut().push((selection, source));
+            if selection == Primary || source.is_none_or(|source| source == manager) {
+                return
+            }
+            if let Some(mime) = selection::negotiate(Clipboard, &["text/plain"]) {
+                let data = selection::transfer(Clipboard, &mime, 4096).unwrap().flatten().collect();
+                selection::offer(Clipboard, manager, vec![(mime, data)]);
+            }
+        });
+        let image = vec![7; 10_000];
+        clients::script(client, vec![
+            (1, Action::SetSelection(Clipboard, vec![("image/png".to_string(), image)]))
+        ]);
+        simulate::dispatch(WlcEvent::CompositorReady);
+        assert_eq!(selection::source(Clipboard), Some(client));
+        let mut transfer = selection::transfer(Clipboard, "image/png", 4096).unwrap();
+        assert_eq!(transfer.next().map(|chunk| chunk.len()), Some(4096));
+        assert_eq!(transfer.remaining(), 10_000 - 4096);
+        assert_eq!(selection::transfer(Clipboard, "text/plain", 1).unwrap_err(),
+                   "Clipboard is not offered as \"text/plain\"");
+        dummy::remove_view(client);
+        assert_eq!(transfer.flatten().count(), 10_000 - 4096);
+
+        let other = dummy::add_view(output);
+        selection::offer(Clipboard, other, vec![("text/plain".to_string(), b"hi".to_vec())]);
+        assert_eq!(selection::source(Clipboard), Some(manager));
+        simulate::disconnect_output(output);
+        assert_eq!(*changes.borrow(), vec![(Clipboard, Some(client)), (Clipboard, None),
+                                           (Clipboard, Some(other)), (Clipboard, Some(manager)),
+                                           (Clipboard, None)]);
+        assert_eq!(selection::source(Clipboard), None);
+    }
+
+    #[test]
+    fn primary() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let terminal = dummy::add_view(output);
+        let editor = dummy::add_view(output);
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let log = changes.clone();
+        selection::on_change(move |selection, source| log.borrow_mut().push((selection, source)));
+        let text = |text: &str| vec![("text/plain".to_string(), text.as_bytes().to_vec())];
+        assert!(selection::offer(Primary, terminal, text("ls -l")));
+        assert!(selection::offer(Clipboard, editor, text("fn main")));
+        assert_eq!(selection::source(Primary), Some(terminal));
+        let pasted: Vec<u8> = selection::transfer(Primary, "text/plain", 64).unwrap()
+            .flatten().collect();
+        assert_eq!(pasted, b"ls -l");
+        selection::set_primary_enabled(false);
+        assert!(!selection::offer(Primary, editor, text("main")));
+        assert_eq!(selection::transfer(Primary, "text/plain", 64).unwrap_err(), "Primary is empty");
+        assert_eq!(selection::source(Clipboard), Some(editor));
+        assert_eq!(*changes.borrow(), vec![(Primary, Some(terminal)), (Clipboard, Some(editor)),
+                                           (Primary, None)]);
+    }
+}
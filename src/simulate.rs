@@ -0,0 +1,1802 @@
+//! Injects events into the dummy compositor, invoking registered callbacks.
+//!
+//! Where wlc invokes callbacks in response to hardware and clients, tests
+//! use the functions in this module. Callbacks receive handles and state
+//! from the `dummy` module, and the current `dummy::time()`.
+//!
+//! # Example
+//! ```rust
+//! use rustwlc::{callback, dummy, simulate};
+//! use rustwlc::{WlcView, Point, Size};
+//! use rustwlc::input::pointer;
+//!
+//! fn pointer_motion(view: WlcView, time: u32, point: &Point) -> bool {
+//!     pointer::set_position(*point);
+//!     false
+//! }
+//!
+//! dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+//! callback::rust::pointer_motion(pointer_motion);
+//! simulate::pointer_motion(Point { x: 10, y: 20 });
+//! assert_eq!(pointer::get_position(), Point { x: 10, y: 20 });
+//! ```
+
+use super::callback;
+use super::calls;
+use super::clients;
+use super::contract;
+use super::dummy::log::Log;
+use super::dummy::{self, state, ClientRequest, FrameClock, InteractiveOp, KeyOrder,
+                   MotionRate, RenderAllocation, ScreenshotTarget, XWayland};
+use super::event::{ScrollSource, WlcEvent};
+use super::handle::{WlcOutput, WlcView};
+use super::invariants;
+use super::input::keys::{self, Key};
+use super::input::pointer;
+use super::raster;
+use super::types::{ButtonState, Geometry, KeyboardModifiers, KeyState, Point, ResizeEdge,
+                   ScrollAxis, Size, TouchType, ViewState};
+use super::types::{KeyMod, MOD_ALT, MOD_CAPS, MOD_CTRL, MOD_MOD2, MOD_MOD4, MOD_NONE, MOD_SHIFT};
+
+use std::mem;
+use std::time::{Duration, Instant};
+
+/// Invokes the callback registered for an event, as is.
+///
+/// Unlike the other functions in this module, this doesn't look at or
+/// change any dummy state: the event is delivered exactly as given. Client
+/// behaviors from the `clients` module run afterwards, and then the
+/// `invariants` are checked.
+///
+/// Returns the callback's result. If the callback returns nothing or isn't
+/// registered, returns what wlc would assume: `true` (allowed) for
+/// `OutputCreated` and `ViewCreated`, `false` (not blocked) otherwise.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, simulate};
+/// use rustwlc::{WlcEvent, WlcView};
+///
+/// fn view_created(view: WlcView) -> bool {
+///     false
+/// }
+///
+/// let event = WlcEvent::ViewCreated(WlcView::dummy(1));
+/// assert!(simulate::dispatch(event));
+/// callback::rust::view_created(view_created);
+/// assert!(!simulate::dispatch(event));
+/// ```
+pub fn dispatch(event: WlcEvent) -> bool {
+    dummy::checkpoint(event);
+    let result = contract::checked(&event, || callback::dispatch(&event));
+    clients::run();
+    invariants::check(&event);
+    raster::captured(&event);
+    result
+}
+
+/// Dispatches the events queued with `dummy::queue_event`, oldest first.
+///
+/// Events queued by callbacks meanwhile are dispatched too. Returns the
+/// number of events dispatched.
+pub fn dispatch_pending() -> usize {
+    let mut count = 0;
+    while let Some(event) = dummy::next_pending() {
+        dispatch(event);
+        count += 1;
+    }
+    count
+}
+
+/// Presses or releases a key, invoking the `keyboard_key` callback.
+///
+/// The callback is given the focused view (or `WlcView::root()`), the
+/// current time, and the modifiers of the held shift, control, alt and
+/// logo keys, including this one if it is being pressed, along with those
+/// latched or locked by caps lock, num lock, or sticky keys. See
+/// `dummy::modifier_state`. With `dummy::set_modal_routing`, keys for a
+/// view with a modal dialog go to the dialog. When running nested in X11,
+/// chords grabbed by the host never reach the callback, see
+/// `dummy::set_host_grabs`. Pressing a held key again, or releasing one
+/// which isn't held, is reported or corrected as `dummy::set_key_order`
+/// says.
+///
+/// Returns whether the callback blocked the key from the view.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, simulate};
+/// use rustwlc::{WlcView, KeyboardModifiers, KeyState, MOD_CTRL};
+/// use rustwlc::input::keys;
+///
+/// fn keyboard_key(view: WlcView, time: u32, mods: &KeyboardModifiers,
+///                 key: u32, state: KeyState) -> bool {
+///     mods.mods == MOD_CTRL && key == keys::KEY_Q.get_code()
+/// }
+///
+/// callback::rust::keyboard_key(keyboard_key);
+/// assert!(!simulate::keyboard_key(keys::KEY_LEFTCTRL, KeyState::Pressed));
+/// assert!(simulate::keyboard_key(keys::KEY_Q, KeyState::Pressed));
+/// ```
+pub fn keyboard_key(key: Key, state: KeyState) -> bool {
+    if held(Input::Key(key, state)) {
+        return false
+    }
+    let pressed = state::with(|st| st.held_keys.contains(&key));
+    if pressed == (state == KeyState::Pressed) {
+        match (dummy::key_order(), state) {
+            (KeyOrder::Correct, KeyState::Pressed) => {
+                keyboard_key(key, KeyState::Released);
+            },
+            (KeyOrder::Correct, KeyState::Released) => return false,
+            (KeyOrder::Report, KeyState::Pressed) => {
+                dummy::violation(format!("{:?} was pressed twice without being released", key));
+            },
+            (KeyOrder::Report, KeyState::Released) => {
+                dummy::violation(format!("{:?} was released without being pressed", key));
+            }
+        }
+    }
+    flush_motion();
+    let held = modifier(key);
+    state::with(|st| match state {
+        KeyState::Pressed => {
+            if !st.held_keys.contains(&key) {
+                st.held_keys.push(key);
+            }
+            st.modifiers.depressed.insert(held);
+            st.modifiers.locked.toggle(lock(key));
+            st.modifier_used = held == MOD_NONE;
+        },
+        KeyState::Released => {
+            st.held_keys.retain(|&other| other != key);
+            if !st.held_keys.iter().any(|&other| modifier(other) == held) {
+                st.modifiers.depressed.remove(held);
+            }
+            // A modifier pressed on its own latches, then locks, then unlocks
+            let mods = &mut st.modifiers;
+            if st.sticky_keys && held != MOD_NONE && !st.modifier_used {
+                if mods.locked.contains(held) {
+                    mods.locked.remove(held);
+                } else if mods.latched.contains(held) {
+                    mods.latched.remove(held);
+                    mods.locked.insert(held);
+                } else {
+                    mods.latched.insert(held);
+                }
+            }
+        }
+    });
+    let view = dummy::route_input(dummy::focused_view());
+    let event = WlcEvent::KeyboardKey {
+        view,
+        time: dummy::time(),
+        mods: modifiers(),
+        key: key.get_code(),
+        state
+    };
+    let blocked = !dummy::grabbed_by_host(key, state, modifiers().mods) && {
+        dummy::deliver_root(dummy::pointer_output(), view, event);
+        dispatch(event)
+    };
+    if state == KeyState::Pressed && held == MOD_NONE {
+        state::with(|st| st.modifiers.latched = MOD_NONE);
+    }
+    blocked
+}
+
+/// Input held while its output is blank, see `dummy::set_modeset_blank`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Input {
+    Key(Key, KeyState),
+    Motion(Point),
+    Button(Key, ButtonState),
+    Scroll(ScrollSource, [f64; 2]),
+    Touch(TouchType, i32, Point)
+}
+
+/// Holds input while the pointer's output is blank, returning whether it
+/// was held.
+fn held(input: Input) -> bool {
+    settle_blanks();
+    let output = dummy::pointer_output();
+    state::with(|state| {
+        let blank = state.blanks.contains_key(&output);
+        if blank {
+            state.blanked_input.push((output, input));
+        }
+        blank
+    })
+}
+
+/// Ends the blanks which are over, and delivers the input held for them.
+fn settle_blanks() {
+    let ready = state::with(|state| {
+        let time = state.time;
+        let outputs: Vec<WlcOutput> = state.outputs.iter().map(|entry| entry.handle).collect();
+        state.blanks.retain(|output, &mut (since, millis)| {
+            outputs.contains(output) && time.wrapping_sub(since) < millis
+        });
+        let blanks = &state.blanks;
+        let (held, ready) = state.blanked_input.drain(..)
+            .partition::<Vec<_>, _>(|&(output, _)| blanks.contains_key(&output));
+        state.blanked_input = held;
+        ready
+    });
+    for (_, input) in ready {
+        match input {
+            Input::Key(key, state) => keyboard_key(key, state),
+            Input::Motion(point) => pointer_motion(point),
+            Input::Button(button, state) => pointer_button(button, state),
+            Input::Scroll(source, amount) => pointer_scroll(source, amount),
+            Input::Touch(kind, slot, point) => touch(kind, slot, point)
+        };
+    }
+}
+
+/// A virtual keyboard a client created, see `create_virtual_keyboard`.
+///
+/// Its keys go through the compositor like those of a real keyboard, but
+/// while they are delivered, `dummy::virtual_keyboard_source` tells the
+/// compositor which client sent them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VirtualKeyboard {
+    client: WlcView
+}
+
+impl VirtualKeyboard {
+    /// Gets the view of the client which created the keyboard.
+    pub fn client(&self) -> WlcView {
+        self.client
+    }
+
+    /// Presses or releases a key, as `keyboard_key`.
+    ///
+    /// Once the client's view is removed, the key does nothing. Returns
+    /// whether the callback blocked the key from the view.
+    pub fn key(&self, key: Key, state: KeyState) -> bool {
+        if dummy::view_info(self.client).is_none() {
+            return false
+        }
+        let outer = state::with(|st| st.virtual_keyboard_source.replace(self.client));
+        let blocked = keyboard_key(key, state);
+        state::with(|st| st.virtual_keyboard_source = outer);
+        blocked
+    }
+}
+
+/// Makes a view's client create a virtual keyboard, to type into other
+/// clients through the compositor.
+///
+/// wlc has no such protocol, so the compositor decides which clients may
+/// with `dummy::set_virtual_keyboard_policy`. Returns `None` if the
+/// compositor denies the client, or has no policy.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, simulate, KeyState, Size};
+/// use rustwlc::input::keys;
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let osk = dummy::add_view(output);
+/// dummy::set_app_id(osk, "onboard");
+/// let other = dummy::add_view(output);
+/// dummy::set_virtual_keyboard_policy(|view| view.get_app_id() == "onboard");
+/// let synthetic = Rc::new(Cell::new(0));
+/// let count = synthetic.clone();
+/// callback::rust::keyboard_key(move |_, _, _, _, _| {
+///     if dummy::virtual_keyboard_source().is_some() {
+///         count.set(count.get() + 1);
+///     }
+///     false
+/// });
+/// assert!(simulate::create_virtual_keyboard(other).is_none());
+/// let keyboard = simulate::create_virtual_keyboard(osk).unwrap();
+/// keyboard.key(keys::KEY_A, KeyState::Pressed);
+/// simulate::keyboard_key(keys::KEY_A, KeyState::Released);
+/// assert_eq!(synthetic.get(), 1);
+/// ```
+pub fn create_virtual_keyboard(view: WlcView) -> Option<VirtualKeyboard> {
+    let policy = state::with(|state| state.virtual_keyboard_policy.clone());
+    match policy {
+        Some(ref policy) if dummy::view_info(view).is_some() && (policy.0)(view) => {
+            Some(VirtualKeyboard { client: view })
+        },
+        _ => None
+    }
+}
+
+/// The modifiers given to callbacks, see `dummy::modifier_state`.
+fn modifiers() -> KeyboardModifiers {
+    dummy::modifier_state().keyboard_modifiers()
+}
+
+/// The modifier a key holds down, if any.
+fn modifier(key: Key) -> KeyMod {
+    match key {
+        keys::KEY_LEFTSHIFT | keys::KEY_RIGHTSHIFT => MOD_SHIFT,
+        keys::KEY_LEFTCTRL | keys::KEY_RIGHTCTRL => MOD_CTRL,
+        keys::KEY_LEFTALT | keys::KEY_RIGHTALT => MOD_ALT,
+        keys::KEY_LEFTMETA | keys::KEY_RIGHTMETA => MOD_MOD4,
+        _ => MOD_NONE
+    }
+}
+
+/// The modifier a key toggles the lock of, if any.
+fn lock(key: Key) -> KeyMod {
+    match key {
+        keys::KEY_CAPSLOCK => MOD_CAPS,
+        keys::KEY_NUMLOCK => MOD_MOD2,
+        _ => MOD_NONE
+    }
+}
+
+/// Moves the pointer, invoking the `pointer_motion` callback.
+///
+/// The callback is given the topmost visible view under `point` on the
+/// pointer's output (the focused output, or the first one), or
+/// `WlcView::root()` if there is none. As in wlc, the pointer only moves if
+/// the callback calls `input::pointer::set_position`. The view under the
+/// pointer afterwards gets pointer focus, see `dummy::pointer_focus`.
+///
+/// Barriers added with `dummy::add_barrier` may stop the motion at the
+/// edge of the output, or split it in two. Hot corners added with
+/// `dummy::add_hot_corner` notice where the pointer ends up. The motion may
+/// be held or split up by `dummy::set_motion_rate`. With
+/// `dummy::set_modal_routing`, pointer input for a view with a modal dialog
+/// goes to the dialog, here and in `pointer_button` and `pointer_scroll`.
+/// During an interactive move or resize (see `dummy::interactive_op`),
+/// motion and buttons go to its view instead.
+///
+/// Returns whether the callback blocked the motion from the view, or, if
+/// it was split up, the last part of it, or `false` if it was held.
+pub fn pointer_motion(point: Point) -> bool {
+    if held(Input::Motion(point)) {
+        return false
+    }
+    match dummy::motion_rate() {
+        MotionRate::Immediate => deliver_motion(point),
+        MotionRate::Coalesce => {
+            state::with(|state| state.pending_motion = Some(point));
+            false
+        },
+        MotionRate::Flood(count) => {
+            let from = pointer::get_position();
+            let count = count.max(1) as i64;
+            let mut blocked = false;
+            for step in 1..count + 1 {
+                let along = |from: i32, to: i32| {
+                    (from as i64 + (to as i64 - from as i64) * step / count) as i32
+                };
+                let x = along(from.x, point.x);
+                blocked = deliver_motion(Point { x, y: along(from.y, point.y) });
+            }
+            blocked
+        }
+    }
+}
+
+/// Delivers the motion held by `dummy::MotionRate::Coalesce`, if any.
+///
+/// This happens by itself at the next frame, key, button or scroll.
+/// Returns whether the callback blocked the motion from the view.
+pub fn flush_motion() -> bool {
+    match state::with(|state| state.pending_motion.take()) {
+        Some(point) => deliver_motion(point),
+        None => false
+    }
+}
+
+/// Delivers one motion.
+fn deliver_motion(point: Point) -> bool {
+    let output = dummy::pointer_output();
+    let time = dummy::time();
+    let mut blocked = false;
+    for point in dummy::apply_barriers(output, pointer::get_position(), point) {
+        let view = dummy::route_pointer(dummy::view_at(output, point));
+        blocked = dispatch(WlcEvent::PointerMotion { view, time, point });
+    }
+    let under = dummy::route_pointer(dummy::update_pointer_focus());
+    if state::with(|state| state.focus_follows_pointer) &&
+        under.is_window() && under != dummy::focused_view() {
+            under.focus();
+        }
+    dummy::update_hot_corners();
+    blocked
+}
+
+/// Connects an output, invoking `output_created`.
+///
+/// If the callback rejects the output, it is removed again, and
+/// `WlcOutput::dummy(0)` is returned.
+pub fn connect_output(name: &str, resolution: Size) -> WlcOutput {
+    let output = dummy::add_output(name, resolution);
+    if dispatch(WlcEvent::OutputCreated(output)) {
+        output
+    } else {
+        dummy::remove_output(output);
+        WlcOutput::dummy(0)
+    }
+}
+
+/// Disconnects an output, invoking `output_destroyed`.
+///
+/// As with `dummy::remove_output`, views on the output are removed with it.
+pub fn disconnect_output(output: WlcOutput) {
+    if dummy::output_info(output).is_some() {
+        dispatch(WlcEvent::OutputDestroyed(output));
+        dummy::remove_output(output);
+    }
+}
+
+/// Changes the resolution of an output, invoking `output_resolution` if it
+/// changed.
+///
+/// The output goes blank first if `dummy::set_modeset_blank` says so.
+pub fn output_resolution(output: WlcOutput, resolution: Size) {
+    let old_size = match dummy::output_info(output) {
+        Some(info) if info.resolution != resolution => info.resolution,
+        _ => return
+    };
+    dummy::update_output(output, |info| info.resolution = resolution);
+    dummy::blank(output);
+    dispatch(WlcEvent::OutputResolution { output, old_size, new_size: resolution });
+}
+
+/// Makes a client request new geometry, invoking `view_request_geometry`.
+///
+/// Returns the serial of the request, which stays in
+/// `dummy::pending_requests` until the compositor answers it with
+/// `WlcView::set_geometry`.
+pub fn request_geometry(view: WlcView, geometry: Geometry) -> u32 {
+    let serial = dummy::push_request(view, ClientRequest::Geometry(geometry));
+    dispatch(WlcEvent::ViewRequestGeometry { view, geometry });
+    serial
+}
+
+/// Makes a client request an interactive move, invoking
+/// `view_request_move`.
+///
+/// This starts an interactive move of the view, see
+/// `dummy::interactive_op`, before the callback runs.
+pub fn request_move(view: WlcView, destination: Point) {
+    dummy::start_interactive_op(InteractiveOp::Move { view, origin: pointer::get_position() });
+    dispatch(WlcEvent::ViewRequestMove { view, destination });
+}
+
+/// Makes a client request an interactive resize, invoking
+/// `view_request_resize`.
+///
+/// This starts an interactive resize of the view, see
+/// `dummy::interactive_op`, before the callback runs.
+pub fn request_resize(view: WlcView, edge: ResizeEdge, location: Point) {
+    let origin = pointer::get_position();
+    dummy::start_interactive_op(InteractiveOp::Resize { view, edge, origin });
+    dispatch(WlcEvent::ViewRequestResize { view, edge, location });
+}
+
+/// Makes a client request a state change, invoking `view_request_state`.
+///
+/// Returns the serial of the request, which stays in
+/// `dummy::pending_requests` until the compositor answers it with
+/// `WlcView::set_state`.
+pub fn request_state(view: WlcView, state: ViewState, toggle: bool) -> u32 {
+    let serial = dummy::push_request(view, ClientRequest::State(state, toggle));
+    dispatch(WlcEvent::ViewRequestState { view, state, handled: toggle });
+    serial
+}
+
+/// Opens a view on the primary output, invoking `view_created`.
+///
+/// The view is added to `WlcOutput::get_primary()`, as a new client's
+/// first window appears there. If the callback rejects the view, it is
+/// removed again, and `WlcView::root()` is returned. If it accepts the view
+/// without placing it, the view is placed as set with
+/// `dummy::set_placement`.
+///
+/// # Panics
+/// If there are no outputs.
+pub fn create_view() -> WlcView {
+    open_view("")
+}
+
+/// Opens a view from an X11 client on the primary output, see
+/// `create_view`.
+///
+/// What happens depends on `dummy::set_xwayland`. While XWayland starts,
+/// the view is added unmapped and waits for `xwayland_ready`, which maps it
+/// and invokes `view_created`. If XWayland doesn't run, no view is added
+/// and `WlcView::root()` is returned.
+///
+/// # Panics
+/// If there are no outputs.
+pub fn create_x11_view() -> WlcView {
+    match dummy::xwayland() {
+        XWayland::Ready => create_view(),
+        XWayland::Starting => {
+            let view = dummy::add_view(WlcOutput::get_primary());
+            dummy::set_mapped(view, false);
+            state::with(|state| state.xwayland_waiting.push(view));
+            view
+        },
+        XWayland::Disabled => WlcView::root()
+    }
+}
+
+/// Makes XWayland ready, after it started in the background.
+///
+/// The hook from `dummy::on_xwayland_ready` is called, then the X11 views
+/// waiting for XWayland are mapped with `map_view`, in the order they were
+/// opened. Does nothing unless XWayland is starting.
+pub fn xwayland_ready() {
+    if dummy::xwayland() != XWayland::Starting {
+        return
+    }
+    let hook = state::with(|state| {
+        state.xwayland = XWayland::Ready;
+        state.xwayland_hook.clone()
+    });
+    if let Some(hook) = hook {
+        (hook.0)();
+    }
+    for view in state::with(|state| mem::take(&mut state.xwayland_waiting)) {
+        map_view(view);
+    }
+}
+
+/// Opens a view with a title on the primary output, see `create_view`.
+pub(crate) fn open_view(title: &str) -> WlcView {
+    let view = dummy::add_view(WlcOutput::get_primary());
+    dummy::set_title(view, title);
+    open(view)
+}
+
+/// Invokes `view_created` for a view just added, see `create_view`.
+pub(crate) fn open(view: WlcView) -> WlcView {
+    if created(view) {
+        view
+    } else {
+        dummy::remove_view(view);
+        WlcView::root()
+    }
+}
+
+/// Unmaps a view, as a client hiding to a tray does.
+///
+/// As in wlc, the compositor sees the view destroyed: if the view is
+/// focused, it loses focus, invoking `view_focus`, then `view_destroyed`
+/// is invoked. Unlike `dummy::remove_view`, the view is kept with the same
+/// handle, title, and stacking, so it can be mapped again with `map_view`.
+///
+/// Returns whether the view was mapped.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{dummy, simulate, Size, WlcView};
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// let view = dummy::add_view(output);
+/// view.focus();
+/// assert!(simulate::unmap_view(view));
+/// assert_eq!(dummy::focused_view(), WlcView::root());
+/// assert_eq!(view.get_geometry(), None);
+/// assert!(simulate::map_view(view));
+/// assert_eq!(output.get_views(), vec![view]);
+/// ```
+pub fn unmap_view(view: WlcView) -> bool {
+    if !dummy::is_mapped(view) {
+        return false
+    }
+    if dummy::focused_view() == view {
+        focus_view(WlcView::root());
+    }
+    dispatch(WlcEvent::ViewDestroyed(view));
+    dummy::set_mapped(view, false);
+    true
+}
+
+/// Maps an unmapped view again, invoking `view_created`.
+///
+/// The view is mapped before the callback runs. If the callback rejects
+/// it, the view is unmapped again without invoking `view_destroyed`. If it
+/// accepts the view without placing it, the view is placed as set with
+/// `dummy::set_placement`.
+///
+/// Returns whether the view was mapped and the callback accepted it.
+pub fn map_view(view: WlcView) -> bool {
+    if dummy::view_info(view).is_none() || dummy::is_mapped(view) {
+        return false
+    }
+    dummy::set_mapped(view, true);
+    let accepted = created(view);
+    if !accepted {
+        dummy::set_mapped(view, false);
+    }
+    accepted
+}
+
+/// Invokes `view_created` for a new or remapped view, placing the view
+/// with `dummy::set_placement` if the callback accepts it without setting
+/// its geometry.
+fn created(view: WlcView) -> bool {
+    let before = view.get_visible_geometry();
+    let accepted = dispatch(WlcEvent::ViewCreated(view));
+    if accepted && view.get_visible_geometry() == before &&
+        dummy::pending_configures(view).is_empty() {
+            dummy::place(view);
+        }
+    accepted
+}
+
+/// Crashes the client of a view, destroying all of its views at once.
+///
+/// The client owns the view, its parent (see `WlcView::get_parent`) and
+/// their parents, and the popups and dialogs of all of those. Unlike a
+/// graceful close, there is no handshake: `view_destroyed` is invoked for
+/// each mapped view back-to-back, children before their parents, with no
+/// `view_focus` in between, and only then are the views removed. Client
+/// behaviors from the `clients` module run once, afterwards.
+///
+/// Returns the views which were removed, in the order they were destroyed.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, fixtures, simulate, Size, WlcView};
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+///
+/// let output = dummy::add_output("WLC-1", Size { w: 1920, h: 1080 });
+/// let player = fixtures::mpv(output);
+/// let dialog = fixtures::mpv_dialog(player);
+/// let browser = fixtures::firefox(output);
+/// dialog.focus();
+/// let destroyed = Rc::new(RefCell::new(Vec::new()));
+/// let log = destroyed.clone();
+/// callback::rust::view_destroyed(move |view| log.borrow_mut().push(view));
+/// assert_eq!(simulate::crash_client(dialog), vec![dialog, player]);
+/// assert_eq!(*destroyed.borrow(), vec![dialog, player]);
+/// assert_eq!(output.get_views(), vec![browser]);
+/// assert_eq!(dummy::focused_view(), WlcView::root());
+/// ```
+pub fn crash_client(view: WlcView) -> Vec<WlcView> {
+    let mut family = vec![client_of(view)];
+    if dummy::view_info(family[0]).is_none() {
+        return Vec::new()
+    }
+    let mut index = 0;
+    while index < family.len() {
+        let parent = family[index];
+        let children = dummy::find_views(|info| info.parent == parent);
+        for child in children {
+            if !family.contains(&child) {
+                family.push(child);
+            }
+        }
+        index += 1;
+    }
+    family.reverse();
+    for &view in &family {
+        if dummy::is_mapped(view) {
+            let event = WlcEvent::ViewDestroyed(view);
+            dummy::checkpoint(event);
+            callback::dispatch(&event);
+        }
+    }
+    for &view in &family {
+        dummy::remove_view(view);
+    }
+    clients::run();
+    if let Some(&view) = family.last() {
+        invariants::check(&WlcEvent::ViewDestroyed(view));
+        raster::captured(&WlcEvent::ViewDestroyed(view));
+    }
+    family
+}
+
+/// The top view of a view's parents, which its client opened first.
+fn client_of(view: WlcView) -> WlcView {
+    let mut seen = vec![view];
+    let mut client = view;
+    while let Some(parent) = dummy::view_info(client).map(|info| info.parent) {
+        if dummy::view_info(parent).is_none() || seen.contains(&parent) {
+            break
+        }
+        seen.push(parent);
+        client = parent;
+    }
+    client
+}
+
+/// Renders a frame of an output, invoking the render callbacks.
+///
+/// Invokes `output_render_pre`, then `view_render_pre` and
+/// `view_render_post` for each visible view from bottom to top, then
+/// `output_render_post`. Outputs which are not part of the dummy compositor
+/// are not rendered. Rendering clears a render scheduled for the output,
+/// and first delivers motion held by `dummy::MotionRate::Coalesce`.
+/// Blank outputs (see `dummy::set_modeset_blank`) are not rendered.
+/// Screenshots asked for with `request_screenshot` are taken after
+/// `output_render_post`.
+///
+/// The frame is timed with the clock of the budget from
+/// `dummy::set_frame_budget`, or the real clock if there is none, plus the
+/// costs from `dummy::set_render_cost` of the views rendered, and counted
+/// in `dummy::frame_stats`. If allocating its framebuffer fails (see
+/// `dummy::fail_allocations`), nothing is rendered, the frame is counted
+/// as dropped, and the output stays scheduled. Returns whether the frame
+/// was dropped.
+pub fn render_frame(output: WlcOutput) -> bool {
+    if state::with(|state| state.output(output).is_none()) {
+        return false
+    }
+    settle_blanks();
+    if dummy::is_blanked(output) {
+        return false
+    }
+    if !dummy::allocate(output, RenderAllocation::Framebuffer) {
+        state::with(|state| state.frame_stats.entry(output).or_default().dropped += 1);
+        return true
+    }
+    flush_motion();
+    let clock = dummy::frame_budget().map_or(FrameClock::Real, |budget| budget.clock);
+    let (start, start_time) = (Instant::now(), dummy::time());
+    let mut cost = Duration::from_millis(0);
+    let outer = state::with(|state| state.rendering.replace(output));
+    dispatch(WlcEvent::OutputRenderPre(output));
+    let views = dummy::visible_views(output);
+    for &view in &views {
+        dispatch(WlcEvent::ViewRenderPre(view));
+        cost += dummy::view_info(view).map(|info| info.render_cost).unwrap_or_default();
+        dispatch(WlcEvent::ViewRenderPost(view));
+    }
+    dispatch(WlcEvent::OutputRenderPost(output));
+    calls::rendered(output, &views);
+    dummy::take_screenshots(output);
+    state::with(|state| state.rendering = outer);
+    let elapsed = cost + match clock {
+        FrameClock::Mock => Duration::from_millis(
+            dummy::time().wrapping_sub(start_time) as u64),
+        FrameClock::Real => start.elapsed()
+    };
+    dummy::record_frame(output, elapsed)
+}
+
+/// Makes a client ask for a screenshot, returning the serial of the
+/// request.
+///
+/// The compositor's handler from `dummy::on_screenshot` takes it during
+/// the next frame of the target's output.
+pub fn request_screenshot(target: ScreenshotTarget) -> u32 {
+    let serial = dummy::next_serial();
+    state::with(|state| state.pending_screenshots.push((serial, target)));
+    serial
+}
+
+/// Presses or releases a pointer button, invoking `pointer_button`.
+///
+/// The callback is given the view under the pointer, which gets pointer
+/// focus (see `dummy::pointer_focus`), the held modifiers, and the pointer
+/// position. The button is already held, or no longer held, by then (see
+/// `input::pointer::get_held_buttons`). Buttons,
+/// scrolls, and keys which hit no view are also recorded in
+/// `dummy::root_deliveries`.
+///
+/// Returns whether the callback blocked the button from the view.
+pub fn pointer_button(button: Key, state: ButtonState) -> bool {
+    if held(Input::Button(button, state)) {
+        return false
+    }
+    flush_motion();
+    state::with(|st| match state {
+        ButtonState::Pressed => if !st.held_buttons.contains(&button) {
+            st.held_buttons.push(button);
+        },
+        ButtonState::Released => st.held_buttons.retain(|&other| other != button)
+    });
+    let point = pointer::get_position();
+    let output = dummy::pointer_output();
+    let view = dummy::route_pointer(dummy::update_pointer_focus());
+    let event = WlcEvent::PointerButton {
+        view,
+        time: dummy::time(),
+        mods: modifiers(),
+        button: button.get_code(),
+        state,
+        point
+    };
+    dummy::deliver_root(output, view, event);
+    let blocked = dispatch(event);
+    if state == ButtonState::Released {
+        dummy::end_interactive_op();
+    }
+    blocked
+}
+
+/// Clicks a button `count` times at a point, `interval` milliseconds apart.
+///
+/// Moves the pointer with `pointer_motion` first, then presses and
+/// releases the button with `pointer_button`, advancing the time with
+/// `dummy::advance_time` between clicks. Clicks a little slower or faster
+/// than a double-click threshold to test both sides of it.
+///
+/// Returns whether the callback blocked any of the presses or releases.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, simulate};
+/// use rustwlc::{WlcView, KeyboardModifiers, ButtonState, Point, Size};
+/// use rustwlc::input::keys::BTN_LEFT;
+/// use rustwlc::input::pointer;
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+///
+/// dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+/// callback::rust::pointer_motion(|_, _, point| { pointer::set_position(*point); false });
+/// // Counts presses within 300ms of the last one as double clicks
+/// let last = Rc::new(Cell::new(None));
+/// let doubles = Rc::new(Cell::new(0));
+/// let (seen, count) = (last.clone(), doubles.clone());
+/// callback::rust::pointer_button(move |_, time, _, _, state, _| {
+///     if state == ButtonState::Pressed {
+///         if seen.get().map_or(false, |last| time - last <= 300) {
+///             count.set(count.get() + 1);
+///         }
+///         seen.set(Some(time));
+///     }
+///     false
+/// });
+/// simulate::multi_click(Point { x: 10, y: 10 }, BTN_LEFT, 2, 300);
+/// assert_eq!(doubles.get(), 1);
+/// last.set(None);
+/// simulate::multi_click(Point { x: 10, y: 10 }, BTN_LEFT, 2, 301);
+/// assert_eq!(doubles.get(), 1);
+/// ```
+pub fn multi_click(point: Point, button: Key, count: u32, interval: u32) -> bool {
+    pointer_motion(point);
+    let mut blocked = false;
+    for click in 0..count {
+        if click > 0 {
+            dummy::advance_time(interval);
+        }
+        blocked |= pointer_button(button, ButtonState::Pressed);
+        blocked |= pointer_button(button, ButtonState::Released);
+    }
+    blocked
+}
+
+/// Scrolls, invoking the `pointer_scroll` callback.
+///
+/// `amount` holds the vertical then horizontal amounts, and the axes with
+/// non-zero amounts are reported as scrolled. The callback is given the
+/// view under the pointer, as with `pointer_motion`, and the held
+/// modifiers. It isn't told the `source`, but handlers from
+/// `callback::add_handler` are. Axes inverted with
+/// `dummy::set_scroll_inverted` are inverted first.
+///
+/// Returns whether the callback blocked the scroll from the view.
+pub fn pointer_scroll(source: ScrollSource, amount: [f64; 2]) -> bool {
+    if held(Input::Scroll(source, amount)) {
+        return false
+    }
+    flush_motion();
+    let inverted = state::with(|state| state.scroll_inverted);
+    let mut amount = amount;
+    let mut source = source;
+    for axis in 0..2 {
+        if inverted[axis] {
+            amount[axis] = -amount[axis];
+            if let ScrollSource::Wheel { ref mut clicks } = source {
+                clicks[axis] = -clicks[axis];
+            }
+        }
+    }
+    let axis = match (amount[0] != 0.0, amount[1] != 0.0) {
+        (false, false) => ScrollAxis::None,
+        (true, false) => ScrollAxis::Vertical,
+        (false, true) => ScrollAxis::Horizontal,
+        (true, true) => ScrollAxis::Both
+    };
+    let output = dummy::pointer_output();
+    let view = dummy::route_input(dummy::update_pointer_focus());
+    let event = WlcEvent::PointerScroll {
+        view,
+        time: dummy::time(),
+        mods: modifiers(),
+        axis,
+        amount,
+        source
+    };
+    dummy::deliver_root(output, view, event);
+    dispatch(event)
+}
+
+/// Touches the touchscreen, invoking the `touch` callback.
+///
+/// The touchscreen covers the output the pointer is on. Down, motion, and
+/// up touches are given the view under `point`; frames and cancels are
+/// given the root view, and a frame has `slot` and `point` zero, as in
+/// wlc. The points held down are tracked, see `input::seat::Touch::points`,
+/// and a cancel lifts them all.
+///
+/// Returns whether the callback blocked the touch from the view, or
+/// `false` if it was held.
+pub fn touch(touch: TouchType, slot: i32, point: Point) -> bool {
+    let (slot, point) = match touch {
+        TouchType::Frame => (0, Point { x: 0, y: 0 }),
+        _ => (slot, point)
+    };
+    if held(Input::Touch(touch, slot, point)) {
+        return false
+    }
+    state::with(|state| {
+        let points = &mut state.touch_points;
+        match touch {
+            TouchType::Down | TouchType::Motion => {
+                match points.iter_mut().find(|&&mut (other, _)| other == slot) {
+                    Some(held) => held.1 = point,
+                    None => points.push((slot, point))
+                }
+            },
+            TouchType::Up => points.retain(|&(other, _)| other != slot),
+            TouchType::Cancel => points.clear(),
+            TouchType::Frame => {}
+        }
+    });
+    let view = match touch {
+        TouchType::Down | TouchType::Motion | TouchType::Up => {
+            dummy::route_input(dummy::view_at(dummy::pointer_output(), point))
+        },
+        TouchType::Frame | TouchType::Cancel => WlcView::root()
+    };
+    dispatch(WlcEvent::Touch { view, time: dummy::time(), mods: modifiers(), touch, slot, point })
+}
+
+/// Clicks the scroll wheel, invoking the `pointer_scroll` callback.
+///
+/// `clicks` holds the vertical then horizontal clicks, positive for down
+/// or right. Each click scrolls by the step set with
+/// `dummy::set_scroll_step`.
+///
+/// # Example
+/// ```rust
+/// use rustwlc::{callback, dummy, simulate};
+/// use rustwlc::{WlcView, KeyboardModifiers, ScrollAxis};
+///
+/// fn pointer_scroll(view: WlcView, time: u32, mods: &KeyboardModifiers,
+///                   axis: ScrollAxis, amount: [f64; 2]) -> bool {
+///     assert_eq!(axis, ScrollAxis::Vertical);
+///     assert_eq!(amount, [-30.0, 0.0]);
+///     true
+/// }
+///
+/// callback::rust::pointer_scroll(pointer_scroll);
+/// dummy::set_scroll_step(15.0);
+/// assert!(simulate::scroll_wheel([-2, 0]));
+/// ```
+pub fn scroll_wheel(clicks: [i32; 2]) -> bool {
+    let step = dummy::scroll_step();
+    pointer_scroll(ScrollSource::Wheel { clicks },
+                   [clicks[0] as f64 * step, clicks[1] as f64 * step])
+}
+
+/// Tears down the dummy compositor for `rustwlc::terminate()`.
+pub(crate) fn terminate() {
+    let mut sequence = Log::new();
+    for output in dummy::outputs() {
+        for view in output.get_views().into_iter().rev() {
+            if dummy::is_mapped(view) {
+                sequence.push(WlcEvent::ViewDestroyed(view));
+                dispatch(WlcEvent::ViewDestroyed(view));
+            }
+            dummy::remove_view(view);
+        }
+        sequence.push(WlcEvent::OutputDestroyed(output));
+        dispatch(WlcEvent::OutputDestroyed(output));
+        dummy::remove_output(output);
+    }
+    sequence.push(WlcEvent::CompositorTerminate);
+    dispatch(WlcEvent::CompositorTerminate);
+    state::with(|state| state.shutdown = sequence);
+}
+
+/// Focuses a view, invoking `view_focus` callbacks if focus changed.
+pub(crate) fn focus_view(view: WlcView) {
+    if state::with(|state| state.modal_routing) {
+        if let Some(dialog) = dummy::modal_dialog(view) {
+            dummy::violation(format!("{:?} was focused while its modal dialog {:?} is open",
+                                     view, dialog));
+        }
+    }
+    let (old, new) = match state::with(|state| {
+        let new = if view.is_window() {
+            if !state.views.get(&view).is_some_and(|info| info.mapped) {
+                return None
+            }
+            Some(view)
+        } else {
+            None
+        };
+        let old = state.focused_view;
+        if old == new {
+            return None
+        }
+        state.focused_view = new;
+        Some((old, new))
+    }) {
+        Some(change) => change,
+        None => return
+    };
+    if let Some(old) = old {
+        dummy::deliver_keyboard(old, false);
+        callback::dispatch(&WlcEvent::ViewFocus(old, false));
+    }
+    if let Some(new) = new {
+        dummy::deliver_keyboard(new, true);
+        let event = WlcEvent::ViewFocus(new, true);
+        contract::checked(&event, || callback::dispatch(&event));
+    }
+    if let Some(new) = new {
+        if state::with(|state| state.pointer_follows_focus) {
+            let point = dummy::view_info(new)
+                .map(|info| info.geometry).unwrap_or_default().center();
+            state::with(|state| state.pointer = point);
+        }
+    }
+}
+
+/// Focuses an output, invoking `output_focus` callbacks if focus changed.
+pub(crate) fn focus_output(output: Option<WlcOutput>) {
+    let old = match state::with(|state| {
+        let new = output.and_then(|output| state.output(output).map(|_| output));
+        let old = state.focused_output;
+        if old == new || (output.is_some() && new.is_none()) {
+            return None
+        }
+        state.focused_output = new;
+        Some(old)
+    }) {
+        Some(old) => old,
+        None => return
+    };
+    if let Some(old) = old {
+        callback::dispatch(&WlcEvent::OutputFocus(old, false));
+    }
+    if let Some(new) = output {
+        callback::dispatch(&WlcEvent::OutputFocus(new, true));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::super::dummy::FrameClock;
+    use super::super::input::pointer;
+    use std::cell::RefCell;
+    use std::time::Duration;
+
+    thread_local! {
+        static FOCUS: RefCell<Vec<(WlcView, bool)>> = const { RefCell::new(Vec::new()) };
+        static RENDERED: RefCell<Vec<WlcView>> = const { RefCell::new(Vec::new()) };
+        static KEYS: RefCell<Vec<(u32, KeyMod)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn view_focus(view: WlcView, focused: bool) {
+        FOCUS.with(|log| log.borrow_mut().push((view, focused)));
+    }
+
+    fn pointer_motion(view: WlcView, time: u32, point: &Point) -> bool {
+        pointer::set_position(*point);
+        false
+    }
+
+    fn geometry(x: i32, y: i32) -> Geometry {
+        Geometry { origin: Point { x, y }, size: Size { w: 100, h: 100 } }
+    }
+
+    fn all_events(event: &WlcEvent) {
+        if let WlcEvent::ViewFocus(view, focused) = *event {
+            FOCUS.with(|log| log.borrow_mut().push((view, !focused)));
+        }
+    }
+
+    fn high_handler(event: &WlcEvent) -> bool {
+        if let WlcEvent::ViewFocus(view, _) = *event {
+            FOCUS.with(|log| log.borrow_mut().push((view, true)));
+        }
+        true
+    }
+
+    fn low_handler(event: &WlcEvent) -> bool {
+        if let WlcEvent::ViewFocus(view, _) = *event {
+            FOCUS.with(|log| log.borrow_mut().push((view, false)));
+        }
+        true
+    }
+
+    #[test]
+    fn handler_priorities() {
+        let view = WlcView::dummy(1);
+        callback::rust::view_focus(view_focus);
+        callback::add_handler(-1, low_handler);
+        let high = callback::add_handler(1, high_handler);
+        // ViewFocus can't be vetoed, so every handler runs
+        simulate::dispatch(WlcEvent::ViewFocus(view, true));
+        FOCUS.with(|log| assert_eq!(*log.borrow(), vec![
+            (view, true), (view, true), (view, false)
+        ]));
+        assert!(callback::remove_handler(high));
+        assert!(!callback::remove_handler(high));
+        // Input is vetoable, so the low handler blocks it
+        assert!(simulate::dispatch(WlcEvent::PointerMotion {
+            view, time: 0, point: Point { x: 1, y: 1 }
+        }));
+        assert!(!simulate::dispatch(WlcEvent::ViewCreated(view)));
+    }
+
+    fn view_render_pre(view: WlcView) {
+        RENDERED.with(|log| log.borrow_mut().push(view));
+        dummy::advance_time(5);
+    }
+
+    #[test]
+    fn render_frame() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let bottom = dummy::add_view(output);
+        let hidden = dummy::add_view(output);
+        let top = dummy::add_view(output);
+        hidden.set_mask(0);
+        callback::rust::view_render_pre(view_render_pre);
+        dummy::set_frame_budget(Some(dummy::FrameBudget {
+            budget: Duration::from_millis(10),
+            clock: FrameClock::Mock
+        }));
+        assert!(!simulate::render_frame(output));
+        RENDERED.with(|log| assert_eq!(*log.borrow(), vec![bottom, top]));
+        hidden.set_mask(1);
+        assert!(simulate::render_frame(output));
+        assert_eq!(dummy::frame_stats(output), dummy::FrameStats {
+            scheduled: 0, frames: 2, dropped: 1, longest: Duration::from_millis(15)
+        });
+        assert!(!simulate::render_frame(WlcOutput::dummy(9)));
+    }
+
+    fn keyboard_key(view: WlcView, time: u32, mods: &KeyboardModifiers,
+                    key: u32, state: KeyState) -> bool {
+        KEYS.with(|log| log.borrow_mut().push((key, mods.mods)));
+        false
+    }
+
+    #[test]
+    fn held_modifiers() {
+        use super::super::input::keys::*;
+        callback::rust::keyboard_key(keyboard_key);
+        simulate::keyboard_key(KEY_LEFTSHIFT, KeyState::Pressed);
+        simulate::keyboard_key(KEY_A, KeyState::Pressed);
+        simulate::keyboard_key(KEY_LEFTSHIFT, KeyState::Released);
+        simulate::keyboard_key(KEY_A, KeyState::Released);
+        KEYS.with(|log| assert_eq!(*log.borrow(), vec![
+            (42, MOD_SHIFT), (30, MOD_SHIFT), (42, MOD_NONE), (30, MOD_NONE)
+        ]));
+    }
+
+    #[test]
+    fn sticky_keys() {
+        use super::super::input::keys::*;
+        callback::rust::keyboard_key(keyboard_key);
+        dummy::set_sticky_keys(true);
+        let tap = |key| {
+            simulate::keyboard_key(key, KeyState::Pressed);
+            simulate::keyboard_key(key, KeyState::Released);
+        };
+        // Shift used as a modifier doesn't latch
+        simulate::keyboard_key(KEY_LEFTSHIFT, KeyState::Pressed);
+        tap(KEY_A);
+        simulate::keyboard_key(KEY_LEFTSHIFT, KeyState::Released);
+        tap(KEY_A);
+        // Latched for one key, then locked, then unlocked
+        tap(KEY_LEFTMETA);
+        tap(KEY_A);
+        tap(KEY_A);
+        tap(KEY_LEFTMETA);
+        tap(KEY_LEFTMETA);
+        tap(KEY_A);
+        tap(KEY_A);
+        tap(KEY_LEFTMETA);
+        tap(KEY_A);
+        let presses: Vec<KeyMod> = KEYS.with(|log| log.borrow().iter()
+            .filter(|&&(key, _)| key == KEY_A.get_code())
+            .map(|&(_, mods)| mods).collect());
+        assert_eq!(presses, vec![
+            MOD_SHIFT, MOD_SHIFT, MOD_NONE, MOD_NONE,
+            MOD_MOD4, MOD_NONE, MOD_NONE, MOD_NONE,
+            MOD_MOD4, MOD_MOD4, MOD_MOD4, MOD_MOD4,
+            MOD_NONE, MOD_NONE
+        ]);
+        tap(KEY_LEFTCTRL);
+        tap(KEY_LEFTCTRL);
+        dummy::set_sticky_keys(false);
+        tap(KEY_A);
+        KEYS.with(|log| assert_eq!(log.borrow().last(), Some(&(30, MOD_NONE))));
+    }
+
+    #[test]
+    fn modifier_state() {
+        use super::super::dummy::ModifierState;
+        use super::super::input::keys::*;
+        use std::rc::Rc;
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let log = seen.clone();
+        callback::rust::pointer_button(move |_, _, mods, _, _, _| {
+            log.borrow_mut().push(*mods);
+            false
+        });
+        let click = || {
+            simulate::pointer_button(BTN_LEFT, ButtonState::Pressed);
+            simulate::pointer_button(BTN_LEFT, ButtonState::Released);
+        };
+        // Both shifts held, releasing one keeps shift depressed
+        simulate::keyboard_key(KEY_LEFTSHIFT, KeyState::Pressed);
+        simulate::keyboard_key(KEY_RIGHTSHIFT, KeyState::Pressed);
+        simulate::keyboard_key(KEY_LEFTSHIFT, KeyState::Released);
+        assert_eq!(dummy::modifier_state().depressed, MOD_SHIFT);
+        simulate::keyboard_key(KEY_RIGHTSHIFT, KeyState::Released);
+        assert_eq!(dummy::modifier_state(), ModifierState::default());
+
+        simulate::keyboard_key(KEY_CAPSLOCK, KeyState::Pressed);
+        simulate::keyboard_key(KEY_CAPSLOCK, KeyState::Released);
+        simulate::keyboard_key(KEY_NUMLOCK, KeyState::Pressed);
+        click();
+        assert_eq!(seen.borrow().last(), Some(&KeyboardModifiers {
+            leds: CAPS_LOCK | NUM_LOCK, mods: MOD_CAPS | MOD_MOD2
+        }));
+        simulate::keyboard_key(KEY_NUMLOCK, KeyState::Released);
+        simulate::keyboard_key(KEY_CAPSLOCK, KeyState::Pressed);
+        assert_eq!(dummy::modifier_state().locked, MOD_MOD2);
+
+        // Set directly, depressed until a key with the modifier is released
+        dummy::set_modifier_state(ModifierState {
+            depressed: MOD_CTRL, group: 2, .. Default::default()
+        });
+        click();
+        assert_eq!(seen.borrow().last().unwrap().mods, MOD_CTRL);
+        simulate::keyboard_key(KEY_LEFTCTRL, KeyState::Released);
+        assert_eq!(dummy::modifier_state(), ModifierState { group: 2, .. Default::default() });
+    }
+
+    fn touchpad_only(event: &WlcEvent) -> bool {
+        match *event {
+            WlcEvent::PointerScroll { source, .. } => source == ScrollSource::Finger,
+            _ => false
+        }
+    }
+
+    #[test]
+    fn scroll_sources() {
+        callback::add_handler(0, touchpad_only);
+        assert!(simulate::pointer_scroll(ScrollSource::Finger, [0.5, 0.0]));
+        assert!(!simulate::pointer_scroll(ScrollSource::Continuous, [0.5, 0.0]));
+        assert!(!simulate::scroll_wheel([1, 0]));
+    }
+
+    #[test]
+    fn scroll_inversion() {
+        use std::rc::Rc;
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let log = seen.clone();
+        callback::add_handler(0, move |event: &WlcEvent| {
+            if let WlcEvent::PointerScroll { amount, source, .. } = *event {
+                log.borrow_mut().push((amount, source));
+            }
+            false
+        });
+        dummy::set_scroll_inverted([true, false]);
+        simulate::scroll_wheel([1, -2]);
+        dummy::set_scroll_inverted([true, true]);
+        simulate::pointer_scroll(ScrollSource::Finger, [0.5, 1.5]);
+        dummy::set_scroll_inverted([false, false]);
+        simulate::pointer_scroll(ScrollSource::Finger, [0.5, 1.5]);
+        assert_eq!(*seen.borrow(), vec![
+            ([-10.0, -20.0], ScrollSource::Wheel { clicks: [-1, -2] }),
+            ([-0.5, -1.5], ScrollSource::Finger),
+            ([0.5, 1.5], ScrollSource::Finger)
+        ]);
+    }
+
+    #[test]
+    fn shutdown_order() {
+        let first = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let second = dummy::add_output("WLC-2", Size { w: 800, h: 600 });
+        let bottom = dummy::add_view(first);
+        let top = dummy::add_view(first);
+        let other = dummy::add_view(second);
+        terminate();
+        assert_eq!(dummy::shutdown_sequence(), vec![
+            WlcEvent::ViewDestroyed(top), WlcEvent::ViewDestroyed(bottom),
+            WlcEvent::OutputDestroyed(first),
+            WlcEvent::ViewDestroyed(other), WlcEvent::OutputDestroyed(second),
+            WlcEvent::CompositorTerminate
+        ]);
+        assert!(WlcOutput::list().is_empty());
+    }
+
+    #[test]
+    fn all_events_first() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let view = dummy::add_view(output);
+        callback::rust::view_focus(view_focus);
+        callback::all_events(all_events);
+        view.focus();
+        FOCUS.with(|log| assert_eq!(*log.borrow(), vec![
+            (view, false), (view, true)
+        ]));
+    }
+
+    #[test]
+    fn focus_callbacks() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let first = dummy::add_view(output);
+        let second = dummy::add_view(output);
+        callback::rust::view_focus(view_focus);
+        first.focus();
+        second.focus();
+        second.focus();
+        WlcView::dummy(100).focus();
+        WlcView::root().focus();
+        assert_eq!(dummy::focused_view(), WlcView::root());
+        FOCUS.with(|log| assert_eq!(*log.borrow(), vec![
+            (first, true), (first, false), (second, true), (second, false)
+        ]));
+    }
+
+    fn view_focus_keyboard(view: WlcView, focused: bool) {
+        // The client has already been told when the callback runs
+        let expected = if focused { view } else { WlcView::root() };
+        assert_eq!(dummy::keyboard_focus(), expected);
+    }
+
+    #[test]
+    fn keyboard_deliveries() {
+        use super::super::dummy::KeyboardDelivery::*;
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let first = dummy::add_view(output);
+        let second = dummy::add_view(output);
+        callback::rust::view_focus(view_focus_keyboard);
+        first.focus();
+        second.focus();
+        dummy::remove_view(second);
+        assert_eq!(dummy::keyboard_focus(), WlcView::root());
+        first.focus();
+        assert_eq!(dummy::keyboard_deliveries(), vec![
+            Enter { view: first, serial: 1 },
+            Leave { view: first, serial: 2 },
+            Enter { view: second, serial: 3 },
+            Enter { view: first, serial: 4 }
+        ]);
+        dummy::clear_keyboard_deliveries();
+        assert!(dummy::keyboard_deliveries().is_empty());
+    }
+
+    #[test]
+    fn map_unmap_cycle() {
+        use std::rc::Rc;
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let view = dummy::add_view(output);
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let events = log.clone();
+        callback::all_events(move |event: &WlcEvent| events.borrow_mut().push(*event));
+        view.focus();
+        assert!(simulate::unmap_view(view));
+        assert!(!simulate::unmap_view(view));
+        // Can't be focused or hit until mapped again
+        view.focus();
+        assert_eq!(dummy::focused_view(), WlcView::root());
+        assert!(dummy::visible_views(output).is_empty());
+        assert!(simulate::map_view(view));
+        assert!(!simulate::map_view(view));
+        view.focus();
+        assert_eq!(*log.borrow(), vec![
+            WlcEvent::ViewFocus(view, true),
+            WlcEvent::ViewFocus(view, false),
+            WlcEvent::ViewDestroyed(view),
+            WlcEvent::ViewCreated(view),
+            WlcEvent::ViewFocus(view, true)
+        ]);
+        // Closing while unmapped doesn't destroy it twice
+        simulate::unmap_view(view);
+        log.borrow_mut().clear();
+        clients::perform(view, &clients::Action::Close);
+        assert!(dummy::view_info(view).is_none());
+        assert!(log.borrow().is_empty());
+    }
+
+    #[test]
+    fn warp_origins() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let view = dummy::add_view(output);
+        view.set_geometry(EDGE_NONE, geometry(0, 0));
+        // Warps twice on motion, and once more when focused
+        callback::rust::pointer_motion(|view, _, point| {
+            pointer::set_position(*point);
+            pointer::set_position(*point);
+            view.focus();
+            false
+        });
+        callback::rust::view_focus(|_, _| {
+            pointer::set_position(Point { x: 1, y: 1 });
+        });
+        pointer::set_position(Point { x: 0, y: 0 });
+        dummy::set_pointer_follows_focus(true);
+        simulate::pointer_motion(Point { x: 50, y: 50 });
+        assert_eq!(dummy::current_event(), None);
+        let origins: Vec<Option<WlcEvent>> = dummy::warps().iter()
+            .map(|warp| warp.origin).collect();
+        let motion = WlcEvent::PointerMotion { view, time: 0, point: Point { x: 50, y: 50 } };
+        assert_eq!(origins, vec![
+            None, Some(motion), Some(motion), Some(WlcEvent::ViewFocus(view, true))
+        ]);
+        // The dummy's own warp isn't one of them
+        assert_eq!(pointer::get_position(), Point { x: 50, y: 50 });
+        dummy::clear_warps();
+        assert!(dummy::warps().is_empty());
+    }
+
+    #[test]
+    fn root_deliveries() {
+        use super::super::input::keys::*;
+        let first = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let second = dummy::add_output("WLC-2", Size { w: 800, h: 600 });
+        let view = dummy::add_view(first);
+        view.set_geometry(EDGE_NONE, geometry(0, 0));
+        callback::rust::pointer_motion(pointer_motion);
+        // Over the view, then over the background of each output
+        simulate::multi_click(Point { x: 50, y: 50 }, BTN_LEFT, 1, 0);
+        simulate::pointer_motion(Point { x: 500, y: 50 });
+        simulate::scroll_wheel([1, 0]);
+        WlcOutput::focus(Some(second));
+        simulate::pointer_button(BTN_RIGHT, ButtonState::Pressed);
+        simulate::keyboard_key(KEY_A, KeyState::Pressed);
+        let deliveries: Vec<(WlcOutput, Option<WlcView>)> = dummy::root_deliveries()
+            .iter().map(|delivery| (delivery.output, delivery.event.view())).collect();
+        let root = Some(WlcView::root());
+        assert_eq!(deliveries, vec![(first, root), (second, root), (second, root)]);
+        match dummy::root_deliveries()[2].event {
+            WlcEvent::KeyboardKey { key, .. } => assert_eq!(key, KEY_A.get_code()),
+            event => panic!("Unexpected {:?}", event)
+        }
+        dummy::clear_root_deliveries();
+        assert!(dummy::root_deliveries().is_empty());
+    }
+
+    #[test]
+    fn focus_follows_pointer() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let left = dummy::add_view(output);
+        let right = dummy::add_view(output);
+        left.set_geometry(EDGE_NONE, geometry(0, 0));
+        right.set_geometry(EDGE_NONE, geometry(100, 0));
+        callback::rust::pointer_motion(pointer_motion);
+
+        simulate::pointer_motion(Point { x: 50, y: 50 });
+        assert_eq!(dummy::focused_view(), WlcView::root());
+        dummy::set_focus_follows_pointer(true);
+        simulate::pointer_motion(Point { x: 150, y: 50 });
+        assert_eq!(dummy::focused_view(), right);
+        simulate::pointer_motion(Point { x: 500, y: 500 });
+        assert_eq!(dummy::focused_view(), right);
+    }
+
+    #[test]
+    fn coalesced_motion() {
+        use super::super::dummy::MotionRate;
+        use super::super::input::keys::BTN_LEFT;
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let view = dummy::add_view(output);
+        view.set_geometry(EDGE_NONE, geometry(100, 0));
+        callback::rust::pointer_motion(pointer_motion);
+        let clicked = std::rc::Rc::new(std::cell::Cell::new(WlcView::root()));
+        let log = clicked.clone();
+        callback::rust::pointer_button(move |view, _, _, _, _, _| {
+            log.set(view);
+            false
+        });
+        dummy::set_motion_rate(MotionRate::Coalesce);
+        simulate::pointer_motion(Point { x: 150, y: 50 });
+        assert_eq!(pointer::get_position(), Point { x: 0, y: 0 });
+        // The button goes to the view the pointer was moved onto
+        simulate::pointer_button(BTN_LEFT, ButtonState::Pressed);
+        assert_eq!(clicked.get(), view);
+        assert_eq!(dummy::pending_motion(), None);
+        simulate::pointer_motion(Point { x: 10, y: 10 });
+        dummy::set_motion_rate(MotionRate::Immediate);
+        assert!(!simulate::flush_motion());
+        assert_eq!(pointer::get_position(), Point { x: 10, y: 10 });
+        assert!(!simulate::flush_motion());
+    }
+
+    #[test]
+    fn modeset_blank() {
+        use super::super::input::keys::BTN_LEFT;
+        use super::super::input::Seat;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        let left = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let right = dummy::add_output("WLC-2", Size { w: 800, h: 600 });
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let log = events.clone();
+        callback::rust::pointer_motion(move |_, _, point| {
+            log.borrow_mut().push(format!("motion {}", point));
+            pointer::set_position(*point);
+            false
+        });
+        let log = events.clone();
+        callback::rust::pointer_button(move |_, _, _, _, _, _| {
+            log.borrow_mut().push("button".to_string());
+            false
+        });
+        dummy::set_modeset_blank(Some(100));
+        simulate::output_resolution(left, Size { w: 1024, h: 768 });
+        WlcOutput::focus(Some(right));
+        simulate::pointer_motion(Point { x: 1, y: 1 });
+        WlcOutput::focus(Some(left));
+        simulate::pointer_motion(Point { x: 2, y: 2 });
+        simulate::pointer_button(BTN_LEFT, ButtonState::Pressed);
+        let log = events.clone();
+        callback::rust::touch(move |_, _, _, touch, _, point| {
+            log.borrow_mut().push(format!("touch {:?} {}", touch, point));
+            false
+        });
+        assert!(!simulate::touch(TouchType::Down, 0, Point { x: 4, y: 4 }));
+        assert!(Seat::default().touch().points().is_empty());
+        assert_eq!(*events.borrow(), vec!["motion (1, 1)".to_string()]);
+        assert!(!simulate::render_frame(left));
+        assert_eq!(dummy::frame_stats(left).frames, 0);
+        dummy::advance_time(100);
+        simulate::pointer_motion(Point { x: 3, y: 3 });
+        assert_eq!(*events.borrow(), vec!["motion (1, 1)".to_string(), "motion (2, 2)".to_string(),
+                                          "button".to_string(), "touch Down (4, 4)".to_string(),
+                                          "motion (3, 3)".to_string()]);
+        assert_eq!(Seat::default().touch().points(), vec![(0, Point { x: 4, y: 4 })]);
+        // Outputs which go away take their blank with them
+        simulate::output_resolution(left, Size { w: 800, h: 600 });
+        simulate::disconnect_output(left);
+        simulate::pointer_button(BTN_LEFT, ButtonState::Released);
+        assert_eq!(events.borrow().len(), 6);
+    }
+
+    #[test]
+    fn virtual_keyboard() {
+        use super::super::input::keys;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let client = dummy::add_view(output);
+        assert!(simulate::create_virtual_keyboard(client).is_none());
+        dummy::set_virtual_keyboard_policy(|_| true);
+        let keyboard = simulate::create_virtual_keyboard(client).unwrap();
+        assert_eq!(keyboard.client(), client);
+        let sources = Rc::new(RefCell::new(Vec::new()));
+        let log = sources.clone();
+        callback::rust::keyboard_key(move |_, _, _, _, _| {
+            log.borrow_mut().push(dummy::virtual_keyboard_source());
+            false
+        });
+        keyboard.key(keys::KEY_A, KeyState::Pressed);
+        simulate::keyboard_key(keys::KEY_B, KeyState::Pressed);
+        dummy::remove_view(client);
+        keyboard.key(keys::KEY_A, KeyState::Released);
+        assert_eq!(*sources.borrow(), vec![Some(client), None]);
+        assert_eq!(dummy::virtual_keyboard_source(), None);
+    }
+
+    #[test]
+    fn pointer_deliveries() {
+        use super::super::dummy::PointerDelivery::*;
+        use super::super::input::keys::BTN_LEFT;
+        use std::rc::Rc;
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let left = dummy::add_view(output);
+        let right = dummy::add_view(output);
+        left.set_geometry(EDGE_NONE, geometry(0, 0));
+        right.set_geometry(EDGE_NONE, geometry(100, 0));
+        callback::rust::pointer_motion(pointer_motion);
+        let clicked = Rc::new(RefCell::new(Vec::new()));
+        let log = clicked.clone();
+        callback::rust::pointer_button(move |view, _, _, _, _, _| {
+            log.borrow_mut().push(view);
+            false
+        });
+
+        simulate::pointer_motion(Point { x: 50, y: 50 });
+        simulate::pointer_motion(Point { x: 60, y: 50 });
+        right.focus();
+        simulate::pointer_button(BTN_LEFT, ButtonState::Pressed);
+        assert_eq!(dummy::pointer_focus(), left);
+        assert_eq!(dummy::keyboard_focus(), right);
+        // The pointer stays, but the view under it moves away
+        left.set_geometry(EDGE_NONE, geometry(300, 300));
+        simulate::pointer_button(BTN_LEFT, ButtonState::Released);
+        assert_eq!(dummy::pointer_focus(), WlcView::root());
+        simulate::pointer_motion(Point { x: 150, y: 50 });
+        dummy::remove_view(right);
+        assert_eq!(*clicked.borrow(), vec![left, WlcView::root()]);
+        assert_eq!(dummy::pointer_deliveries(), vec![
+            Enter { view: left, serial: 1, point: Point { x: 50, y: 50 } },
+            Leave { view: left, serial: 3 },
+            Enter { view: right, serial: 4, point: Point { x: 150, y: 50 } }
+        ]);
+        assert_eq!(dummy::pointer_focus(), WlcView::root());
+    }
+
+    #[test]
+    fn placement() {
+        use super::super::dummy::Placement;
+        let output = dummy::add_output("WLC-1", Size { w: 200, h: 200 });
+        let place = |placement| {
+            dummy::set_placement(placement);
+            let view = dummy::add_view(output);
+            view.set_geometry(EDGE_NONE, geometry(0, 0));
+            dummy::set_mapped(view, false);
+            simulate::map_view(view);
+            view.get_visible_geometry().origin
+        };
+        assert_eq!(place(Placement::Cascade), Point { x: 0, y: 0 });
+        assert_eq!(place(Placement::Cascade), Point { x: 32, y: 32 });
+        assert_eq!(place(Placement::Cascade), Point { x: 64, y: 64 });
+        assert_eq!(place(Placement::Cascade), Point { x: 96, y: 96 });
+        assert_eq!(place(Placement::Cascade), Point { x: 0, y: 0 });
+        pointer::set_position(Point { x: 190, y: 40 });
+        assert_eq!(place(Placement::AtPointer), Point { x: 100, y: 0 });
+        assert_eq!(place(Placement::Client), Point { x: 0, y: 0 });
+        // The compositor placed it
+        callback::rust::view_created(|view| {
+            view.set_geometry(EDGE_NONE, geometry(10, 10));
+            true
+        });
+        assert_eq!(place(Placement::Centered), Point { x: 10, y: 10 });
+    }
+
+    #[test]
+    fn pointer_follows_focus() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let view = dummy::add_view(output);
+        view.set_geometry(EDGE_NONE, geometry(100, 200));
+        view.focus();
+        assert_eq!(pointer::get_position(), Point { x: 0, y: 0 });
+        WlcView::root().focus();
+        dummy::set_pointer_follows_focus(true);
+        view.focus();
+        assert_eq!(pointer::get_position(), Point { x: 150, y: 250 });
+    }
+
+    #[cfg(feature = "off-thread-callbacks")]
+    #[test]
+    fn off_thread_callbacks() {
+        use std::panic::{self, AssertUnwindSafe};
+        use std::sync::{Arc, Mutex};
+        use std::thread;
+        use super::super::dummy::Threading;
+
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let view = dummy::add_view(output);
+        let threads = Arc::new(Mutex::new(Vec::new()));
+        let focus_threads = threads.clone();
+        callback::rust::view_created(|view| {
+            view.focus();
+            true
+        });
+        callback::rust::view_focus(move |view, focused| {
+            focus_threads.lock().unwrap().push(thread::current().id());
+            // The compositor's thread-local state is on another thread
+            view_focus(view, focused);
+        });
+        // Nothing the callbacks use is tied to this thread
+        unsafe { dummy::set_threading(Threading::OffThread) };
+        assert!(simulate::dispatch(WlcEvent::ViewCreated(view)));
+        assert_eq!(dummy::focused_view(), view);
+        let threads = threads.lock().unwrap().clone();
+        assert_eq!(threads.len(), 1);
+        assert!(threads[0] != thread::current().id());
+        FOCUS.with(|log| assert!(log.borrow().is_empty()));
+
+        callback::rust::compositor_ready(|| panic!("compositor_ready"));
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            simulate::dispatch(WlcEvent::CompositorReady)
+        }));
+        assert!(result.is_err());
+        unsafe { dummy::set_threading(Threading::LoopThread) };
+        WlcView::root().focus();
+        FOCUS.with(|log| assert_eq!(*log.borrow(), vec![(view, false)]));
+    }
+
+    #[test]
+    fn interactive_ops() {
+        use super::super::dummy::InteractiveOp;
+        use super::super::input::keys::BTN_LEFT;
+        use std::rc::Rc;
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let left = dummy::add_view(output);
+        let right = dummy::add_view(output);
+        left.set_geometry(EDGE_NONE, Geometry {
+            origin: Point { x: 0, y: 0 }, size: Size { w: 400, h: 600 }
+        });
+        right.set_geometry(EDGE_NONE, Geometry {
+            origin: Point { x: 400, y: 0 }, size: Size { w: 400, h: 600 }
+        });
+        let moved = Rc::new(RefCell::new(Vec::new()));
+        let log = moved.clone();
+        callback::rust::pointer_motion(move |view, _, point| {
+            log.borrow_mut().push(view);
+            pointer::set_position(*point);
+            false
+        });
+        simulate::pointer_motion(Point { x: 399, y: 10 });
+        simulate::pointer_button(BTN_LEFT, ButtonState::Pressed);
+        simulate::request_resize(left, RESIZE_RIGHT, Point { x: 399, y: 10 });
+        assert_eq!(dummy::interactive_op(), Some(InteractiveOp::Resize {
+            view: left, edge: RESIZE_RIGHT, origin: Point { x: 399, y: 10 }
+        }));
+        // Dragged over the right view, but still resizing the left one
+        simulate::pointer_motion(Point { x: 500, y: 10 });
+        right.focus();
+        assert_eq!(pointer::get_held_buttons(), vec![BTN_LEFT.get_code()]);
+        simulate::pointer_button(BTN_LEFT, ButtonState::Released);
+        assert_eq!(dummy::interactive_op(), None);
+        assert!(pointer::get_held_buttons().is_empty());
+        simulate::pointer_motion(Point { x: 501, y: 10 });
+        assert_eq!(*moved.borrow(), vec![left, left, right]);
+
+        simulate::request_move(right, Point { x: 0, y: 0 });
+        assert_eq!(dummy::interactive_op().map(|op| op.view()), Some(right));
+        dummy::remove_view(right);
+        assert_eq!(dummy::interactive_op(), None);
+        simulate::request_move(right, Point { x: 0, y: 0 });
+        assert_eq!(dummy::interactive_op(), None);
+        // Removing a view with its output ends its op too
+        simulate::request_move(left, Point { x: 0, y: 0 });
+        assert_eq!(dummy::interactive_op().map(|op| op.view()), Some(left));
+        dummy::remove_output(output);
+        assert_eq!(dummy::interactive_op(), None);
+    }
+    #[test]
+    fn xwayland() {
+        use super::super::dummy::XWayland;
+        use std::rc::Rc;
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let created = Rc::new(RefCell::new(Vec::new()));
+        let log = created.clone();
+        callback::rust::view_created(move |view| { log.borrow_mut().push(view); true });
+        dummy::set_xwayland(XWayland::Disabled);
+        assert!(simulate::create_x11_view().is_root());
+        dummy::set_xwayland(XWayland::Starting);
+        let first = simulate::create_x11_view();
+        let closed = simulate::create_x11_view();
+        let second = simulate::create_x11_view();
+        dummy::remove_view(closed);
+        let native = simulate::create_view();
+        assert_eq!(*created.borrow(), vec![native]);
+        let seen = created.clone();
+        dummy::on_xwayland_ready(move || assert_eq!(*seen.borrow(), vec![native]));
+        simulate::xwayland_ready();
+        assert_eq!(*created.borrow(), vec![native, first, second]);
+        assert_eq!(output.get_views(), vec![first, second, native]);
+        // Ready already, so nothing happens
+        simulate::xwayland_ready();
+        assert_eq!(simulate::create_x11_view().get_output(), output);
+        assert_eq!(created.borrow().len(), 4);
+        // Views waiting on an output which is removed don't open, even if
+        // their handle is reused
+        let other = dummy::add_output("WLC-2", Size { w: 800, h: 600 });
+        output.set_primary();
+        dummy::on_xwayland_ready(|| {});
+        dummy::set_xwayland(XWayland::Starting);
+        simulate::create_x11_view();
+        dummy::set_handle_policy(dummy::HandlePolicy::Reuse);
+        dummy::remove_output(output);
+        let reused = dummy::add_view(other);
+        dummy::set_mapped(reused, false);
+        simulate::xwayland_ready();
+        assert_eq!(created.borrow().len(), 4);
+    }
+}
@@ -1,5 +1,19 @@
 //! Contains struct and enum declarations for
 //! structs defined by wlc.
+//!
+//! # Layout
+//! The types here have the same layout as their wlc counterparts, so they
+//! can be passed across the FFI boundary as-is:
+//!
+//! * `Point` is two `i32`s, `x` then `y` (`struct wlc_point`)
+//! * `Size` is two `u32`s, `w` then `h` (`struct wlc_size`)
+//! * `Geometry` is a `Point` then a `Size` (`struct wlc_geometry`)
+//! * `KeyboardModifiers` is two `u32`s, `leds` then `mods`
+//!   (`struct wlc_modifiers`)
+//! * The bitflags are a single `u32`, and the enums are C `enum`s
+//!
+//! These are checked at compile time, so an accidental change to any of
+//! them fails the build.
 
 use std::fmt;
 
@@ -19,9 +33,10 @@ pub enum LogType {
 
 /// Type of backend that a window is being composited in
 #[repr(C)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum BackendType {
     /// Backend type is unknown
+    #[default]
     None,
     /// Standard wayland client
     DRM,
@@ -128,6 +143,12 @@ bitflags! {
     }
 }
 
+impl Default for KeyMod {
+    fn default() -> KeyMod {
+        MOD_NONE
+    }
+}
+
 bitflags! {
     /// "LEDs" or active key-locks.
     /// i.e. caps lock, scroll lock
@@ -210,7 +231,7 @@ pub struct KeyboardModifiers {
 
 /// Represents the location of a view.
 #[repr(C)]
-#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash, Default)]
 pub struct Point {
     /// x coordinate
     pub x: i32,
@@ -226,7 +247,7 @@ impl fmt::Display for Point {
 
 /// Represents the height and width of a view.
 #[repr(C)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub struct Size {
     /// Width
     pub w: u32,
@@ -240,9 +261,53 @@ impl fmt::Display for Size {
     }
 }
 
+impl Size {
+    /// Multiplies both dimensions by a factor, rounding to the nearest
+    /// pixel.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rustwlc::Size;
+    ///
+    /// assert_eq!(Size { w: 1280, h: 720 }.scale(1.5), Size { w: 1920, h: 1080 });
+    /// assert_eq!(Size { w: 5, h: 3 }.scale(0.5), Size { w: 3, h: 2 });
+    /// ```
+    pub fn scale(&self, factor: f64) -> Size {
+        Size {
+            w: (self.w as f64 * factor).round().max(0.0) as u32,
+            h: (self.h as f64 * factor).round().max(0.0) as u32
+        }
+    }
+
+    /// Gets the largest size with the same aspect ratio which fits within
+    /// `bounds`, growing or shrinking as needed.
+    ///
+    /// An empty size stays empty.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rustwlc::Size;
+    ///
+    /// let screen = Size { w: 1920, h: 1200 };
+    /// assert_eq!(Size { w: 1280, h: 720 }.fit_within(&screen), Size { w: 1920, h: 1080 });
+    /// assert_eq!(Size { w: 600, h: 800 }.fit_within(&screen), Size { w: 900, h: 1200 });
+    /// ```
+    pub fn fit_within(&self, bounds: &Size) -> Size {
+        if self.w == 0 || self.h == 0 {
+            return *self
+        }
+        // Compare w / h against bounds.w / bounds.h without rounding
+        if self.w as u64 * bounds.h as u64 > bounds.w as u64 * self.h as u64 {
+            Size { w: bounds.w, h: (bounds.w as u64 * self.h as u64 / self.w as u64) as u32 }
+        } else {
+            Size { w: (bounds.h as u64 * self.w as u64 / self.h as u64) as u32, h: bounds.h }
+        }
+    }
+}
+
 /// Represents the location and size of a view
 #[repr(C)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub struct Geometry {
     /// The location of the object
     pub origin: Point,
@@ -250,12 +315,121 @@ pub struct Geometry {
     pub size: Size
 }
 
+impl Geometry {
+    /// Whether a point lies within the geometry.
+    ///
+    /// The origin is inside the geometry, the opposite corner is not.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rustwlc::{Geometry, Point, Size};
+    ///
+    /// let geometry = Geometry {
+    ///     origin: Point { x: 10, y: 10 },
+    ///     size: Size { w: 10, h: 10 }
+    /// };
+    /// assert!(geometry.contains_point(Point { x: 10, y: 19 }));
+    /// assert!(!geometry.contains_point(Point { x: 20, y: 10 }));
+    /// ```
+    pub fn contains_point(&self, point: Point) -> bool {
+        point.x >= self.origin.x && point.y >= self.origin.y &&
+            ((point.x - self.origin.x) as i64) < self.size.w as i64 &&
+            ((point.y - self.origin.y) as i64) < self.size.h as i64
+    }
+
+    /// Gets the center of the geometry, rounding towards the origin.
+    pub fn center(&self) -> Point {
+        self.anchor(Gravity::Center)
+    }
+
+    /// Gets the point of the geometry on an edge, corner, or its center.
+    ///
+    /// The opposite corner of the origin is just outside the geometry, see
+    /// `contains_point`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rustwlc::{Geometry, Gravity, Point, Size};
+    ///
+    /// let geometry = Geometry {
+    ///     origin: Point { x: 10, y: 10 },
+    ///     size: Size { w: 100, h: 50 }
+    /// };
+    /// assert_eq!(geometry.center(), Point { x: 60, y: 35 });
+    /// assert_eq!(geometry.anchor(Gravity::TopRight), Point { x: 110, y: 10 });
+    /// assert_eq!(geometry.anchor(Gravity::Bottom), Point { x: 60, y: 60 });
+    /// ```
+    pub fn anchor(&self, gravity: Gravity) -> Point {
+        let (w, h) = (self.size.w as i64, self.size.h as i64);
+        let (x, y) = match gravity {
+            Gravity::TopLeft => (0, 0),
+            Gravity::Top => (w / 2, 0),
+            Gravity::TopRight => (w, 0),
+            Gravity::Left => (0, h / 2),
+            Gravity::Center => (w / 2, h / 2),
+            Gravity::Right => (w, h / 2),
+            Gravity::BottomLeft => (0, h),
+            Gravity::Bottom => (w / 2, h),
+            Gravity::BottomRight => (w, h)
+        };
+        Point {
+            x: (self.origin.x as i64 + x) as i32,
+            y: (self.origin.y as i64 + y) as i32
+        }
+    }
+}
+
 impl fmt::Display for Geometry {
     fn fmt(&self, format: &mut fmt::Formatter) -> fmt::Result {
         write!(format, "[{} at {}]", self.size, self.origin)
     }
 }
 
+/// An edge, corner, or the center of a geometry, see `Geometry::anchor`.
+///
+/// Unlike the other types here, this isn't part of wlc.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Gravity {
+    /// Top left corner, the origin
+    TopLeft,
+    /// Middle of the top edge
+    Top,
+    /// Top right corner
+    TopRight,
+    /// Middle of the left edge
+    Left,
+    /// Center
+    Center,
+    /// Middle of the right edge
+    Right,
+    /// Bottom left corner
+    BottomLeft,
+    /// Middle of the bottom edge
+    Bottom,
+    /// Bottom right corner
+    BottomRight
+}
+
 /// Not currently supporting libinput
 #[repr(C)]
 pub struct LibinputDevice;
+
+// The layout documented above. `offset_of!` needs Rust 1.77, see the README.
+const _: () = {
+    use std::mem::{align_of, offset_of, size_of};
+    assert!(size_of::<Point>() == 8 && align_of::<Point>() == 4);
+    assert!(offset_of!(Point, x) == 0 && offset_of!(Point, y) == 4);
+    assert!(size_of::<Size>() == 8 && align_of::<Size>() == 4);
+    assert!(offset_of!(Size, w) == 0 && offset_of!(Size, h) == 4);
+    assert!(size_of::<Geometry>() == 16 && align_of::<Geometry>() == 4);
+    assert!(offset_of!(Geometry, origin) == 0 && offset_of!(Geometry, size) == 8);
+    assert!(size_of::<KeyboardModifiers>() == 8 && align_of::<KeyboardModifiers>() == 4);
+    assert!(offset_of!(KeyboardModifiers, leds) == 0 &&
+            offset_of!(KeyboardModifiers, mods) == 4);
+    assert!(size_of::<EventBit>() == 4 && size_of::<ViewState>() == 4 &&
+            size_of::<ViewType>() == 4 && size_of::<ResizeEdge>() == 4 &&
+            size_of::<KeyMod>() == 4 && size_of::<KeyboardLed>() == 4);
+    assert!(size_of::<LogType>() == 4 && size_of::<BackendType>() == 4 &&
+            size_of::<KeyState>() == 4 && size_of::<ButtonState>() == 4 &&
+            size_of::<ScrollAxis>() == 4 && size_of::<TouchType>() == 4);
+};
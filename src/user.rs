@@ -0,0 +1,222 @@
+//! A driver which acts like a user at the keyboard and mouse.
+//!
+//! The `simulate` module injects single events. This module strings them
+//! together into what a user does, so integration tests can read like user
+//! stories:
+//!
+//! ```rust
+//! use rustwlc::{dummy, fixtures, user};
+//! use rustwlc::Size;
+//!
+//! let output = dummy::add_output("WLC-1", Size { w: 1920, h: 1080 });
+//! let term = fixtures::xterm(output);
+//! # use rustwlc::{callback, WlcView, Point};
+//! # use rustwlc::input::pointer;
+//! # fn pointer_motion(view: WlcView, time: u32, point: &Point) -> bool {
+//! #     pointer::set_position(*point);
+//! #     false
+//! # }
+//! # callback::rust::pointer_motion(pointer_motion);
+//! user::click_on_title("xterm").unwrap();
+//! user::type_str("ls -l\n").unwrap();
+//! ```
+
+use super::dummy;
+use super::handle::{WlcOutput, WlcView};
+use super::input::keys::*;
+use super::input::pointer;
+use super::simulate;
+use super::types::{ButtonState, KeyState};
+
+/// Types text on a US QWERTY keyboard.
+///
+/// Each character is pressed and released in turn, holding shift for
+/// characters which need it. Newlines are typed with enter and tabs with
+/// tab.
+///
+/// Fails without typing anything if the text has a character which is not
+/// on the keyboard.
+pub fn type_str(text: &str) -> Result<(), &'static str> {
+    let strokes = text.chars().map(key_for).collect::<Option<Vec<_>>>()
+        .ok_or("Text has a character which is not on a US keyboard")?;
+    for (key, shift) in strokes {
+        if shift {
+            simulate::keyboard_key(KEY_LEFTSHIFT, KeyState::Pressed);
+        }
+        simulate::keyboard_key(key, KeyState::Pressed);
+        simulate::keyboard_key(key, KeyState::Released);
+        if shift {
+            simulate::keyboard_key(KEY_LEFTSHIFT, KeyState::Released);
+        }
+    }
+    Ok(())
+}
+
+/// Clicks the center of the topmost visible view with a title.
+///
+/// Focuses the view's output if needed, then moves the pointer to the
+/// center of the view and clicks the left button. As in wlc, the
+/// compositor must move the pointer in its `pointer_motion` callback.
+///
+/// Fails if no visible view has the title, or if the pointer is not over
+/// the view when clicking, i.e. because another view covers its center.
+pub fn click_on_title(title: &str) -> Result<(), &'static str> {
+    let view = find_title(title).ok_or("No visible view has that title")?;
+    let output = view.get_output();
+    if dummy::pointer_output() != output {
+        WlcOutput::focus(Some(output));
+    }
+    let center = view.get_visible_geometry().center();
+    simulate::pointer_motion(center);
+    if dummy::view_at(output, pointer::get_position()) != view {
+        return Err("The pointer did not reach the view")
+    }
+    simulate::pointer_button(BTN_LEFT, ButtonState::Pressed);
+    simulate::pointer_button(BTN_LEFT, ButtonState::Released);
+    Ok(())
+}
+
+/// Finds the topmost visible view with a title, on the first output with one.
+fn find_title(title: &str) -> Option<WlcView> {
+    dummy::outputs().into_iter()
+        .filter_map(|output| dummy::visible_views(output).into_iter().rev()
+                    .find(|view| view.get_title() == title))
+        .next()
+}
+
+/// The character a key types on a US QWERTY keyboard, with or without
+/// shift, if any.
+pub(crate) fn char_for(key: Key, shift: bool) -> Option<char> {
+    (' '..='~').chain(vec!['\n', '\t']).find(|&c| key_for(c) == Some((key, shift)))
+}
+
+/// The key which types a character, and whether it needs shift.
+fn key_for(c: char) -> Option<(Key, bool)> {
+    if c.is_ascii_uppercase() {
+        return key_for(c.to_ascii_lowercase()).map(|(key, _)| (key, true))
+    }
+    Some(match c {
+        'a' => (KEY_A, false),
+        'b' => (KEY_B, false),
+        'c' => (KEY_C, false),
+        'd' => (KEY_D, false),
+        'e' => (KEY_E, false),
+        'f' => (KEY_F, false),
+        'g' => (KEY_G, false),
+        'h' => (KEY_H, false),
+        'i' => (KEY_I, false),
+        'j' => (KEY_J, false),
+        'k' => (KEY_K, false),
+        'l' => (KEY_L, false),
+        'm' => (KEY_M, false),
+        'n' => (KEY_N, false),
+        'o' => (KEY_O, false),
+        'p' => (KEY_P, false),
+        'q' => (KEY_Q, false),
+        'r' => (KEY_R, false),
+        's' => (KEY_S, false),
+        't' => (KEY_T, false),
+        'u' => (KEY_U, false),
+        'v' => (KEY_V, false),
+        'w' => (KEY_W, false),
+        'x' => (KEY_X, false),
+        'y' => (KEY_Y, false),
+        'z' => (KEY_Z, false),
+        '1' => (KEY_1, false),
+        '!' => (KEY_1, true),
+        '2' => (KEY_2, false),
+        '@' => (KEY_2, true),
+        '3' => (KEY_3, false),
+        '#' => (KEY_3, true),
+        '4' => (KEY_4, false),
+        '$' => (KEY_4, true),
+        '5' => (KEY_5, false),
+        '%' => (KEY_5, true),
+        '6' => (KEY_6, false),
+        '^' => (KEY_6, true),
+        '7' => (KEY_7, false),
+        '&' => (KEY_7, true),
+        '8' => (KEY_8, false),
+        '*' => (KEY_8, true),
+        '9' => (KEY_9, false),
+        '(' => (KEY_9, true),
+        '0' => (KEY_0, false),
+        ')' => (KEY_0, true),
+        '-' => (KEY_MINUS, false),
+        '_' => (KEY_MINUS, true),
+        '=' => (KEY_EQUAL, false),
+        '+' => (KEY_EQUAL, true),
+        '[' => (KEY_LEFTBRACE, false),
+        '{' => (KEY_LEFTBRACE, true),
+        ']' => (KEY_RIGHTBRACE, false),
+        '}' => (KEY_RIGHTBRACE, true),
+        '\\' => (KEY_BACKSLASH, false),
+        '|' => (KEY_BACKSLASH, true),
+        ';' => (KEY_SEMICOLON, false),
+        ':' => (KEY_SEMICOLON, true),
+        '\'' => (KEY_APOSTROPHE, false),
+        '"' => (KEY_APOSTROPHE, true),
+        '`' => (KEY_GRAVE, false),
+        '~' => (KEY_GRAVE, true),
+        ',' => (KEY_COMMA, false),
+        '<' => (KEY_COMMA, true),
+        '.' => (KEY_DOT, false),
+        '>' => (KEY_DOT, true),
+        '/' => (KEY_SLASH, false),
+        '?' => (KEY_SLASH, true),
+        ' ' => (KEY_SPACE, false),
+        '\n' => (KEY_ENTER, false),
+        '\t' => (KEY_TAB, false),
+        _ => return None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::super::input::keys::*;
+    use super::super::input::pointer;
+    use std::cell::RefCell;
+
+    thread_local! {
+        static EVENTS: RefCell<Vec<WlcEvent>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn log(event: &WlcEvent) {
+        EVENTS.with(|log| log.borrow_mut().push(*event));
+    }
+
+    fn pointer_motion(view: WlcView, time: u32, point: &Point) -> bool {
+        pointer::set_position(*point);
+        false
+    }
+
+    #[test]
+    fn type_str() {
+        callback::all_events(log);
+        assert!(user::type_str("é").is_err());
+        user::type_str("A!").unwrap();
+        let keys = EVENTS.with(|log| log.borrow().iter().filter_map(|event| match *event {
+            WlcEvent::KeyboardKey { key, state: KeyState::Pressed, .. } => Some(key),
+            _ => None
+        }).collect::<Vec<_>>());
+        assert_eq!(keys, vec![42, 30, 42, 2]);
+    }
+
+    #[test]
+    fn click_on_title() {
+        let output = dummy::add_output("WLC-1", Size { w: 1920, h: 1080 });
+        let term = fixtures::xterm(output);
+        pointer::set_position(Point { x: 1000, y: 1000 });
+        assert_eq!(user::click_on_title("xterm"), Err("The pointer did not reach the view"));
+        callback::rust::pointer_motion(pointer_motion);
+        assert_eq!(user::click_on_title("Mozilla Firefox"), Err("No visible view has that title"));
+        callback::all_events(log);
+        user::click_on_title("xterm").unwrap();
+        EVENTS.with(|log| assert_eq!(log.borrow()[1], WlcEvent::PointerButton {
+            view: term, time: 0, button: BTN_LEFT.get_code(), state: ButtonState::Pressed,
+            point: Point { x: 242, y: 158 },
+            mods: KeyboardModifiers { mods: MOD_NONE, leds: KeyboardLed::empty() }
+        }));
+    }
+}
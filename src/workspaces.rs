@@ -0,0 +1,120 @@
+//! Named workspaces, implemented purely over view and output masks.
+//!
+//! wlc draws a view on an output when their masks share a bit. A common way
+//! to build workspaces on top of that is to give each workspace one bit,
+//! assign views to workspaces by setting their mask to that bit, and switch
+//! workspaces by setting the output's mask. This module does exactly that,
+//! so compositors can compare their own bookkeeping against it.
+//!
+//! Workspaces are named bits from the `masks` module, which are handed out
+//! in order of first use. Since outputs start with a mask of `1`, the first
+//! workspace used is the one initially shown. Bits the compositor allocates
+//! for its own use are shared with workspaces, so there are at most 32 of
+//! both together, and those bits are seen as workspaces here too.
+//!
+//! # Example
+//! ```rust
+//! use rustwlc::Size;
+//! use rustwlc::{dummy, workspaces};
+//!
+//! let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+//! let term = dummy::add_view(output);
+//! let browser = dummy::add_view(output);
+//! workspaces::assign(term, "term").unwrap();
+//! workspaces::assign(browser, "web").unwrap();
+//!
+//! workspaces::show(output, "web").unwrap();
+//! assert_eq!(dummy::visible_views(output), vec![browser]);
+//! assert_eq!(workspaces::shown(output), vec!["web".to_string()]);
+//! assert_eq!(workspaces::of_view(term), Some("term".to_string()));
+//! ```
+
+use super::dummy::ViewFilter;
+use super::handle::{WlcOutput, WlcView};
+use super::masks;
+
+const EXHAUSTED: &str = "All 32 workspace mask bits are in use";
+
+/// Gets the mask bit of a workspace, allocating one if it's new.
+///
+/// Returns `None` if there are no bits left for a new workspace.
+pub fn mask(name: &str) -> Option<u32> {
+    masks::allocate(name)
+}
+
+/// Gets the names of all workspaces used so far, in order of their bits.
+pub fn names() -> Vec<String> {
+    masks::names()
+}
+
+/// Gets the names of the workspaces whose bits are set in a mask.
+pub fn names_in_mask(mask: u32) -> Vec<String> {
+    masks::names_in(mask)
+}
+
+/// Moves a view to a workspace by setting its mask to the workspace's bit.
+pub fn assign(view: WlcView, name: &str) -> Result<(), &'static str> {
+    let bit = mask(name).ok_or(EXHAUSTED)?;
+    view.set_mask(bit);
+    Ok(())
+}
+
+/// Shows a single workspace on an output by setting the output's mask.
+pub fn show(output: WlcOutput, name: &str) -> Result<(), &'static str> {
+    let bit = mask(name).ok_or(EXHAUSTED)?;
+    output.set_mask(bit);
+    Ok(())
+}
+
+/// Gets the workspace a view is on.
+///
+/// If the view's mask has several workspace bits, the lowest one is used.
+pub fn of_view(view: WlcView) -> Option<String> {
+    names_in_mask(view.get_mask()).into_iter().next()
+}
+
+/// Gets the workspaces an output is showing.
+pub fn shown(output: WlcOutput) -> Vec<String> {
+    names_in_mask(output.get_mask())
+}
+
+/// Gets the views of an output which are on a workspace, in stack order.
+pub fn views(output: WlcOutput, name: &str) -> Vec<WlcView> {
+    let bit = match masks::bit(name) {
+        Some(bit) => bit,
+        None => return Vec::new()
+    };
+    output.get_views_by_type(ViewFilter { mask: Some(bit), .. ViewFilter::default() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn bits_are_allocated_in_order() {
+        assert_eq!(workspaces::mask("1"), Some(1));
+        assert_eq!(workspaces::mask("2"), Some(2));
+        assert_eq!(workspaces::mask("1"), Some(1));
+        for index in 3..33 {
+            assert_eq!(workspaces::mask(&index.to_string()),
+                       Some(1 << (index - 1)));
+        }
+        assert_eq!(workspaces::mask("33"), None);
+        assert!(workspaces::assign(WlcView::dummy(1), "33").is_err());
+        assert_eq!(workspaces::names_in_mask(5), vec!["1", "3"]);
+    }
+
+    #[test]
+    fn views_on_workspaces() {
+        let output = dummy::add_output("WLC-1", Size { w: 800, h: 600 });
+        let first = dummy::add_view(output);
+        let second = dummy::add_view(output);
+        workspaces::assign(first, "a").unwrap();
+        workspaces::assign(second, "b").unwrap();
+        assert_eq!(workspaces::shown(output), vec!["a"]);
+        assert_eq!(dummy::visible_views(output), vec![first]);
+        assert_eq!(workspaces::views(output, "b"), vec![second]);
+        assert!(workspaces::views(output, "c").is_empty());
+    }
+}